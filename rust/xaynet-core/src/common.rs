@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
 use sodiumoxide::{self, crypto::box_};
 
-use crate::{crypto::ByteObject, mask::MaskConfigPair, CoordinatorPublicKey};
+use crate::{
+    crypto::ByteObject,
+    mask::{MaskConfigPair, PrivacyBudget},
+    message::PROTOCOL_VERSION,
+    CoordinatorPublicKey,
+};
 
 /// The round parameters.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,6 +23,75 @@ pub struct RoundParameters {
     pub mask_config: MaskConfigPair,
     /// The length of the model.
     pub model_length: usize,
+    /// The privacy budget spent so far, if differential privacy is enabled.
+    pub privacy_budget: Option<PrivacyBudget>,
+    /// The version of the PET protocol implemented by the coordinator.
+    pub version: u16,
+    /// The time at which the `sum` phase of the round starts, in seconds since the unix epoch, if
+    /// the coordinator has postponed the start of the round. Participants can wait until then.
+    pub starts_at: Option<u64>,
+}
+
+/// The round parameters as they were serialized before the protocol versioning.
+///
+/// Coordinators and participants of this version stored the round parameters in this format, so
+/// it is only kept to restore such states.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LegacyRoundParameters {
+    /// The public key of the coordinator used for encryption.
+    pub pk: CoordinatorPublicKey,
+    /// Fraction of participants to be selected for the sum task.
+    pub sum: f64,
+    /// Fraction of participants to be selected for the update task.
+    pub update: f64,
+    /// The random round seed.
+    pub seed: RoundSeed,
+    /// The masking configuration
+    pub mask_config: MaskConfigPair,
+    /// The length of the model.
+    pub model_length: usize,
+}
+
+impl From<LegacyRoundParameters> for RoundParameters {
+    /// Converts the legacy round parameters without a privacy budget and without a postponed
+    /// start. The version is the current one, because a restored state continues with the
+    /// current protocol.
+    fn from(params: LegacyRoundParameters) -> Self {
+        let LegacyRoundParameters {
+            pk,
+            sum,
+            update,
+            seed,
+            mask_config,
+            model_length,
+        } = params;
+        Self {
+            pk,
+            sum,
+            update,
+            seed,
+            mask_config,
+            model_length,
+            privacy_budget: None,
+            version: PROTOCOL_VERSION,
+            starts_at: None,
+        }
+    }
+}
+
+/// The metadata of a global model which has been created in a completed round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GlobalModelInfo {
+    /// The round in which the global model was created.
+    pub round_id: u64,
+    /// The seed of the round in which the global model was created.
+    pub round_seed: RoundSeed,
+    /// The id of the global model in the model storage of the coordinator.
+    pub model_id: String,
+    /// The length of the global model.
+    pub model_length: usize,
+    /// The time at which the round was completed, in seconds since the unix epoch.
+    pub completed_at: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
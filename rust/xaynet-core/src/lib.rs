@@ -16,6 +16,7 @@ pub mod common;
 pub mod crypto;
 pub mod mask;
 pub mod message;
+pub mod proof;
 #[cfg(any(feature = "testutils", test))]
 #[cfg_attr(docsrs, doc(cfg(feature = "testutils")))]
 pub mod testutils;
@@ -6,7 +6,7 @@
 
 pub(crate) mod serialization;
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 use num::{
     bigint::{BigInt, BigUint},
@@ -88,6 +88,34 @@ impl TryFrom<u8> for DataType {
     }
 }
 
+impl fmt::Display for DataType {
+    /// Formats the data type as the name of the primitive type, e.g. `f32`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataType::F32 => "f32",
+            DataType::F64 => "f64",
+            DataType::I32 => "i32",
+            DataType::I64 => "i64",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for DataType {
+    type Err = InvalidMaskConfigError;
+
+    /// Parses the data type from the name of the primitive type, e.g. `f32`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "f32" => Ok(DataType::F32),
+            "f64" => Ok(DataType::F64),
+            "i32" => Ok(DataType::I32),
+            "i64" => Ok(DataType::I64),
+            _ => Err(InvalidMaskConfigError::DataType),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 /// The bounds of the numerical values.
@@ -0,0 +1,310 @@
+//! Differential privacy for local models.
+//!
+//! See the [mask module] documentation since this is a private module anyways.
+//!
+//! [mask module]: crate::mask
+
+use std::f64::consts::PI;
+
+use num::{
+    bigint::BigInt,
+    rational::Ratio,
+    traits::{FromPrimitive, ToPrimitive},
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::mask::{
+    config::MaskConfig,
+    model::{float_to_ratio_bounded, ratio_to_float, Model},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The noise mechanism to privatize local models with.
+pub enum NoiseMechanism {
+    /// Continuous Gaussian noise.
+    Gaussian,
+    /// Discrete Gaussian noise on the lattice of the masking configuration.
+    ///
+    /// The noise is a multiple of the inverse exponential shift of the masking configuration,
+    /// hence it is embedded into the finite group without rounding.
+    DiscreteGaussian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// The differential privacy mode, which determines how the noise is shared among participants.
+pub enum DpMode {
+    /// Each participant adds enough noise to privatize its local model on its own.
+    Local,
+    /// The participants share the noise, such that the aggregation of the local models of at
+    /// least `participants` participants is privatized.
+    Distributed { participants: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// A differential privacy configuration for local models.
+pub struct DpConfig {
+    /// The maximal L2 norm of a local model. Local models with a larger norm are scaled down.
+    pub clipping_bound: f64,
+    /// The ratio of the standard deviation of the noise to the clipping bound.
+    pub noise_multiplier: f64,
+    /// The noise mechanism.
+    pub mechanism: NoiseMechanism,
+    /// The differential privacy mode.
+    pub mode: DpMode,
+}
+
+impl DpConfig {
+    /// Gets the standard deviation of the noise which is added to each weight of a local model.
+    pub fn std_dev(&self) -> f64 {
+        let std_dev = self.noise_multiplier * self.clipping_bound;
+        match self.mode {
+            DpMode::Local => std_dev,
+            DpMode::Distributed { participants } => std_dev / (participants.max(1) as f64).sqrt(),
+        }
+    }
+
+    /// Clips the `model` wrt the clipping bound and adds noise to its weights wrt the masking
+    /// `config`uration.
+    ///
+    /// This is meant to be done right before the masking. The privatized weights might exceed the
+    /// bounds of the masking configuration, in which case they are clamped during the masking.
+    pub fn privatize(&self, model: &Model, config: MaskConfig) -> Model {
+        let mut prng = ChaCha20Rng::from_entropy();
+        let clipping_factor = float_to_ratio_bounded(self.clipping_factor(model));
+        let std_dev = self.std_dev();
+        let exp_shift = config.exp_shift();
+        // the lattice of the masking configuration might be too fine to sample discrete noise on
+        // it, but then it is indistinguishable from continuous noise anyways
+        let lattice_std_dev = exp_shift
+            .to_f64()
+            .map(|exp_shift| std_dev * exp_shift)
+            .filter(|lattice_std_dev| lattice_std_dev.is_finite());
+
+        model
+            .iter()
+            .map(|weight| {
+                let noise = match (self.mechanism, lattice_std_dev) {
+                    (NoiseMechanism::DiscreteGaussian, Some(lattice_std_dev)) => {
+                        let noise = sample_discrete_gaussian(&mut prng, lattice_std_dev);
+                        // safe unwrap: the discrete noise is a finite integer
+                        Ratio::new(BigInt::from_f64(noise).unwrap(), exp_shift.clone())
+                    }
+                    _ => float_to_ratio_bounded(std_dev * sample_gaussian(&mut prng)),
+                };
+                weight * &clipping_factor + noise
+            })
+            .collect()
+    }
+
+    /// Gets the factor to scale the `model` with, such that its L2 norm is within the clipping
+    /// bound.
+    fn clipping_factor(&self, model: &Model) -> f64 {
+        let norm = model
+            .iter()
+            .map(|weight| ratio_to_float::<f64>(weight).unwrap_or(f64::MAX).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        if norm > self.clipping_bound {
+            self.clipping_bound / norm
+        } else {
+            1.
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// The privacy budget of the global model in terms of zero-concentrated differential privacy.
+///
+/// Each round which is privatized by the (discrete) Gaussian mechanism with a noise multiplier `z`
+/// spends `1 / (2 * z^2)`-zCDP and the spendings add up over the rounds.
+pub struct PrivacyBudget {
+    /// The noise multiplier which the participants are expected to use.
+    pub noise_multiplier: f64,
+    /// The delta of the (epsilon, delta)-differential privacy guarantee.
+    pub delta: f64,
+    /// The number of rounds which have spent the budget so far.
+    pub rounds: u64,
+}
+
+impl PrivacyBudget {
+    /// Creates a new privacy budget without any rounds spent.
+    pub fn new(noise_multiplier: f64, delta: f64) -> Self {
+        Self {
+            noise_multiplier,
+            delta,
+            rounds: 0,
+        }
+    }
+
+    /// Spends the budget for one more round.
+    pub fn spend(&mut self) {
+        self.rounds += 1;
+    }
+
+    /// Gets the zero-concentrated differential privacy parameter of the spent budget.
+    pub fn rho(&self) -> f64 {
+        self.rounds as f64 / (2. * self.noise_multiplier.powi(2))
+    }
+
+    /// Gets the epsilon of the (epsilon, delta)-differential privacy guarantee of the spent
+    /// budget.
+    pub fn epsilon(&self) -> f64 {
+        let rho = self.rho();
+        rho + 2. * (rho * (1. / self.delta).ln()).sqrt()
+    }
+}
+
+/// Samples from the standard normal distribution via the Box-Muller transform.
+fn sample_gaussian<R: Rng>(prng: &mut R) -> f64 {
+    // the first uniform sample is shifted into (0, 1] to avoid the logarithm of zero
+    let u1 = 1. - prng.gen::<f64>();
+    let u2 = prng.gen::<f64>();
+    (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+}
+
+/// Samples from the discrete Laplace distribution on the integers with scale `t`.
+fn sample_discrete_laplace<R: Rng>(prng: &mut R, t: f64) -> f64 {
+    loop {
+        // geometric sample with success probability `1 - exp(-1 / t)` via the inverse CDF
+        let u = 1. - prng.gen::<f64>();
+        let magnitude = (-t * u.ln()).floor();
+        let negative = prng.gen::<bool>();
+        // reject the negative zero, otherwise zero would be sampled twice as often
+        if !negative {
+            break magnitude;
+        } else if magnitude > 0. {
+            break -magnitude;
+        }
+    }
+}
+
+/// Samples from the discrete Gaussian distribution on the integers with standard deviation
+/// `sigma`.
+///
+/// This is the rejection sampler of Canonne, Kamath and Steinke, "The Discrete Gaussian for
+/// Differential Privacy" (2020), in floating point arithmetic.
+fn sample_discrete_gaussian<R: Rng>(prng: &mut R, sigma: f64) -> f64 {
+    if sigma <= 0. {
+        return 0.;
+    }
+    let t = sigma.floor() + 1.;
+    let sigma_sq = sigma.powi(2);
+    loop {
+        let y = sample_discrete_laplace(prng, t);
+        let exponent = (y.abs() - sigma_sq / t).powi(2) / (2. * sigma_sq);
+        if prng.gen::<f64>() < (-exponent).exp() {
+            break y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::traits::Zero;
+
+    use super::*;
+    use crate::mask::{BoundType, DataType, FromPrimitives, GroupType, IntoPrimitives, ModelType};
+
+    fn mask_config() -> MaskConfig {
+        MaskConfig {
+            group_type: GroupType::Prime,
+            data_type: DataType::F32,
+            bound_type: BoundType::B0,
+            model_type: ModelType::M3,
+        }
+    }
+
+    fn dp_config(mechanism: NoiseMechanism, noise_multiplier: f64) -> DpConfig {
+        DpConfig {
+            clipping_bound: 1.,
+            noise_multiplier,
+            mechanism,
+            mode: DpMode::Local,
+        }
+    }
+
+    fn mean_and_variance(samples: &[f64]) -> (f64, f64) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+        (mean, variance)
+    }
+
+    #[test]
+    fn test_std_dev() {
+        let mut dp = dp_config(NoiseMechanism::Gaussian, 2.);
+        assert!((dp.std_dev() - 2.).abs() < f64::EPSILON);
+        dp.mode = DpMode::Distributed { participants: 16 };
+        assert!((dp.std_dev() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_privatize_clips_model() {
+        let model = Model::from_primitives(vec![3_f32, 4_f32].into_iter()).unwrap();
+        let dp = dp_config(NoiseMechanism::Gaussian, 0.);
+        let privatized = dp
+            .privatize(&model, mask_config())
+            .into_primitives_unchecked()
+            .collect::<Vec<f32>>();
+        assert!((privatized[0] - 0.6).abs() < 1e-6);
+        assert!((privatized[1] - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_privatize_keeps_small_model() {
+        let model = Model::from_primitives(vec![0.3_f32, 0.4_f32].into_iter()).unwrap();
+        let dp = dp_config(NoiseMechanism::Gaussian, 0.);
+        assert_eq!(dp.privatize(&model, mask_config()), model);
+    }
+
+    #[test]
+    fn test_privatize_discrete_noise_on_lattice() {
+        let model = Model::from_primitives(vec![0_f32; 100].into_iter()).unwrap();
+        let dp = dp_config(NoiseMechanism::DiscreteGaussian, 0.001);
+        let exp_shift = Ratio::from_integer(mask_config().exp_shift());
+        let privatized = dp.privatize(&model, mask_config());
+        assert!(privatized
+            .iter()
+            .all(|weight| (weight * &exp_shift).is_integer()));
+        assert!(privatized.iter().any(|weight| !weight.is_zero()));
+    }
+
+    #[test]
+    fn test_sample_gaussian() {
+        let mut prng = ChaCha20Rng::from_seed([0_u8; 32]);
+        let samples = (0..10_000)
+            .map(|_| sample_gaussian(&mut prng))
+            .collect::<Vec<_>>();
+        let (mean, variance) = mean_and_variance(&samples);
+        assert!(mean.abs() < 0.05);
+        assert!((variance - 1.).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_sample_discrete_gaussian() {
+        let mut prng = ChaCha20Rng::from_seed([0_u8; 32]);
+        let samples = (0..10_000)
+            .map(|_| sample_discrete_gaussian(&mut prng, 10.))
+            .collect::<Vec<_>>();
+        assert!(samples.iter().all(|sample| sample.fract() == 0.));
+        let (mean, variance) = mean_and_variance(&samples);
+        assert!(mean.abs() < 0.5);
+        assert!((variance - 100.).abs() < 10.);
+    }
+
+    #[test]
+    fn test_privacy_budget() {
+        let mut budget = PrivacyBudget::new(1., 1e-5);
+        assert!(budget.epsilon().abs() < f64::EPSILON);
+
+        budget.spend();
+        assert!((budget.rho() - 0.5).abs() < f64::EPSILON);
+        let epsilon = 0.5 + 2. * (0.5 * 1e5_f64.ln()).sqrt();
+        assert!((budget.epsilon() - epsilon).abs() < 1e-9);
+
+        budget.spend();
+        assert!(budget.epsilon() > epsilon);
+    }
+}
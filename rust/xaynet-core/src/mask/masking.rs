@@ -13,6 +13,7 @@ use num::{
 };
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
@@ -67,7 +68,7 @@ pub enum AggregationError {
     ScalarMismatch,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// An aggregator for masks and masked models.
 pub struct Aggregation {
     nb_models: usize,
@@ -107,6 +108,11 @@ impl Aggregation {
         self.object_size
     }
 
+    /// Gets the number of aggregated masks or masked models.
+    pub fn nb_models(&self) -> usize {
+        self.nb_models
+    }
+
     /// Gets the masking configurations of the aggregator.
     pub fn config(&self) -> MaskConfigPair {
         MaskConfigPair {
@@ -111,6 +111,26 @@
 //! let local_mask_2 = local_mask_seed_2.derive_mask(number_weights, config.into());
 //! ```
 //!
+//! ## Differential privacy
+//! A [`Model`] can optionally be privatized with a [`DpConfig`] before it is masked. Its L2 norm
+//! is clipped to a bound and (discrete) Gaussian noise is added to its weights, either by each
+//! participant on its own or shared among a minimal number of participants. The [`PrivacyBudget`]
+//! keeps track of the privacy spent over the rounds.
+//!
+//! ```
+//! # use xaynet_core::mask::{BoundType, DataType, DpConfig, DpMode, FromPrimitives, GroupType, MaskConfig, Masker, Model, ModelType, NoiseMechanism};
+//! # let config = MaskConfig { group_type: GroupType::Prime, data_type: DataType::F32, bound_type: BoundType::B0, model_type: ModelType::M3};
+//! let local_model = Model::from_primitives_bounded(vec![0.5_f32; 10].into_iter());
+//! let dp = DpConfig {
+//!     clipping_bound: 1.0,
+//!     noise_multiplier: 1.0,
+//!     mechanism: NoiseMechanism::DiscreteGaussian,
+//!     mode: DpMode::Distributed { participants: 100 },
+//! };
+//! let private_local_model = dp.privatize(&local_model, config);
+//! let (local_mask_seed, masked_local_model) = Masker::new(config.into()).mask(1.0, &private_local_model);
+//! ```
+//!
 //! ## Aggregation
 //! Masked models can be aggregated via an [`Aggregation`]. Masks themselves can be aggregated via
 //! an [`Aggregation`] as well. An aggregated masked model can only be unmasked by the aggregation
@@ -182,6 +202,7 @@
 //! ```
 
 pub(crate) mod config;
+pub(crate) mod dp;
 pub(crate) mod masking;
 pub(crate) mod model;
 pub(crate) mod object;
@@ -198,8 +219,17 @@ pub use self::{
         MaskConfigPair,
         ModelType,
     },
+    dp::{DpConfig, DpMode, NoiseMechanism, PrivacyBudget},
     masking::{Aggregation, AggregationError, Masker, UnmaskingError},
-    model::{FromPrimitives, IntoPrimitives, Model, ModelCastError, PrimitiveCastError},
+    model::{
+        FromPrimitives,
+        IntoPrimitives,
+        Model,
+        ModelCastError,
+        PrimitiveCastError,
+        PrimitiveModel,
+        PrimitiveModelDecodeError,
+    },
     object::{
         serialization::vect::MaskVectBuffer,
         InvalidMaskObjectError,
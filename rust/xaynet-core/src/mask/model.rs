@@ -5,8 +5,10 @@
 //! [mask module]: crate::mask
 
 use std::{
+    convert::TryInto,
     fmt::Debug,
     iter::{FromIterator, IntoIterator},
+    mem,
     slice::{Iter, IterMut},
 };
 
@@ -20,6 +22,8 @@ use num::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::mask::config::DataType;
+
 #[derive(Debug, Clone, PartialEq, Hash, From, Index, IndexMut, Into, Serialize, Deserialize)]
 /// A numerical representation of a machine learning model.
 pub struct Model(Vec<Ratio<BigInt>>);
@@ -266,6 +270,147 @@ impl FromPrimitives<f64> for Model {
     }
 }
 
+/// A model with weights of a primitive data type.
+///
+/// This is a compact representation of a [`Model`] for its transmission, which is encoded as
+/// the primitive weights in little endian byte order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrimitiveModel {
+    /// A model with weights of type f32.
+    F32(Vec<f32>),
+    /// A model with weights of type f64.
+    F64(Vec<f64>),
+    /// A model with weights of type i32.
+    I32(Vec<i32>),
+    /// A model with weights of type i64.
+    I64(Vec<i64>),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Could not decode {len} bytes as primitive values of type {data_type:?}")]
+/// Errors related to the decoding of primitive models.
+pub struct PrimitiveModelDecodeError {
+    len: usize,
+    data_type: DataType,
+}
+
+/// Encodes primitive values in little endian byte order.
+macro_rules! encode_primitives {
+    ($values:expr) => {{
+        let mut bytes = Vec::with_capacity(mem::size_of_val(&$values[..]));
+        for value in $values.iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }};
+}
+
+/// Decodes primitive values of the given type from bytes in little endian byte order.
+macro_rules! decode_primitives {
+    ($bytes:expr, $primitive:ty) => {
+        $bytes
+            .chunks_exact(mem::size_of::<$primitive>())
+            // safe unwrap: the chunks have the size of the primitive type
+            .map(|chunk| <$primitive>::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    };
+}
+
+impl PrimitiveModel {
+    /// Converts the weights of the `model` into primitive values of the given data type.
+    ///
+    /// # Errors
+    /// Fails if a weight can't be converted into a primitive value.
+    pub fn from_model(model: &Model, data_type: DataType) -> Result<Self, ModelCastError> {
+        fn to_vec<P: 'static>(model: &Model) -> Result<Vec<P>, ModelCastError>
+        where
+            Model: IntoPrimitives<P>,
+        {
+            model.to_primitives().collect()
+        }
+
+        Ok(match data_type {
+            DataType::F32 => Self::F32(to_vec(model)?),
+            DataType::F64 => Self::F64(to_vec(model)?),
+            DataType::I32 => Self::I32(to_vec(model)?),
+            DataType::I64 => Self::I64(to_vec(model)?),
+        })
+    }
+
+    /// Converts the primitive weights into a model.
+    ///
+    /// Weights which are not finite are clamped, see [`FromPrimitives::from_primitives_bounded()`].
+    pub fn into_model(self) -> Model {
+        match self {
+            Self::F32(values) => Model::from_primitives_bounded(values.into_iter()),
+            Self::F64(values) => Model::from_primitives_bounded(values.into_iter()),
+            Self::I32(values) => Model::from_primitives_bounded(values.into_iter()),
+            Self::I64(values) => Model::from_primitives_bounded(values.into_iter()),
+        }
+    }
+
+    /// Gets the data type of the weights.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Self::F32(_) => DataType::F32,
+            Self::F64(_) => DataType::F64,
+            Self::I32(_) => DataType::I32,
+            Self::I64(_) => DataType::I64,
+        }
+    }
+
+    /// Gets the number of weights.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::F32(values) => values.len(),
+            Self::F64(values) => values.len(),
+            Self::I32(values) => values.len(),
+            Self::I64(values) => values.len(),
+        }
+    }
+
+    /// Checks whether the model has no weights.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Encodes the weights in little endian byte order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::F32(values) => encode_primitives!(values),
+            Self::F64(values) => encode_primitives!(values),
+            Self::I32(values) => encode_primitives!(values),
+            Self::I64(values) => encode_primitives!(values),
+        }
+    }
+
+    /// Decodes weights of the given data type from bytes in little endian byte order.
+    ///
+    /// # Errors
+    /// Fails if the number of bytes is not a multiple of the size of the data type.
+    pub fn from_bytes(
+        bytes: &[u8],
+        data_type: DataType,
+    ) -> Result<Self, PrimitiveModelDecodeError> {
+        let size = match data_type {
+            DataType::F32 | DataType::I32 => 4,
+            DataType::F64 | DataType::I64 => 8,
+        };
+        if bytes.len() % size != 0 {
+            return Err(PrimitiveModelDecodeError {
+                len: bytes.len(),
+                data_type,
+            });
+        }
+        Ok(match data_type {
+            DataType::F32 => Self::F32(decode_primitives!(bytes, f32)),
+            DataType::F64 => Self::F64(decode_primitives!(bytes, f64)),
+            DataType::I32 => Self::I32(decode_primitives!(bytes, i32)),
+            DataType::I64 => Self::I64(decode_primitives!(bytes, i64)),
+        })
+    }
+}
+
 /// Converts a numerical value into a primitive floating point value.
 ///
 /// # Errors
@@ -462,4 +607,40 @@ mod tests {
         let ratio = &f64_max * BigInt::from(10_usize) / (f64_max * BigInt::from(100_usize));
         assert_eq!(ratio_to_float::<f64>(&ratio).unwrap(), 0.1_f64);
     }
+
+    #[test]
+    fn test_primitive_model() {
+        let model = Model::from_primitives(vec![-1_i32, 0, 2].into_iter()).unwrap();
+
+        let primitive_model = PrimitiveModel::from_model(&model, DataType::I32).unwrap();
+        assert_eq!(primitive_model, PrimitiveModel::I32(vec![-1, 0, 2]));
+        let bytes = primitive_model.to_bytes();
+        assert_eq!(bytes, [255, 255, 255, 255, 0, 0, 0, 0, 2, 0, 0, 0]);
+        let decoded = PrimitiveModel::from_bytes(&bytes, DataType::I32).unwrap();
+        assert_eq!(decoded, primitive_model);
+        assert_eq!(decoded.into_model(), model);
+
+        let primitive_model = PrimitiveModel::from_model(&model, DataType::F64).unwrap();
+        assert_eq!(primitive_model, PrimitiveModel::F64(vec![-1., 0., 2.]));
+        assert_eq!(primitive_model.data_type(), DataType::F64);
+        assert_eq!(primitive_model.len(), 3);
+        let bytes = primitive_model.to_bytes();
+        assert_eq!(bytes.len(), 24);
+        let decoded = PrimitiveModel::from_bytes(&bytes, DataType::F64).unwrap();
+        assert_eq!(decoded.into_model(), model);
+    }
+
+    #[test]
+    fn test_primitive_model_invalid() {
+        let model = Model::from_primitives(iter::once(i64::MAX)).unwrap();
+        assert!(PrimitiveModel::from_model(&model, DataType::I32).is_err());
+
+        assert_eq!(
+            PrimitiveModel::from_bytes(&[0; 6], DataType::F32).unwrap_err(),
+            PrimitiveModelDecodeError {
+                len: 6,
+                data_type: DataType::F32,
+            },
+        );
+    }
 }
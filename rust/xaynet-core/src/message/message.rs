@@ -20,6 +20,16 @@ pub const SUM_COUNT_MIN: u64 = 1;
 /// The minimum number of accepted `update` messages for the PET protocol to function correctly.
 pub const UPDATE_COUNT_MIN: u64 = 3;
 
+/// The version of the PET protocol which is implemented by this crate.
+///
+/// The version is bumped whenever the layout of a message or its payload changes. Messages of
+/// version `0` have been sent by participants which predate the versioning, when the version field
+/// was still reserved and zeroed. Version `2` introduced chunks with an extended header, see
+/// [`ChunkBuffer`].
+///
+/// [`ChunkBuffer`]: crate::message::ChunkBuffer
+pub const PROTOCOL_VERSION: u16 = 2;
+
 pub(crate) mod ranges {
     use std::ops::Range;
 
@@ -41,12 +51,12 @@ pub(crate) mod ranges {
     pub const TAG: usize = LENGTH.end;
     /// Byte range corresponding to the flags in a message header
     pub const FLAGS: usize = TAG + 1;
-    /// Byte range reserved for future use
-    pub const RESERVED: Range<usize> = range(FLAGS + 1, 2);
+    /// Byte range corresponding to the protocol version in a message header
+    pub const VERSION: Range<usize> = range(FLAGS + 1, 2);
 }
 
 /// Length in bytes of a message header
-pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
+pub const HEADER_LENGTH: usize = ranges::VERSION.end;
 
 /// A wrapper around a buffer that contains a [`Message`].
 ///
@@ -123,7 +133,7 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// |                             length                            |
 /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
-/// |      tag      |     flags     |          reserved             |
+/// |      tag      |     flags     |           version             |
 /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// |                                                               |
 /// +                    payload (variable length)                  +
@@ -144,8 +154,10 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 ///   messages can be as big as 2^32 = 4,294,967,296 bytes.
 /// - `tag` indicates the type of message (sum, update, sum2 or
 ///   multipart message)
-/// - the `flags` field currently supports a single flag, that
-///   indicates whether this is a multipart message
+/// - the `flags` field indicates whether this is a multipart message
+///   and whether its payload is compressed, see [`Flags`]
+/// - `version` is the version of the PET protocol the message
+///   complies with, see [`PROTOCOL_VERSION`]
 ///
 /// # Examples
 /// ## Reading a sum message
@@ -160,7 +172,7 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 /// bytes.extend(&200_u32.to_be_bytes()); // Length field
 /// bytes.push(0x01); // tag (sum message)
 /// bytes.push(0x00); // flags (not a multipart message)
-/// bytes.extend(vec![0x00, 0x00]); // reserved
+/// bytes.extend(vec![0x00, 0x02]); // protocol version
 ///
 /// // Payload: a sum message contains a signature and an ephemeral public key
 /// bytes.extend(vec![0xaa; 32]); // signature
@@ -172,6 +184,7 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 /// assert_eq!(buffer.coordinator_pk(), vec![0x33; 32].as_slice());
 /// assert_eq!(Tag::try_from(buffer.tag()).unwrap(), Tag::Sum);
 /// assert_eq!(Flags::try_from(buffer.flags()).unwrap(), Flags::empty());
+/// assert_eq!(buffer.version(), 2);
 /// assert_eq!(
 ///     buffer.payload(),
 ///     [vec![0xaa; 32], vec![0xbb; 32]].concat().as_slice()
@@ -190,7 +203,7 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 /// expected.extend(&200_u32.to_be_bytes()); // length field
 /// expected.push(0x01); // tag (sum message)
 /// expected.push(0x00); // flags (not a multipart message)
-/// expected.extend(vec![0x00, 0x00]); // reserved
+/// expected.extend(vec![0x00, 0x02]); // protocol version
 ///
 /// // Payload: a sum message contains a signature and an ephemeral public key
 /// expected.extend(vec![0xaa; 32]); // signature
@@ -210,6 +223,7 @@ pub const HEADER_LENGTH: usize = ranges::RESERVED.end;
 /// buffer.set_length(200 as u32);
 /// buffer.set_tag(Tag::Sum.into());
 /// buffer.set_flags(Flags::empty());
+/// buffer.set_version(2);
 /// buffer
 ///     .payload_mut()
 ///     .copy_from_slice([vec![0xaa; 32], vec![0xbb; 32]].concat().as_slice());
@@ -296,6 +310,16 @@ impl<T: AsRef<[u8]>> MessageBuffer<T> {
         // long
         u32::from_be_bytes(self.inner.as_ref()[ranges::LENGTH].try_into().unwrap())
     }
+
+    /// Gets the protocol version field.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn version(&self) -> u16 {
+        // Unwrapping is OK, as the slice is guaranteed to be 2 bytes
+        // long
+        u16::from_be_bytes(self.inner.as_ref()[ranges::VERSION].try_into().unwrap())
+    }
 }
 
 impl<'a, T: AsRef<[u8]> + ?Sized> MessageBuffer<&'a T> {
@@ -384,6 +408,15 @@ impl<T: AsMut<[u8]> + AsRef<[u8]>> MessageBuffer<T> {
         self.inner.as_mut()[ranges::LENGTH].copy_from_slice(&bytes[..]);
     }
 
+    /// Sets the protocol version field.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn set_version(&mut self, value: u16) {
+        let bytes = value.to_be_bytes();
+        self.inner.as_mut()[ranges::VERSION].copy_from_slice(&bytes[..]);
+    }
+
     /// Gets a mutable reference to the message signature field.
     ///
     /// # Panics
@@ -433,6 +466,10 @@ bitflags::bitflags! {
     pub struct Flags: u8 {
         /// Indicates whether this message is a multipart message
         const MULTIPART = 1 << 0;
+        /// Indicates whether the payload is compressed with zstd. Only
+        /// multipart messages can be compressed, in which case the
+        /// concatenated data of all the chunks is compressed.
+        const COMPRESSED = 1 << 1;
     }
 }
 
@@ -483,6 +520,9 @@ pub struct Message {
     pub coordinator_pk: PublicEncryptKey,
     /// Wether this is a multipart message
     pub is_multipart: bool,
+    /// Whether the payload of the full message is compressed. This
+    /// can only be `true` for multipart messages.
+    pub is_compressed: bool,
     /// The type of message. This information is partially redundant
     /// with the `payload` field. So when serializing the message,
     /// this field is ignored if the payload is a [`Payload::Sum`],
@@ -506,6 +546,7 @@ impl Message {
             participant_pk,
             coordinator_pk,
             is_multipart: false,
+            is_compressed: false,
             tag: Tag::Sum,
             payload: message.into(),
         }
@@ -523,6 +564,7 @@ impl Message {
             participant_pk,
             coordinator_pk,
             is_multipart: false,
+            is_compressed: false,
             tag: Tag::Sum2,
             payload: message.into(),
         }
@@ -540,6 +582,7 @@ impl Message {
             participant_pk,
             coordinator_pk,
             is_multipart: false,
+            is_compressed: false,
             tag: Tag::Update,
             payload: message.into(),
         }
@@ -558,6 +601,7 @@ impl Message {
             participant_pk,
             coordinator_pk,
             is_multipart: true,
+            is_compressed: false,
             tag,
             payload: message.into(),
         }
@@ -576,7 +620,12 @@ impl Message {
             .context("failed to parse public key")?;
 
         let tag = reader.tag().try_into()?;
-        let is_multipart = reader.flags().contains(Flags::MULTIPART);
+        let flags = reader.flags();
+        let is_multipart = flags.contains(Flags::MULTIPART);
+        let is_compressed = flags.contains(Flags::COMPRESSED);
+        if is_compressed && !is_multipart {
+            return Err(anyhow!("only multipart messages can be compressed"));
+        }
 
         let payload = if is_multipart {
             Chunk::from_byte_slice(&reader.payload()).map(Into::into)
@@ -595,13 +644,15 @@ impl Message {
             signature: Some(signature),
             payload,
             is_multipart,
+            is_compressed,
             tag,
         })
     }
 
-    /// Serialize this message. If the `signature` attribute is
-    /// `Some`, the signature will be directly inserted in the message
-    /// header. Otherwise it will be computed.
+    /// Serialize this message with the current [`PROTOCOL_VERSION`]. If
+    /// the `signature` attribute is `Some`, the signature will be
+    /// directly inserted in the message header. Otherwise it will be
+    /// computed.
     ///
     /// # Panic
     ///
@@ -618,12 +669,11 @@ impl Message {
             .to_bytes(&mut writer.participant_pk_mut());
         self.coordinator_pk
             .to_bytes(&mut writer.coordinator_pk_mut());
-        let flags = if self.is_multipart {
-            Flags::MULTIPART
-        } else {
-            Flags::empty()
-        };
+        let mut flags = Flags::empty();
+        flags.set(Flags::MULTIPART, self.is_multipart);
+        flags.set(Flags::COMPRESSED, self.is_compressed);
         writer.set_flags(flags);
+        writer.set_version(PROTOCOL_VERSION);
         self.payload.to_bytes(&mut writer.payload_mut());
         // Determine the tag from the payload type if
         // possible. Otherwise, use the self.tag field.
@@ -655,6 +705,7 @@ mod tests {
 
     use super::*;
     use crate::{
+        crypto::SigningKeyPair,
         message::{Message, Tag},
         testutils::messages as helpers,
     };
@@ -678,6 +729,7 @@ mod tests {
             helpers::coordinator_pk().1.as_slice()
         );
         assert_eq!(buffer.length() as usize, bytes.len());
+        assert_eq!(buffer.version(), PROTOCOL_VERSION);
         assert_eq!(buffer.payload(), helpers::sum::payload().1.as_slice());
     }
 
@@ -698,9 +750,39 @@ mod tests {
             .copy_from_slice(helpers::coordinator_pk().1.as_slice());
         buffer.set_tag(Tag::Sum.into());
         buffer.set_length(expected.len() as u32);
+        buffer.set_version(PROTOCOL_VERSION);
         buffer
             .payload_mut()
             .copy_from_slice(helpers::sum::payload().1.as_slice());
         assert_eq!(bytes, expected);
     }
+
+    #[test]
+    fn compressed_message() {
+        let keys = SigningKeyPair::generate();
+        let chunk = Chunk {
+            id: 0,
+            message_id: 1,
+            last: true,
+            count: None,
+            data: vec![0xff; 4],
+        };
+        let mut message =
+            Message::new_multipart(keys.public, helpers::coordinator_pk().0, chunk, Tag::Update);
+        message.is_compressed = true;
+        let mut bytes = vec![0; message.buffer_length()];
+        message.to_bytes(&mut bytes, &keys.secret);
+
+        let buffer = MessageBuffer::new(&bytes).unwrap();
+        assert_eq!(buffer.flags(), Flags::MULTIPART | Flags::COMPRESSED);
+        let parsed = Message::from_byte_slice(&bytes).unwrap();
+        message.signature = parsed.signature;
+        assert_eq!(parsed, message);
+
+        // only multipart messages can be compressed
+        MessageBuffer::new(&mut bytes)
+            .unwrap()
+            .set_flags(Flags::COMPRESSED);
+        assert!(Message::from_byte_slice(&bytes).is_err());
+    }
 }
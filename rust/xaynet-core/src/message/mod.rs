@@ -36,6 +36,7 @@ pub use self::{
         MessageBuffer,
         Tag,
         HEADER_LENGTH as MESSAGE_HEADER_LENGTH,
+        PROTOCOL_VERSION,
         SUM_COUNT_MIN,
         UPDATE_COUNT_MIN,
     },
@@ -19,30 +19,61 @@ pub(crate) mod ranges {
     pub const FLAGS: usize = MESSAGE_ID.end;
     /// Byte range reserved for future use
     pub const RESERVED: Range<usize> = range(FLAGS + 1, 3);
+    /// Byte range corresponding to the chunk ID in an extended chunk
+    /// message
+    pub const EXTENDED_ID: Range<usize> = range(RESERVED.end, 4);
+    /// Byte range corresponding to the message ID in an extended
+    /// chunk message
+    pub const EXTENDED_MESSAGE_ID: Range<usize> = range(EXTENDED_ID.end, 4);
+    /// Byte range corresponding to the total number of chunks in an
+    /// extended chunk message
+    pub const COUNT: Range<usize> = range(EXTENDED_MESSAGE_ID.end, 4);
+    /// Byte range corresponding to the checksum of the data in an
+    /// extended chunk message
+    pub const CHECKSUM: Range<usize> = range(COUNT.end, 4);
 }
 
 /// Length in bytes of a chunk message header
 const HEADER_LENGTH: usize = ranges::RESERVED.end;
 
+/// Length in bytes of an extended chunk message header
+const EXTENDED_HEADER_LENGTH: usize = ranges::CHECKSUM.end;
+
 /// A message chunk.
+///
+/// A chunk is serialized with the extended header if its total
+/// number of chunks `count` is set, otherwise with the legacy header
+/// which only supports IDs up to [`u16::MAX`].
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct Chunk {
     /// Chunk ID
-    pub id: u16,
+    pub id: u32,
     /// ID of the message this chunk belongs to
-    pub message_id: u16,
+    pub message_id: u32,
     /// `true` if this is the last chunk of the message, `false` otherwise
     pub last: bool,
+    /// The total number of chunks of the message. It is only part of
+    /// the extended header.
+    pub count: Option<u32>,
     /// Data contained in this chunk.
     pub data: Vec<u8>,
 }
 
+impl Chunk {
+    /// Checks whether the chunk is serialized with the extended header.
+    pub fn is_extended(&self) -> bool {
+        self.count.is_some()
+    }
+}
+
 bitflags::bitflags! {
     /// A bitmask that defines flags for a [`Chunk`].
     pub struct Flags: u8 {
         /// Indicates whether this message is the last chunk of a
         /// multipart message
         const LAST_CHUNK = 1 << 0;
+        /// Indicates whether the chunk has an extended header
+        const EXTENDED = 1 << 1;
     }
 }
 
@@ -61,8 +92,36 @@ bitflags::bitflags! {
 ///
 /// - `id`: ID of the chunk
 /// - `message_id`: ID of the message this chunk belong to
-/// - `flags`: currently the only supported flag indicates whether
-///   this is the last chunk or not
+/// - `flags`: indicates whether this is the last chunk and whether
+///   the chunk has an extended header
+///
+/// A chunk with the `EXTENDED` flag has an extended header, which
+/// supports larger messages. The legacy `id` and `message_id` fields
+/// are zeroed:
+///
+/// ```no_rust
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                            reserved                           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |     flags     |                    reserved                   |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                               id                              |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                           message_id                          |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                             count                             |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                            checksum                           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                                                               |
+/// +                       data (variable length)                  +
+/// |                                                               |
+/// ```
+///
+/// - `count`: total number of chunks of the message
+/// - `checksum`: CRC-32 checksum of the data
 pub struct ChunkBuffer<T> {
     inner: T,
 }
@@ -100,9 +159,28 @@ impl<T: AsRef<[u8]>> ChunkBuffer<T> {
                 HEADER_LENGTH
             ));
         }
+        if len < self.header_length() {
+            return Err(anyhow!(
+                "invalid buffer length for an extended chunk: {} < {}",
+                len,
+                EXTENDED_HEADER_LENGTH
+            ));
+        }
         Ok(())
     }
 
+    /// Gets the length of the header, which depends on the `EXTENDED` flag.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn header_length(&self) -> usize {
+        if self.flags().contains(Flags::EXTENDED) {
+            EXTENDED_HEADER_LENGTH
+        } else {
+            HEADER_LENGTH
+        }
+    }
+
     /// Gets the flags field.
     ///
     /// # Panics
@@ -115,20 +193,62 @@ impl<T: AsRef<[u8]>> ChunkBuffer<T> {
     ///
     /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
-    pub fn id(&self) -> u16 {
-        // Unwrapping is OK, as the slice is guaranteed to be 4 bytes
-        // long
-        u16::from_be_bytes(self.inner.as_ref()[ranges::ID].try_into().unwrap())
+    pub fn id(&self) -> u32 {
+        if self.flags().contains(Flags::EXTENDED) {
+            self.read_u32(ranges::EXTENDED_ID)
+        } else {
+            self.read_u16(ranges::ID) as u32
+        }
     }
 
     /// Gets the message ID field
     ///
     /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
-    pub fn message_id(&self) -> u16 {
+    pub fn message_id(&self) -> u32 {
+        if self.flags().contains(Flags::EXTENDED) {
+            self.read_u32(ranges::EXTENDED_MESSAGE_ID)
+        } else {
+            self.read_u16(ranges::MESSAGE_ID) as u32
+        }
+    }
+
+    /// Gets the total number of chunks field. This is `None` if the
+    /// chunk doesn't have an extended header.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn count(&self) -> Option<u32> {
+        if self.flags().contains(Flags::EXTENDED) {
+            Some(self.read_u32(ranges::COUNT))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the checksum field. This is `None` if the chunk doesn't
+    /// have an extended header.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn checksum(&self) -> Option<u32> {
+        if self.flags().contains(Flags::EXTENDED) {
+            Some(self.read_u32(ranges::CHECKSUM))
+        } else {
+            None
+        }
+    }
+
+    fn read_u16(&self, range: std::ops::Range<usize>) -> u16 {
+        // Unwrapping is OK, as the slice is guaranteed to be 2 bytes
+        // long
+        u16::from_be_bytes(self.inner.as_ref()[range].try_into().unwrap())
+    }
+
+    fn read_u32(&self, range: std::ops::Range<usize>) -> u32 {
         // Unwrapping is OK, as the slice is guaranteed to be 4 bytes
         // long
-        u16::from_be_bytes(self.inner.as_ref()[ranges::MESSAGE_ID].try_into().unwrap())
+        u32::from_be_bytes(self.inner.as_ref()[range].try_into().unwrap())
     }
 }
 
@@ -138,12 +258,26 @@ impl<'a, T: AsRef<[u8]> + ?Sized> ChunkBuffer<&'a T> {
     /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
     pub fn payload(&self) -> &'a [u8] {
-        &self.inner.as_ref()[HEADER_LENGTH..]
+        &self.inner.as_ref()[self.header_length()..]
+    }
+
+    /// Checks that the checksum of an extended chunk matches its data.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn check_checksum(&self) -> Result<(), DecodeError> {
+        match self.checksum() {
+            Some(checksum) if checksum != crc32fast::hash(self.payload()) => {
+                Err(anyhow!("invalid chunk checksum"))
+            }
+            _ => Ok(()),
+        }
     }
 }
 
 impl<T: AsMut<[u8]> + AsRef<[u8]>> ChunkBuffer<T> {
-    /// Sets the flags field.
+    /// Sets the flags field. The flags must be set before the other
+    /// fields, because the `EXTENDED` flag determines their position.
     ///
     /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
@@ -154,19 +288,43 @@ impl<T: AsMut<[u8]> + AsRef<[u8]>> ChunkBuffer<T> {
     /// Sets the chunk ID field.
     ///
     /// # Panics
-    /// Accessing the field may panic if the buffer has not been checked before.
-    pub fn set_id(&mut self, value: u16) {
-        let bytes = value.to_be_bytes();
-        self.inner.as_mut()[ranges::ID].copy_from_slice(&bytes[..]);
+    /// Accessing the field may panic if the buffer has not been checked before or if the `value`
+    /// exceeds [`u16::MAX`] in a chunk without an extended header.
+    pub fn set_id(&mut self, value: u32) {
+        if self.flags().contains(Flags::EXTENDED) {
+            self.write_u32(ranges::EXTENDED_ID, value);
+        } else {
+            self.write_u16(ranges::ID, value);
+        }
     }
 
     /// Sets the message ID field.
     ///
     /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before or if the `value`
+    /// exceeds [`u16::MAX`] in a chunk without an extended header.
+    pub fn set_message_id(&mut self, value: u32) {
+        if self.flags().contains(Flags::EXTENDED) {
+            self.write_u32(ranges::EXTENDED_MESSAGE_ID, value);
+        } else {
+            self.write_u16(ranges::MESSAGE_ID, value);
+        }
+    }
+
+    /// Sets the total number of chunks field of an extended chunk.
+    ///
+    /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
-    pub fn set_message_id(&mut self, value: u16) {
-        let bytes = value.to_be_bytes();
-        self.inner.as_mut()[ranges::MESSAGE_ID].copy_from_slice(&bytes[..]);
+    pub fn set_count(&mut self, value: u32) {
+        self.write_u32(ranges::COUNT, value);
+    }
+
+    /// Sets the checksum field of an extended chunk.
+    ///
+    /// # Panics
+    /// Accessing the field may panic if the buffer has not been checked before.
+    pub fn set_checksum(&mut self, value: u32) {
+        self.write_u32(ranges::CHECKSUM, value);
     }
 
     /// Gets a mutable reference to the rest of the message.
@@ -174,17 +332,42 @@ impl<T: AsMut<[u8]> + AsRef<[u8]>> ChunkBuffer<T> {
     /// # Panics
     /// Accessing the field may panic if the buffer has not been checked before.
     pub fn payload_mut(&mut self) -> &mut [u8] {
-        &mut self.inner.as_mut()[HEADER_LENGTH..]
+        let header_length = self.header_length();
+        &mut self.inner.as_mut()[header_length..]
+    }
+
+    fn write_u16(&mut self, range: std::ops::Range<usize>, value: u32) {
+        let value: u16 = value
+            .try_into()
+            .expect("the chunk requires an extended header");
+        self.inner.as_mut()[range].copy_from_slice(&value.to_be_bytes());
+    }
+
+    fn write_u32(&mut self, range: std::ops::Range<usize>, value: u32) {
+        self.inner.as_mut()[range].copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Checks that the chunk ID is within the total number of chunks.
+fn check_count(id: u32, count: Option<u32>) -> Result<(), DecodeError> {
+    match count {
+        Some(count) if id >= count => Err(anyhow!("invalid chunk id: {} >= {} chunks", id, count)),
+        _ => Ok(()),
     }
 }
 
 impl FromBytes for Chunk {
     fn from_byte_slice<T: AsRef<[u8]>>(buffer: &T) -> Result<Self, DecodeError> {
         let reader = ChunkBuffer::new(buffer.as_ref()).context("Invalid chunk buffer")?;
+        reader.check_checksum()?;
+        let id = reader.id();
+        let count = reader.count();
+        check_count(id, count)?;
         Ok(Self {
             last: reader.flags().contains(Flags::LAST_CHUNK),
-            id: reader.id(),
+            id,
             message_id: reader.message_id(),
+            count,
             data: reader.payload().to_vec(),
         })
     }
@@ -198,31 +381,64 @@ impl FromBytes for Chunk {
         let id = u16::from_byte_stream(iter).context("cannot parse id")?;
         let message_id = u16::from_byte_stream(iter).context("cannot parse message id")?;
         let flags = Flags::from_bits_truncate(iter.next().unwrap());
-        let data: Vec<u8> = iter.skip(3).collect();
+        let mut iter = iter.skip(3);
+        if !flags.contains(Flags::EXTENDED) {
+            return Ok(Self {
+                id: id as u32,
+                message_id: message_id as u32,
+                data: iter.collect(),
+                last: flags.contains(Flags::LAST_CHUNK),
+                count: None,
+            });
+        }
+
+        if iter.len() < EXTENDED_HEADER_LENGTH - HEADER_LENGTH {
+            return Err(anyhow!("byte stream exhausted"));
+        }
+        let id = u32::from_byte_stream(&mut iter).context("cannot parse id")?;
+        let message_id = u32::from_byte_stream(&mut iter).context("cannot parse message id")?;
+        let count = u32::from_byte_stream(&mut iter).context("cannot parse count")?;
+        let checksum = u32::from_byte_stream(&mut iter).context("cannot parse checksum")?;
+        let data: Vec<u8> = iter.collect();
+        if checksum != crc32fast::hash(&data) {
+            return Err(anyhow!("invalid chunk checksum"));
+        }
+        check_count(id, Some(count))?;
         Ok(Self {
             id,
             message_id,
             data,
             last: flags.contains(Flags::LAST_CHUNK),
+            count: Some(count),
         })
     }
 }
 
 impl ToBytes for Chunk {
     fn buffer_length(&self) -> usize {
-        HEADER_LENGTH + self.data.len()
+        if self.is_extended() {
+            EXTENDED_HEADER_LENGTH + self.data.len()
+        } else {
+            HEADER_LENGTH + self.data.len()
+        }
     }
 
+    /// Serializes the chunk.
+    ///
+    /// # Panics
+    /// Panics if the IDs of a chunk without a total number of chunks exceed [`u16::MAX`].
     fn to_bytes<T: AsMut<[u8]> + AsRef<[u8]>>(&self, buffer: &mut T) {
-        let mut writer = ChunkBuffer::new(buffer.as_mut()).unwrap();
-        let flags = if self.last {
-            Flags::LAST_CHUNK
-        } else {
-            Flags::empty()
-        };
+        let mut writer = ChunkBuffer::new_unchecked(buffer.as_mut());
+        let mut flags = Flags::empty();
+        flags.set(Flags::LAST_CHUNK, self.last);
+        flags.set(Flags::EXTENDED, self.is_extended());
         writer.set_flags(flags);
         writer.set_id(self.id);
         writer.set_message_id(self.message_id);
+        if let Some(count) = self.count {
+            writer.set_count(count);
+            writer.set_checksum(crc32fast::hash(&self.data));
+        }
         writer.payload_mut()[..self.data.len()].copy_from_slice(self.data.as_slice());
     }
 }
@@ -259,9 +475,30 @@ mod tests {
         bytes.extend(data());
 
         let message = Chunk {
-            id: id().1,
-            message_id: message_id().1,
+            id: id().1 as u32,
+            message_id: message_id().1 as u32,
             last: flags().1.contains(Flags::LAST_CHUNK),
+            count: None,
+            data: data(),
+        };
+        (bytes, message)
+    }
+
+    fn extended_chunk() -> (Vec<u8>, Chunk) {
+        let mut bytes = vec![0x00; 4];
+        bytes.push((Flags::LAST_CHUNK | Flags::EXTENDED).bits());
+        bytes.extend(vec![0x00, 0x00, 0x00]);
+        bytes.extend(&0x0001_dddd_u32.to_be_bytes());
+        bytes.extend(&0xeeee_eeee_u32.to_be_bytes());
+        bytes.extend(&0x0001_ddde_u32.to_be_bytes());
+        bytes.extend(&crc32fast::hash(&data()).to_be_bytes());
+        bytes.extend(data());
+
+        let message = Chunk {
+            id: 0x0001_dddd,
+            message_id: 0xeeee_eeee,
+            last: true,
+            count: Some(0x0001_ddde),
             data: data(),
         };
         (bytes, message)
@@ -271,9 +508,10 @@ mod tests {
     fn buffer_read() {
         let bytes = chunk().0;
         let buffer = ChunkBuffer::new(&bytes).unwrap();
-        assert_eq!(buffer.id(), id().1);
-        assert_eq!(buffer.message_id(), message_id().1);
+        assert_eq!(buffer.id(), id().1 as u32);
+        assert_eq!(buffer.message_id(), message_id().1 as u32);
         assert_eq!(buffer.flags(), flags().1);
+        assert_eq!(buffer.count(), None);
         assert_eq!(buffer.payload(), &data()[..]);
     }
 
@@ -290,10 +528,48 @@ mod tests {
         let mut bytes = vec![0; expected.len()];
         let mut buffer = ChunkBuffer::new_unchecked(&mut bytes);
 
-        buffer.set_id(id().1);
-        buffer.set_message_id(message_id().1);
         buffer.set_flags(flags().1);
+        buffer.set_id(id().1 as u32);
+        buffer.set_message_id(message_id().1 as u32);
         buffer.payload_mut().copy_from_slice(data().as_slice());
         assert_eq!(bytes, expected);
     }
+
+    #[test]
+    fn extended_chunk_roundtrip() {
+        let (bytes, expected) = extended_chunk();
+        assert_eq!(expected.buffer_length(), bytes.len());
+
+        let buffer = ChunkBuffer::new(&bytes).unwrap();
+        assert_eq!(buffer.header_length(), EXTENDED_HEADER_LENGTH);
+        assert_eq!(buffer.count(), expected.count);
+        assert_eq!(Chunk::from_byte_slice(&bytes).unwrap(), expected);
+        assert_eq!(
+            Chunk::from_byte_stream(&mut bytes.clone().into_iter()).unwrap(),
+            expected
+        );
+
+        let mut serialized = vec![0; expected.buffer_length()];
+        expected.to_bytes(&mut serialized);
+        assert_eq!(serialized, bytes);
+    }
+
+    #[test]
+    fn extended_chunk_invalid() {
+        // corrupted data
+        let mut bytes = extended_chunk().0;
+        *bytes.last_mut().unwrap() = 0x00;
+        assert!(Chunk::from_byte_slice(&bytes).is_err());
+        assert!(Chunk::from_byte_stream(&mut bytes.into_iter()).is_err());
+
+        // id out of the total number of chunks
+        let mut chunk = extended_chunk().1;
+        chunk.count = Some(chunk.id);
+        let mut bytes = vec![0; chunk.buffer_length()];
+        chunk.to_bytes(&mut bytes);
+        assert!(Chunk::from_byte_slice(&bytes).is_err());
+
+        // truncated header
+        assert!(ChunkBuffer::new(&extended_chunk().0[..HEADER_LENGTH + 4]).is_err());
+    }
 }
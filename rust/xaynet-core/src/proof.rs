@@ -0,0 +1,277 @@
+//! Proofs of global models.
+//!
+//! After each round, the coordinator publishes a [`ModelProof`] of the new global model to a trust
+//! anchor. The proofs are signed by the coordinator and hash-chained, i.e. each proof contains the
+//! hash of its predecessor. Hence, the published proofs form an append-only log in which no proof
+//! can be altered, removed or inserted without being detected by [`verify_chain()`].
+//!
+//! A participant can check that a downloaded global model matches its published proof via
+//! [`ModelProof::verify_model()`].
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    common::RoundSeed,
+    crypto::{ByteObject, PublicSigningKey, SecretSigningKey, Sha256, Signature},
+    mask::Model,
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+/// Errors related to the verification of model proofs.
+pub enum ProofError {
+    #[error("invalid signature of the proof of round {0}")]
+    InvalidSignature(u64),
+    #[error("the proof of round {0} is not chained to its predecessor")]
+    BrokenChain(u64),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid model proof: {0}")]
+/// An error related to the parsing of a model proof.
+pub struct ParseProofError(&'static str);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A signed proof of a global model.
+pub struct ModelProof {
+    /// The round in which the global model was created.
+    pub round_id: u64,
+    /// The seed of the round in which the global model was created.
+    pub round_seed: RoundSeed,
+    /// The hash of the global model, see [`ModelProof::hash_model()`].
+    pub model_hash: Sha256,
+    /// The hash of the previous proof or zero for the first proof.
+    pub prev_hash: Sha256,
+    /// The signature of the coordinator.
+    pub signature: Signature,
+}
+
+impl ModelProof {
+    /// Creates a proof of the global `model` which is chained to the `prev`ious proof and signed
+    /// with the `secret_key` of the coordinator.
+    pub fn new(
+        round_id: u64,
+        round_seed: RoundSeed,
+        model: &Model,
+        prev: Option<&ModelProof>,
+        secret_key: &SecretSigningKey,
+    ) -> Self {
+        let mut proof = Self {
+            round_id,
+            round_seed,
+            model_hash: Self::hash_model(model),
+            prev_hash: prev.map(ModelProof::hash).unwrap_or_else(Sha256::zeroed),
+            signature: Signature::zeroed(),
+        };
+        proof.signature = secret_key.sign_detached(&proof.signed_data());
+        proof
+    }
+
+    /// Computes the hash of a global `model`.
+    ///
+    /// The hash is computed over the numerators and denominators of the weights of the model in
+    /// their signed little endian byte representation, each prefixed with its length.
+    pub fn hash_model(model: &Model) -> Sha256 {
+        let mut data = Vec::new();
+        for weight in model.iter() {
+            for int in [weight.numer(), weight.denom()].iter() {
+                let bytes = int.to_signed_bytes_le();
+                data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                data.extend_from_slice(&bytes);
+            }
+        }
+        Sha256::hash(&data)
+    }
+
+    /// Computes the hash of this proof, which is included in the next proof.
+    pub fn hash(&self) -> Sha256 {
+        let mut data = self.signed_data();
+        data.extend_from_slice(self.signature.as_slice());
+        Sha256::hash(&data)
+    }
+
+    /// Verifies the signature of this proof with the `public_key` of the coordinator.
+    pub fn verify_signature(&self, public_key: &PublicSigningKey) -> bool {
+        public_key.verify_detached(&self.signature, &self.signed_data())
+    }
+
+    /// Checks that the global `model` matches this proof.
+    pub fn verify_model(&self, model: &Model) -> bool {
+        self.model_hash == Self::hash_model(model)
+    }
+
+    // Gets the data which is signed by the coordinator.
+    fn signed_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + RoundSeed::LENGTH + 2 * Sha256::LENGTH);
+        data.extend_from_slice(&self.round_id.to_le_bytes());
+        data.extend_from_slice(self.round_seed.as_slice());
+        data.extend_from_slice(self.model_hash.as_slice());
+        data.extend_from_slice(self.prev_hash.as_slice());
+        data
+    }
+}
+
+/// Verifies a log of `proofs`, ordered from the first to the latest proof, with the
+/// `public_key` of the coordinator.
+///
+/// # Errors
+/// Fails if any proof isn't signed by the coordinator or isn't chained to its predecessor.
+pub fn verify_chain(
+    proofs: &[ModelProof],
+    public_key: &PublicSigningKey,
+) -> Result<(), ProofError> {
+    let mut prev_hash = Sha256::zeroed();
+    for proof in proofs {
+        if !proof.verify_signature(public_key) {
+            return Err(ProofError::InvalidSignature(proof.round_id));
+        }
+        if proof.prev_hash != prev_hash {
+            return Err(ProofError::BrokenChain(proof.round_id));
+        }
+        prev_hash = proof.hash();
+    }
+    Ok(())
+}
+
+/// Formats a proof as a single line of whitespace separated fields, where all fields except the
+/// round id are hex encoded.
+impl fmt::Display for ModelProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.round_id,
+            hex::encode(self.round_seed.as_slice()),
+            hex::encode(self.model_hash.as_slice()),
+            hex::encode(self.prev_hash.as_slice()),
+            hex::encode(self.signature.as_slice()),
+        )
+    }
+}
+
+impl FromStr for ModelProof {
+    type Err = ParseProofError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_field<T: ByteObject>(
+            field: Option<&str>,
+            name: &'static str,
+        ) -> Result<T, ParseProofError> {
+            field
+                .and_then(|field| hex::decode(field).ok())
+                .and_then(|bytes| T::from_slice(&bytes))
+                .ok_or(ParseProofError(name))
+        }
+
+        let mut fields = s.split_whitespace();
+        let round_id = fields
+            .next()
+            .and_then(|round_id| round_id.parse().ok())
+            .ok_or(ParseProofError("round id"))?;
+        let proof = Self {
+            round_id,
+            round_seed: parse_field(fields.next(), "round seed")?,
+            model_hash: parse_field(fields.next(), "model hash")?,
+            prev_hash: parse_field(fields.next(), "previous hash")?,
+            signature: parse_field(fields.next(), "signature")?,
+        };
+        if fields.next().is_some() {
+            return Err(ParseProofError("too many fields"));
+        }
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::SigningKeyPair,
+        mask::{FromPrimitives, Model},
+    };
+
+    fn model(weights: Vec<f32>) -> Model {
+        Model::from_primitives(weights.into_iter()).unwrap()
+    }
+
+    fn proofs(keys: &SigningKeyPair, rounds: u64) -> Vec<ModelProof> {
+        let mut proofs: Vec<ModelProof> = Vec::new();
+        for round_id in 1..=rounds {
+            let proof = ModelProof::new(
+                round_id,
+                RoundSeed::generate(),
+                &model(vec![round_id as f32; 4]),
+                proofs.last(),
+                &keys.secret,
+            );
+            proofs.push(proof);
+        }
+        proofs
+    }
+
+    #[test]
+    fn test_verify_model() {
+        let keys = SigningKeyPair::generate();
+        let proof = ModelProof::new(
+            1,
+            RoundSeed::generate(),
+            &model(vec![0.5, -1.]),
+            None,
+            &keys.secret,
+        );
+        assert!(proof.verify_signature(&keys.public));
+        assert!(proof.verify_model(&model(vec![0.5, -1.])));
+        assert!(!proof.verify_model(&model(vec![0.5, 1.])));
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let keys = SigningKeyPair::generate();
+        let proofs = proofs(&keys, 3);
+        assert_eq!(proofs[0].prev_hash, Sha256::zeroed());
+        assert!(verify_chain(&proofs, &keys.public).is_ok());
+
+        let other_keys = SigningKeyPair::generate();
+        assert_eq!(
+            verify_chain(&proofs, &other_keys.public),
+            Err(ProofError::InvalidSignature(1)),
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_tampered() {
+        let keys = SigningKeyPair::generate();
+
+        // a proof is removed
+        let mut removed = proofs(&keys, 3);
+        removed.remove(1);
+        assert_eq!(
+            verify_chain(&removed, &keys.public),
+            Err(ProofError::BrokenChain(3)),
+        );
+
+        // a proof is altered
+        let mut altered = proofs(&keys, 3);
+        altered[1].model_hash = ModelProof::hash_model(&model(vec![0.; 4]));
+        assert_eq!(
+            verify_chain(&altered, &keys.public),
+            Err(ProofError::InvalidSignature(2)),
+        );
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        let keys = SigningKeyPair::generate();
+        for proof in proofs(&keys, 2) {
+            let parsed = proof.to_string().parse::<ModelProof>().unwrap();
+            assert_eq!(parsed, proof);
+        }
+
+        assert_eq!(
+            "1 00".parse::<ModelProof>(),
+            Err(ParseProofError("round seed")),
+        );
+    }
+}
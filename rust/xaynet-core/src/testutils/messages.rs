@@ -8,7 +8,7 @@ use num::BigUint;
 use crate::{
     crypto::{ByteObject, PublicEncryptKey, PublicSigningKey, Signature},
     mask::EncryptedMaskSeed,
-    message::{Message, Payload, Sum, Sum2, Tag, Update},
+    message::{Message, Payload, Sum, Sum2, Tag, Update, PROTOCOL_VERSION},
     LocalSeedDict,
 };
 
@@ -20,7 +20,7 @@ use crate::{
 // - a length field (4 bytes)
 // - a tag (1 byte)
 // - flags (1 byte)
-// - a reserved field (2 bytes)
+// - a protocol version (2 bytes)
 pub const HEADER_LENGTH: usize = 136;
 
 pub fn signature() -> (Signature, Vec<u8>) {
@@ -60,6 +60,7 @@ where
         coordinator_pk: coordinator_pk().0,
         payload,
         is_multipart: false,
+        is_compressed: false,
         tag,
     };
 
@@ -69,7 +70,8 @@ where
     let length = payload_bytes.len() + HEADER_LENGTH;
     buf.extend(&(length as u32).to_be_bytes());
     buf.push(tag.into());
-    buf.extend(vec![0, 0, 0]);
+    buf.push(0); // flags
+    buf.extend(&PROTOCOL_VERSION.to_be_bytes());
     buf.extend(payload_bytes);
 
     (message, buf)
@@ -105,3 +105,5 @@ pub const ERR_GLOBALMODEL_DATATYPE: c_int = 12;
 pub const ERR_GLOBALMODEL_LEN: c_int = 13;
 /// Failed to get the global model: invalid model
 pub const ERR_GLOBALMODEL_CONVERT: c_int = 14;
+/// Invalid settings: invalid differential privacy configuration
+pub const ERR_SETTINGS_DP: c_int = 15;
@@ -6,7 +6,7 @@ use std::{
 };
 
 use ffi_support::{ByteBuffer, FfiStr};
-use xaynet_core::mask::{DataType, FromPrimitives, IntoPrimitives, Model};
+use xaynet_core::mask::{DataType, FromPrimitives, Model, PrimitiveModel};
 
 use super::{
     LocalModelConfig,
@@ -20,7 +20,7 @@ use super::{
     GLOBALMODEL_NONE,
     OK,
 };
-use crate::{into_primitives, Participant, Settings, Task};
+use crate::{Participant, Settings, Task};
 
 mod pv {
     use super::Participant;
@@ -377,7 +377,7 @@ pub unsafe extern "C" fn xaynet_ffi_participant_set_model(
 /// - [`ERR_GLOBALMODEL_IO`] if the communication with the coordinator failed
 /// - [`ERR_GLOBALMODEL_DATATYPE`] if the datatype is invalid
 /// - [`ERR_GLOBALMODEL_LEN`] if the length of the buffer does not match the length of the model
-/// - [`ERR_GLOBALMODEL_CONVERT`] if the conversion of the model failed
+/// - [`ERR_GLOBALMODEL_CONVERT`] if the conversion of the model into the data type failed
 ///
 /// # Note
 ///
@@ -414,44 +414,40 @@ pub unsafe extern "C" fn xaynet_ffi_participant_global_model(
         return ERR_NULLPTR;
     }
 
-    let global_model = match participant.global_model() {
-        Ok(Some(model)) => model,
-        Ok(None) => return GLOBALMODEL_NONE,
-        Err(_) => return ERR_GLOBALMODEL_IO,
-    };
-
     let data_type = match DataType::try_from(data_type) {
         Ok(data_type) => data_type,
         Err(_) => return ERR_GLOBALMODEL_DATATYPE,
     };
 
+    let global_model = match participant.global_primitive_model(data_type) {
+        Ok(Some(model)) => model,
+        Ok(None) => return GLOBALMODEL_NONE,
+        Err(e) if e.is_conversion_error() => return ERR_GLOBALMODEL_CONVERT,
+        Err(_) => return ERR_GLOBALMODEL_IO,
+    };
+
     let len = len as usize;
     if len != global_model.len() {
         return ERR_GLOBALMODEL_LEN;
     }
 
-    match data_type {
-        DataType::F32 => into_primitives!(global_model, buffer, f32, len),
-        DataType::F64 => into_primitives!(global_model, buffer, f64, len),
-        DataType::I32 => into_primitives!(global_model, buffer, i32, len),
-        DataType::I64 => into_primitives!(global_model, buffer, i64, len),
+    match global_model {
+        PrimitiveModel::F32(weights) => unsafe { copy_weights(&weights, buffer) },
+        PrimitiveModel::F64(weights) => unsafe { copy_weights(&weights, buffer) },
+        PrimitiveModel::I32(weights) => unsafe { copy_weights(&weights, buffer) },
+        PrimitiveModel::I64(weights) => unsafe { copy_weights(&weights, buffer) },
     }
+    OK
 }
 
-#[macro_export]
-macro_rules! into_primitives {
-    ($global_model:expr, $buffer:expr, $data_type:ty, $len:expr) => {{
-        if let Ok(global_model) = $global_model
-            .into_primitives()
-            .collect::<Result<Vec<$data_type>, _>>()
-        {
-            let buffer = unsafe { slice::from_raw_parts_mut($buffer as *mut $data_type, $len) };
-            buffer.copy_from_slice(global_model.as_slice());
-            OK
-        } else {
-            ERR_GLOBALMODEL_CONVERT
-        }
-    }};
+/// Copy the `weights` into the `buffer`.
+///
+/// # Safety
+///
+/// The `buffer` must be valid for writing `weights.len()` values of type `P`.
+unsafe fn copy_weights<P: Copy>(weights: &[P], buffer: *mut c_void) {
+    let buffer = unsafe { slice::from_raw_parts_mut(buffer as *mut P, weights.len()) };
+    buffer.copy_from_slice(weights);
 }
 
 /// Return the local model configuration of the model that is expected in the
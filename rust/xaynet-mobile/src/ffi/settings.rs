@@ -1,7 +1,10 @@
 use std::os::raw::{c_double, c_int};
 
 use ffi_support::{ByteBuffer, FfiStr};
-use xaynet_core::crypto::{ByteObject, PublicSigningKey, SecretSigningKey, SigningKeyPair};
+use xaynet_core::{
+    crypto::{ByteObject, PublicSigningKey, SecretSigningKey, SigningKeyPair},
+    mask::{DpConfig, DpMode, NoiseMechanism},
+};
 use zeroize::Zeroize;
 
 use super::{
@@ -9,6 +12,7 @@ use super::{
     ERR_CRYPTO_SECRET_KEY,
     ERR_INVALID_URL,
     ERR_NULLPTR,
+    ERR_SETTINGS_DP,
     ERR_SETTINGS_KEYS,
     ERR_SETTINGS_URL,
     OK,
@@ -129,6 +133,107 @@ pub unsafe extern "C" fn xaynet_ffi_settings_set_url(
     }
 }
 
+/// Enable or disable the compression of messages, which shrinks large update messages.
+/// The coordinator must support compressed messages. The compression is disabled if
+/// `compress` is `0` and enabled otherwise.
+///
+/// # Return value
+///
+/// - [`OK`] if successful
+/// - [`ERR_NULLPTR`] if `settings` is `NULL`
+///
+/// # Safety
+///
+/// When calling this method, you have to ensure that *either* the pointer is NULL *or*
+/// all of the following is true:
+/// - The pointer must be properly [aligned].
+/// - It must be "dereferencable" in the sense defined in the [`std::ptr`] module
+///   documentation.
+///
+/// [`std::ptr`]: https://doc.rust-lang.org/std/ptr/index.html#safety
+/// [aligned]: https://doc.rust-lang.org/std/ptr/index.html#alignment
+#[no_mangle]
+pub unsafe extern "C" fn xaynet_ffi_settings_set_compress_messages(
+    settings: *mut Settings,
+    compress: c_int,
+) -> c_int {
+    match unsafe { settings.as_mut() } {
+        Some(settings) => {
+            settings.set_compress_messages(compress != 0);
+            OK
+        }
+        None => ERR_NULLPTR,
+    }
+}
+
+/// Continuous Gaussian noise, see [`xaynet_ffi_settings_set_dp()`]
+pub const DP_MECHANISM_GAUSSIAN: c_int = 0;
+/// Discrete Gaussian noise on the lattice of the masking configuration, see
+/// [`xaynet_ffi_settings_set_dp()`]
+pub const DP_MECHANISM_DISCRETE_GAUSSIAN: c_int = 1;
+
+/// Enable differential privacy for the local models.
+///
+/// The local models are clipped to an L2 norm of `clipping_bound` and noise with a
+/// standard deviation of `noise_multiplier * clipping_bound` is added to them. The
+/// `mechanism` is either [`DP_MECHANISM_GAUSSIAN`] or
+/// [`DP_MECHANISM_DISCRETE_GAUSSIAN`]. If `participants` is `0`, each participant adds
+/// the noise in full, otherwise the noise is shared among at least `participants`
+/// participants.
+///
+/// # Return value
+///
+/// - [`OK`] if successful
+/// - [`ERR_SETTINGS_DP`] if `clipping_bound` is not positive, if `noise_multiplier` is
+///   negative or if `mechanism` is unknown
+/// - [`ERR_NULLPTR`] if `settings` is `NULL`
+///
+/// # Safety
+///
+/// When calling this method, you have to ensure that *either* the pointer is NULL *or*
+/// all of the following is true:
+/// - The pointer must be properly [aligned].
+/// - It must be "dereferencable" in the sense defined in the [`std::ptr`] module
+///   documentation.
+///
+/// [`std::ptr`]: https://doc.rust-lang.org/std/ptr/index.html#safety
+/// [aligned]: https://doc.rust-lang.org/std/ptr/index.html#alignment
+#[no_mangle]
+pub unsafe extern "C" fn xaynet_ffi_settings_set_dp(
+    settings: *mut Settings,
+    clipping_bound: c_double,
+    noise_multiplier: c_double,
+    mechanism: c_int,
+    participants: u64,
+) -> c_int {
+    let settings = match unsafe { settings.as_mut() } {
+        Some(settings) => settings,
+        None => return ERR_NULLPTR,
+    };
+    // also rejects NaN
+    if !(clipping_bound.is_finite() && clipping_bound > 0.0)
+        || !(noise_multiplier.is_finite() && noise_multiplier >= 0.0)
+    {
+        return ERR_SETTINGS_DP;
+    }
+    let mechanism = match mechanism {
+        DP_MECHANISM_GAUSSIAN => NoiseMechanism::Gaussian,
+        DP_MECHANISM_DISCRETE_GAUSSIAN => NoiseMechanism::DiscreteGaussian,
+        _ => return ERR_SETTINGS_DP,
+    };
+    let mode = match participants {
+        0 => DpMode::Local,
+        participants => DpMode::Distributed { participants },
+    };
+    settings.set_dp(DpConfig {
+        clipping_bound,
+        noise_multiplier,
+        mechanism,
+        mode,
+    });
+    OK
+}
+
 // TODO: add a way to save the key pair
 /// A signing key pair
 pub struct KeyPair {
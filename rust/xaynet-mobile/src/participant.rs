@@ -7,7 +7,7 @@ use tokio::{
     runtime::Runtime,
     sync::{mpsc, Mutex},
 };
-use xaynet_core::mask::Model;
+use xaynet_core::mask::{DataType, Model, PrimitiveModel};
 use xaynet_sdk::{
     client::Client,
     LocalModelConfig,
@@ -180,6 +180,14 @@ pub enum InitError {
 #[error("failed to fetch global model: {}", self.0)]
 pub struct GetGlobalModelError(xaynet_sdk::client::ClientError);
 
+impl GetGlobalModelError {
+    /// Check whether the global model could not be decoded or converted into the requested data
+    /// type.
+    pub(crate) fn is_conversion_error(&self) -> bool {
+        matches!(self.0, xaynet_sdk::client::ClientError::Deserialize(_))
+    }
+}
+
 impl Participant {
     /// Create a new participant with the given settings
     pub fn new(settings: Settings) -> Result<Self, InitError> {
@@ -195,8 +203,11 @@ impl Participant {
     /// Restore a participant from it's serialized state. The coordinator client that
     /// the participant uses internally is not part of the participant state, so the
     /// `url` is used to instantiate a new one.
+    ///
+    /// A state which has been saved before the protocol versioning is converted, see
+    /// [`SerializableState::from_bytes()`].
     pub fn restore(state: &[u8], url: &str) -> Result<Self, InitError> {
-        let state: SerializableState = bincode::deserialize(state)?;
+        let state = SerializableState::from_bytes(state)?;
         let (events, notifier) = Events::new();
         let store = Store::new();
         let client = new_client(url, None, None)?;
@@ -348,6 +359,33 @@ impl Participant {
         global_model
     }
 
+    /// Retrieve the current global model as primitive values of the given data type, if
+    /// available.
+    ///
+    /// The coordinator sends the model in a compact encoding of the primitive values, which is
+    /// smaller and faster to decode than the [`Model`] fetched by [`Participant::global_model()`].
+    pub fn global_primitive_model(
+        &mut self,
+        data_type: DataType,
+    ) -> Result<Option<PrimitiveModel>, GetGlobalModelError> {
+        let Self {
+            ref mut runtime,
+            ref mut client,
+            ..
+        } = self;
+
+        let global_model = runtime.block_on(async {
+            client
+                .get_primitive_model(data_type)
+                .await
+                .map_err(GetGlobalModelError)
+        });
+        if global_model.is_ok() {
+            self.new_global_model = false;
+        }
+        global_model
+    }
+
     /// Return the local model configuration of the model that is expected in the
     /// [`Participant::set_model`] method.
     pub fn local_model_config(&self) -> LocalModelConfig {
@@ -356,3 +394,49 @@ impl Participant {
         state_machine.local_model_config()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use xaynet_core::crypto::SigningKeyPair;
+
+    use super::*;
+
+    #[test]
+    fn test_global_model_conversion_error() {
+        sodiumoxide::init().unwrap();
+        // a coordinator which can't convert the global model into the requested data type
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let coordinator = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0_u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 406 Not Acceptable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                .unwrap();
+        });
+
+        let mut settings = Settings::new();
+        settings.set_keys(SigningKeyPair::generate());
+        settings.set_url(url);
+        let mut participant = Participant::new(settings).unwrap();
+
+        let error = participant
+            .global_primitive_model(DataType::F32)
+            .unwrap_err();
+        assert!(error.is_conversion_error());
+        coordinator.join().unwrap();
+
+        // the coordinator is unreachable now
+        let error = participant
+            .global_primitive_model(DataType::F32)
+            .unwrap_err();
+        assert!(!error.is_conversion_error());
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::convert::TryInto;
 use thiserror::Error;
-use xaynet_core::crypto::SigningKeyPair;
+use xaynet_core::{crypto::SigningKeyPair, mask::DpConfig};
 use xaynet_sdk::settings::{MaxMessageSize, PetSettings};
 
 /// A participant settings
@@ -18,6 +18,10 @@ pub struct Settings {
     scalar: f64,
     /// The maximum possible size of a message.
     max_message_size: MaxMessageSize,
+    /// The differential privacy configuration.
+    dp: Option<DpConfig>,
+    /// Whether to compress the messages.
+    compress_messages: bool,
 }
 
 impl Default for Settings {
@@ -34,6 +38,8 @@ impl Settings {
             keys: None,
             scalar: 1.0,
             max_message_size: MaxMessageSize::default(),
+            dp: None,
+            compress_messages: false,
         }
     }
 
@@ -57,6 +63,17 @@ impl Settings {
         self.max_message_size = size;
     }
 
+    /// Enables differential privacy for the local models with the given configuration.
+    pub fn set_dp(&mut self, dp: DpConfig) {
+        self.dp = Some(dp);
+    }
+
+    /// Enables the compression of messages, which shrinks large update messages. The
+    /// coordinator must support compressed messages.
+    pub fn set_compress_messages(&mut self, compress: bool) {
+        self.compress_messages = compress;
+    }
+
     /// Check whether the settings are complete and valid
     pub fn check(&self) -> Result<(), SettingsError> {
         if self.url.is_none() {
@@ -87,6 +104,8 @@ impl TryInto<(String, PetSettings)> for Settings {
             url,
             scalar,
             max_message_size,
+            dp,
+            compress_messages,
         } = self;
 
         let url = url.ok_or(SettingsError::MissingUrl)?;
@@ -97,6 +116,8 @@ impl TryInto<(String, PetSettings)> for Settings {
             keys,
             scalar,
             max_message_size,
+            dp,
+            compress_messages,
         };
 
         Ok((url, pet_settings))
@@ -46,6 +46,42 @@ static char *test_settings_set_url() {
   return 0;
 }
 
+static char *test_settings_set_compress_messages() {
+  Settings *settings = xaynet_ffi_settings_new();
+
+  int err = xaynet_ffi_settings_set_compress_messages(settings, 1);
+  mu_assert("failed to enable compression", !err);
+
+  err = xaynet_ffi_settings_set_compress_messages(NULL, 1);
+  mu_assert("settings NULL pointer should fail", err == ERR_NULLPTR);
+
+  xaynet_ffi_settings_destroy(settings);
+  return 0;
+}
+
+static char *test_settings_set_dp() {
+  Settings *settings = xaynet_ffi_settings_new();
+
+  int err = xaynet_ffi_settings_set_dp(settings, 0.0, 1.0, DP_MECHANISM_GAUSSIAN, 0);
+  mu_assert("settings invalid clipping bound should fail", err == ERR_SETTINGS_DP);
+
+  err = xaynet_ffi_settings_set_dp(settings, 1.0, 1.0, 42, 0);
+  mu_assert("settings invalid mechanism should fail", err == ERR_SETTINGS_DP);
+
+  err = xaynet_ffi_settings_set_dp(settings, 1.0, 1.0, DP_MECHANISM_GAUSSIAN, 0);
+  mu_assert("failed to set local dp", !err);
+
+  err = xaynet_ffi_settings_set_dp(settings, 1.0, 1.0,
+                                   DP_MECHANISM_DISCRETE_GAUSSIAN, 10);
+  mu_assert("failed to set distributed dp", !err);
+
+  err = xaynet_ffi_settings_set_dp(NULL, 1.0, 1.0, DP_MECHANISM_GAUSSIAN, 0);
+  mu_assert("settings NULL pointer should fail", err == ERR_NULLPTR);
+
+  xaynet_ffi_settings_destroy(settings);
+  return 0;
+}
+
 void with_keys(Settings *settings) {
   const KeyPair *keys = xaynet_ffi_generate_key_pair();
   int err = xaynet_ffi_settings_set_keys(settings, keys);
@@ -175,6 +211,8 @@ static char *all_tests() {
   mu_run_test(test_settings_new);
   mu_run_test(test_settings_set_keys);
   mu_run_test(test_settings_set_url);
+  mu_run_test(test_settings_set_compress_messages);
+  mu_run_test(test_settings_set_dp);
   mu_run_test(test_settings);
   mu_run_test(test_global_model);
   mu_run_test(test_participant_save_and_restore);
@@ -82,6 +82,11 @@
  */
 #define ERR_GLOBALMODEL_CONVERT 14
 
+/**
+ * Invalid settings: invalid differential privacy configuration
+ */
+#define ERR_SETTINGS_DP 15
+
 /**
  * The participant is not taking part in the sum or update task
  */
@@ -112,6 +117,17 @@
  */
 #define PARTICIPANT_NEW_GLOBALMODEL (1 << 5)
 
+/**
+ * Continuous Gaussian noise, see [`xaynet_ffi_settings_set_dp()`]
+ */
+#define DP_MECHANISM_GAUSSIAN 0
+
+/**
+ * Discrete Gaussian noise on the lattice of the masking configuration, see
+ * [`xaynet_ffi_settings_set_dp()`]
+ */
+#define DP_MECHANISM_DISCRETE_GAUSSIAN 1
+
 /**
  * The original primitive data type of the numerical values to be masked.
  */
@@ -578,7 +594,7 @@ int xaynet_ffi_participant_set_model(struct Participant *participant,
  * - [`ERR_GLOBALMODEL_IO`] if the communication with the coordinator failed
  * - [`ERR_GLOBALMODEL_DATATYPE`] if the datatype is invalid
  * - [`ERR_GLOBALMODEL_LEN`] if the length of the buffer does not match the length of the model
- * - [`ERR_GLOBALMODEL_CONVERT`] if the conversion of the model failed
+ * - [`ERR_GLOBALMODEL_CONVERT`] if the conversion of the model into the data type failed
  *
  * # Note
  *
@@ -700,6 +716,63 @@ int xaynet_ffi_settings_set_scalar(struct Settings *settings, double scalar);
  */
 int xaynet_ffi_settings_set_url(struct Settings *settings, FfiStr url);
 
+/**
+ * Enable or disable the compression of messages, which shrinks large update messages.
+ * The coordinator must support compressed messages. The compression is disabled if
+ * `compress` is `0` and enabled otherwise.
+ *
+ * # Return value
+ *
+ * - [`OK`] if successful
+ * - [`ERR_NULLPTR`] if `settings` is `NULL`
+ *
+ * # Safety
+ *
+ * When calling this method, you have to ensure that *either* the pointer is NULL *or*
+ * all of the following is true:
+ * - The pointer must be properly [aligned].
+ * - It must be "dereferencable" in the sense defined in the [`std::ptr`] module
+ *   documentation.
+ *
+ * [`std::ptr`]: https://doc.rust-lang.org/std/ptr/index.html#safety
+ * [aligned]: https://doc.rust-lang.org/std/ptr/index.html#alignment
+ */
+int xaynet_ffi_settings_set_compress_messages(struct Settings *settings, int compress);
+
+/**
+ * Enable differential privacy for the local models.
+ *
+ * The local models are clipped to an L2 norm of `clipping_bound` and noise with a
+ * standard deviation of `noise_multiplier * clipping_bound` is added to them. The
+ * `mechanism` is either [`DP_MECHANISM_GAUSSIAN`] or
+ * [`DP_MECHANISM_DISCRETE_GAUSSIAN`]. If `participants` is `0`, each participant adds
+ * the noise in full, otherwise the noise is shared among at least `participants`
+ * participants.
+ *
+ * # Return value
+ *
+ * - [`OK`] if successful
+ * - [`ERR_SETTINGS_DP`] if `clipping_bound` is not positive, if `noise_multiplier` is
+ *   negative or if `mechanism` is unknown
+ * - [`ERR_NULLPTR`] if `settings` is `NULL`
+ *
+ * # Safety
+ *
+ * When calling this method, you have to ensure that *either* the pointer is NULL *or*
+ * all of the following is true:
+ * - The pointer must be properly [aligned].
+ * - It must be "dereferencable" in the sense defined in the [`std::ptr`] module
+ *   documentation.
+ *
+ * [`std::ptr`]: https://doc.rust-lang.org/std/ptr/index.html#safety
+ * [aligned]: https://doc.rust-lang.org/std/ptr/index.html#alignment
+ */
+int xaynet_ffi_settings_set_dp(struct Settings *settings,
+                               double clipping_bound,
+                               double noise_multiplier,
+                               int mechanism,
+                               uint64_t participants);
+
 /**
  * Generate a new signing key pair that can be used in the [`Settings`]. **Before
  * calling this function you must initialize the crypto library with
@@ -1,16 +1,33 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
+use futures::{lock::Mutex, stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::{debug, warn};
 use url::Url;
 
-use crate::XaynetClient;
+use crate::{
+    notifications::{Notification, NotificationParser},
+    XaynetClient,
+};
 use xaynet_core::{
-    common::RoundParameters,
+    common::{GlobalModelInfo, RoundParameters},
     crypto::{ByteObject, PublicSigningKey},
-    mask::Model,
+    mask::{DataType, Model, ModelCastError, PrimitiveModel, PrimitiveModelDecodeError},
+    proof::ModelProof,
     SumDict,
     UpdateSeedDict,
 };
 
+/// The media type of a global model encoded as primitive values, see [`PrimitiveModel`].
+const PRIMITIVE_MODEL_MEDIA_TYPE: &str = "application/x-xaynet-primitive-model";
+
 /// Error returned upon failing to build a new [`Client`]
 #[derive(Debug, Error)]
 pub enum ClientError {
@@ -34,6 +51,9 @@ pub enum ClientError {
 
     #[error("No certificate found")]
     NoCertificate,
+
+    #[error("The message was not accepted: {0}")]
+    Message(#[source] MessageError),
 }
 
 #[cfg_attr(not(feature = "reqwest-client"), allow(dead_code))]
@@ -55,6 +75,122 @@ impl From<std::num::ParseIntError> for ClientError {
     }
 }
 
+impl From<PrimitiveModelDecodeError> for ClientError {
+    fn from(e: PrimitiveModelDecodeError) -> Self {
+        Self::Deserialize(format!("{}", e))
+    }
+}
+
+impl From<ModelCastError> for ClientError {
+    fn from(e: ModelCastError) -> Self {
+        Self::Deserialize(format!("{}", e))
+    }
+}
+
+/// The reason of the coordinator for not accepting a PET message.
+///
+/// See the `xaynet_server::rest` docs for the status code of each error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageErrorCode {
+    /// The message could not be decrypted, e.g. because it was encrypted for another round.
+    Decrypt,
+    /// The message could not be parsed.
+    Parsing,
+    /// The message signature is invalid.
+    InvalidMessageSignature,
+    /// The protocol version of the message is not accepted by the coordinator, which requires an
+    /// update of the SDK.
+    UnsupportedVersion,
+    /// The participant is not eligible for the sum task.
+    NotSumEligible,
+    /// The participant is not eligible for the update task.
+    NotUpdateEligible,
+    /// The message is addressed to another round.
+    InvalidCoordinatorPublicKey,
+    /// The message is not expected in the current phase.
+    UnexpectedMessage,
+    /// The multipart message exceeds the maximal message size of the coordinator.
+    MessageTooLarge,
+    /// The message was rejected by the coordinator.
+    MessageRejected,
+    /// The message was discarded because the current phase received enough messages.
+    MessageDiscarded,
+    /// The sum participant has already sent a sum message.
+    DuplicateSumParticipant,
+    /// The model or scalar could not be aggregated.
+    AggregationFailed,
+    /// The local seed dictionary is invalid.
+    InvalidLocalSeedDict,
+    /// The mask is invalid.
+    InvalidMask,
+    /// The coordinator failed to process the message.
+    InternalError,
+    /// The coordinator buffers too many incomplete multipart messages at the moment.
+    MultipartCapacityExceeded,
+    /// An error code unknown to this version of the SDK.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The error response of the coordinator to a PET message which has not been accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[error("{message} ({code:?})")]
+pub struct MessageError {
+    /// The error code.
+    pub code: MessageErrorCode,
+    /// A human readable description of the error.
+    pub message: String,
+}
+
+impl MessageError {
+    /// Parses the JSON body of an error response to a PET message.
+    ///
+    /// Returns `None` if the body is not a valid error response, e.g. because it comes from a proxy
+    /// in front of the coordinator.
+    pub fn from_body(body: &[u8]) -> Option<Self> {
+        serde_json::from_slice(body).ok()
+    }
+
+    /// Checks whether resending the same message is pointless.
+    ///
+    /// This is the case for all errors except internal errors of the coordinator, an exceeded
+    /// multipart capacity and unknown errors.
+    pub fn is_final(&self) -> bool {
+        !matches!(
+            self.code,
+            MessageErrorCode::InternalError
+                | MessageErrorCode::MultipartCapacityExceeded
+                | MessageErrorCode::Unknown
+        )
+    }
+}
+
+/// A streamed HTTP response body.
+pub type ByteStream = BoxStream<'static, Result<Vec<u8>, ClientError>>;
+
+/// The response to a conditional HTTP `GET` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionalResponse<R> {
+    /// The response is `NOT_MODIFIED`, i.e. the data still has the requested entity tag.
+    NotModified,
+    /// The response is `NO_CONTENT`.
+    NoContent,
+    /// The response body along with the entity tag of the data, if any.
+    Modified { body: R, etag: Option<String> },
+}
+
+/// The response to an HTTP `GET` request with content negotiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiatedResponse<R> {
+    /// The response body, which is not decoded according to its content encoding.
+    pub body: R,
+    /// The `Content-Type` header of the response, if any.
+    pub content_type: Option<String>,
+    /// The `Content-Encoding` header of the response, if any.
+    pub content_encoding: Option<String>,
+}
+
 /// A basic HTTP interface that [`Client`] HTTP backends must implement.
 #[async_trait]
 pub trait XaynetHttpClient {
@@ -70,7 +206,197 @@ pub trait XaynetHttpClient {
     async fn get(&mut self, url: &str) -> Result<Option<Self::GetResponse>, ClientError>;
 
     /// Perform an HTTP `POST` on the given URL, with the given body.
+    ///
+    /// If the response is an error with a [`MessageError`] body, the implementor should return
+    /// [`ClientError::Message`].
     async fn post(&mut self, url: &str, body: Vec<u8>) -> Result<(), ClientError>;
+
+    /// Perform an HTTP `GET` on the given URL and stream the response body.
+    ///
+    /// If streaming is not supported, the implementor must return `Ok(None)`, which is the
+    /// default. The [`Client`] then falls back to polling instead of subscribing to the push
+    /// notifications of the coordinator.
+    async fn get_stream(&mut self, _url: &str) -> Result<Option<ByteStream>, ClientError> {
+        Ok(None)
+    }
+
+    /// Perform a conditional HTTP `GET` on the given URL.
+    ///
+    /// If an `etag` is given, the implementor should send it in an `If-None-Match` header and
+    /// return [`ConditionalResponse::NotModified`] if the response is `NOT_MODIFIED`. The entity
+    /// tag of a modified response is taken from its `ETag` header. The default falls back to an
+    /// unconditional [`get()`].
+    ///
+    /// [`get()`]: XaynetHttpClient::get
+    async fn get_if_none_match(
+        &mut self,
+        url: &str,
+        _etag: Option<&str>,
+    ) -> Result<ConditionalResponse<Self::GetResponse>, ClientError> {
+        Ok(match self.get(url).await? {
+            Some(body) => ConditionalResponse::Modified { body, etag: None },
+            None => ConditionalResponse::NoContent,
+        })
+    }
+
+    /// Perform an HTTP `GET` on the given URL with content negotiation.
+    ///
+    /// The implementor should send `accept` in an `Accept` header and `accept_encoding` in an
+    /// `Accept-Encoding` header. If the response is `NO_CONTENT`, the implementor must return
+    /// `Ok(None)`. If the response is `NOT_ACCEPTABLE`, the implementor should return
+    /// [`ClientError::UnexpectedResponse`] with the status code `406`. Otherwise, the response
+    /// body must be returned as is along with its `Content-Type` and `Content-Encoding` headers.
+    /// The default falls back to a plain [`get()`].
+    ///
+    /// [`get()`]: XaynetHttpClient::get
+    async fn get_negotiated(
+        &mut self,
+        url: &str,
+        _accept: &str,
+        _accept_encoding: &str,
+    ) -> Result<Option<NegotiatedResponse<Self::GetResponse>>, ClientError> {
+        Ok(self.get(url).await?.map(|body| NegotiatedResponse {
+            body,
+            content_type: None,
+            content_encoding: None,
+        }))
+    }
+}
+
+/// A response body which has been cached along with its entity tag.
+#[derive(Debug)]
+struct CachedResponse {
+    etag: String,
+    body: Vec<u8>,
+}
+
+/// How long to wait for push notifications which have already been sent by the coordinator.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// How long the push notifications may be silent before the subscription is considered stale.
+///
+/// The coordinator sends a keep-alive comment every 15 seconds, hence a healthy subscription
+/// receives several chunks within this time, even if no events happen.
+const MAX_STALENESS: Duration = Duration::from_secs(60);
+
+/// How long to wait before subscribing again after a failed subscription. The delay doubles with
+/// each further failure in a row, up to [`MAX_RESUBSCRIBE_DELAY`].
+const MIN_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(30);
+
+/// The maximal delay before subscribing again after failed subscriptions.
+const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(3600);
+
+/// Gets the delay before subscribing again after the given number of failed subscriptions in a
+/// row.
+fn resubscribe_delay(failures: u32) -> Duration {
+    MIN_RESUBSCRIBE_DELAY
+        .checked_mul(2_u32.saturating_pow(failures.saturating_sub(1)))
+        .unwrap_or(MAX_RESUBSCRIBE_DELAY)
+        .min(MAX_RESUBSCRIBE_DELAY)
+}
+
+/// The state of the push notifications of a client.
+#[derive(Debug, Default)]
+struct PushNotifications {
+    /// The current subscription, if any.
+    subscription: Option<Subscription>,
+    /// Whether the HTTP client doesn't support streaming, in which case the client only polls.
+    unsupported: bool,
+    /// The number of failed subscriptions in a row.
+    failures: u32,
+    /// When to subscribe again after a failed subscription.
+    retry_at: Option<Instant>,
+}
+
+impl PushNotifications {
+    /// Checks whether the client should try to subscribe now.
+    fn should_subscribe(&self) -> bool {
+        let now = Instant::now();
+        !self.unsupported && self.retry_at.map_or(true, |retry_at| retry_at <= now)
+    }
+
+    /// Updates the state with the result of a subscription.
+    ///
+    /// A coordinator which doesn't serve the push notifications or can't be reached isn't asked
+    /// again before the [`resubscribe_delay()`] has passed.
+    fn subscribed(&mut self, result: Result<Option<Subscription>, ClientError>) {
+        match result {
+            Ok(Some(subscription)) => {
+                debug!("subscribed to push notifications");
+                self.subscription = Some(subscription);
+                self.failures = 0;
+                self.retry_at = None;
+            }
+            Ok(None) => {
+                debug!("push notifications are not supported by the HTTP client");
+                self.unsupported = true;
+            }
+            Err(e) => {
+                self.failures = self.failures.saturating_add(1);
+                let delay = resubscribe_delay(self.failures);
+                warn!(
+                    "failed to subscribe to push notifications, retrying in {:?}: {}",
+                    delay, e
+                );
+                self.retry_at = Some(Instant::now() + delay);
+            }
+        }
+    }
+}
+
+/// A subscription to the push notifications of the coordinator.
+struct Subscription {
+    /// The stream of server-sent events.
+    stream: ByteStream,
+    /// The parser of the server-sent events.
+    parser: NotificationParser,
+    /// When the last chunk of the stream has been received, including keep-alive comments.
+    received_at: Instant,
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+
+impl Subscription {
+    /// Checks whether new round parameters have been announced since the last check.
+    ///
+    /// Returns `None` if the subscription has been closed or if it is stale, i.e. nothing has
+    /// been received for longer than [`MAX_STALENESS`], e.g. because a proxy silently dropped
+    /// the connection.
+    async fn params_announced(&mut self) -> Option<bool> {
+        let mut announced = false;
+        loop {
+            match tokio::time::timeout(NOTIFICATION_TIMEOUT, self.stream.next()).await {
+                Ok(Some(Ok(chunk))) => {
+                    self.received_at = Instant::now();
+                    let notifications = self.parser.parse(&chunk);
+                    debug!("received push notifications: {:?}", notifications);
+                    announced |= notifications
+                        .iter()
+                        .any(|notification| matches!(notification, Notification::Params { .. }));
+                }
+                Ok(Some(Err(e))) => {
+                    warn!("push notifications failed: {}", e);
+                    return None;
+                }
+                Ok(None) => {
+                    warn!("push notifications closed by the coordinator");
+                    return None;
+                }
+                // no more notifications for now
+                Err(_) if self.received_at.elapsed() > MAX_STALENESS => {
+                    warn!("push notifications are stale");
+                    return None;
+                }
+                Err(_) => return Some(announced),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +406,10 @@ pub struct Client<C> {
     client: C,
     /// Coordinator URL
     base_url: Url,
+    /// State of the push notifications, shared by all clones of the client
+    push: Arc<Mutex<PushNotifications>>,
+    /// Cached response bodies by URL, shared by all clones of the client
+    cache: Arc<std::sync::Mutex<HashMap<String, CachedResponse>>>,
 }
 
 /// Error returned when trying to client a [`Client`] with an invalid
@@ -111,6 +441,8 @@ where
         Ok(Self {
             client: http_client,
             base_url,
+            push: Arc::new(Mutex::new(PushNotifications::default())),
+            cache: Arc::new(std::sync::Mutex::new(HashMap::new())),
         })
     }
 
@@ -121,7 +453,48 @@ where
         url
     }
 
+    /// Fetch the data from the given URL.
+    ///
+    /// The entity tag of the last response is sent along, such that the coordinator doesn't
+    /// need to send unmodified data again.
     async fn get<T>(&mut self, url: &Url) -> Result<Option<T>, ClientError>
+    where
+        T: for<'a> serde::Deserialize<'a>,
+    {
+        let etag = self.cached_etag(url);
+        let response = self
+            .client
+            .get_if_none_match(url.as_str(), etag.as_deref())
+            .await?;
+        // safe unwraps: the lock is never held across a panic
+        let mut cache = self.cache.lock().unwrap();
+        Ok(match response {
+            ConditionalResponse::NotModified => match cache.get(url.as_str()) {
+                Some(cached) => Some(bincode::deserialize::<T>(&cached.body)?),
+                None => return Err(ClientError::UnexpectedResponse(304)),
+            },
+            ConditionalResponse::NoContent => {
+                cache.remove(url.as_str());
+                None
+            }
+            ConditionalResponse::Modified { body, etag } => {
+                let data = bincode::deserialize::<T>(body.as_ref())?;
+                match etag {
+                    Some(etag) => {
+                        let body = body.as_ref().to_vec();
+                        cache.insert(url.to_string(), CachedResponse { etag, body });
+                    }
+                    None => {
+                        cache.remove(url.as_str());
+                    }
+                }
+                Some(data)
+            }
+        })
+    }
+
+    /// Fetch the data from the given URL without caching it.
+    async fn get_uncached<T>(&mut self, url: &Url) -> Result<Option<T>, ClientError>
     where
         T: for<'a> serde::Deserialize<'a>,
     {
@@ -131,9 +504,100 @@ where
         })
     }
 
+    /// Retrieve the current global model as primitive values of the given data type, if
+    /// available.
+    ///
+    /// The coordinator is asked for the compact primitive encoding of the model, which may be
+    /// compressed with `zstd`. If it responds with a model serialized with `bincode` instead, the
+    /// weights are converted into the data type.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or if the model can't be decoded or converted into the data
+    /// type. A conversion error is a [`ClientError::Deserialize`], regardless of whether the
+    /// coordinator or the client failed to convert the weights.
+    pub async fn get_primitive_model(
+        &mut self,
+        data_type: DataType,
+    ) -> Result<Option<PrimitiveModel>, ClientError> {
+        let url = self.url("model");
+        let accept = format!("{}; data-type={}", PRIMITIVE_MODEL_MEDIA_TYPE, data_type);
+        let response = match self
+            .client
+            .get_negotiated(url.as_str(), &accept, "zstd")
+            .await
+        {
+            Ok(Some(response)) => response,
+            Ok(None) => return Ok(None),
+            Err(ClientError::UnexpectedResponse(406)) => {
+                return Err(ClientError::Deserialize(format!(
+                    "the coordinator can't convert the model weights into {}",
+                    data_type
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+        let body = response.body.as_ref();
+
+        let content_type = response.content_type.unwrap_or_default();
+        let mut params = content_type.split(';').map(str::trim);
+        // safe unwrap: `split` yields at least one part
+        if params.next().unwrap() != PRIMITIVE_MODEL_MEDIA_TYPE {
+            // the coordinator doesn't support the primitive encoding
+            let model: Model = bincode::deserialize(body)?;
+            return Ok(Some(PrimitiveModel::from_model(&model, data_type)?));
+        }
+        let expected_param = format!("data-type={}", data_type);
+        if !params.any(|param| param == expected_param) {
+            return Err(ClientError::Deserialize(format!(
+                "unexpected content type: {}",
+                content_type
+            )));
+        }
+
+        let model = match response.content_encoding.as_deref() {
+            None | Some("identity") => PrimitiveModel::from_bytes(body, data_type)?,
+            Some("zstd") => {
+                let bytes = zstd::decode_all(body)
+                    .map_err(|e| ClientError::Deserialize(format!("{}", e)))?;
+                PrimitiveModel::from_bytes(&bytes, data_type)?
+            }
+            Some(encoding) => {
+                return Err(ClientError::Deserialize(format!(
+                    "unexpected content encoding: {}",
+                    encoding
+                )))
+            }
+        };
+        Ok(Some(model))
+    }
+
+    /// Get the entity tag of the cached response for the given URL.
+    fn cached_etag(&self, url: &Url) -> Option<String> {
+        let cache = self.cache.lock().unwrap();
+        cache.get(url.as_str()).map(|cached| cached.etag.clone())
+    }
+
     async fn post(&mut self, url: &Url, data: Vec<u8>) -> Result<(), ClientError> {
         self.client.post(url.as_str(), data).await
     }
+
+    /// Subscribe to the push notifications of the coordinator.
+    ///
+    /// Returns `None` if the HTTP client doesn't support streaming.
+    async fn subscribe(&mut self) -> Result<Option<Subscription>, ClientError> {
+        let url = self.url("events");
+        let subscription = self
+            .client
+            .get_stream(url.as_str())
+            .await?
+            .map(|stream| Subscription {
+                stream,
+                parser: NotificationParser::new(),
+                received_at: Instant::now(),
+            });
+        Ok(subscription)
+    }
 }
 
 #[async_trait]
@@ -171,10 +635,67 @@ where
         Ok(self.get(&url).await?)
     }
 
+    async fn get_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> Result<Vec<GlobalModelInfo>, Self::Error> {
+        let mut url = self.url("models");
+        url.query_pairs_mut()
+            .append_pair("from", &from_round_id.to_string())
+            .append_pair("limit", &limit.to_string());
+        // each page has its own url, so caching them would only hold them in memory
+        let infos: Option<Vec<GlobalModelInfo>> = self.get_uncached(&url).await?;
+        Ok(infos.unwrap_or_default())
+    }
+
+    async fn get_global_model(&mut self, round_id: u64) -> Result<Option<Model>, Self::Error> {
+        let mut url = self.url("models");
+        url.path_segments_mut().unwrap().push(&round_id.to_string());
+        // the global models of past rounds never change, so caching them would only hold them in
+        // memory
+        self.get_uncached(&url).await
+    }
+
+    async fn get_proofs(&mut self) -> Result<Vec<ModelProof>, Self::Error> {
+        let url = self.url("proofs");
+        let proofs: Option<Vec<ModelProof>> = self.get_uncached(&url).await?;
+        Ok(proofs.unwrap_or_default())
+    }
+
+    async fn get_proof(&mut self, round_id: u64) -> Result<Option<ModelProof>, Self::Error> {
+        let mut url = self.url("proofs");
+        url.path_segments_mut().unwrap().push(&round_id.to_string());
+        self.get_uncached(&url).await
+    }
+
+    async fn get_proof_key(&mut self) -> Result<Option<PublicSigningKey>, Self::Error> {
+        let mut url = self.url("proofs");
+        url.path_segments_mut().unwrap().push("key");
+        self.get_uncached(&url).await
+    }
+
     async fn send_message(&mut self, msg: Vec<u8>) -> Result<(), Self::Error> {
         let url = self.url("message");
         self.post(&url, msg).await
     }
+
+    async fn round_changed(&mut self) -> bool {
+        let shared = self.push.clone();
+        let mut push = shared.lock().await;
+        if let Some(subscription) = push.subscription.as_mut() {
+            if let Some(announced) = subscription.params_announced().await {
+                return announced;
+            }
+            push.subscription = None;
+        }
+        // (re)subscribe and fall back to polling, since notifications might have been missed
+        if push.should_subscribe() {
+            let result = self.subscribe().await;
+            push.subscribed(result);
+        }
+        true
+    }
 }
 
 #[cfg(feature = "reqwest-client")]
@@ -201,13 +722,479 @@ impl XaynetHttpClient for reqwest::Client {
     }
 
     async fn post(&mut self, url: &str, body: Vec<u8>) -> Result<(), ClientError> {
-        let _resp = reqwest::Client::post(self, url)
+        let resp = reqwest::Client::post(self, url)
             .body(body)
+            .send()
+            .await
+            .map_err(ClientError::http_error)?;
+        let status = resp.status();
+        if status.is_client_error() || status.is_server_error() {
+            let body = resp.bytes().await.map_err(ClientError::http_error)?;
+            return Err(match MessageError::from_body(&body) {
+                Some(error) => ClientError::Message(error),
+                None => ClientError::UnexpectedResponse(status.as_u16()),
+            });
+        }
+        Ok(())
+    }
+
+    async fn get_if_none_match(
+        &mut self,
+        url: &str,
+        etag: Option<&str>,
+    ) -> Result<ConditionalResponse<Self::GetResponse>, ClientError> {
+        let mut request = reqwest::Client::get(self, url);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let resp = request
             .send()
             .await
             .map_err(ClientError::http_error)?
             .error_for_status()
             .map_err(ClientError::http_error)?;
-        Ok(())
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                let etag = resp
+                    .headers()
+                    .get(reqwest::header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(String::from);
+                let body = resp.bytes().await.map_err(ClientError::http_error)?;
+                Ok(ConditionalResponse::Modified { body, etag })
+            }
+            reqwest::StatusCode::NOT_MODIFIED => Ok(ConditionalResponse::NotModified),
+            reqwest::StatusCode::NO_CONTENT => Ok(ConditionalResponse::NoContent),
+            status => Err(ClientError::UnexpectedResponse(status.as_u16())),
+        }
+    }
+
+    async fn get_negotiated(
+        &mut self,
+        url: &str,
+        accept: &str,
+        accept_encoding: &str,
+    ) -> Result<Option<NegotiatedResponse<Self::GetResponse>>, ClientError> {
+        let resp = reqwest::Client::get(self, url)
+            .header(reqwest::header::ACCEPT, accept)
+            .header(reqwest::header::ACCEPT_ENCODING, accept_encoding)
+            .send()
+            .await
+            .map_err(ClientError::http_error)?;
+        if resp.status() == reqwest::StatusCode::NOT_ACCEPTABLE {
+            return Err(ClientError::UnexpectedResponse(406));
+        }
+        let resp = resp.error_for_status().map_err(ClientError::http_error)?;
+        match resp.status() {
+            reqwest::StatusCode::OK => {
+                let header = |name: reqwest::header::HeaderName| {
+                    resp.headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .map(String::from)
+                };
+                let content_type = header(reqwest::header::CONTENT_TYPE);
+                let content_encoding = header(reqwest::header::CONTENT_ENCODING);
+                let body = resp.bytes().await.map_err(ClientError::http_error)?;
+                Ok(Some(NegotiatedResponse {
+                    body,
+                    content_type,
+                    content_encoding,
+                }))
+            }
+            reqwest::StatusCode::NO_CONTENT => Ok(None),
+            status => Err(ClientError::UnexpectedResponse(status.as_u16())),
+        }
+    }
+
+    async fn get_stream(&mut self, url: &str) -> Result<Option<ByteStream>, ClientError> {
+        let resp = reqwest::Client::get(self, url)
+            .send()
+            .await
+            .map_err(ClientError::http_error)?
+            .error_for_status()
+            .map_err(ClientError::http_error)?;
+        let stream = futures::stream::unfold(Some(resp), |resp| async move {
+            let mut resp = resp?;
+            match resp.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(resp))),
+                Ok(None) => None,
+                // end the stream after an error
+                Err(e) => Some((Err(ClientError::http_error(e)), None)),
+            }
+        });
+        Ok(Some(stream.boxed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc;
+    use mockall::{automock, Sequence};
+
+    use super::*;
+    use xaynet_core::{
+        common::RoundSeed,
+        crypto::SigningKeyPair,
+        mask::FromPrimitives,
+        proof::verify_chain,
+    };
+
+    /// The HTTP requests of a [`Client`] with owned arguments, which can be mocked.
+    #[automock]
+    trait Http {
+        fn get(&mut self, url: String) -> Result<Option<Vec<u8>>, ClientError>;
+        fn post(&mut self, url: String, body: Vec<u8>) -> Result<(), ClientError>;
+        fn get_stream(&mut self, url: String) -> Result<Option<ByteStream>, ClientError>;
+        fn get_if_none_match(
+            &mut self,
+            url: String,
+            etag: Option<String>,
+        ) -> Result<ConditionalResponse<Vec<u8>>, ClientError>;
+        fn get_negotiated(
+            &mut self,
+            url: String,
+            accept: String,
+            accept_encoding: String,
+        ) -> Result<Option<NegotiatedResponse<Vec<u8>>>, ClientError>;
+    }
+
+    #[async_trait]
+    impl XaynetHttpClient for MockHttp {
+        type Error = ClientError;
+        type GetResponse = Vec<u8>;
+
+        async fn get(&mut self, url: &str) -> Result<Option<Self::GetResponse>, ClientError> {
+            Http::get(self, url.to_string())
+        }
+
+        async fn post(&mut self, url: &str, body: Vec<u8>) -> Result<(), ClientError> {
+            Http::post(self, url.to_string(), body)
+        }
+
+        async fn get_stream(&mut self, url: &str) -> Result<Option<ByteStream>, ClientError> {
+            Http::get_stream(self, url.to_string())
+        }
+
+        async fn get_if_none_match(
+            &mut self,
+            url: &str,
+            etag: Option<&str>,
+        ) -> Result<ConditionalResponse<Self::GetResponse>, ClientError> {
+            Http::get_if_none_match(self, url.to_string(), etag.map(String::from))
+        }
+
+        async fn get_negotiated(
+            &mut self,
+            url: &str,
+            accept: &str,
+            accept_encoding: &str,
+        ) -> Result<Option<NegotiatedResponse<Self::GetResponse>>, ClientError> {
+            Http::get_negotiated(
+                self,
+                url.to_string(),
+                accept.to_string(),
+                accept_encoding.to_string(),
+            )
+        }
+    }
+
+    /// Creates a mocked HTTP client which streams the events of the `stream` once.
+    fn streaming_client(stream: ByteStream) -> MockHttp {
+        let mut http = MockHttp::new();
+        let mut stream = Some(stream);
+        http.expect_get_stream()
+            .withf(|url| url == "http://localhost:8081/events")
+            .returning(move |_| Ok(stream.take()));
+        http
+    }
+
+    #[tokio::test]
+    async fn test_round_changed() {
+        let (tx, rx) = mpsc::unbounded();
+        let http = streaming_client(rx.boxed());
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        // poll once after subscribing
+        assert!(client.round_changed().await);
+        assert!(!client.round_changed().await);
+
+        let phase = b"event:phase\ndata:{\"round_id\":1,\"phase\":\"sum\"}\n\n";
+        tx.unbounded_send(Ok(phase.to_vec())).unwrap();
+        assert!(!client.round_changed().await);
+
+        let params = b"event:params\ndata:{\"round_id\":2}\n\n";
+        tx.unbounded_send(Ok(params.to_vec())).unwrap();
+        assert!(client.round_changed().await);
+        assert!(!client.round_changed().await);
+
+        // fall back to polling if the subscription is closed
+        drop(tx);
+        assert!(client.round_changed().await);
+        assert!(client.round_changed().await);
+    }
+
+    /// Sets the time when the subscription of the `client` received its last chunk.
+    async fn set_received_at<C>(client: &Client<C>, received_at: Instant) {
+        let mut push = client.push.lock().await;
+        push.subscription.as_mut().unwrap().received_at = received_at;
+    }
+
+    #[tokio::test]
+    async fn test_round_changed_stale() {
+        let (tx, rx) = mpsc::unbounded();
+        let http = streaming_client(rx.boxed());
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+        assert!(client.round_changed().await);
+
+        // a keep-alive comment keeps the subscription fresh
+        let stale = Instant::now() - MAX_STALENESS - Duration::from_secs(1);
+        set_received_at(&client, stale).await;
+        tx.unbounded_send(Ok(b":\n\n".to_vec())).unwrap();
+        assert!(!client.round_changed().await);
+
+        // fall back to polling if nothing has been received for too long
+        set_received_at(&client, stale).await;
+        assert!(client.round_changed().await);
+        assert!(client.push.lock().await.subscription.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_round_changed_unsupported() {
+        let mut http = MockHttp::new();
+        http.expect_get_stream().times(1).returning(|_| Ok(None));
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        // the client doesn't subscribe again if it can't stream
+        assert!(client.round_changed().await);
+        assert!(client.round_changed().await);
+        assert!(client.push.lock().await.unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_round_changed_backoff() {
+        let mut http = MockHttp::new();
+        http.expect_get_stream()
+            .times(2)
+            .returning(|_| Err(ClientError::UnexpectedResponse(404)));
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        // the client doesn't subscribe again before the delay has passed
+        assert!(client.round_changed().await);
+        assert!(client.round_changed().await);
+        let retry_at = client.push.lock().await.retry_at.unwrap();
+        assert!(retry_at > Instant::now() + MIN_RESUBSCRIBE_DELAY - Duration::from_secs(1));
+
+        // the delay doubles with each failure
+        client.push.lock().await.retry_at = Some(Instant::now());
+        assert!(client.round_changed().await);
+        let push = client.push.lock().await;
+        assert_eq!(push.failures, 2);
+        assert!(push.retry_at.unwrap() > retry_at + MIN_RESUBSCRIBE_DELAY / 2);
+    }
+
+    #[test]
+    fn test_resubscribe_delay() {
+        assert_eq!(resubscribe_delay(1), MIN_RESUBSCRIBE_DELAY);
+        assert_eq!(resubscribe_delay(2), 2 * MIN_RESUBSCRIBE_DELAY);
+        assert_eq!(resubscribe_delay(3), 4 * MIN_RESUBSCRIBE_DELAY);
+        assert_eq!(resubscribe_delay(u32::MAX), MAX_RESUBSCRIBE_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_get_conditional() {
+        let modified = |value: u32, etag: &str| ConditionalResponse::Modified {
+            body: bincode::serialize(&value).unwrap(),
+            etag: Some(etag.to_string()),
+        };
+        // the entity tags sent by the client along with the responses of the coordinator
+        let requests = vec![
+            (None, modified(1, "\"1\"")),
+            (Some("\"1\""), ConditionalResponse::NotModified),
+            (Some("\"1\""), modified(2, "\"2\"")),
+            (Some("\"2\""), ConditionalResponse::NoContent),
+            (None, ConditionalResponse::NoContent),
+        ];
+        let mut http = MockHttp::new();
+        let mut seq = Sequence::new();
+        for (etag, response) in requests {
+            let etag = etag.map(String::from);
+            http.expect_get_if_none_match()
+                .withf(move |url, sent| url == "http://localhost:8081/model" && sent == &etag)
+                .times(1)
+                .in_sequence(&mut seq)
+                .return_once(move |_, _| Ok(response));
+        }
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+        let url = client.url("model");
+
+        assert_eq!(client.get::<u32>(&url).await.unwrap(), Some(1));
+        assert_eq!(client.get::<u32>(&url).await.unwrap(), Some(1));
+        assert_eq!(client.get::<u32>(&url).await.unwrap(), Some(2));
+        assert_eq!(client.get::<u32>(&url).await.unwrap(), None);
+        assert_eq!(client.get::<u32>(&url).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_model_history() {
+        let mut http = MockHttp::new();
+        let mut seq = Sequence::new();
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/models?from=1&limit=10")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(None));
+        // the global models of past rounds are not cached
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/models/3")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(None));
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        assert!(client.get_model_history(1, 10).await.unwrap().is_empty());
+        assert!(client.get_global_model(3).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_proofs() {
+        let keys = SigningKeyPair::generate();
+        let model = Model::from_primitives(vec![1_f32, 2_f32].into_iter()).unwrap();
+        let proof = ModelProof::new(1, RoundSeed::generate(), &model, None, &keys.secret);
+
+        let mut http = MockHttp::new();
+        let mut seq = Sequence::new();
+        let body = bincode::serialize(&vec![proof.clone()]).unwrap();
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/proofs")
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_once(move |_| Ok(Some(body)));
+        let body = bincode::serialize(&proof).unwrap();
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/proofs/1")
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_once(move |_| Ok(Some(body)));
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/proofs/2")
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(None));
+        let body = bincode::serialize(&keys.public).unwrap();
+        http.expect_get()
+            .withf(|url| url == "http://localhost:8081/proofs/key")
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_once(move |_| Ok(Some(body)));
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        let proofs = client.get_proofs().await.unwrap();
+        assert_eq!(proofs, vec![proof]);
+        let proof = client.get_proof(1).await.unwrap().unwrap();
+        assert!(proof.verify_model(&model));
+        assert!(client.get_proof(2).await.unwrap().is_none());
+        let key = client.get_proof_key().await.unwrap().unwrap();
+        assert!(verify_chain(&proofs, &key).is_ok());
+    }
+
+    /// Expects a negotiated request for the model as primitive values of the `data_type`.
+    fn expect_primitive_model(
+        http: &mut MockHttp,
+        seq: &mut Sequence,
+        data_type: &str,
+        response: Option<NegotiatedResponse<Vec<u8>>>,
+    ) {
+        let accept = format!(
+            "application/x-xaynet-primitive-model; data-type={}",
+            data_type
+        );
+        http.expect_get_negotiated()
+            .withf(move |url, sent_accept, accept_encoding| {
+                url == "http://localhost:8081/model"
+                    && sent_accept == &accept
+                    && accept_encoding == "zstd"
+            })
+            .times(1)
+            .in_sequence(seq)
+            .return_once(move |_, _, _| Ok(response));
+    }
+
+    #[tokio::test]
+    async fn test_get_primitive_model() {
+        let primitive_model = PrimitiveModel::F32(vec![1., -2.5]);
+        let identity = NegotiatedResponse {
+            body: primitive_model.to_bytes(),
+            content_type: Some("application/x-xaynet-primitive-model; data-type=f32".into()),
+            content_encoding: None,
+        };
+        let zstd = NegotiatedResponse {
+            body: zstd::encode_all(primitive_model.to_bytes().as_slice(), 0).unwrap(),
+            content_type: Some("application/x-xaynet-primitive-model;data-type=f32".into()),
+            content_encoding: Some("zstd".into()),
+        };
+        let bincode = NegotiatedResponse {
+            body: bincode::serialize(&primitive_model.clone().into_model()).unwrap(),
+            content_type: Some("application/octet-stream".into()),
+            content_encoding: None,
+        };
+        let mut http = MockHttp::new();
+        let mut seq = Sequence::new();
+        expect_primitive_model(&mut http, &mut seq, "f32", None);
+        expect_primitive_model(&mut http, &mut seq, "f32", Some(identity.clone()));
+        expect_primitive_model(&mut http, &mut seq, "f64", Some(identity));
+        expect_primitive_model(&mut http, &mut seq, "f32", Some(zstd));
+        expect_primitive_model(&mut http, &mut seq, "f64", Some(bincode));
+        http.expect_get_negotiated()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Err(ClientError::UnexpectedResponse(406)));
+        let mut client = Client::new(http, "http://localhost:8081").unwrap();
+
+        assert!(client
+            .get_primitive_model(DataType::F32)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            client.get_primitive_model(DataType::F32).await.unwrap(),
+            Some(primitive_model.clone()),
+        );
+        // the coordinator must respond with the requested data type
+        assert!(client.get_primitive_model(DataType::F64).await.is_err());
+        assert_eq!(
+            client.get_primitive_model(DataType::F32).await.unwrap(),
+            Some(primitive_model),
+        );
+        // fall back to a model serialized with bincode
+        assert_eq!(
+            client.get_primitive_model(DataType::F64).await.unwrap(),
+            Some(PrimitiveModel::F64(vec![1., -2.5])),
+        );
+        // the coordinator can't convert the weights into the data type
+        assert!(matches!(
+            client.get_primitive_model(DataType::I32).await,
+            Err(ClientError::Deserialize(_)),
+        ));
+    }
+
+    #[test]
+    fn test_message_error_from_body() {
+        let body = br#"{"code":"message_discarded","message":"the message was discarded"}"#;
+        let error = MessageError::from_body(body).unwrap();
+        assert_eq!(error.code, MessageErrorCode::MessageDiscarded);
+        assert!(error.is_final());
+
+        let body = br#"{"code":"multipart_capacity_exceeded","message":"try again later"}"#;
+        let error = MessageError::from_body(body).unwrap();
+        assert_eq!(error.code, MessageErrorCode::MultipartCapacityExceeded);
+        assert!(!error.is_final());
+
+        let body = br#"{"code":"something_new","message":"unknown"}"#;
+        let error = MessageError::from_body(body).unwrap();
+        assert_eq!(error.code, MessageErrorCode::Unknown);
+        assert!(!error.is_final());
+
+        assert!(MessageError::from_body(b"<html>Bad Gateway</html>").is_none());
     }
 }
@@ -202,6 +202,7 @@
 
 pub mod client;
 mod message_encoder;
+pub mod notifications;
 pub mod settings;
 mod state_machine;
 mod traits;
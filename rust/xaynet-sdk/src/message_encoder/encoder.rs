@@ -18,49 +18,151 @@ pub struct MultipartEncoder {
     coordinator_pk: PublicEncryptKey,
     /// Serialized message payload.
     data: Vec<u8>,
-    /// Next chunk ID to be produced by the iterator
-    id: u16,
+    /// ID of the next chunk which hasn't been acknowledged yet. All
+    /// the chunks with a lower ID have been acknowledged.
+    id: u32,
     /// Message tag
     tag: Tag,
     /// The maximum size allowed for the payload. `self.data` is split
     /// in chunks of this size.
     payload_size: usize,
     /// A random ID common to all the message chunks.
-    message_id: u16,
+    message_id: u32,
+    /// Whether `self.data` is compressed.
+    compressed: bool,
+    /// Whether the chunks have an extended header, because `self.data`
+    /// is split in more chunks than a legacy chunk header can address.
+    extended: bool,
+}
+
+/// A multipart encoder as it was serialized before the protocol
+/// versioning, when the chunk ids were `u16`.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct LegacyMultipartEncoder {
+    pub keys: SigningKeyPair,
+    pub coordinator_pk: PublicEncryptKey,
+    pub data: Vec<u8>,
+    /// Next chunk ID to be produced by the iterator
+    pub id: u16,
+    pub tag: Tag,
+    pub payload_size: usize,
+    pub message_id: u16,
 }
 
 /// Overhead induced by wrapping the data in [`Payload::Chunk`]
 pub const CHUNK_OVERHEAD: usize = 8;
 pub const MIN_PAYLOAD_SIZE: usize = CHUNK_OVERHEAD + 1;
+/// Overhead induced by wrapping the data in a [`Payload::Chunk`] with
+/// an extended header
+pub const EXTENDED_CHUNK_OVERHEAD: usize = 24;
+
+impl MultipartEncoder {
+    /// Create a new encoder that splits `data` in chunks. The chunks
+    /// only have an extended header if the number of chunks exceeds
+    /// what a legacy chunk header can address, so that the messages
+    /// remain readable by older coordinators whenever possible.
+    ///
+    /// # Errors
+    ///
+    /// An [`InvalidEncodingInput::PayloadSize`] error is returned
+    /// when `payload_size` is too small to split `data` in chunks.
+    fn new(
+        keys: SigningKeyPair,
+        coordinator_pk: PublicEncryptKey,
+        data: Vec<u8>,
+        tag: Tag,
+        payload_size: usize,
+        compressed: bool,
+    ) -> Result<Self, InvalidEncodingInput> {
+        let nb_chunks = Chunker::new(&data, payload_size - CHUNK_OVERHEAD).nb_chunks();
+        let extended = nb_chunks > u16::MAX as usize + 1;
+        let message_id = if extended {
+            if payload_size <= EXTENDED_CHUNK_OVERHEAD {
+                return Err(InvalidEncodingInput::PayloadSize);
+            }
+            let nb_chunks = Chunker::new(&data, payload_size - EXTENDED_CHUNK_OVERHEAD).nb_chunks();
+            if nb_chunks > u32::MAX as usize {
+                return Err(InvalidEncodingInput::PayloadSize);
+            }
+            rand::random::<u32>()
+        } else {
+            rand::random::<u16>() as u32
+        };
+        Ok(Self {
+            keys,
+            coordinator_pk,
+            data,
+            id: 0,
+            tag,
+            payload_size,
+            message_id,
+            compressed,
+            extended,
+        })
+    }
 
-impl Iterator for MultipartEncoder {
-    type Item = Vec<u8>;
+    /// Get the total number of chunks.
+    fn nb_chunks(&self) -> usize {
+        Chunker::new(&self.data, self.payload_size - self.chunk_overhead()).nb_chunks()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let chunker = Chunker::new(&self.data, self.payload_size - CHUNK_OVERHEAD);
+    /// Serialize the next chunk which hasn't been acknowledged yet.
+    fn current(&self) -> Option<Vec<u8>> {
+        let chunker = Chunker::new(&self.data, self.payload_size - self.chunk_overhead());
+        let nb_chunks = chunker.nb_chunks();
 
-        if self.id as usize >= chunker.nb_chunks() {
+        if self.id as usize >= nb_chunks {
             return None;
         }
 
         let chunk = Chunk {
             id: self.id,
             message_id: self.message_id,
-            last: self.id as usize == chunker.nb_chunks() - 1,
+            last: self.id as usize == nb_chunks - 1,
+            count: if self.extended {
+                Some(nb_chunks as u32)
+            } else {
+                None
+            },
             data: chunker.get_chunk(self.id as usize).to_vec(),
         };
-        self.id += 1;
 
         let message = Message {
             // The signature is computed when serializing the message
             signature: None,
             participant_pk: self.keys.public,
             is_multipart: true,
+            is_compressed: self.compressed,
             tag: self.tag,
             payload: Payload::Chunk(chunk),
             coordinator_pk: self.coordinator_pk,
         };
-        let data = serialize_message(&message, &self.keys.secret);
+        Some(serialize_message(&message, &self.keys.secret))
+    }
+
+    /// Mark the current chunk as acknowledged.
+    fn acknowledge(&mut self) {
+        if (self.id as usize) < self.nb_chunks() {
+            self.id += 1;
+        }
+    }
+
+    /// Get the overhead of the chunk header.
+    fn chunk_overhead(&self) -> usize {
+        if self.extended {
+            EXTENDED_CHUNK_OVERHEAD
+        } else {
+            CHUNK_OVERHEAD
+        }
+    }
+}
+
+impl Iterator for MultipartEncoder {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.current()?;
+        self.acknowledge();
         Some(data)
     }
 }
@@ -70,6 +172,10 @@ impl Iterator for MultipartEncoder {
 /// is added, and the message is serialized and signed. If
 /// the [`Payload`] is too large to fit in a single message, it is
 /// split in chunks which are also serialized and signed.
+///
+/// The encoder records which parts of the message have been
+/// acknowledged (see [`MessageEncoder::acknowledge()`]), so that a
+/// deserialized encoder only yields the remaining parts.
 #[derive(Serialize, Deserialize, Debug)]
 pub enum MessageEncoder {
     /// Encoder for a payload that fits in a single message.
@@ -79,14 +185,65 @@ pub enum MessageEncoder {
     Multipart(MultipartEncoder),
 }
 
+/// A message encoder as it was serialized before the protocol
+/// versioning.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) enum LegacyMessageEncoder {
+    Simple(Option<Vec<u8>>),
+    Multipart(LegacyMultipartEncoder),
+}
+
+impl LegacyMessageEncoder {
+    /// Convert the legacy encoder into an encoder which yields the
+    /// remaining parts, starting with the part that `failed` to be
+    /// sent.
+    ///
+    /// The legacy encoder already yielded the failed part, which has
+    /// been kept encrypted. A multipart message recreates the part
+    /// from its chunk, but a single message can't be recreated, in
+    /// which case `None` is returned.
+    pub(crate) fn into_encoder(self, failed: Option<Vec<u8>>) -> Option<MessageEncoder> {
+        match self {
+            LegacyMessageEncoder::Simple(_) if failed.is_some() => None,
+            LegacyMessageEncoder::Simple(data) => Some(MessageEncoder::Simple(data)),
+            LegacyMessageEncoder::Multipart(encoder) => {
+                let LegacyMultipartEncoder {
+                    keys,
+                    coordinator_pk,
+                    data,
+                    id,
+                    tag,
+                    payload_size,
+                    message_id,
+                } = encoder;
+                let id = if failed.is_some() {
+                    id.saturating_sub(1)
+                } else {
+                    id
+                };
+                Some(MessageEncoder::Multipart(MultipartEncoder {
+                    keys,
+                    coordinator_pk,
+                    data,
+                    id: id as u32,
+                    tag,
+                    payload_size,
+                    message_id: message_id as u32,
+                    compressed: false,
+                    extended: false,
+                }))
+            }
+        }
+    }
+}
+
 impl Iterator for MessageEncoder {
     type Item = Vec<u8>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            MessageEncoder::Simple(ref mut data) => data.take(),
-            MessageEncoder::Multipart(ref mut multipart_encoder) => multipart_encoder.next(),
-        }
+        let data = self.current()?;
+        self.acknowledge();
+        Some(data)
     }
 }
 
@@ -99,6 +256,44 @@ pub enum InvalidEncodingInput {
 }
 
 impl MessageEncoder {
+    /// Get the next part of the message which hasn't been
+    /// acknowledged yet, or `None` if all the parts have been
+    /// acknowledged.
+    ///
+    /// Getting the part again, _e.g._ after it failed to be sent,
+    /// yields the same part until it is acknowledged.
+    pub fn current(&self) -> Option<Vec<u8>> {
+        match self {
+            MessageEncoder::Simple(data) => data.clone(),
+            MessageEncoder::Multipart(multipart_encoder) => multipart_encoder.current(),
+        }
+    }
+
+    /// Mark the part returned by [`MessageEncoder::current()`] as
+    /// acknowledged by the coordinator.
+    pub fn acknowledge(&mut self) {
+        match self {
+            MessageEncoder::Simple(data) => *data = None,
+            MessageEncoder::Multipart(multipart_encoder) => multipart_encoder.acknowledge(),
+        }
+    }
+
+    /// Get the number of parts which have been acknowledged.
+    pub fn acknowledged(&self) -> usize {
+        match self {
+            MessageEncoder::Simple(data) => data.is_none() as usize,
+            MessageEncoder::Multipart(multipart_encoder) => multipart_encoder.id as usize,
+        }
+    }
+
+    /// Get the total number of parts of the message.
+    pub fn nb_parts(&self) -> usize {
+        match self {
+            MessageEncoder::Simple(_) => 1,
+            MessageEncoder::Multipart(multipart_encoder) => multipart_encoder.nb_chunks(),
+        }
+    }
+
     // NOTE: the only reason we need to consume the payload is because creating the Message
     // consumes it.
     /// Create a new encoder for the given payload. The `participant`
@@ -118,6 +313,53 @@ impl MessageEncoder {
         coordinator_pk: PublicEncryptKey,
         max_payload_size: usize,
     ) -> Result<Self, InvalidEncodingInput> {
+        Self::check_input(&payload, max_payload_size)?;
+
+        if max_payload_size != 0 && payload.buffer_length() > max_payload_size {
+            Self::new_multipart(keys, coordinator_pk, payload, max_payload_size)
+        } else {
+            Ok(Self::new_simple(keys, coordinator_pk, payload))
+        }
+    }
+
+    /// Create a new encoder for the given payload, which compresses
+    /// the serialized payload with zstd. A compressed payload is
+    /// always sent as a multipart message, which is split in multiple
+    /// chunks if the compressed payload is larger than
+    /// `max_payload_size`. If compression doesn't shrink the payload,
+    /// it is encoded uncompressed like with [`MessageEncoder::new()`].
+    ///
+    /// # Errors
+    ///
+    /// See [`MessageEncoder::new()`].
+    pub fn new_compressed(
+        keys: SigningKeyPair,
+        payload: Payload,
+        coordinator_pk: PublicEncryptKey,
+        max_payload_size: usize,
+    ) -> Result<Self, InvalidEncodingInput> {
+        Self::check_input(&payload, max_payload_size)?;
+
+        let mut data = vec![0; payload.buffer_length()];
+        payload.to_bytes(&mut data);
+        match zstd::encode_all(data.as_slice(), 0) {
+            // Unlike an uncompressed message, a compressed message
+            // always has the overhead of a chunk
+            Ok(compressed) if compressed.len() + CHUNK_OVERHEAD < data.len() => {
+                let payload_size = if max_payload_size == 0 {
+                    compressed.len() + CHUNK_OVERHEAD
+                } else {
+                    max_payload_size
+                };
+                let tag = Self::get_tag_from_payload(&payload);
+                MultipartEncoder::new(keys, coordinator_pk, compressed, tag, payload_size, true)
+                    .map(Self::Multipart)
+            }
+            _ => Self::new(keys, payload, coordinator_pk, max_payload_size),
+        }
+    }
+
+    fn check_input(payload: &Payload, max_payload_size: usize) -> Result<(), InvalidEncodingInput> {
         // Reject payloads of type Payload::Chunk. It is the job of the encoder to produce those if
         // the payload is deemed to big to be sent in a single message
         if payload.is_chunk() {
@@ -128,16 +370,7 @@ impl MessageEncoder {
             return Err(InvalidEncodingInput::PayloadSize);
         }
 
-        if max_payload_size != 0 && payload.buffer_length() > max_payload_size {
-            Ok(Self::new_multipart(
-                keys,
-                coordinator_pk,
-                payload,
-                max_payload_size,
-            ))
-        } else {
-            Ok(Self::new_simple(keys, coordinator_pk, payload))
-        }
+        Ok(())
     }
 
     fn new_simple(
@@ -150,6 +383,7 @@ impl MessageEncoder {
             signature: None,
             participant_pk: keys.public,
             is_multipart: false,
+            is_compressed: false,
             coordinator_pk,
             tag: Self::get_tag_from_payload(&payload),
             payload,
@@ -163,19 +397,12 @@ impl MessageEncoder {
         coordinator_pk: PublicEncryptKey,
         payload: Payload,
         payload_size: usize,
-    ) -> Self {
+    ) -> Result<Self, InvalidEncodingInput> {
         let tag = Self::get_tag_from_payload(&payload);
         let mut data = vec![0; payload.buffer_length()];
         payload.to_bytes(&mut data);
-        Self::Multipart(MultipartEncoder {
-            keys,
-            data,
-            id: 0,
-            tag,
-            coordinator_pk,
-            payload_size,
-            message_id: rand::random::<u16>(),
-        })
+        MultipartEncoder::new(keys, coordinator_pk, data, tag, payload_size, false)
+            .map(Self::Multipart)
     }
 
     fn get_tag_from_payload(payload: &Payload) -> Tag {
@@ -192,7 +419,7 @@ impl MessageEncoder {
 mod tests {
     use xaynet_core::{
         crypto::{ByteObject, EncryptKeyPair, EncryptKeySeed, SigningKeyPair, SigningKeySeed},
-        message::{FromBytes, Update},
+        message::{FromBytes, Sum, Update},
         testutils::multipart as helpers,
     };
 
@@ -214,6 +441,7 @@ mod tests {
             signature: None,
             participant_pk: participant_keys().public,
             is_multipart: false,
+            is_compressed: false,
             tag: Tag::Update,
             payload,
             coordinator_pk: coordinator_keys().public,
@@ -293,6 +521,156 @@ mod tests {
         assert_eq!(update, extract_update(msg));
     }
 
+    #[test]
+    fn acknowledge() {
+        let msg = small_message();
+        let mut enc = MessageEncoder::new(
+            participant_keys(),
+            msg.clone().payload,
+            msg.coordinator_pk,
+            200,
+        )
+        .unwrap();
+        assert_eq!(enc.nb_parts(), 2);
+
+        // the current chunk is yielded until it is acknowledged
+        let chunk = extract_chunk(Message::from_byte_slice(&enc.current().unwrap()).unwrap());
+        assert_eq!(chunk.id, 0);
+        let chunk = extract_chunk(Message::from_byte_slice(&enc.current().unwrap()).unwrap());
+        assert_eq!(chunk.id, 0);
+        assert_eq!(enc.acknowledged(), 0);
+
+        // a deserialized encoder resumes after the acknowledged chunks
+        enc.acknowledge();
+        let mut enc: MessageEncoder =
+            bincode::deserialize(&bincode::serialize(&enc).unwrap()).unwrap();
+        assert_eq!(enc.acknowledged(), 1);
+        let chunk = extract_chunk(Message::from_byte_slice(&enc.current().unwrap()).unwrap());
+        assert_eq!(chunk.id, 1);
+        assert!(chunk.last);
+
+        enc.acknowledge();
+        assert_eq!(enc.acknowledged(), 2);
+        assert!(enc.current().is_none());
+        assert!(enc.next().is_none());
+
+        let mut enc =
+            MessageEncoder::new(participant_keys(), msg.payload, msg.coordinator_pk, 0).unwrap();
+        assert_eq!(enc.nb_parts(), 1);
+        assert!(enc.current().is_some());
+        enc.acknowledge();
+        assert_eq!(enc.acknowledged(), 1);
+        assert!(enc.current().is_none());
+    }
+
+    #[test]
+    fn extended_chunks() {
+        // a legacy chunk header can address at most 65536 chunks of
+        // 25 - 8 = 17 bytes
+        let msg = message(80 + 32 + 4, 18 + 6 * 200_000);
+        let payload_len = msg.payload.buffer_length();
+        assert!(payload_len > 17 * 65536);
+
+        let mut enc = match MessageEncoder::new(
+            participant_keys(),
+            msg.clone().payload,
+            msg.coordinator_pk,
+            25,
+        )
+        .unwrap()
+        {
+            MessageEncoder::Multipart(enc) => enc,
+            MessageEncoder::Simple(_) => panic!("not a multipart encoder"),
+        };
+
+        // an extended chunk contains 25 - 24 = 1 byte
+        let parsed = Message::from_byte_slice(&enc.next().unwrap().as_slice()).unwrap();
+        let chunk = extract_chunk(parsed);
+        assert_eq!(chunk.id, 0);
+        assert!(!chunk.last);
+        assert_eq!(chunk.count, Some(payload_len as u32));
+        assert_eq!(chunk.data.len(), 1);
+
+        // skip to the last chunk
+        enc.id = payload_len as u32 - 1;
+        let parsed = Message::from_byte_slice(&enc.next().unwrap().as_slice()).unwrap();
+        let chunk = extract_chunk(parsed);
+        assert_eq!(chunk.id, payload_len as u32 - 1);
+        assert!(chunk.last);
+        assert!(enc.next().is_none());
+
+        // the payload size doesn't leave room for an extended chunk header
+        assert!(
+            MessageEncoder::new(participant_keys(), msg.payload, msg.coordinator_pk, 24).is_err()
+        );
+    }
+
+    #[test]
+    fn compressed() {
+        // the seed dict and the mask object of the test update are highly redundant
+        let msg = message(4 + 112 * 50, 18 + 6 * 1000);
+        let payload_len = msg.payload.buffer_length();
+
+        let enc = MessageEncoder::new_compressed(
+            participant_keys(),
+            msg.clone().payload,
+            msg.coordinator_pk,
+            0,
+        )
+        .unwrap();
+        let chunks: Vec<Chunk> = enc
+            .map(|data| {
+                let parsed = Message::from_byte_slice(&data.as_slice()).unwrap();
+                assert!(parsed.is_multipart);
+                assert!(parsed.is_compressed);
+                extract_chunk(parsed)
+            })
+            .collect();
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].last);
+        assert!(chunks[0].data.len() < payload_len);
+
+        let payload_data = zstd::decode_all(chunks[0].data.as_slice()).unwrap();
+        let update = Update::from_byte_slice(&payload_data).unwrap();
+        assert_eq!(update, extract_update(msg.clone()));
+
+        let enc = MessageEncoder::new_compressed(
+            participant_keys(),
+            msg.clone().payload,
+            msg.coordinator_pk,
+            100,
+        )
+        .unwrap();
+        let chunks: Vec<Chunk> = enc
+            .map(|data| extract_chunk(Message::from_byte_slice(&data.as_slice()).unwrap()))
+            .collect();
+        assert!(chunks.len() > 1);
+        let compressed: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.data).collect();
+        let payload_data = zstd::decode_all(compressed.as_slice()).unwrap();
+        let update = Update::from_byte_slice(&payload_data).unwrap();
+        assert_eq!(update, extract_update(msg));
+    }
+
+    #[test]
+    fn compressed_fallback() {
+        // a sum message is too small to be compressed
+        let keys = participant_keys();
+        let payload: Payload = Sum {
+            sum_signature: keys.secret.sign_detached(b"sum"),
+            ephm_pk: coordinator_keys().public,
+        }
+        .into();
+
+        let mut enc =
+            MessageEncoder::new_compressed(keys, payload.clone(), coordinator_keys().public, 0)
+                .unwrap();
+        let parsed = Message::from_byte_slice(&enc.next().unwrap().as_slice()).unwrap();
+        assert!(!parsed.is_multipart);
+        assert!(!parsed.is_compressed);
+        assert_eq!(parsed.payload, payload);
+        assert!(enc.next().is_none());
+    }
+
     fn extract_chunk(message: Message) -> Chunk {
         if let Payload::Chunk(c) = message.payload {
             c
@@ -2,4 +2,7 @@ mod chunker;
 mod encoder;
 
 use chunker::Chunker;
+pub(crate) use encoder::LegacyMessageEncoder;
+#[cfg(test)]
+pub(crate) use encoder::LegacyMultipartEncoder;
 pub use encoder::MessageEncoder;
@@ -0,0 +1,163 @@
+//! Push notifications of the coordinator.
+//!
+//! The coordinator streams its state machine events as server-sent events on the `/events`
+//! endpoint. A [`Client`] subscribes to these notifications and only fetches the round
+//! parameters after new round parameters have been announced, see
+//! [`XaynetClient::round_changed()`].
+//!
+//! [`Client`]: crate::client::Client
+//! [`XaynetClient::round_changed()`]: crate::XaynetClient::round_changed
+
+use std::mem;
+
+use serde::Deserialize;
+
+/// A push notification of the coordinator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// The coordinator started a new phase.
+    Phase {
+        /// The current round.
+        round_id: u64,
+        /// The name of the new phase, e.g. `sum`.
+        phase: String,
+    },
+    /// The coordinator published new round parameters.
+    Params {
+        /// The current round.
+        round_id: u64,
+    },
+    /// The coordinator published a new global model or invalidated the previous one.
+    Model {
+        /// The current round.
+        round_id: u64,
+        /// Whether a global model is available.
+        available: bool,
+    },
+}
+
+#[derive(Deserialize)]
+struct PhaseData {
+    round_id: u64,
+    phase: String,
+}
+
+#[derive(Deserialize)]
+struct ParamsData {
+    round_id: u64,
+}
+
+#[derive(Deserialize)]
+struct ModelData {
+    round_id: u64,
+    available: bool,
+}
+
+impl Notification {
+    /// Parses a server-sent event with the given name and data.
+    ///
+    /// Returns `None` for unknown or malformed events.
+    fn parse(event: &str, data: &str) -> Option<Self> {
+        match event {
+            "phase" => serde_json::from_str(data)
+                .ok()
+                .map(|PhaseData { round_id, phase }| Self::Phase { round_id, phase }),
+            "params" => serde_json::from_str(data)
+                .ok()
+                .map(|ParamsData { round_id }| Self::Params { round_id }),
+            "model" => serde_json::from_str(data)
+                .ok()
+                .map(|data: ModelData| Self::Model {
+                    round_id: data.round_id,
+                    available: data.available,
+                }),
+            _ => None,
+        }
+    }
+}
+
+/// An incremental parser of the server-sent events stream of the coordinator.
+#[derive(Debug, Default)]
+pub struct NotificationParser {
+    /// Bytes of an incomplete line.
+    line: Vec<u8>,
+    /// The name of the current event.
+    event: String,
+    /// The data of the current event.
+    data: String,
+}
+
+impl NotificationParser {
+    /// Creates a new parser.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the next chunk of the stream and returns the notifications completed by it.
+    pub fn parse(&mut self, chunk: &[u8]) -> Vec<Notification> {
+        let mut notifications = Vec::new();
+        for byte in chunk {
+            if *byte != b'\n' {
+                self.line.push(*byte);
+                continue;
+            }
+            let bytes = mem::take(&mut self.line);
+            let text = String::from_utf8_lossy(&bytes);
+            let line = text.strip_suffix('\r').unwrap_or(&text);
+            if line.is_empty() {
+                // an empty line dispatches the event
+                let event = mem::take(&mut self.event);
+                let data = mem::take(&mut self.data);
+                notifications.extend(Notification::parse(&event, &data));
+                continue;
+            }
+            let (field, value) = match line.find(':') {
+                // a line starting with a colon is a comment, e.g. a keep-alive
+                Some(0) => continue,
+                Some(index) => (&line[..index], &line[index + 1..]),
+                None => (line, ""),
+            };
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = value.to_string(),
+                "data" => {
+                    if !self.data.is_empty() {
+                        self.data.push('\n');
+                    }
+                    self.data.push_str(value);
+                }
+                _ => {}
+            }
+        }
+        notifications
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut parser = NotificationParser::new();
+        assert_eq!(
+            parser.parse(b"event:params\ndata:{\"round_id\":1}\n\n:\n\nevent: phase\n"),
+            vec![Notification::Params { round_id: 1 }],
+        );
+        assert!(parser.parse(b"data: {\"round_id\":1,").is_empty());
+        assert_eq!(
+            parser.parse(b"\"phase\":\"sum\"}\r\n\r\nevent:unknown\ndata:{}\n\n"),
+            vec![Notification::Phase {
+                round_id: 1,
+                phase: "sum".to_string(),
+            }],
+        );
+        assert_eq!(
+            parser.parse(b"event:model\ndata:{\"round_id\":2,\"available\":true}\n\n"),
+            vec![Notification::Model {
+                round_id: 2,
+                available: true,
+            }],
+        );
+    }
+}
@@ -3,13 +3,19 @@ mod max_message_size;
 use serde::{Deserialize, Serialize};
 
 pub use max_message_size::{InvalidMaxMessageSize, MaxMessageSize, MIN_MESSAGE_SIZE};
-use xaynet_core::crypto::SigningKeyPair;
+use xaynet_core::{crypto::SigningKeyPair, mask::DpConfig};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PetSettings {
     pub keys: SigningKeyPair,
     pub scalar: f64,
     pub max_message_size: MaxMessageSize,
+    /// Differential privacy configuration to privatize the local model with before masking it.
+    /// Differential privacy is disabled if this is `None`.
+    pub dp: Option<DpConfig>,
+    /// Whether to compress the messages with zstd, which the coordinator must support. Only
+    /// messages which shrink by compression are sent compressed.
+    pub compress_messages: bool,
 }
 
 impl PetSettings {
@@ -18,6 +24,8 @@ impl PetSettings {
             keys,
             scalar: 1.0,
             max_message_size: MaxMessageSize::default(),
+            dp: None,
+            compress_messages: false,
         }
     }
 }
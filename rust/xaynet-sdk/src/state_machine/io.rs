@@ -63,6 +63,8 @@ pub(crate) trait IO: Send + 'static {
     async fn get_model(&mut self) -> Result<Option<Model>, Box<dyn Error>>;
     /// Send the given signed and encrypted PET message to the coordinator
     async fn send_message(&mut self, msg: Vec<u8>) -> Result<(), Box<dyn Error>>;
+    /// Check whether the round parameters may have changed
+    async fn round_changed(&mut self) -> bool;
 
     /// Notify the participant that a new round started
     fn notify_new_round(&mut self);
@@ -154,6 +156,10 @@ where
             .map_err(|e| Box::new(e) as Box<dyn Error>)
     }
 
+    async fn round_changed(&mut self) -> bool {
+        self.xaynet_client.round_changed().await
+    }
+
     fn notify_new_round(&mut self) {
         self.notifier.new_round()
     }
@@ -206,6 +212,10 @@ impl IO for Box<dyn IO<Model = Box<dyn AsRef<Model> + Send>>> {
         self.as_mut().send_message(msg).await
     }
 
+    async fn round_changed(&mut self) -> bool {
+        self.as_mut().round_changed().await
+    }
+
     fn notify_new_round(&mut self) {
         self.as_mut().notify_new_round()
     }
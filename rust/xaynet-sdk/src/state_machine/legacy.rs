@@ -0,0 +1,127 @@
+//! The participant states as they were serialized before the protocol versioning.
+//!
+//! The fields of the current states have been appended or widened, so these states are kept to
+//! convert a state which has been saved by an older version when it is restored, see
+//! [`SerializableState::from_bytes()`].
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Awaiting,
+    NewRound,
+    SendingSum,
+    SendingSum2,
+    SendingUpdate,
+    SerializableState,
+    SharedState,
+    State,
+    Sum,
+    Sum2,
+    Update,
+};
+use crate::{message_encoder::LegacyMessageEncoder, settings::MaxMessageSize, MessageEncoder};
+use xaynet_core::{common::LegacyRoundParameters, crypto::SigningKeyPair};
+
+/// The shared state before the protocol versioning.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct LegacySharedState {
+    pub keys: SigningKeyPair,
+    pub scalar: f64,
+    pub message_size: MaxMessageSize,
+    pub round_params: LegacyRoundParameters,
+}
+
+impl From<LegacySharedState> for SharedState {
+    /// Converts the legacy shared state into a state without differential privacy and without
+    /// message compression, which weren't supported back then.
+    fn from(shared: LegacySharedState) -> Self {
+        let LegacySharedState {
+            keys,
+            scalar,
+            message_size,
+            round_params,
+        } = shared;
+        Self {
+            keys,
+            scalar,
+            message_size,
+            round_params: round_params.into(),
+            dp: None,
+            compress_messages: false,
+        }
+    }
+}
+
+/// The state of a phase before the protocol versioning.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct LegacyState<P> {
+    pub private: Box<P>,
+    pub shared: Box<LegacySharedState>,
+}
+
+impl<P> From<LegacyState<P>> for State<P> {
+    fn from(state: LegacyState<P>) -> Self {
+        State::new(Box::new((*state.shared).into()), state.private)
+    }
+}
+
+/// The state of a sending phase before the protocol versioning.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct LegacySending<N> {
+    pub message: LegacyMessageEncoder,
+    pub failed: Option<Vec<u8>>,
+    pub next: N,
+}
+
+/// The serializable state before the protocol versioning.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) enum LegacySerializableState {
+    NewRound(LegacyState<NewRound>),
+    Awaiting(LegacyState<Awaiting>),
+    Sum(LegacyState<Sum>),
+    Update(LegacyState<Update>),
+    Sum2(LegacyState<Sum2>),
+    SendingSum(LegacyState<LegacySending<Sum2>>),
+    SendingUpdate(LegacyState<LegacySending<Awaiting>>),
+    SendingSum2(LegacyState<LegacySending<Awaiting>>),
+}
+
+impl From<LegacySerializableState> for SerializableState {
+    fn from(state: LegacySerializableState) -> Self {
+        match state {
+            LegacySerializableState::NewRound(state) => State::from(state).into(),
+            LegacySerializableState::Awaiting(state) => State::from(state).into(),
+            LegacySerializableState::Sum(state) => State::from(state).into(),
+            LegacySerializableState::Update(state) => State::from(state).into(),
+            LegacySerializableState::Sum2(state) => State::from(state).into(),
+            LegacySerializableState::SendingSum(state) => from_sending(state, SendingSum::new),
+            LegacySerializableState::SendingUpdate(state) => {
+                from_sending(state, SendingUpdate::new)
+            }
+            LegacySerializableState::SendingSum2(state) => from_sending(state, SendingSum2::new),
+        }
+    }
+}
+
+/// Converts the state of a legacy sending phase.
+///
+/// If the message can't be sent again (see [`LegacyMessageEncoder::into_encoder()`]), the state
+/// goes to the awaiting phase, like when the coordinator refuses a message.
+fn from_sending<N, S>(
+    state: LegacyState<LegacySending<N>>,
+    new: fn(MessageEncoder, N) -> S,
+) -> SerializableState
+where
+    State<S>: Into<SerializableState>,
+{
+    let LegacySending {
+        message,
+        failed,
+        next,
+    } = *state.private;
+    let shared = Box::new(SharedState::from(*state.shared));
+    match message.into_encoder(failed) {
+        Some(message) => State::new(shared, Box::new(new(message, next))).into(),
+        None => State::new(shared, Box::new(Awaiting)).into(),
+    }
+}
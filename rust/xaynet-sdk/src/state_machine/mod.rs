@@ -3,6 +3,7 @@
 #[macro_use]
 mod phase;
 mod io;
+mod legacy;
 mod phases;
 #[allow(clippy::module_inception)]
 mod state_machine;
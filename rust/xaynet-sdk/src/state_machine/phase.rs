@@ -1,10 +1,22 @@
 use async_trait::async_trait;
+use bincode::Options;
 use derive_more::From;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
-use super::{Awaiting, NewRound, SendingSum, SendingSum2, SendingUpdate, Sum, Sum2, Update, IO};
+use super::{
+    legacy::LegacySerializableState,
+    Awaiting,
+    NewRound,
+    SendingSum,
+    SendingSum2,
+    SendingUpdate,
+    Sum,
+    Sum2,
+    Update,
+    IO,
+};
 use crate::{
     settings::{MaxMessageSize, PetSettings},
     state_machine::{StateMachine, TransitionOutcome},
@@ -13,8 +25,8 @@ use crate::{
 use xaynet_core::{
     common::{RoundParameters, RoundSeed},
     crypto::{ByteObject, PublicEncryptKey, SigningKeyPair},
-    mask::{self, DataType, MaskConfig, Model},
-    message::Payload,
+    mask::{self, DataType, DpConfig, MaskConfig, Model},
+    message::{Payload, PROTOCOL_VERSION},
 };
 
 /// State of the state machine
@@ -70,6 +82,10 @@ pub struct SharedState {
     pub message_size: MaxMessageSize,
     /// Current round parameters
     pub round_params: RoundParameters,
+    /// Differential privacy configuration used before masking
+    pub dp: Option<DpConfig>,
+    /// Whether the messages are compressed
+    pub compress_messages: bool,
 }
 
 /// Get arbitrary round parameters. These round parameters are never used, we just
@@ -90,6 +106,9 @@ fn dummy_round_parameters() -> RoundParameters {
         }
         .into(),
         model_length: 0,
+        privacy_budget: None,
+        version: PROTOCOL_VERSION,
+        starts_at: None,
     }
 }
 
@@ -100,6 +119,8 @@ impl SharedState {
             scalar: settings.scalar,
             message_size: settings.max_message_size,
             round_params: dummy_round_parameters(),
+            dp: settings.dp,
+            compress_messages: settings.compress_messages,
         }
     }
 }
@@ -181,6 +202,10 @@ where
     /// Check whether the coordinator has published new round parameters. In other
     /// words, this checks whether a new round has started.
     async fn check_round_freshness(&mut self) -> RoundFreshness {
+        if !self.io.round_changed().await {
+            debug!("no new round parameters have been announced");
+            return RoundFreshness::Fresh;
+        }
         match self.io.get_round_params().await {
             Err(e) => {
                 warn!("failed to fetch round parameters {:?}", e);
@@ -223,15 +248,17 @@ impl<P> Phase<P> {
     /// The encoder takes care of converting the given `payload` into one or several
     /// signed and encrypted PET messages.
     pub fn message_encoder(&self, payload: Payload) -> MessageEncoder {
-        MessageEncoder::new(
-            self.state.shared.keys.clone(),
+        let shared = &self.state.shared;
+        let new = if shared.compress_messages {
+            MessageEncoder::new_compressed
+        } else {
+            MessageEncoder::new
+        };
+        new(
+            shared.keys.clone(),
             payload,
-            self.state.shared.round_params.pk,
-            self.state
-                .shared
-                .message_size
-                .max_payload_size()
-                .unwrap_or(0),
+            shared.round_params.pk,
+            shared.message_size.max_payload_size().unwrap_or(0),
         )
         // the encoder rejects Chunk payload, but in the state
         // machine, we never manually create such payloads so
@@ -312,6 +339,37 @@ pub enum SerializableState {
     SendingSum2(State<SendingSum2>),
 }
 
+impl SerializableState {
+    /// Deserializes a state from its bincode representation.
+    ///
+    /// A state which has been saved before the protocol versioning is converted into a state
+    /// without differential privacy and without message compression. If such a state was sending
+    /// a single message which failed to be sent, the message is lost and the state goes to the
+    /// awaiting phase.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the error of the current format if `bytes` holds neither format.
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        state_options().deserialize(bytes).or_else(|error| {
+            state_options()
+                .deserialize::<LegacySerializableState>(bytes)
+                .map(Into::into)
+                .map_err(|_| error)
+        })
+    }
+}
+
+/// The bincode options of a serialized state.
+///
+/// They match [`bincode::serialize()`], except that trailing bytes are rejected, so that a legacy
+/// state can't be mistaken for a current state by only reading its prefix.
+fn state_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+}
+
 impl<P> Into<SerializableState> for Phase<P>
 where
     State<P>: Into<SerializableState>,
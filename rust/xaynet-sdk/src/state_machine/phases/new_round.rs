@@ -1,7 +1,12 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::info;
-use xaynet_core::crypto::{ByteObject, Signature};
+use tracing::{debug, info, warn};
+use xaynet_core::{
+    crypto::{ByteObject, Signature},
+    message::PROTOCOL_VERSION,
+};
 
 use crate::state_machine::{
     Awaiting,
@@ -30,6 +35,24 @@ impl Step for Phase<NewRound> {
     async fn step(mut self) -> TransitionOutcome {
         info!("new_round task");
 
+        // a coordinator of an older protocol version can't parse the messages of the participant
+        let version = self.state.shared.round_params.version;
+        if version < PROTOCOL_VERSION {
+            warn!(
+                "the coordinator implements protocol version {}, but the participant requires \
+                 version {}, going to sleep until the coordinator is updated",
+                version, PROTOCOL_VERSION
+            );
+            return TransitionOutcome::Pending(self.into());
+        }
+
+        if let Some(starts_at) = self.state.shared.round_params.starts_at {
+            if starts_at > unix_now() {
+                debug!("round starts at {}, going to sleep until then", starts_at);
+                return TransitionOutcome::Pending(self.into());
+            }
+        }
+
         info!("checking eligibility for sum task");
         let sum_signature = self.sign(b"sum");
         if sum_signature.is_eligible(self.state.shared.round_params.sum) {
@@ -77,3 +100,11 @@ impl Phase<NewRound> {
         state.into_phase(self.io)
     }
 }
+
+/// Gets the current unix timestamp in seconds.
+pub(super) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
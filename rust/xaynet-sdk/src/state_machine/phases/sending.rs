@@ -1,11 +1,14 @@
+use std::error::Error;
+
 use async_trait::async_trait;
 use paste::paste;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
+    client::{MessageError, MessageErrorCode},
     state_machine::{
-        phases::Sum2,
+        phases::{new_round::unix_now, Sum2},
         Awaiting,
         IntoPhase,
         Phase,
@@ -19,6 +22,29 @@ use crate::{
     MessageEncoder,
 };
 
+/// The number of seconds after the announced start of a round during which the coordinator may
+/// not expect the messages of the round yet, because the clocks of the participant and the
+/// coordinator differ.
+const CLOCK_SKEW: u64 = 60;
+
+/// Finds a [`MessageError`] in the chain of `error` sources which makes resending pointless.
+///
+/// A message which is not expected shortly after the round of the `starts_at` timestamp started
+/// is worth resending, since the round may not have started yet according to the clock of the
+/// coordinator.
+fn final_message_error<'a>(
+    error: &'a (dyn Error + 'static),
+    starts_at: Option<u64>,
+) -> Option<&'a MessageError> {
+    let starting = starts_at.map_or(false, |starts_at| {
+        unix_now() < starts_at.saturating_add(CLOCK_SKEW)
+    });
+    std::iter::successors(Some(error), |error| error.source())
+        .find_map(|error| error.downcast_ref::<MessageError>())
+        .filter(|error| error.is_final())
+        .filter(|error| !(starting && error.code == MessageErrorCode::UnexpectedMessage))
+}
+
 /// Implements the `SendingSum`, `SendingUpdate` and `SendingSum2` phases and transitions.
 macro_rules! impl_sending {
     ($Phase: ty, $Next: ty, $phase: expr, $next: expr) => {
@@ -26,12 +52,10 @@ macro_rules! impl_sending {
             #[doc = "The state of the " $phase " sending phase."]
             #[derive(Serialize, Deserialize, Debug)]
             pub struct [<Sending $Phase>] {
-                /// The message to send.
+                /// The message to send. It records which parts have been acknowledged by the
+                /// coordinator, so that a restored phase only sends the remaining parts.
                 message: MessageEncoder,
 
-                /// Chunk that couldn't be sent and should be tried again.
-                failed: Option<Vec<u8>>,
-
                 /// State of the phase to transition to, after this one completes.
                 next: $Next,
             }
@@ -39,11 +63,7 @@ macro_rules! impl_sending {
             impl [<Sending $Phase>] {
                 #[doc = "Creates a new " $phase " sending state."]
                 pub fn new(message: MessageEncoder, next: $Next) -> Self {
-                    Self {
-                        message,
-                        failed: None,
-                        next,
-                    }
+                    Self { message, next }
                 }
             }
 
@@ -75,12 +95,28 @@ macro_rules! impl_sending {
             impl Phase<[<Sending $Phase>]> {
                 #[doc = "Tries to send a " $phase " message and reports back on the progress made."]
                 async fn try_send(mut self, data: Vec<u8>) -> Progress<[<Sending $Phase>]> {
-                    info!("sending {} message (size = {})", $phase, data.len());
+                    info!(
+                        "sending {} message part {}/{} (size = {})",
+                        $phase,
+                        self.state.private.message.acknowledged() + 1,
+                        self.state.private.message.nb_parts(),
+                        data.len(),
+                    );
                     if let Err(e) = self.io.send_message(data.clone()).await {
                         error!("failed to send {} message: {:?}", $phase, e);
-                        self.state.private.failed = Some(data);
+                        let starts_at = self.state.shared.round_params.starts_at;
+                        if let Some(e) = final_message_error(e.as_ref(), starts_at) {
+                            warn!(
+                                "the coordinator refused the {} message: {}, going to awaiting phase",
+                                $phase, e
+                            );
+                            let awaiting = State::new(self.state.shared, Box::new(Awaiting))
+                                .into_phase(self.io);
+                            return Progress::Updated(awaiting.into());
+                        }
                         Progress::Stuck(self)
                     } else {
+                        self.state.private.message.acknowledge();
                         Progress::Updated(self.into())
                     }
                 }
@@ -88,26 +124,18 @@ macro_rules! impl_sending {
                 #[doc =
                     "Sends the next " $phase " message and reports back on the progress made.\n"
                     "\n"
-                    "Retries to send a previously failed message. Otherwise, tries to send the "
-                    "next message."
+                    "Sends the next part which hasn't been acknowledged yet, including a part which "
+                    "previously failed to be sent."
                 ]
-                async fn send_next(mut self) -> Progress<[<Sending $Phase>]> {
-                    if let Some(data) = self.state.private.failed.take() {
-                        debug!(
-                            "retrying to send {} message that couldn't be sent previously",
-                            $phase
-                        );
-                        self.try_send(data).await
-                    } else {
-                        match self.state.private.message.next() {
-                            Some(data) => {
-                                let data = self.state.shared.round_params.pk.encrypt(data.as_slice());
-                                self.try_send(data).await
-                            }
-                            None => {
-                                debug!("nothing left to send");
-                                Progress::Continue(self)
-                            }
+                async fn send_next(self) -> Progress<[<Sending $Phase>]> {
+                    match self.state.private.message.current() {
+                        Some(data) => {
+                            let data = self.state.shared.round_params.pk.encrypt(data.as_slice());
+                            self.try_send(data).await
+                        }
+                        None => {
+                            debug!("nothing left to send");
+                            Progress::Continue(self)
                         }
                     }
                 }
@@ -211,16 +211,38 @@ impl Phase<Update> {
             debug!("already computed the masked model, continuing");
             return Progress::Continue(self);
         }
+        self.check_privacy_budget();
         info!("computing masked model");
         let config = self.state.shared.round_params.mask_config;
         let masker = Masker::new(config);
         // UNWRAP_SAFE: the model is set, per the `has_masked_model()` check above
         let model = self.state.private.model.take().unwrap();
         let scalar = self.state.shared.scalar;
-        self.state.private.mask = Some(masker.mask(scalar, model.as_ref()));
+        let mask = if let Some(dp) = self.state.shared.dp {
+            info!("privatizing local model");
+            let private_model = dp.privatize(model.as_ref(), config.vect);
+            masker.mask(scalar, &private_model)
+        } else {
+            masker.mask(scalar, model.as_ref())
+        };
+        self.state.private.mask = Some(mask);
         Progress::Updated(self.into())
     }
 
+    /// Warns if the local differential privacy configuration doesn't meet the noise multiplier
+    /// expected by the coordinator.
+    fn check_privacy_budget(&self) {
+        if let Some(budget) = self.state.shared.round_params.privacy_budget {
+            let noise_multiplier = self.state.shared.dp.map(|dp| dp.noise_multiplier);
+            if noise_multiplier.map_or(true, |multiplier| multiplier < budget.noise_multiplier) {
+                warn!(
+                    "the coordinator expects a noise multiplier of {}, but the local model is privatized with {:?}",
+                    budget.noise_multiplier, noise_multiplier,
+                );
+            }
+        }
+    }
+
     // Create a local seed dictionary from a sum dictionary.
     pub(crate) fn build_seed_dict(mut self) -> Progress<Update> {
         if self.state.private.has_built_seed_dict() {
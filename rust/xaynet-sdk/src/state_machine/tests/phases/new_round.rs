@@ -1,3 +1,5 @@
+use xaynet_core::message::PROTOCOL_VERSION;
+
 use crate::{
     state_machine::{
         tests::utils::{shared_state, SelectFor},
@@ -35,6 +37,22 @@ async fn test_not_selected() {
     unwrap_step!(phase, complete, awaiting);
 }
 
+#[tokio::test]
+async fn test_round_not_started() {
+    let io = MockIO::new();
+    let mut phase = make_phase(SelectFor::Sum, io);
+    phase.state.shared.round_params.starts_at = Some(u64::MAX);
+    unwrap_step!(phase, pending, new_round);
+}
+
+#[tokio::test]
+async fn test_outdated_coordinator() {
+    let io = MockIO::new();
+    let mut phase = make_phase(SelectFor::Sum, io);
+    phase.state.shared.round_params.version = PROTOCOL_VERSION - 1;
+    unwrap_step!(phase, pending, new_round);
+}
+
 /// Instantiate a new round phase.
 ///
 /// - `task` is the task we want the simulated participant to be selected for. If you want a
@@ -0,0 +1,209 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use xaynet_core::{
+    common::LegacyRoundParameters,
+    crypto::{ByteObject, EncryptKeySeed},
+    message::{FromBytes, Message, Payload, Sum, Tag},
+};
+
+use crate::{
+    client::{ClientError, MessageError, MessageErrorCode},
+    message_encoder::{LegacyMessageEncoder, LegacyMultipartEncoder},
+    state_machine::{
+        legacy::{LegacySending, LegacySerializableState, LegacySharedState, LegacyState},
+        tests::utils::{shared_state, SelectFor},
+        Awaiting,
+        IntoPhase,
+        MockIO,
+        Phase,
+        SendingUpdate,
+        SerializableState,
+        SharedState,
+        State,
+    },
+    unwrap_as,
+    unwrap_step,
+    MessageEncoder,
+};
+
+/// Instantiate a sending phase with a message which is split in 3 chunks.
+fn make_phase(io: MockIO) -> Phase<SendingUpdate> {
+    let shared = shared_state(SelectFor::Update);
+    let payload: Payload = Sum {
+        sum_signature: shared.keys.secret.sign_detached(b"sum"),
+        ephm_pk: shared.round_params.pk,
+    }
+    .into();
+    let message =
+        MessageEncoder::new(shared.keys.clone(), payload, shared.round_params.pk, 40).unwrap();
+    let sending = Box::new(SendingUpdate::new(message, Awaiting));
+    State::new(shared, sending).into_phase(Box::new(io))
+}
+
+/// Expect a single message, which must be the chunk with the given ID.
+fn expect_chunk(io: &mut MockIO, id: u32) {
+    io.expect_send_message()
+        .times(1)
+        .withf(move |data| chunk_id(data) == id)
+        .returning(|_| Ok(()));
+}
+
+/// Decrypt a message and get the ID of its chunk.
+fn chunk_id(data: &[u8]) -> u32 {
+    let keys = EncryptKeySeed::zeroed().derive_encrypt_key_pair();
+    let data = keys.1.decrypt(data, &keys.0).unwrap();
+    match Message::from_byte_slice(&data).unwrap().payload {
+        Payload::Chunk(chunk) => chunk.id,
+        _ => panic!("not a chunk message"),
+    }
+}
+
+/// Serialize and deserialize the phase, and restore it with the given `io`.
+fn save_and_restore(phase: Phase<SendingUpdate>, io: MockIO) -> Phase<SendingUpdate> {
+    let state: SerializableState = phase.into();
+    let bytes = bincode::serialize(&state).unwrap();
+    let state = unwrap_as!(
+        bincode::deserialize(&bytes).unwrap(),
+        SerializableState::SendingUpdate
+    );
+    state.into_phase(Box::new(io))
+}
+
+#[tokio::test]
+async fn test_resume_sending() {
+    let mut io = MockIO::new();
+    expect_chunk(&mut io, 0);
+    let phase = make_phase(io);
+    let phase = unwrap_step!(phase, complete, sending_update);
+
+    // the second chunk is not acknowledged
+    let mut io = MockIO::new();
+    io.expect_send_message().times(1).returning(|_| {
+        Err(Box::new(ClientError::Message(MessageError {
+            code: MessageErrorCode::InternalError,
+            message: "Internal error: oops".into(),
+        })))
+    });
+    let phase = save_and_restore(phase, io);
+    let phase = unwrap_step!(phase, pending, sending_update);
+
+    // the restored phase only sends the chunks which haven't been acknowledged
+    let mut io = MockIO::new();
+    expect_chunk(&mut io, 1);
+    let phase = save_and_restore(phase, io);
+    let phase = unwrap_step!(phase, complete, sending_update);
+
+    let mut io = MockIO::new();
+    expect_chunk(&mut io, 2);
+    io.expect_notify_idle().times(1).return_const(());
+    let phase = save_and_restore(phase, io);
+    let phase = unwrap_step!(phase, complete, sending_update);
+    let _phase = unwrap_step!(phase, complete, awaiting);
+}
+
+/// Instantiate a sending phase for a round which started `started` seconds ago and whose
+/// coordinator doesn't expect the message.
+fn make_unexpected_phase(mut io: MockIO, started: u64) -> Phase<SendingUpdate> {
+    io.expect_send_message().times(1).returning(|_| {
+        Err(Box::new(ClientError::Message(MessageError {
+            code: MessageErrorCode::UnexpectedMessage,
+            message: "the message was not expected".into(),
+        })))
+    });
+    let mut phase = make_phase(io);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    phase.state.shared.round_params.starts_at = Some(now - started);
+    phase
+}
+
+#[tokio::test]
+async fn test_unexpected_message_at_round_start() {
+    // the round may not have started yet according to the clock of the coordinator
+    let phase = make_unexpected_phase(MockIO::new(), 1);
+    let _phase = unwrap_step!(phase, pending, sending_update);
+
+    // the coordinator must have started the round by now
+    let mut io = MockIO::new();
+    io.expect_notify_idle().times(1).return_const(());
+    let phase = make_unexpected_phase(io, 3600);
+    let _phase = unwrap_step!(phase, complete, awaiting);
+}
+
+/// Serialize a sending phase as it was saved before the protocol versioning, whose last part
+/// failed to be sent.
+fn save_legacy(message: LegacyMessageEncoder) -> Vec<u8> {
+    let SharedState {
+        keys,
+        scalar,
+        message_size,
+        round_params,
+        ..
+    } = *shared_state(SelectFor::Update);
+    let shared = LegacySharedState {
+        keys,
+        scalar,
+        message_size,
+        round_params: LegacyRoundParameters {
+            pk: round_params.pk,
+            sum: round_params.sum,
+            update: round_params.update,
+            seed: round_params.seed,
+            mask_config: round_params.mask_config,
+            model_length: round_params.model_length,
+        },
+    };
+    let sending = LegacySending {
+        message,
+        failed: Some(vec![0; 40]),
+        next: Awaiting,
+    };
+    let state = LegacySerializableState::SendingUpdate(LegacyState {
+        private: Box::new(sending),
+        shared: Box::new(shared),
+    });
+    bincode::serialize(&state).unwrap()
+}
+
+#[tokio::test]
+async fn test_restore_legacy_multipart_message() {
+    let shared = shared_state(SelectFor::Update);
+    // the second and last chunk failed to be sent
+    let message = LegacyMessageEncoder::Multipart(LegacyMultipartEncoder {
+        keys: shared.keys.clone(),
+        coordinator_pk: shared.round_params.pk,
+        data: vec![0; 60],
+        id: 2,
+        tag: Tag::Update,
+        payload_size: 40,
+        message_id: 1,
+    });
+    let state = unwrap_as!(
+        SerializableState::from_bytes(&save_legacy(message)).unwrap(),
+        SerializableState::SendingUpdate
+    );
+    assert_eq!(state.shared.round_params.starts_at, None);
+    assert!(state.shared.dp.is_none());
+    assert!(!state.shared.compress_messages);
+
+    // the restored phase sends the chunk again
+    let mut io = MockIO::new();
+    expect_chunk(&mut io, 1);
+    io.expect_notify_idle().times(1).return_const(());
+    let phase = state.into_phase(Box::new(io));
+    let phase = unwrap_step!(phase, complete, sending_update);
+    let _phase = unwrap_step!(phase, complete, awaiting);
+}
+
+#[tokio::test]
+async fn test_restore_legacy_single_message() {
+    // the single message failed to be sent and is only kept encrypted
+    let message = LegacyMessageEncoder::Simple(None);
+    let state = unwrap_as!(
+        SerializableState::from_bytes(&save_legacy(message)).unwrap(),
+        SerializableState::Awaiting
+    );
+    assert!(state.shared.dp.is_none());
+}
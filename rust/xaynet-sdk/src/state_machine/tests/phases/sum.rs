@@ -2,6 +2,7 @@ use thiserror::Error;
 use xaynet_core::crypto::{ByteObject, EncryptKeyPair, EncryptKeySeed};
 
 use crate::{
+    client::{ClientError, MessageError, MessageErrorCode},
     state_machine::{
         tests::utils::{shared_state, SelectFor},
         IntoPhase,
@@ -48,6 +49,35 @@ async fn test_phase() {
     let _phase = unwrap_step!(phase, complete, sending_sum);
 }
 
+#[tokio::test]
+async fn test_sending_refused() {
+    let mut io = MockIO::new();
+    io.expect_send_message().times(1).returning(|_| {
+        Err(Box::new(MessageError {
+            code: MessageErrorCode::NotSumEligible,
+            message: "participant is not eligible for sum task".into(),
+        }))
+    });
+    io.expect_notify_idle().times(1).return_const(());
+    let phase = make_phase(io);
+    let phase = unwrap_step!(phase, complete, sending_sum);
+    let _phase = unwrap_step!(phase, complete, awaiting);
+}
+
+#[tokio::test]
+async fn test_sending_retry() {
+    let mut io = MockIO::new();
+    io.expect_send_message().times(1).returning(|_| {
+        Err(Box::new(ClientError::Message(MessageError {
+            code: MessageErrorCode::InternalError,
+            message: "Internal error: oops".into(),
+        })))
+    });
+    let phase = make_phase(io);
+    let phase = unwrap_step!(phase, complete, sending_sum);
+    let _phase = unwrap_step!(phase, pending, sending_sum);
+}
+
 #[derive(Error, Debug)]
 #[error("error")]
 struct DummyErr;
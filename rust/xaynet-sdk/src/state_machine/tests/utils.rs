@@ -2,6 +2,7 @@ use xaynet_core::{
     common::{RoundParameters, RoundSeed},
     crypto::{ByteObject, EncryptKeyPair, EncryptKeySeed, SigningKeyPair, SigningKeySeed},
     mask::{self, MaskConfig},
+    message::PROTOCOL_VERSION,
 };
 
 use crate::{settings::MaxMessageSize, state_machine::SharedState};
@@ -32,6 +33,13 @@ macro_rules! unwrap_step {
             $state_machine
         )
     };
+    ($phase:expr, $transition_outcome:path, new_round) => {
+        unwrap_step!(
+            $phase,
+            $transition_outcome,
+            $crate::state_machine::StateMachine::NewRound
+        )
+    };
     ($phase:expr, $transition_outcome:path, awaiting) => {
         unwrap_step!(
             $phase,
@@ -148,6 +156,9 @@ pub fn round_params(task: SelectFor) -> RoundParameters {
         seed: RoundSeed::zeroed(),
         mask_config: mask_config().into(),
         model_length: 0,
+        privacy_budget: None,
+        version: PROTOCOL_VERSION,
+        starts_at: None,
     }
 }
 
@@ -157,6 +168,8 @@ pub fn shared_state(task: SelectFor) -> Box<SharedState> {
         scalar: 1.0,
         message_size: MaxMessageSize::unlimited(),
         round_params: round_params(task),
+        dp: None,
+        compress_messages: false,
     })
 }
 
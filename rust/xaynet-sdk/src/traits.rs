@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 
 use xaynet_core::{
-    common::RoundParameters,
+    common::{GlobalModelInfo, RoundParameters},
+    crypto::PublicSigningKey,
     mask::Model,
+    proof::ModelProof,
     SumDict,
     SumParticipantPublicKey,
     UpdateSeedDict,
@@ -68,6 +70,77 @@ pub trait XaynetClient {
     /// Retrieve the current global model, if available.
     async fn get_model(&mut self) -> Result<Option<Model>, Self::Error>;
 
+    /// Retrieve a page of the infos of the global models of the completed rounds, in the order
+    /// of their creation.
+    ///
+    /// The page lists at most `limit` infos of the rounds from `from_round_id` on. The
+    /// coordinator may cap the `limit`, hence the next page starts after the round of the last
+    /// info and an empty page marks the end of the history. The default implementation returns
+    /// an empty page, i.e. the client doesn't support the global model history.
+    async fn get_model_history(
+        &mut self,
+        _from_round_id: u64,
+        _limit: usize,
+    ) -> Result<Vec<GlobalModelInfo>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Retrieve the global model which was created in the given round, if available.
+    ///
+    /// The default implementation returns `None`, i.e. the client doesn't support the global
+    /// model history.
+    async fn get_global_model(&mut self, _round_id: u64) -> Result<Option<Model>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Retrieve the proofs of the global models which the coordinator published to its trust
+    /// anchor, in the order of their publication.
+    ///
+    /// The proofs can be verified with [`verify_chain()`] and the key of
+    /// [`XaynetClient::get_proof_key()`]. The default implementation returns no proofs, i.e. the
+    /// client doesn't support the proofs.
+    ///
+    /// [`verify_chain()`]: xaynet_core::proof::verify_chain
+    async fn get_proofs(&mut self) -> Result<Vec<ModelProof>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Retrieve the published proof of the global model which was created in the given round, if
+    /// available.
+    ///
+    /// A downloaded global model can be checked against its proof with
+    /// [`ModelProof::verify_model()`]. The default implementation returns `None`.
+    async fn get_proof(&mut self, _round_id: u64) -> Result<Option<ModelProof>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Retrieve the public key to verify the published proofs with, if the coordinator signs
+    /// proofs.
+    ///
+    /// The default implementation returns `None`.
+    async fn get_proof_key(&mut self) -> Result<Option<PublicSigningKey>, Self::Error> {
+        Ok(None)
+    }
+
     /// Send an encrypted and signed PET message to the coordinator.
+    ///
+    /// If the coordinator doesn't accept the message, the error should be or be caused by a
+    /// [`MessageError`]. The [`StateMachine`] stops resending a message which has been refused
+    /// for good.
+    ///
+    /// [`MessageError`]: crate::client::MessageError
+    /// [`StateMachine`]: crate::StateMachine
     async fn send_message(&mut self, msg: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Check whether the round parameters may have changed since the last check.
+    ///
+    /// The [`StateMachine`] only fetches the round parameters if this returns `true`. A client
+    /// subscribed to the push notifications of the coordinator can return `false` until new
+    /// round parameters are announced, as long as the subscription is alive. The default
+    /// implementation always returns `true`, i.e. the round parameters are polled.
+    ///
+    /// [`StateMachine`]: crate::StateMachine
+    async fn round_changed(&mut self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,258 @@
+//! A HTTP API for coordinator operators.
+//!
+//! The admin API is served on its own address next to the participant API of [`crate::rest`].
+//! Every request must be authenticated with the token of the [`AdminSettings`] as a bearer token
+//! in the `Authorization` header.
+//!
+//! - `GET /status`: the current round, phase, message counters and phase deadlines as JSON
+//! - `POST /round/skip`: aborts the current round and starts a new round
+//! - `POST /shutdown`: shuts the coordinator down gracefully
+
+use std::{convert::Infallible, sync::Arc};
+
+use serde::Serialize;
+use sodiumoxide::utils::memcmp;
+use tracing::{error, info, warn};
+use warp::{http::StatusCode, Filter, Rejection, Reply};
+
+use crate::{
+    settings::AdminSettings,
+    state_machine::{
+        commands::{Command, CommandSender},
+        events::{EventListener, EventSubscriber, PhaseStatus},
+        phases::PhaseName,
+    },
+};
+
+/// The status of the coordinator.
+#[derive(Debug, Serialize)]
+pub struct Status {
+    /// The current round.
+    pub round_id: u64,
+    /// The current phase.
+    pub phase: PhaseName,
+    /// The progress of the current phase.
+    #[serde(flatten)]
+    pub progress: PhaseStatus,
+}
+
+/// A handle to observe and control the coordinator.
+#[derive(Debug, Clone)]
+pub struct Admin {
+    phase: EventListener<PhaseName>,
+    status: EventListener<PhaseStatus>,
+    commands: CommandSender,
+}
+
+impl Admin {
+    /// Creates a new admin handle.
+    pub fn new(event_subscriber: &EventSubscriber, commands: CommandSender) -> Self {
+        Self {
+            phase: event_subscriber.phase_listener(),
+            status: event_subscriber.status_listener(),
+            commands,
+        }
+    }
+
+    /// Gets the current status of the coordinator.
+    pub fn status(&self) -> Status {
+        let phase = self.phase.get_latest();
+        Status {
+            round_id: phase.round_id,
+            phase: phase.event,
+            progress: self.status.get_latest().event,
+        }
+    }
+}
+
+/// Starts a HTTP server at the address of the `admin_settings`, listening to authenticated
+/// requests of coordinator operators.
+pub async fn serve(admin_settings: AdminSettings, admin: Admin) {
+    let AdminSettings {
+        bind_address,
+        token,
+    } = admin_settings;
+    warp::serve(routes(token, admin)).run(bind_address).await
+}
+
+/// Creates the filter of all admin routes.
+fn routes(
+    token: String,
+    admin: Admin,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let status = warp::path!("status")
+        .and(warp::get())
+        .and(with_admin(admin.clone()))
+        .and_then(handle_status);
+
+    let skip_round = warp::path!("round" / "skip")
+        .and(warp::post())
+        .map(|| Command::SkipRound)
+        .and(with_admin(admin.clone()))
+        .and_then(handle_command);
+
+    let shutdown = warp::path!("shutdown")
+        .and(warp::post())
+        .map(|| Command::Shutdown)
+        .and(with_admin(admin))
+        .and_then(handle_command);
+
+    authorized(token)
+        .and(status.or(skip_round).or(shutdown))
+        .recover(handle_reject)
+        .with(warp::log("admin"))
+}
+
+/// Handles and responds to a request for the coordinator status.
+async fn handle_status(admin: Admin) -> Result<impl Reply, Infallible> {
+    Ok(warp::reply::json(&admin.status()))
+}
+
+/// Handles and responds to a command for the state machine.
+async fn handle_command(command: Command, admin: Admin) -> Result<impl Reply, Infallible> {
+    info!("received admin command {:?}", command);
+    Ok(match admin.commands.send(command) {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(err) => {
+            warn!("failed to handle admin command: {}", err);
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    })
+}
+
+/// Converts an admin handle into a `warp` filter.
+fn with_admin(admin: Admin) -> impl Filter<Extract = (Admin,), Error = Infallible> + Clone {
+    warp::any().map(move || admin.clone())
+}
+
+/// Rejects requests without the bearer `token` in the `Authorization` header.
+pub(crate) fn authorized(token: String) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let expected = Arc::new(format!("Bearer {}", token));
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let expected = expected.clone();
+            async move {
+                match header {
+                    // constant time comparison to not leak the token via timing
+                    Some(header) if memcmp(header.as_bytes(), expected.as_bytes()) => Ok(()),
+                    _ => Err(warp::reject::custom(Unauthorized)),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Handles `warp` rejections of bad requests.
+pub(crate) async fn handle_reject(err: Rejection) -> Result<impl Reply, Infallible> {
+    let code = if let Some(Unauthorized) = err.find() {
+        StatusCode::UNAUTHORIZED
+    } else if err.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        StatusCode::METHOD_NOT_ALLOWED
+    } else {
+        error!("unhandled rejection: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    // reply with empty body; the status code is the interesting part
+    Ok(warp::reply::with_status(Vec::new(), code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        services::tests::utils::new_event_channels,
+        state_machine::commands::CommandReceiver,
+    };
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    #[tokio::test]
+    async fn test_unauthorized() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (_command_rx, command_tx) = CommandReceiver::new();
+        let routes = routes(TOKEN.to_string(), Admin::new(&subscriber, command_tx));
+
+        let response = warp::test::request().path("/status").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = warp::test::request()
+            .path("/status")
+            .header("authorization", "Bearer wrong")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let (_command_rx, command_tx) = CommandReceiver::new();
+        let routes = routes(TOKEN.to_string(), Admin::new(&subscriber, command_tx));
+
+        publisher.set_round_id(3);
+        publisher.broadcast_phase(PhaseName::Sum);
+        publisher.broadcast_status(PhaseStatus {
+            accepted: 2,
+            rejected: 1,
+            started_at: 100,
+            min_deadline: Some(105),
+            max_deadline: Some(110),
+            ..PhaseStatus::default()
+        });
+
+        let response = warp::test::request()
+            .path("/status")
+            .header("authorization", format!("Bearer {}", TOKEN))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        assert_eq!(
+            body,
+            concat!(
+                r#"{"round_id":3,"phase":"sum","accepted":2,"rejected":1,"discarded":0,"#,
+                r#""started_at":100,"min_deadline":105,"max_deadline":110}"#,
+            ),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (mut command_rx, command_tx) = CommandReceiver::new();
+        let routes = routes(TOKEN.to_string(), Admin::new(&subscriber, command_tx));
+
+        for (path, command) in [
+            ("/round/skip", Command::SkipRound),
+            ("/shutdown", Command::Shutdown),
+        ]
+        .iter()
+        {
+            let response = warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("authorization", format!("Bearer {}", TOKEN))
+                .reply(&routes)
+                .await;
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+            assert_eq!(command_rx.try_recv(), Some(command.clone()));
+        }
+
+        // the state machine has shut down
+        drop(command_rx);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/shutdown")
+            .header("authorization", format!("Bearer {}", TOKEN))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
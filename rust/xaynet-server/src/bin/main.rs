@@ -1,19 +1,67 @@
-use std::{path::PathBuf, process};
+use std::{path::PathBuf, process, time::Duration};
 
+use async_trait::async_trait;
+use futures::future;
 use structopt::StructOpt;
-use tokio::signal;
-use tracing::warn;
+use tokio::{signal, task::JoinHandle};
+use tracing::{info, warn};
 use tracing_subscriber::*;
 
 #[cfg(feature = "metrics")]
-use xaynet_server::{metrics, settings::InfluxSettings};
+use xaynet_server::{
+    metrics::{
+        self,
+        recorders::{influxdb, prometheus},
+    },
+    settings::MetricsSettings,
+};
 
+use xaynet_core::{common::RoundSeed, crypto::PublicSigningKey, mask::Model, proof::ModelProof};
+#[cfg(feature = "embedded-storage")]
+use xaynet_server::storage::coordinator_storage::sled;
 use xaynet_server::{
-    rest::{serve, RestError},
+    admin::{self, Admin},
+    cluster::{self, Frontend, Leader, Lease},
+    rest::{serve, HealthCheck, ModelHistory, Notifications, RestError},
     services,
-    settings::{LoggingSettings, RedisSettings, Settings},
-    state_machine::StateMachineInitializer,
-    storage::{coordinator_storage::redis, Storage, Store},
+    settings::{
+        AdminSettings,
+        AggregationSettings,
+        ApiSettings,
+        ClusterRole,
+        ClusterSettings,
+        LoggingSettings,
+        MaskSettings,
+        ModelSettings,
+        ModelStorageBackend,
+        ModelStorageSettings,
+        MultipartSettings,
+        PetSettings,
+        ProtocolSettings,
+        RestoreSettings,
+        ScheduleSettings,
+        Settings,
+        StorageBackend,
+        TrustAnchorBackend,
+        TrustAnchorSettings,
+    },
+    state_machine::{
+        commands::{Command, CommandSender},
+        events::EventSubscriber,
+        requests::RequestSender,
+        StateMachineInitializer,
+    },
+    storage::{
+        coordinator_storage::{in_memory, redis},
+        model_storage::{filesystem, noop},
+        trust_anchor::{self, proof_log},
+        ModelStorage,
+        Retention,
+        Storage,
+        StorageResult,
+        Store,
+        TrustAnchor,
+    },
 };
 #[cfg(feature = "model-persistence")]
 use xaynet_server::{settings::S3Settings, storage::model_storage::s3};
@@ -30,7 +78,7 @@ struct Opt {
 async fn main() {
     let opt = Opt::from_args();
 
-    let settings = Settings::new(opt.config_path).unwrap_or_else(|err| {
+    let settings = Settings::new(&opt.config_path).unwrap_or_else(|err| {
         eprintln!("{}", err);
         process::exit(1);
     });
@@ -40,11 +88,26 @@ async fn main() {
         api: api_settings,
         log: log_settings,
         model: model_settings,
+        aggregation: aggregation_settings,
+        storage: storage_settings,
+        model_storage: model_storage_settings,
+        trust_anchor: trust_anchor_settings,
+        protocol: protocol_settings,
+        multipart: multipart_settings,
+        admin: admin_settings,
+        cluster: cluster_settings,
         redis: redis_settings,
         ..
     } = settings;
 
-    init_tracing(log_settings);
+    let log_handle = init_tracing(log_settings);
+    let reloader = Reloader {
+        config_path: opt.config_path,
+        pet_settings,
+        mask_settings,
+        model_settings: model_settings.clone(),
+        log_handle,
+    };
 
     // This should already called internally when instantiating the
     // state machine but it doesn't hurt making sure the crypto layer
@@ -52,85 +115,485 @@ async fn main() {
     sodiumoxide::init().unwrap();
 
     #[cfg(feature = "metrics")]
-    init_metrics(settings.metrics.influxdb);
+    init_metrics(settings.metrics).expect("failed to bind the Prometheus metrics server");
 
-    let store = init_store(
-        redis_settings,
+    // the global model history is pruned along with the global models
+    let retention = Retention {
+        max_models: model_storage_settings.max_models,
+        max_age: model_storage_settings.max_age.map(Duration::from_secs),
+    };
+    let model_store = init_model_store(
+        model_storage_settings,
+        retention,
         #[cfg(feature = "model-persistence")]
         settings.s3,
     )
     .await;
 
-    let (state_machine, requests_tx, event_subscriber) = StateMachineInitializer::new(
-        pet_settings,
-        mask_settings,
-        model_settings,
-        #[cfg(feature = "model-persistence")]
-        settings.restore,
-        store,
-    )
-    .init()
-    .await
-    .expect("failed to initialize state machine");
+    let trust_anchor = init_trust_anchor(trust_anchor_settings).await;
+
+    let coordinator_settings = CoordinatorSettings {
+        pet: pet_settings,
+        mask: mask_settings,
+        model: model_settings,
+        aggregation: aggregation_settings,
+        restore: settings.restore,
+        schedule: settings.schedule,
+        api: api_settings,
+        protocol: protocol_settings,
+        multipart: multipart_settings,
+        admin: admin_settings,
+    };
+
+    match storage_settings.backend {
+        StorageBackend::Redis => {
+            let coordinator_store = redis::Client::new(redis_settings.url)
+                .await
+                .expect("failed to establish a connection to Redis");
+            let store =
+                Store::new_with_trust_anchor(coordinator_store.clone(), model_store, trust_anchor)
+                    .with_retention(retention);
+            let leader = match cluster_settings {
+                Some(cluster_settings) if cluster_settings.role == ClusterRole::Frontend => {
+                    return run_frontend(
+                        coordinator_store,
+                        cluster_settings,
+                        store,
+                        coordinator_settings,
+                        reloader,
+                    )
+                    .await;
+                }
+                Some(cluster_settings) => {
+                    let mut lease = Lease::new(
+                        coordinator_store,
+                        // safe unwrap: the url is validated for the leader role
+                        cluster_settings.url.clone().unwrap(),
+                        cluster_settings.lease(),
+                    );
+                    let acquired = lease.acquire().await;
+                    // restoring the state machine may outlast the lease, hence it is renewed
+                    // right away
+                    let leadership = tokio::spawn(lease.keep(acquired));
+                    Some((cluster_settings, leadership))
+                }
+                None => None,
+            };
+            run(store, coordinator_settings, leader, reloader).await
+        }
+        StorageBackend::Memory => {
+            let store =
+                Store::new_with_trust_anchor(in_memory::InMemory::new(), model_store, trust_anchor)
+                    .with_retention(retention);
+            run(store, coordinator_settings, None, reloader).await
+        }
+        #[cfg(feature = "embedded-storage")]
+        StorageBackend::Sled => {
+            // safe unwrap: the path is validated for the sled backend
+            let coordinator_store = sled::Client::new(storage_settings.path.unwrap())
+                .expect("failed to open the sled database");
+            let store = Store::new_with_trust_anchor(coordinator_store, model_store, trust_anchor)
+                .with_retention(retention);
+            run(store, coordinator_settings, None, reloader).await
+        }
+    }
+}
 
-    let fetcher = services::fetchers::fetcher(&event_subscriber);
-    let message_handler =
-        services::messages::PetMessageHandler::new(&event_subscriber, requests_tx);
+/// The settings of the state machine and of the APIs, which are the same for each storage
+/// backend.
+struct CoordinatorSettings {
+    pet: PetSettings,
+    mask: MaskSettings,
+    model: ModelSettings,
+    aggregation: AggregationSettings,
+    restore: RestoreSettings,
+    schedule: ScheduleSettings,
+    api: ApiSettings,
+    protocol: ProtocolSettings,
+    multipart: MultipartSettings,
+    admin: Option<AdminSettings>,
+}
+
+/// Runs the state machine on the `store` and serves the APIs until one of them terminates.
+async fn run<S>(
+    store: S,
+    settings: CoordinatorSettings,
+    leader: Option<(ClusterSettings, JoinHandle<()>)>,
+    reloader: Reloader,
+) where
+    S: Storage,
+{
+    let CoordinatorSettings {
+        pet,
+        mask,
+        model,
+        aggregation,
+        restore,
+        schedule,
+        api,
+        protocol,
+        multipart,
+        admin: admin_settings,
+    } = settings;
+    let initializer = StateMachineInitializer::new(
+        pet,
+        mask,
+        model,
+        aggregation,
+        restore,
+        schedule,
+        store.clone(),
+    );
+    let (state_machine, requests_tx, commands_tx, event_subscriber) = initializer
+        .init()
+        .await
+        .expect("failed to initialize state machine");
+
+    tokio::spawn(reloader.run(Some(commands_tx.clone())));
+
+    if let Some(admin_settings) = admin_settings {
+        let admin = Admin::new(&event_subscriber, commands_tx);
+        tokio::spawn(admin::serve(admin_settings, admin));
+    }
 
+    // the leader runs the state machine as long as it holds the lease
+    let leadership = match leader {
+        Some((cluster_settings, leadership)) => {
+            let leader = Leader::new(&event_subscriber, requests_tx.clone());
+            tokio::spawn(cluster::serve(cluster_settings, leader));
+            future::Either::Left(leadership)
+        }
+        None => future::Either::Right(future::pending()),
+    };
+
+    // the state machine doesn't start if the leadership has been lost during its initialization
     tokio::select! {
+        biased;
+        _ = leadership => {
+            warn!("shutting down: lost the cluster leadership");
+        }
         _ = state_machine.run() => {
             warn!("shutting down: Service terminated");
         }
-        result = serve(api_settings, fetcher, message_handler) => {
-            match result {
-                Ok(()) => warn!("shutting down: REST server terminated"),
-                Err(RestError::InvalidTlsConfig) => {
-                    warn!("shutting down: invalid TLS settings for REST server");
-                },
-            }
+        _ = serve_participants(
+            store,
+            &event_subscriber,
+            requests_tx,
+            api,
+            protocol,
+            multipart,
+        ) => {}
+        _ =  signal::ctrl_c() => {}
+    }
+}
+
+/// Runs a cluster frontend, which forwards the PET messages to the cluster leader.
+async fn run_frontend<S>(
+    coordinator_store: redis::Client,
+    cluster_settings: ClusterSettings,
+    store: S,
+    settings: CoordinatorSettings,
+    reloader: Reloader,
+) where
+    S: Storage,
+{
+    if settings.admin.is_some() {
+        warn!("the admin API is only served by the cluster leader");
+    }
+    let client =
+        cluster::http_client(&cluster_settings).expect("failed to create the cluster HTTP client");
+    let (frontend, event_subscriber, requests_tx) =
+        Frontend::connect(coordinator_store, client, cluster_settings.token).await;
+
+    // the state machine of the leader runs with the PET settings of the leader
+    tokio::spawn(reloader.run(None));
+
+    tokio::select! {
+        _ = frontend.run() => {
+            warn!("shutting down: Frontend terminated");
         }
+        _ = serve_participants(
+            store,
+            &event_subscriber,
+            requests_tx,
+            settings.api,
+            settings.protocol,
+            settings.multipart,
+        ) => {}
         _ =  signal::ctrl_c() => {}
     }
 }
 
-fn init_tracing(settings: LoggingSettings) {
-    let _fmt_subscriber = FmtSubscriber::builder()
-        .with_env_filter(settings.filter)
+/// Serves the REST API to the participants until the REST server terminates.
+async fn serve_participants<S>(
+    store: S,
+    event_subscriber: &EventSubscriber,
+    requests_tx: RequestSender,
+    api_settings: ApiSettings,
+    protocol_settings: ProtocolSettings,
+    multipart_settings: MultipartSettings,
+) where
+    S: Storage,
+{
+    let fetcher = services::fetchers::fetcher(event_subscriber);
+    let message_handler = services::messages::PetMessageHandler::new(
+        event_subscriber,
+        requests_tx,
+        protocol_settings,
+        multipart_settings,
+    );
+    let health_check = HealthCheck::new(store.clone(), event_subscriber);
+    let notifications = Notifications::new(event_subscriber);
+    let model_history = ModelHistory::new(store);
+
+    let result = serve(
+        api_settings,
+        fetcher,
+        message_handler,
+        health_check,
+        notifications,
+        model_history,
+    )
+    .await;
+    match result {
+        Ok(()) => warn!("shutting down: REST server terminated"),
+        Err(RestError::InvalidTlsConfig) => {
+            warn!("shutting down: invalid TLS settings for REST server");
+        }
+    }
+}
+
+/// A handle to replace the logging filter of the global subscriber.
+type LogFilterHandle = reload::Handle<EnvFilter, fmt::Formatter>;
+
+fn init_tracing(settings: LoggingSettings) -> LogFilterHandle {
+    let builder = FmtSubscriber::builder()
         .with_ansi(true)
-        .init();
+        .with_env_filter(settings.filter)
+        .with_filter_reloading();
+    let log_handle = builder.reload_handle();
+    builder.init();
+    log_handle
+}
+
+/// Reloads the settings from the configuration file while the coordinator is running.
+struct Reloader {
+    config_path: PathBuf,
+    pet_settings: PetSettings,
+    mask_settings: MaskSettings,
+    model_settings: ModelSettings,
+    log_handle: LogFilterHandle,
+}
+
+impl Reloader {
+    /// Reloads the settings whenever the coordinator receives a `SIGHUP`.
+    ///
+    /// The logging filter is replaced right away, whereas the PET settings are sent to the state
+    /// machine via the `commands`, if any, which applies them when the next round starts.
+    #[cfg(unix)]
+    async fn run(mut self, commands: Option<CommandSender>) {
+        let mut hangups = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                warn!("failed to listen for SIGHUP: {}", err);
+                return;
+            }
+        };
+        while hangups.recv().await.is_some() {
+            info!("reloading the settings");
+            self.reload(commands.as_ref());
+        }
+    }
+
+    /// Reloading the settings is only supported via `SIGHUP` on unix.
+    #[cfg(not(unix))]
+    async fn run(self, _commands: Option<CommandSender>) {}
+
+    /// Reloads the settings once. Invalid settings are logged and ignored.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn reload(&mut self, commands: Option<&CommandSender>) {
+        let settings = match Settings::reload(
+            &self.config_path,
+            &self.pet_settings,
+            self.mask_settings,
+            &self.model_settings,
+        ) {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!("failed to reload the settings: {}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = self.log_handle.reload(settings.log.filter) {
+            warn!("failed to reload the logging settings: {}", err);
+        }
+        if let Some(commands) = commands {
+            let command = Command::UpdatePetSettings(Box::new(settings.pet));
+            if let Err(err) = commands.send(command) {
+                warn!("failed to update the PET settings: {}", err);
+                return;
+            }
+        }
+        self.pet_settings = settings.pet;
+    }
 }
 
 #[cfg(feature = "metrics")]
-fn init_metrics(settings: InfluxSettings) {
-    let recorder = metrics::Recorder::new(settings);
-    if metrics::GlobalRecorder::install(recorder).is_err() {
+fn init_metrics(settings: MetricsSettings) -> Result<(), warp::Error> {
+    let mut recorders: Vec<Box<dyn metrics::Recorder>> = Vec::new();
+    if let Some(influxdb_settings) = settings.influxdb {
+        recorders.push(Box::new(influxdb::Recorder::new(influxdb_settings)));
+    }
+    if let Some(prometheus_settings) = settings.prometheus {
+        let recorder = prometheus::Recorder::new();
+        let server = prometheus::bind(prometheus_settings.bind_address, recorder.registry())?;
+        tokio::spawn(server);
+        recorders.push(Box::new(recorder));
+    }
+
+    if recorders.is_empty() {
+        warn!("no metrics backend configured");
+    } else if metrics::GlobalRecorder::install(recorders).is_err() {
         warn!("failed to install metrics recorder");
     };
+    Ok(())
 }
 
-async fn init_store(
-    redis_settings: RedisSettings,
+async fn init_model_store(
+    model_storage_settings: ModelStorageSettings,
+    retention: Retention,
     #[cfg(feature = "model-persistence")] s3_settings: S3Settings,
-) -> impl Storage {
-    let coordinator_store = redis::Client::new(redis_settings.url)
-        .await
-        .expect("failed to establish a connection to Redis");
-
-    let model_store = {
-        #[cfg(not(feature = "model-persistence"))]
-        {
-            xaynet_server::storage::model_storage::noop::NoOp
+) -> ModelStore {
+    match model_storage_settings.backend {
+        ModelStorageBackend::NoOp => ModelStore::NoOp(noop::NoOp),
+        ModelStorageBackend::Filesystem => {
+            // safe unwrap: the path is validated for the filesystem backend
+            let client = filesystem::Client::new(model_storage_settings.path.unwrap(), retention)
+                .await
+                .expect("failed to create the global models directory");
+            ModelStore::Filesystem(client)
         }
-
         #[cfg(feature = "model-persistence")]
-        {
+        ModelStorageBackend::S3 => {
             let s3 = s3::Client::new(s3_settings).expect("failed to create S3 client");
             s3.create_global_models_bucket()
                 .await
                 .expect("failed to create bucket for global models");
-            s3
+            ModelStore::S3(s3)
+        }
+    }
+}
+
+#[derive(Clone)]
+/// The model store which is selected in the settings.
+enum ModelStore {
+    NoOp(noop::NoOp),
+    Filesystem(filesystem::Client),
+    #[cfg(feature = "model-persistence")]
+    S3(s3::Client),
+}
+
+/// Forwards a method call to the selected model store.
+macro_rules! dispatch {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            ModelStore::NoOp(store) => store.$method($($arg),*),
+            ModelStore::Filesystem(store) => store.$method($($arg),*),
+            #[cfg(feature = "model-persistence")]
+            ModelStore::S3(store) => store.$method($($arg),*),
         }
     };
+}
+
+#[async_trait]
+impl ModelStorage for ModelStore {
+    async fn set_global_model(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<String> {
+        dispatch!(self.set_global_model(round_id, round_seed, global_model)).await
+    }
+
+    async fn global_model(&mut self, id: &str) -> StorageResult<Option<Model>> {
+        dispatch!(self.global_model(id)).await
+    }
+
+    fn persists_global_models(&self) -> bool {
+        dispatch!(self.persists_global_models())
+    }
 
-    Store::new(coordinator_store, model_store)
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        dispatch!(self.is_ready()).await
+    }
+}
+
+async fn init_trust_anchor(trust_anchor_settings: TrustAnchorSettings) -> TrustAnchorStore {
+    match trust_anchor_settings.backend {
+        TrustAnchorBackend::NoOp => TrustAnchorStore::NoOp(trust_anchor::noop::NoOp),
+        TrustAnchorBackend::Log => {
+            // safe unwrap: the path is validated for the log backend
+            let client = proof_log::Client::new(trust_anchor_settings.path.unwrap())
+                .await
+                .expect("failed to open the proof log");
+            TrustAnchorStore::Log(client)
+        }
+    }
+}
+
+#[derive(Clone)]
+/// The trust anchor which is selected in the settings.
+enum TrustAnchorStore {
+    NoOp(trust_anchor::noop::NoOp),
+    Log(proof_log::Client),
+}
+
+#[async_trait]
+impl TrustAnchor for TrustAnchorStore {
+    async fn publish_proof(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<()> {
+        match self {
+            Self::NoOp(anchor) => anchor.publish_proof(round_id, round_seed, global_model),
+            Self::Log(anchor) => anchor.publish_proof(round_id, round_seed, global_model),
+        }
+        .await
+    }
+
+    async fn proofs(&mut self) -> StorageResult<Vec<ModelProof>> {
+        match self {
+            Self::NoOp(anchor) => anchor.proofs(),
+            Self::Log(anchor) => anchor.proofs(),
+        }
+        .await
+    }
+
+    async fn proof(&mut self, round_id: u64) -> StorageResult<Option<ModelProof>> {
+        match self {
+            Self::NoOp(anchor) => anchor.proof(round_id),
+            Self::Log(anchor) => anchor.proof(round_id),
+        }
+        .await
+    }
+
+    async fn proof_key(&mut self) -> StorageResult<Option<PublicSigningKey>> {
+        match self {
+            Self::NoOp(anchor) => anchor.proof_key(),
+            Self::Log(anchor) => anchor.proof_key(),
+        }
+        .await
+    }
+
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        match self {
+            Self::NoOp(anchor) => anchor.is_ready(),
+            Self::Log(anchor) => anchor.is_ready(),
+        }
+        .await
+    }
 }
@@ -0,0 +1,348 @@
+//! The frontend of a cluster.
+
+use std::{collections::VecDeque, time::Duration};
+
+use futures::future;
+use thiserror::Error;
+use tokio::{
+    sync::watch,
+    time::{sleep, timeout},
+};
+use tracing::{info, warn};
+use tracing_futures::Instrument;
+
+use crate::{
+    cluster::{ClusterEvent, ForwardedError, FrameDecoder, Snapshot, KEEP_ALIVE},
+    settings::ClusterSettings,
+    state_machine::{
+        events::{EventPublisher, EventSubscriber},
+        requests::{RequestReceiver, RequestSender, StateMachineRequest},
+        RequestError,
+        StateMachineResult,
+    },
+    storage::coordinator_storage::redis::{self, RedisError},
+};
+
+/// The delay between two attempts to connect to the leader.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The timeout to establish a connection to the leader.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The timeout of a request to the leader, except for the event stream.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Errors which can occur while connected to the leader.
+#[derive(Debug, Error)]
+enum ConnectionError {
+    #[error("no cluster leader has been elected")]
+    NoLeader,
+    #[error("failed to look up the cluster leader: {0}")]
+    Lookup(#[from] RedisError),
+    #[error("failed to connect to the cluster leader: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("invalid event of the cluster leader: {0}")]
+    Decode(#[from] bincode::Error),
+    #[error("the event stream of the cluster leader doesn't start with a snapshot")]
+    MissingSnapshot,
+    #[error("the cluster leader closed the event stream")]
+    Closed,
+    #[error("the cluster leader timed out")]
+    Timeout,
+}
+
+/// Errors which can occur while creating the HTTP client of a frontend.
+#[derive(Debug, Error)]
+pub enum HttpClientError {
+    #[error("failed to read the cluster certificate: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to create the HTTP client: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Creates the HTTP client of a frontend for the cluster API of the leaders.
+///
+/// The client trusts the certificate of the `settings` in addition to the common root
+/// certificates.
+#[cfg_attr(not(feature = "tls"), allow(unused_mut, unused_variables))]
+pub fn http_client(settings: &ClusterSettings) -> Result<reqwest::Client, HttpClientError> {
+    let mut builder = reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT);
+    #[cfg(feature = "tls")]
+    {
+        if let Some(path) = &settings.tls_certificate {
+            let certificate = reqwest::Certificate::from_pem(&std::fs::read(path)?)?;
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// A client of the cluster API of the leader.
+#[derive(Clone)]
+struct LeaderClient {
+    store: redis::Client,
+    client: reqwest::Client,
+    token: String,
+}
+
+impl LeaderClient {
+    /// Connects to the event stream of the current leader.
+    async fn connect(&mut self) -> Result<(Connection, Snapshot), ConnectionError> {
+        let url = self
+            .store
+            .leader()
+            .await?
+            .ok_or(ConnectionError::NoLeader)?;
+        // the event stream never ends, hence only the response head is awaited with a timeout
+        let request = self
+            .client
+            .get(&format!("{}/events", url))
+            .bearer_auth(&self.token)
+            .send();
+        let response = timeout(REQUEST_TIMEOUT, request)
+            .await
+            .map_err(|_| ConnectionError::Timeout)??
+            .error_for_status()?;
+        let mut connection = Connection {
+            url,
+            response,
+            decoder: FrameDecoder::default(),
+            events: VecDeque::new(),
+        };
+        match connection.next().await? {
+            ClusterEvent::Snapshot(snapshot) => Ok((connection, *snapshot)),
+            _ => Err(ConnectionError::MissingSnapshot),
+        }
+    }
+
+    /// Connects to the event stream of the current leader, retrying until it succeeds.
+    async fn reconnect(&mut self) -> (Connection, Snapshot) {
+        loop {
+            match self.connect().await {
+                Ok((connection, snapshot)) => {
+                    info!("connected to the cluster leader {}", connection.url);
+                    return (connection, snapshot);
+                }
+                Err(err) => warn!("failed to connect to the cluster leader: {}", err),
+            }
+            sleep(RETRY_DELAY).await;
+        }
+    }
+
+    /// Forwards a request to the leader at the `url`.
+    async fn forward(&self, url: &str, request: &StateMachineRequest) -> StateMachineResult {
+        let internal_error =
+            |_| RequestError::InternalError("failed to forward the request to the cluster leader");
+        // safe unwrap: the request is serializable
+        let body = bincode::serialize(request).unwrap();
+        let response = self
+            .client
+            .post(&format!("{}/requests", url))
+            .bearer_auth(&self.token)
+            .timeout(REQUEST_TIMEOUT)
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(internal_error)?;
+        let body = response.bytes().await.map_err(internal_error)?;
+        let result: Result<(), ForwardedError> = bincode::deserialize(&body)
+            .map_err(|_| RequestError::InternalError("invalid response of the cluster leader"))?;
+        result.map_err(RequestError::from)
+    }
+}
+
+/// A connection to the event stream of a leader.
+struct Connection {
+    /// The URL of the cluster API of the leader.
+    url: String,
+    response: reqwest::Response,
+    decoder: FrameDecoder,
+    /// Events which have been received but not yet returned.
+    events: VecDeque<ClusterEvent>,
+}
+
+impl Connection {
+    /// Receives the next event.
+    ///
+    /// Fails if the leader doesn't send any event, including the keep alive events, for a
+    /// while.
+    async fn next(&mut self) -> Result<ClusterEvent, ConnectionError> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+            let chunk = timeout(3 * KEEP_ALIVE, self.response.chunk())
+                .await
+                .map_err(|_| ConnectionError::Timeout)??
+                .ok_or(ConnectionError::Closed)?;
+            self.events.extend(self.decoder.decode(&chunk)?);
+        }
+    }
+}
+
+/// A frontend, which forwards the requests of its message handler to the leader and publishes
+/// the events of the leader.
+pub struct Frontend {
+    client: LeaderClient,
+    connection: Connection,
+    publisher: EventPublisher,
+    requests: RequestReceiver,
+}
+
+impl Frontend {
+    /// Connects to the current leader, waiting until a leader has been elected.
+    ///
+    /// Returns the frontend along with the subscriber to the events of the leader and the sender
+    /// of the requests to forward to the leader, which replace those of the state machine in the
+    /// services of the frontend.
+    pub async fn connect(
+        store: redis::Client,
+        client: reqwest::Client,
+        token: String,
+    ) -> (Self, EventSubscriber, RequestSender) {
+        let mut client = LeaderClient {
+            store,
+            client,
+            token,
+        };
+        let (connection, snapshot) = client.reconnect().await;
+        let (mut publisher, subscriber) = EventPublisher::init(
+            snapshot.phase.round_id,
+            snapshot.keys.event.clone(),
+            snapshot.params.event.clone(),
+            snapshot.phase.event,
+            snapshot.model.event.clone(),
+        );
+        ClusterEvent::Snapshot(Box::new(snapshot)).publish(&mut publisher);
+        let (requests, requests_tx) = RequestReceiver::new();
+
+        let frontend = Self {
+            client,
+            connection,
+            publisher,
+            requests,
+        };
+        (frontend, subscriber, requests_tx)
+    }
+
+    /// Forwards the requests to the leader and publishes the events of the leader.
+    ///
+    /// The frontend connects to the next leader if the connection to the current leader fails.
+    pub async fn run(self) {
+        let Self {
+            client,
+            mut connection,
+            mut publisher,
+            mut requests,
+        } = self;
+        let (url_tx, url_rx) = watch::channel(connection.url.clone());
+
+        let mut events_client = client.clone();
+        let events = async move {
+            loop {
+                match connection.next().await {
+                    Ok(event) => event.publish(&mut publisher),
+                    Err(err) => {
+                        warn!("lost the connection to the cluster leader: {}", err);
+                        let (next_connection, snapshot) = events_client.reconnect().await;
+                        let _ = url_tx.send(next_connection.url.clone());
+                        ClusterEvent::Snapshot(Box::new(snapshot)).publish(&mut publisher);
+                        connection = next_connection;
+                    }
+                }
+            }
+        };
+
+        let requests = async move {
+            while let Some((request, span, response_tx)) = requests.recv().await {
+                let client = client.clone();
+                let url = url_rx.borrow().clone();
+                tokio::spawn(
+                    async move {
+                        let result = client.forward(&url, &request).await;
+                        let _ = response_tx.send(result);
+                    }
+                    .instrument(span),
+                );
+            }
+        };
+
+        future::join(events, requests).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::{
+        cluster::{serve, Leader},
+        services::tests::utils::{new_event_channels, new_sum_message},
+        settings::ClusterRole,
+        state_machine::phases::PhaseName,
+        storage::coordinator_storage::redis::tests::init_client,
+    };
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_frontend() {
+        // the leader serves the cluster API
+        let (mut leader_publisher, leader_subscriber) = new_event_channels();
+        let (mut leader_requests, leader_requests_tx) = RequestReceiver::new();
+        let leader = Leader::new(&leader_subscriber, leader_requests_tx);
+        let url = "http://127.0.0.1:18083";
+        let settings = ClusterSettings {
+            role: ClusterRole::Leader,
+            bind_address: Some(([127, 0, 0, 1], 18083).into()),
+            url: Some(url.to_string()),
+            token: TOKEN.to_string(),
+            lease: None,
+            #[cfg(feature = "tls")]
+            tls_certificate: None,
+            #[cfg(feature = "tls")]
+            tls_key: None,
+        };
+        let client = http_client(&settings).unwrap();
+        tokio::spawn(serve(settings, leader));
+        let mut store = init_client().await;
+        assert!(store
+            .acquire_leader_lease(url, Duration::from_secs(10))
+            .await
+            .unwrap());
+
+        // the frontend starts with the latest events of the leader
+        let (frontend, subscriber, requests_tx) =
+            Frontend::connect(store, client, TOKEN.to_string()).await;
+        let keys = subscriber.keys_listener().get_latest();
+        assert_eq!(keys, leader_subscriber.keys_listener().get_latest());
+        tokio::spawn(frontend.run());
+
+        // the frontend publishes the following events of the leader
+        let mut phase = subscriber.phase_listener();
+        leader_publisher.set_round_id(1);
+        leader_publisher.broadcast_phase(PhaseName::Sum);
+        loop {
+            let event = phase.next().await.unwrap();
+            if event.event == PhaseName::Sum {
+                assert_eq!(event.round_id, 1);
+                break;
+            }
+        }
+
+        // the frontend forwards the requests to the leader
+        tokio::spawn(async move {
+            let (request, _, response_tx) = leader_requests.recv().await.unwrap();
+            assert!(matches!(request, StateMachineRequest::Sum(_)));
+            let _ = response_tx.send(Err(RequestError::MessageDiscarded));
+        });
+        let (message, _) = new_sum_message(&subscriber.params_listener().get_latest().event);
+        let result = requests_tx
+            .request(StateMachineRequest::from(message), tracing::Span::none())
+            .await;
+        assert!(matches!(result, Err(RequestError::MessageDiscarded)));
+    }
+}
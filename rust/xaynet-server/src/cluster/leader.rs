@@ -0,0 +1,399 @@
+//! The leader of a cluster.
+
+use std::{
+    convert::Infallible,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{future, stream, Stream, StreamExt};
+use tokio::time::{sleep, timeout};
+use tracing::{debug, info, warn, Span};
+use warp::{
+    http::{Response, StatusCode},
+    hyper::Body,
+    Filter,
+    Reply,
+};
+
+use crate::{
+    admin::{authorized, handle_reject},
+    cluster::{encode_frame, ClusterEvent, ForwardedError, Snapshot, KEEP_ALIVE},
+    settings::ClusterSettings,
+    state_machine::{
+        events::{
+            DictionaryUpdate,
+            Event,
+            EventListener,
+            EventSubscriber,
+            ModelUpdate,
+            PhaseStatus,
+        },
+        phases::PhaseName,
+        requests::{RequestSender, StateMachineRequest},
+    },
+    storage::coordinator_storage::redis,
+};
+use xaynet_core::{common::RoundParameters, crypto::EncryptKeyPair, SeedDict, SumDict};
+
+/// A handle of the leader to serve the cluster API.
+#[derive(Debug, Clone)]
+pub struct Leader {
+    keys: EventListener<EncryptKeyPair>,
+    params: EventListener<RoundParameters>,
+    phase: EventListener<PhaseName>,
+    status: EventListener<PhaseStatus>,
+    model: EventListener<ModelUpdate>,
+    sum_dict: EventListener<DictionaryUpdate<SumDict>>,
+    seed_dict: EventListener<DictionaryUpdate<SeedDict>>,
+    requests: RequestSender,
+}
+
+impl Leader {
+    /// Creates a new leader handle for the state machine of the `event_subscriber`.
+    pub fn new(event_subscriber: &EventSubscriber, requests: RequestSender) -> Self {
+        Self {
+            keys: event_subscriber.keys_listener(),
+            params: event_subscriber.params_listener(),
+            phase: event_subscriber.phase_listener(),
+            status: event_subscriber.status_listener(),
+            model: event_subscriber.model_listener(),
+            sum_dict: event_subscriber.sum_dict_listener(),
+            seed_dict: event_subscriber.seed_dict_listener(),
+            requests,
+        }
+    }
+
+    /// Gets the latest events.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            keys: self.keys.get_latest(),
+            params: self.params.get_latest(),
+            phase: self.phase.get_latest(),
+            status: self.status.get_latest(),
+            model: self.model.get_latest(),
+            sum_dict: self.sum_dict.get_latest(),
+            seed_dict: self.seed_dict.get_latest(),
+        }
+    }
+
+    /// Streams a snapshot of the latest events and all following events, interleaved with keep
+    /// alive events.
+    fn events(self) -> impl Stream<Item = ClusterEvent> + Send + 'static {
+        let snapshot = ClusterEvent::Snapshot(Box::new(self.snapshot()));
+        let events = stream::select_all(vec![
+            changes(self.keys, ClusterEvent::Keys),
+            changes(self.params, ClusterEvent::Params),
+            changes(self.phase, ClusterEvent::Phase),
+            changes(self.status, ClusterEvent::Status),
+            changes(self.model, ClusterEvent::Model),
+            changes(self.sum_dict, ClusterEvent::SumDict),
+            changes(self.seed_dict, ClusterEvent::SeedDict),
+        ]);
+        let keep_alive = stream::unfold((), |_| async {
+            sleep(KEEP_ALIVE).await;
+            Some((ClusterEvent::KeepAlive, ()))
+        });
+        stream::once(future::ready(snapshot)).chain(stream::select(events, keep_alive))
+    }
+}
+
+/// Streams the events of the `listener` which follow its latest event.
+fn changes<E, F>(
+    listener: EventListener<E>,
+    into_event: F,
+) -> stream::BoxStream<'static, ClusterEvent>
+where
+    E: Clone + Send + Sync + 'static,
+    F: Fn(Event<E>) -> ClusterEvent + Send + 'static,
+{
+    stream::unfold(listener, |mut listener| async move {
+        listener.next().await.map(|event| (event, listener))
+    })
+    .map(into_event)
+    .boxed()
+}
+
+/// Starts a HTTP server at the bind address of the `settings`, serving the cluster API to the
+/// frontends which are authenticated with the token of the `settings`.
+///
+/// The cluster API is served via TLS if the `settings` contain a certificate and a key.
+pub async fn serve(settings: ClusterSettings, leader: Leader) {
+    // safe unwrap: the bind address is validated for the leader role
+    let bind_address = settings.bind_address.unwrap();
+    let server = warp::serve(routes(settings.token, leader));
+    #[cfg(feature = "tls")]
+    {
+        if let (Some(cert), Some(key)) = (settings.tls_certificate, settings.tls_key) {
+            return server
+                .tls()
+                .cert_path(cert)
+                .key_path(key)
+                .run(bind_address)
+                .await;
+        }
+    }
+    server.run(bind_address).await
+}
+
+/// Creates the filter of all cluster routes.
+fn routes(
+    token: String,
+    leader: Leader,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let events = warp::path!("events")
+        .and(warp::get())
+        .and(with_leader(leader.clone()))
+        .map(handle_events);
+
+    let requests = warp::path!("requests")
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .and(with_leader(leader))
+        .and_then(handle_request);
+
+    authorized(token)
+        .and(events.or(requests))
+        .recover(handle_reject)
+        .with(warp::log("cluster"))
+}
+
+/// Handles and responds to a request for the event stream.
+fn handle_events(leader: Leader) -> Response<Body> {
+    info!("a frontend subscribed to the events");
+    let frames = leader
+        .events()
+        .map(|event| Ok::<_, Infallible>(encode_frame(&event)));
+    Response::new(Body::wrap_stream(frames))
+}
+
+/// Handles and responds to a request which a frontend forwards to the state machine.
+async fn handle_request(body: Bytes, leader: Leader) -> Result<impl Reply, Infallible> {
+    let request: StateMachineRequest = match bincode::deserialize(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            warn!("invalid request of a frontend: {}", err);
+            return Ok(warp::reply::with_status(
+                Vec::new(),
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+    };
+    let result = leader
+        .requests
+        .request(request, Span::none())
+        .await
+        .map_err(|err| {
+            debug!("forwarded request failed: {}", err);
+            ForwardedError::from(err)
+        });
+    // safe unwrap: the result is serializable
+    let body = bincode::serialize(&result).unwrap();
+    Ok(warp::reply::with_status(body, StatusCode::OK))
+}
+
+/// Converts a leader handle into a `warp` filter.
+fn with_leader(leader: Leader) -> impl Filter<Extract = (Leader,), Error = Infallible> + Clone {
+    warp::any().map(move || leader.clone())
+}
+
+/// The leadership of a leader candidate.
+///
+/// The leader holds a lease in Redis, which expires unless the leader renews it. The lease holds
+/// the URL of the cluster API of the leader, see [`redis::Client::leader()`].
+pub struct Lease {
+    store: redis::Client,
+    url: String,
+    ttl: Duration,
+}
+
+impl Lease {
+    /// Creates the lease of the leader candidate with the cluster API `url`.
+    pub fn new(store: redis::Client, url: String, ttl: Duration) -> Self {
+        Self { store, url, ttl }
+    }
+
+    /// Waits until the candidate is elected as the leader.
+    ///
+    /// Returns the instant from which on the lease runs, which has to be passed to
+    /// [`keep()`](Self::keep).
+    pub async fn acquire(&mut self) -> Instant {
+        loop {
+            let acquiring = Instant::now();
+            match self.store.acquire_leader_lease(&self.url, self.ttl).await {
+                Ok(true) => {
+                    info!("elected as the cluster leader");
+                    return acquiring;
+                }
+                Ok(false) => debug!("another coordinator is the cluster leader"),
+                Err(err) => warn!("failed to acquire the cluster leadership: {}", err),
+            }
+            sleep(self.ttl / 2).await;
+        }
+    }
+
+    /// Renews the lease, which has been `acquired` at the given instant, until the leadership is
+    /// lost.
+    ///
+    /// The leadership is lost if another candidate has been elected or if the lease may have
+    /// expired because it couldn't be renewed in time.
+    pub async fn keep(mut self, acquired: Instant) {
+        let mut renewed = acquired;
+        loop {
+            sleep(self.ttl / 3).await;
+            let renewing = Instant::now();
+            // the renewal must succeed before the lease expires, otherwise another candidate may
+            // already have been elected in the meantime
+            let remaining = self.ttl.saturating_sub(renewed.elapsed());
+            if remaining == Duration::from_secs(0) {
+                warn!("the cluster leadership may have expired");
+                return;
+            }
+            match timeout(
+                remaining,
+                self.store.acquire_leader_lease(&self.url, self.ttl),
+            )
+            .await
+            {
+                Ok(Ok(true)) => renewed = renewing,
+                Ok(Ok(false)) => {
+                    warn!("another coordinator has been elected as the cluster leader");
+                    return;
+                }
+                Ok(Err(err)) => warn!("failed to renew the cluster leadership: {}", err),
+                Err(_) => {
+                    warn!("renewing the cluster leadership timed out");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::{
+        cluster::FrameDecoder,
+        services::tests::utils::{new_event_channels, new_sum_message},
+        state_machine::{requests::RequestReceiver, RequestError},
+        storage::coordinator_storage::redis::tests::init_client,
+    };
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    #[tokio::test]
+    async fn test_unauthorized() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (_request_rx, request_tx) = RequestReceiver::new();
+        let routes = routes(TOKEN.to_string(), Leader::new(&subscriber, request_tx));
+
+        let response = warp::test::request().path("/events").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let (_request_rx, request_tx) = RequestReceiver::new();
+        let leader = Leader::new(&subscriber, request_tx);
+        let snapshot = leader.snapshot();
+        let mut events = leader.events().boxed();
+
+        assert_eq!(
+            events.next().await,
+            Some(ClusterEvent::Snapshot(Box::new(snapshot))),
+        );
+
+        publisher.set_round_id(2);
+        publisher.broadcast_phase(PhaseName::Sum);
+        // the latest events may be streamed again after the snapshot
+        let phase = ClusterEvent::Phase(Event {
+            round_id: 2,
+            event: PhaseName::Sum,
+        });
+        while let Some(event) = events.next().await {
+            if event == phase {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (mut request_rx, request_tx) = RequestReceiver::new();
+        let routes = routes(TOKEN.to_string(), Leader::new(&subscriber, request_tx));
+
+        let (message, _) = new_sum_message(&subscriber.params_listener().get_latest().event);
+        let request = StateMachineRequest::from(message);
+        tokio::spawn(async move {
+            let (_, _, response_tx) = request_rx.recv().await.unwrap();
+            let _ = response_tx.send(Err(RequestError::MessageRejected));
+        });
+        let response = warp::test::request()
+            .method("POST")
+            .path("/requests")
+            .header("authorization", format!("Bearer {}", TOKEN))
+            .body(bincode::serialize(&request).unwrap())
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let result: Result<(), ForwardedError> = bincode::deserialize(response.body()).unwrap();
+        assert!(matches!(result, Err(ForwardedError::MessageRejected)));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/requests")
+            .header("authorization", format!("Bearer {}", TOKEN))
+            .body("invalid")
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_lease() {
+        let client = init_client().await;
+        let ttl = Duration::from_millis(300);
+
+        let mut lease = Lease::new(client.clone(), "leader-1".to_string(), ttl);
+        let acquired = lease.acquire().await;
+
+        // the renewed lease doesn't expire
+        let keep = tokio::time::timeout(2 * ttl, lease.keep(acquired)).await;
+        assert!(keep.is_err());
+        assert_eq!(
+            client.clone().leader().await.unwrap().as_deref(),
+            Some("leader-1")
+        );
+
+        // the leadership may have been lost if the lease hasn't been renewed in time
+        let expired =
+            Lease::new(client.clone(), "leader-1".to_string(), ttl).keep(Instant::now() - ttl);
+        tokio::time::timeout(ttl, expired).await.unwrap();
+
+        // the leadership is lost once another candidate holds the lease
+        sleep(2 * ttl).await;
+        let mut lease = Lease::new(client.clone(), "leader-2".to_string(), ttl);
+        lease.acquire().await;
+        let lost = Lease::new(client, "leader-1".to_string(), ttl).keep(Instant::now());
+        tokio::time::timeout(ttl, lost).await.unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_frame() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (_request_rx, request_tx) = RequestReceiver::new();
+        let snapshot =
+            ClusterEvent::Snapshot(Box::new(Leader::new(&subscriber, request_tx).snapshot()));
+        let mut decoder = FrameDecoder::default();
+        assert_eq!(
+            decoder.decode(&encode_frame(&snapshot)).unwrap(),
+            vec![snapshot]
+        );
+    }
+}
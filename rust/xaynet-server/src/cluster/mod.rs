@@ -0,0 +1,283 @@
+//! A cluster of coordinators with a single state machine leader.
+//!
+//! The state machine of a cluster runs on a single coordinator, the leader, which is elected
+//! among the leader candidates via a [`Lease`] in Redis. Any number of frontends serve the REST
+//! API to the participants: they decrypt, parse and validate the PET messages and forward the
+//! resulting [`StateMachineRequest`]s to the leader. In turn, the leader streams the events of its
+//! state machine to the frontends, which publish them to their own [`EventSubscriber`], so that
+//! the fetchers and the message handler of a frontend serve the same round as the leader.
+//!
+//! The leader serves the cluster API on its own address, see the [`ClusterSettings`]. Every
+//! request must be authenticated with the token of the settings as a bearer token in the
+//! `Authorization` header.
+//!
+//! - `GET /events`: a stream of [`ClusterEvent`]s, starting with a snapshot of the latest events.
+//!   Each event is encoded with `bincode` and prefixed with its length as a big endian `u32`.
+//! - `POST /requests`: a `bincode` encoded [`StateMachineRequest`], answered with the `bincode`
+//!   encoded result of the state machine
+//!
+//! The events include the encryption keys of the coordinator, hence the cluster API must only be
+//! reachable by the coordinators of the cluster. A leader serves the cluster API via TLS if the
+//! `tls` feature is enabled and a certificate is configured, otherwise it must be bound to a
+//! loopback or private address. The chunks of a multipart message are buffered
+//! by the frontend which receives them, hence a participant must send all chunks of a message to
+//! the same frontend, e.g. via a load balancer with sticky sessions.
+//!
+//! [`StateMachineRequest`]: crate::state_machine::requests::StateMachineRequest
+//! [`EventSubscriber`]: crate::state_machine::events::EventSubscriber
+//! [`ClusterSettings`]: crate::settings::ClusterSettings
+
+mod frontend;
+mod leader;
+
+use std::{convert::TryFrom, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    frontend::{http_client, Frontend, HttpClientError},
+    leader::{serve, Leader, Lease},
+};
+use crate::{
+    state_machine::{
+        events::{DictionaryUpdate, Event, EventPublisher, ModelUpdate, PhaseStatus},
+        phases::PhaseName,
+        RequestError,
+    },
+    storage::{LocalSeedDictAddError, MaskScoreIncrError, SumPartAddError},
+};
+use xaynet_core::{common::RoundParameters, crypto::EncryptKeyPair, SeedDict, SumDict};
+
+/// The interval in which the leader sends keep alive events to the frontends.
+const KEEP_ALIVE: Duration = Duration::from_secs(5);
+
+/// The latest events of the state machine of the leader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub keys: Event<EncryptKeyPair>,
+    pub params: Event<RoundParameters>,
+    pub phase: Event<PhaseName>,
+    pub status: Event<PhaseStatus>,
+    pub model: Event<ModelUpdate>,
+    pub sum_dict: Event<DictionaryUpdate<SumDict>>,
+    pub seed_dict: Event<DictionaryUpdate<SeedDict>>,
+}
+
+/// An event of the state machine of the leader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClusterEvent {
+    /// The latest events, which a frontend receives first.
+    Snapshot(Box<Snapshot>),
+    Keys(Event<EncryptKeyPair>),
+    Params(Event<RoundParameters>),
+    Phase(Event<PhaseName>),
+    Status(Event<PhaseStatus>),
+    Model(Event<ModelUpdate>),
+    SumDict(Event<DictionaryUpdate<SumDict>>),
+    SeedDict(Event<DictionaryUpdate<SeedDict>>),
+    /// An event without data, which shows that the leader is still alive.
+    KeepAlive,
+}
+
+impl ClusterEvent {
+    /// Publishes the event to the `publisher` of a frontend.
+    pub fn publish(self, publisher: &mut EventPublisher) {
+        match self {
+            Self::Snapshot(snapshot) => {
+                let Snapshot {
+                    keys,
+                    params,
+                    phase,
+                    status,
+                    model,
+                    sum_dict,
+                    seed_dict,
+                } = *snapshot;
+                // the phase is published last, so that the data of the phase is available once a
+                // listener sees the phase
+                for event in vec![
+                    Self::Keys(keys),
+                    Self::Params(params),
+                    Self::Model(model),
+                    Self::SumDict(sum_dict),
+                    Self::SeedDict(seed_dict),
+                    Self::Status(status),
+                    Self::Phase(phase),
+                ] {
+                    event.publish(publisher);
+                }
+            }
+            Self::Keys(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_keys(event.event);
+            }
+            Self::Params(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_params(event.event);
+            }
+            Self::Phase(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_phase(event.event);
+            }
+            Self::Status(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_status(event.event);
+            }
+            Self::Model(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_model(event.event);
+            }
+            Self::SumDict(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_sum_dict(event.event);
+            }
+            Self::SeedDict(event) => {
+                publisher.set_round_id(event.round_id);
+                publisher.broadcast_seed_dict(event.event);
+            }
+            Self::KeepAlive => {}
+        }
+    }
+}
+
+/// Encodes an event as a frame of the event stream.
+fn encode_frame(event: &ClusterEvent) -> Vec<u8> {
+    // safe unwrap: the event is serializable
+    let data = bincode::serialize(event).unwrap();
+    let mut frame = Vec::with_capacity(4 + data.len());
+    frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&data);
+    frame
+}
+
+/// An incremental decoder of the frames of the event stream.
+#[derive(Debug, Default)]
+struct FrameDecoder {
+    /// Bytes of an incomplete frame.
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Decodes the next chunk of the stream and returns the events completed by it.
+    fn decode(&mut self, chunk: &[u8]) -> Result<Vec<ClusterEvent>, bincode::Error> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        let mut start = 0;
+        while self.buffer.len() - start >= 4 {
+            let mut length = [0; 4];
+            length.copy_from_slice(&self.buffer[start..start + 4]);
+            let end = start + 4 + u32::from_be_bytes(length) as usize;
+            if self.buffer.len() < end {
+                break;
+            }
+            events.push(bincode::deserialize(&self.buffer[start + 4..end])?);
+            start = end;
+        }
+        self.buffer.drain(..start);
+        Ok(events)
+    }
+}
+
+/// A [`RequestError`] of the state machine of the leader, which is sent to the frontend.
+#[derive(Debug, Serialize, Deserialize)]
+enum ForwardedError {
+    MessageRejected,
+    MessageDiscarded,
+    AggregationFailed,
+    InternalError(String),
+    CoordinatorStorage(String),
+    LocalSeedDictAdd(i64),
+    SumPartAdd(i64),
+    MaskScoreIncr(i64),
+}
+
+impl From<RequestError> for ForwardedError {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::MessageRejected => Self::MessageRejected,
+            RequestError::MessageDiscarded => Self::MessageDiscarded,
+            RequestError::AggregationFailed => Self::AggregationFailed,
+            RequestError::InternalError(message) => Self::InternalError(message.to_string()),
+            RequestError::CoordinatorStorage(error) => Self::CoordinatorStorage(error.to_string()),
+            RequestError::LocalSeedDictAdd(error) => Self::LocalSeedDictAdd(error as i64),
+            RequestError::SumPartAdd(error) => Self::SumPartAdd(error as i64),
+            RequestError::MaskScoreIncr(error) => Self::MaskScoreIncr(error as i64),
+        }
+    }
+}
+
+impl From<ForwardedError> for RequestError {
+    fn from(error: ForwardedError) -> Self {
+        let invalid = || Self::InternalError("invalid error of the cluster leader");
+        match error {
+            ForwardedError::MessageRejected => Self::MessageRejected,
+            ForwardedError::MessageDiscarded => Self::MessageDiscarded,
+            ForwardedError::AggregationFailed => Self::AggregationFailed,
+            // the leader logs the message of its internal error
+            ForwardedError::InternalError(_) => {
+                Self::InternalError("the cluster leader failed to process the request")
+            }
+            ForwardedError::CoordinatorStorage(message) => {
+                Self::CoordinatorStorage(anyhow::anyhow!(message))
+            }
+            ForwardedError::LocalSeedDictAdd(code) => LocalSeedDictAddError::try_from(code)
+                .map(Self::LocalSeedDictAdd)
+                .unwrap_or_else(|_| invalid()),
+            ForwardedError::SumPartAdd(code) => SumPartAddError::try_from(code)
+                .map(Self::SumPartAdd)
+                .unwrap_or_else(|_| invalid()),
+            ForwardedError::MaskScoreIncr(code) => MaskScoreIncrError::try_from(code)
+                .map(Self::MaskScoreIncr)
+                .unwrap_or_else(|_| invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_decoder() {
+        let events = vec![
+            ClusterEvent::KeepAlive,
+            ClusterEvent::Phase(Event {
+                round_id: 1,
+                event: PhaseName::Sum,
+            }),
+            ClusterEvent::SumDict(Event {
+                round_id: 1,
+                event: DictionaryUpdate::Invalidate,
+            }),
+        ];
+        let stream = events.iter().flat_map(encode_frame).collect::<Vec<u8>>();
+
+        // the frames are split across the chunks
+        let mut decoder = FrameDecoder::default();
+        let (first, second) = stream.split_at(7);
+        assert_eq!(decoder.decode(first).unwrap(), events[..1].to_vec());
+        assert_eq!(decoder.decode(second).unwrap(), events[1..].to_vec());
+        assert!(decoder.buffer.is_empty());
+
+        // a frame which isn't an event
+        let mut decoder = FrameDecoder::default();
+        assert!(decoder.decode(&[0, 0, 0, 1, 42]).is_err());
+    }
+
+    #[test]
+    fn test_forwarded_error() {
+        let error = RequestError::from(ForwardedError::from(RequestError::SumPartAdd(
+            SumPartAddError::AlreadyExists,
+        )));
+        assert!(matches!(
+            error,
+            RequestError::SumPartAdd(SumPartAddError::AlreadyExists)
+        ));
+
+        let error = RequestError::from(ForwardedError::from(RequestError::MessageDiscarded));
+        assert!(matches!(error, RequestError::MessageDiscarded));
+
+        let error = RequestError::from(ForwardedError::SumPartAdd(42));
+        assert!(matches!(error, RequestError::InternalError(_)));
+    }
+}
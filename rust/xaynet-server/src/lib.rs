@@ -14,6 +14,8 @@
 
 pub mod examples;
 
+pub mod admin;
+pub mod cluster;
 pub mod metrics;
 pub mod rest;
 pub mod services;
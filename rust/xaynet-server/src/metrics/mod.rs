@@ -1,10 +1,43 @@
 //! Utils to record metrics.
+//!
+//! The metrics and events are recorded via the [`metric!`] and [`event!`] macros by the globally
+//! installed [`Recorder`], see [`GlobalRecorder`].
 
+mod models;
 pub mod recorders;
-pub use self::recorders::influxdb::{Measurement, Recorder, Tags};
+
 use once_cell::sync::OnceCell;
 
-static RECORDER: OnceCell<Recorder> = OnceCell::new();
+pub use self::models::{Measurement, Tags, Value};
+
+/// A recorder of metrics and events.
+pub trait Recorder: Send + Sync {
+    /// Records a new metric.
+    fn metric(&self, measurement: Measurement, value: Value, tags: Option<Tags>);
+
+    /// Records a new event.
+    fn event(&self, title: String, description: Option<&str>, tags: Option<&[&str]>);
+}
+
+/// Several recorders, which all record the same metrics and events.
+impl Recorder for Vec<Box<dyn Recorder>> {
+    fn metric(&self, measurement: Measurement, value: Value, tags: Option<Tags>) {
+        if let Some((last, recorders)) = self.split_last() {
+            for recorder in recorders {
+                recorder.metric(measurement, value.clone(), tags.clone());
+            }
+            last.metric(measurement, value, tags);
+        }
+    }
+
+    fn event(&self, title: String, description: Option<&str>, tags: Option<&[&str]>) {
+        for recorder in self {
+            recorder.event(title.clone(), description, tags);
+        }
+    }
+}
+
+static RECORDER: OnceCell<Box<dyn Recorder>> = OnceCell::new();
 
 pub struct GlobalRecorder;
 
@@ -12,14 +45,14 @@ impl GlobalRecorder {
     /// Gets the reference to the global recorder.
     /// Returns `None` if no recorder is set or is currently being initialized.
     /// This method never blocks.
-    pub fn global() -> Option<&'static Recorder> {
-        RECORDER.get()
+    pub fn global() -> Option<&'static dyn Recorder> {
+        RECORDER.get().map(|recorder| &**recorder)
     }
 
     /// Installs a new global recorder.
     /// Returns Err(Recorder) if a recorder has already been set.
-    pub fn install(recorder: Recorder) -> Result<(), Recorder> {
-        RECORDER.set(recorder)
+    pub fn install(recorder: impl Recorder + 'static) -> Result<(), Box<dyn Recorder>> {
+        RECORDER.set(Box::new(recorder))
     }
 }
 
@@ -45,17 +78,17 @@ impl GlobalRecorder {
 macro_rules! event {
     ($title: expr) => {
         if let Some(recorder) = crate::metrics::GlobalRecorder::global() {
-            recorder.event($title, None, None)
+            recorder.event($title.into(), None, None)
         }
     };
     ($title: expr, $description: expr) => {
         if let Some(recorder) = crate::metrics::GlobalRecorder::global() {
-            recorder.event($title, Some($description), None)
+            recorder.event($title.into(), Some($description), None)
         }
     };
     ($title: expr, $description: expr, $tags: expr) => {
         if let Some(recorder) = crate::metrics::GlobalRecorder::global() {
-            recorder.event($title, Some($description), Some(&$tags))
+            recorder.event($title.into(), Some($description), Some(&$tags))
         }
     };
 }
@@ -83,7 +116,7 @@ macro_rules! event {
 macro_rules! metric {
     ($measurement: expr, $value: expr $(,)?) => {
         if let Some(recorder) = crate::metrics::GlobalRecorder::global() {
-            recorder.metric($measurement, $value, None)
+            recorder.metric($measurement, $value.into(), None)
         }
     };
     ($measurement: expr, $value: expr, $($tag: expr),+ $(,)?) => {
@@ -94,7 +127,7 @@ macro_rules! metric {
                 tags.add($tag.0, $tag.1);
             )*
 
-            recorder.metric($measurement, $value, Some(tags))
+            recorder.metric($measurement, $value.into(), Some(tags))
         }
     };
 }
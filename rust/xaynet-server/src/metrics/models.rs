@@ -0,0 +1,132 @@
+//! Recorder agnostic metrics models.
+
+/// The value of a metric or a tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Boolean(bool),
+    Float(f64),
+    SignedInteger(i64),
+    UnsignedInteger(u64),
+    Text(String),
+}
+
+macro_rules! impl_from_for_value {
+    ($variant: ident, $target: ty, $($ty: ty),+) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::$variant(value as $target)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_for_value!(Float, f64, f32, f64);
+impl_from_for_value!(SignedInteger, i64, i8, i16, i32, i64);
+impl_from_for_value!(UnsignedInteger, u64, u8, u16, u32, u64);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+/// An enum that contains all supported measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measurement {
+    RoundParamSum,
+    RoundParamUpdate,
+    RoundParamPrivacyEpsilon,
+    RoundParticipationSum,
+    RoundParticipationUpdate,
+    Phase,
+    MasksTotalNumber,
+    RoundTotalNumber,
+    MessageSum,
+    MessageUpdate,
+    MessageSum2,
+    MessageDiscarded,
+    MessageRejected,
+    MultipartMessagesPartial,
+    MultipartBufferedBytes,
+    MultipartMessagesEvicted,
+}
+
+impl From<&Measurement> for &'static str {
+    fn from(measurement: &Measurement) -> &'static str {
+        match measurement {
+            Measurement::RoundParamSum => "round_param_sum",
+            Measurement::RoundParamUpdate => "round_param_update",
+            Measurement::RoundParamPrivacyEpsilon => "round_param_privacy_epsilon",
+            Measurement::RoundParticipationSum => "round_participation_sum",
+            Measurement::RoundParticipationUpdate => "round_participation_update",
+            Measurement::Phase => "phase",
+            Measurement::MasksTotalNumber => "masks_total_number",
+            Measurement::RoundTotalNumber => "round_total_number",
+            Measurement::MessageSum => "message_sum",
+            Measurement::MessageUpdate => "message_update",
+            Measurement::MessageSum2 => "message_sum2",
+            Measurement::MessageDiscarded => "message_discarded",
+            Measurement::MessageRejected => "message_rejected",
+            Measurement::MultipartMessagesPartial => "multipart_messages_partial",
+            Measurement::MultipartBufferedBytes => "multipart_buffered_bytes",
+            Measurement::MultipartMessagesEvicted => "multipart_messages_evicted",
+        }
+    }
+}
+
+impl ToString for Measurement {
+    fn to_string(&self) -> String {
+        Into::<&str>::into(self).into()
+    }
+}
+
+/// A container that contains the tags of a metric.
+#[derive(Debug, Clone)]
+pub struct Tags(Vec<(String, Value)>);
+
+impl Tags {
+    /// Creates a new empty container for tags.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Adds a tag to the metric.
+    pub fn add<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.0.push((key.into(), value.into()))
+    }
+
+    /// Gets the value of the tag with the given key.
+    pub(in crate::metrics) fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find_map(|(tag, value)| if tag == key { Some(value) } else { None })
+    }
+
+    pub(in crate::metrics) fn into_inner(self) -> Vec<(String, Value)> {
+        self.0
+    }
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,14 +1,13 @@
+//! An InfluxDB metrics recorder.
+
 mod dispatcher;
 mod models;
 mod recorder;
 mod service;
 
+pub use self::recorder::Recorder;
 pub(in crate::metrics) use self::{
     dispatcher::{Dispatcher, Request},
     models::{Event, Metric},
     service::InfluxDbService,
 };
-pub use self::{
-    models::{Measurement, Tags},
-    recorder::Recorder,
-};
@@ -1,82 +1,30 @@
 use chrono::{DateTime, Utc};
 use influxdb::InfluxDbWriteable;
 
-/// An enum that contains all supported measurements.
-pub enum Measurement {
-    RoundParamSum,
-    RoundParamUpdate,
-    Phase,
-    MasksTotalNumber,
-    RoundTotalNumber,
-    MessageSum,
-    MessageUpdate,
-    MessageSum2,
-    MessageDiscarded,
-    MessageRejected,
-}
-
-impl From<&Measurement> for &'static str {
-    fn from(measurement: &Measurement) -> &'static str {
-        match measurement {
-            Measurement::RoundParamSum => "round_param_sum",
-            Measurement::RoundParamUpdate => "round_param_update",
-            Measurement::Phase => "phase",
-            Measurement::MasksTotalNumber => "masks_total_number",
-            Measurement::RoundTotalNumber => "round_total_number",
-            Measurement::MessageSum => "message_sum",
-            Measurement::MessageUpdate => "message_update",
-            Measurement::MessageSum2 => "message_sum2",
-            Measurement::MessageDiscarded => "message_discarded",
-            Measurement::MessageRejected => "message_rejected",
+use crate::metrics::{Measurement, Tags, Value};
+
+impl From<Value> for influxdb::Type {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Boolean(value) => influxdb::Type::Boolean(value),
+            Value::Float(value) => influxdb::Type::Float(value),
+            Value::SignedInteger(value) => influxdb::Type::SignedInteger(value),
+            Value::UnsignedInteger(value) => influxdb::Type::UnsignedInteger(value),
+            Value::Text(value) => influxdb::Type::Text(value),
         }
     }
 }
 
-impl ToString for Measurement {
-    fn to_string(&self) -> String {
-        Into::<&str>::into(self).into()
-    }
-}
-
-/// A container that contains the tags of a metric.
-pub struct Tags(Vec<(String, influxdb::Type)>);
-
-impl Tags {
-    /// Creates a new empty container for tags.
-    pub fn new() -> Self {
-        Self(Vec::new())
-    }
-
-    /// Adds a tag to the metric.
-    pub fn add<K, V>(&mut self, key: K, value: V)
-    where
-        K: Into<String>,
-        V: Into<influxdb::Type>,
-    {
-        self.0.push((key.into(), value.into()))
-    }
-
-    pub(in crate::metrics) fn into_inner(self) -> Vec<(String, influxdb::Type)> {
-        self.0
-    }
-}
-
-impl Default for Tags {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// A metrics data point.
 pub(in crate::metrics) struct Metric {
     name: Measurement,
     time: DateTime<Utc>,
-    value: influxdb::Type,
+    value: Value,
     tags: Option<Tags>,
 }
 
 impl Metric {
-    pub(in crate::metrics) fn new(measurement: Measurement, value: influxdb::Type) -> Self {
+    pub(in crate::metrics) fn new(measurement: Measurement, value: Value) -> Self {
         Self {
             name: measurement,
             time: Utc::now(),
@@ -96,11 +44,11 @@ impl Metric {
     pub(in crate::metrics) fn into_query(self) -> influxdb::WriteQuery {
         let timestamp: ::influxdb::Timestamp = self.time.into();
         let mut query = timestamp.into_query(self.name.to_string());
-        query = query.add_field("value", self.value);
+        query = query.add_field("value", influxdb::Type::from(self.value));
 
         if let Some(tags) = self.tags {
             for (tag, value) in tags.into_inner() {
-                query = query.add_tag(tag, value);
+                query = query.add_tag(tag, influxdb::Type::from(value));
             }
         }
 
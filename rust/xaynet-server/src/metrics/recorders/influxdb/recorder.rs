@@ -1,5 +1,8 @@
-use super::{Dispatcher, Event, InfluxDbService, Measurement, Metric, Request, Tags};
-use crate::settings::InfluxSettings;
+use super::{Dispatcher, Event, InfluxDbService, Metric, Request};
+use crate::{
+    metrics::{Measurement, Recorder as RecorderTrait, Tags, Value},
+    settings::InfluxSettings,
+};
 
 use futures::future::poll_fn;
 use tower::Service;
@@ -20,12 +23,24 @@ impl Recorder {
         }
     }
 
+    fn call(&self, req: Request) {
+        let mut handle = self.service.0.clone();
+        tokio::spawn(async move {
+            if let Err(err) = poll_fn(|cx| handle.poll_ready(cx)).await {
+                error!("influx service temporarily unavailable: {}", err)
+            }
+
+            if let Err(err) = handle.call(req).await {
+                warn!("influx service error: {}", err)
+            }
+        });
+    }
+}
+
+impl RecorderTrait for Recorder {
     /// Records a new metric and dispatches it to an InfluxDB instance.
-    pub fn metric<V>(&self, measurement: Measurement, value: V, tags: Option<Tags>)
-    where
-        V: Into<influxdb::Type> + Send + 'static,
-    {
-        let mut metric = Metric::new(measurement, value.into());
+    fn metric(&self, measurement: Measurement, value: Value, tags: Option<Tags>) {
+        let mut metric = Metric::new(measurement, value);
         if let Some(tags) = tags {
             metric.with_tags(tags);
         }
@@ -34,11 +49,8 @@ impl Recorder {
     }
 
     /// Records a new event and dispatches it to an InfluxDB instance.
-    pub fn event<T>(&self, title: T, description: Option<&str>, tags: Option<&[&str]>)
-    where
-        T: Into<String> + Send + 'static,
-    {
-        let mut event = Event::new(title.into());
+    fn event(&self, title: String, description: Option<&str>, tags: Option<&[&str]>) {
+        let mut event = Event::new(title);
 
         if let Some(description) = description {
             event.with_description(description);
@@ -50,17 +62,4 @@ impl Recorder {
 
         self.call(Request::from(event))
     }
-
-    fn call(&self, req: Request) {
-        let mut handle = self.service.0.clone();
-        tokio::spawn(async move {
-            if let Err(err) = poll_fn(|cx| handle.poll_ready(cx)).await {
-                error!("influx service temporarily unavailable: {}", err)
-            }
-
-            if let Err(err) = handle.call(req).await {
-                warn!("influx service error: {}", err)
-            }
-        });
-    }
 }
@@ -1 +1,4 @@
+//! Metrics recorders.
+
 pub mod influxdb;
+pub mod prometheus;
@@ -0,0 +1,295 @@
+//! A Prometheus metrics recorder.
+//!
+//! The recorded metrics are exposed in the Prometheus text format on a `/metrics` endpoint, see
+//! [`bind()`]. The metrics are named after their [`Measurement`] with a `xaynet_` prefix:
+//!
+//! - The round parameters, the estimated participation of the adaptive selection, the phase, the
+//!   number of masks, the number of rounds and the number of incomplete multipart messages and
+//!   their bytes are gauges of their latest value.
+//! - The messages are counted by the counter `xaynet_messages_total` with the labels `phase` and
+//!   `status` (one of `accepted`, `discarded` or `rejected`).
+//! - The evicted incomplete multipart messages are counted by the counter
+//!   `xaynet_multipart_messages_evicted_total` with the label `reason`.
+//! - The events are counted by the counter `xaynet_events_total` with the label `title`.
+
+use std::{collections::HashMap, future::Future, net::SocketAddr};
+
+use prometheus::{Encoder, Gauge, IntCounterVec, Opts, Registry, TextEncoder, TEXT_FORMAT};
+use tracing::{error, warn};
+use warp::{http::Response, Filter};
+
+use crate::metrics::{Measurement, Recorder as RecorderTrait, Tags, Value};
+
+/// The measurements which are recorded as gauges.
+const GAUGES: [Measurement; 10] = [
+    Measurement::RoundParamSum,
+    Measurement::RoundParamUpdate,
+    Measurement::RoundParamPrivacyEpsilon,
+    Measurement::RoundParticipationSum,
+    Measurement::RoundParticipationUpdate,
+    Measurement::Phase,
+    Measurement::MasksTotalNumber,
+    Measurement::RoundTotalNumber,
+    Measurement::MultipartMessagesPartial,
+    Measurement::MultipartBufferedBytes,
+];
+
+/// A Prometheus metrics / events recorder.
+pub struct Recorder {
+    /// The registry of all metrics.
+    registry: Registry,
+    /// The gauges of the measurements.
+    gauges: HashMap<Measurement, Gauge>,
+    /// The counter of the messages.
+    messages: IntCounterVec,
+    /// The counter of the evicted multipart messages.
+    evicted: IntCounterVec,
+    /// The counter of the events.
+    events: IntCounterVec,
+}
+
+impl Recorder {
+    /// Creates a new Prometheus recorder.
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        // safe unwraps: the metric names and labels are valid and unique
+        let gauges = GAUGES
+            .iter()
+            .map(|measurement| {
+                let name = format!("xaynet_{}", measurement.to_string());
+                let gauge =
+                    Gauge::new(name, format!("The latest {:?} value.", measurement)).unwrap();
+                registry.register(Box::new(gauge.clone())).unwrap();
+                (*measurement, gauge)
+            })
+            .collect();
+        let messages = IntCounterVec::new(
+            Opts::new("xaynet_messages_total", "The number of messages."),
+            &["phase", "status"],
+        )
+        .unwrap();
+        registry.register(Box::new(messages.clone())).unwrap();
+        let evicted = IntCounterVec::new(
+            Opts::new(
+                "xaynet_multipart_messages_evicted_total",
+                "The number of evicted incomplete multipart messages.",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+        registry.register(Box::new(evicted.clone())).unwrap();
+        let events = IntCounterVec::new(
+            Opts::new("xaynet_events_total", "The number of events."),
+            &["title"],
+        )
+        .unwrap();
+        registry.register(Box::new(events.clone())).unwrap();
+
+        Self {
+            registry,
+            gauges,
+            messages,
+            evicted,
+            events,
+        }
+    }
+
+    /// Gets the registry of the recorded metrics.
+    pub fn registry(&self) -> Registry {
+        self.registry.clone()
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecorderTrait for Recorder {
+    fn metric(&self, measurement: Measurement, value: Value, tags: Option<Tags>) {
+        let value = match to_f64(&value) {
+            Some(value) => value,
+            None => {
+                warn!("invalid value of metric {:?}", measurement);
+                return;
+            }
+        };
+
+        let status = match measurement {
+            Measurement::MessageSum | Measurement::MessageUpdate | Measurement::MessageSum2 => {
+                "accepted"
+            }
+            Measurement::MessageDiscarded => "discarded",
+            Measurement::MessageRejected => "rejected",
+            Measurement::MultipartMessagesEvicted => {
+                let reason = tags
+                    .as_ref()
+                    .and_then(|tags| tags.get("reason"))
+                    .map(to_label)
+                    .unwrap_or_default();
+                self.evicted
+                    .with_label_values(&[&reason])
+                    .inc_by(value as u64);
+                return;
+            }
+            _ => {
+                if let Some(gauge) = self.gauges.get(&measurement) {
+                    gauge.set(value);
+                }
+                return;
+            }
+        };
+        let phase = tags
+            .as_ref()
+            .and_then(|tags| tags.get("phase"))
+            .map(to_label)
+            .unwrap_or_default();
+        self.messages
+            .with_label_values(&[&phase, status])
+            .inc_by(value as u64);
+    }
+
+    fn event(&self, title: String, _description: Option<&str>, _tags: Option<&[&str]>) {
+        self.events.with_label_values(&[&title]).inc();
+    }
+}
+
+/// Converts a value into a float, unless it is a text.
+fn to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Boolean(value) => Some(if *value { 1. } else { 0. }),
+        Value::Float(value) => Some(*value),
+        Value::SignedInteger(value) => Some(*value as f64),
+        Value::UnsignedInteger(value) => Some(*value as f64),
+        Value::Text(_) => None,
+    }
+}
+
+/// Converts a value into a label value.
+fn to_label(value: &Value) -> String {
+    match value {
+        Value::Boolean(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::SignedInteger(value) => value.to_string(),
+        Value::UnsignedInteger(value) => value.to_string(),
+        Value::Text(value) => value.clone(),
+    }
+}
+
+/// Encodes the metrics of the `registry` in the Prometheus text format.
+fn encode(registry: &Registry) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Err(err) = TextEncoder::new().encode(&registry.gather(), &mut buffer) {
+        error!("failed to encode metrics: {}", err);
+    }
+    buffer
+}
+
+/// Creates the filter of the `/metrics` endpoint.
+fn metrics(
+    registry: Registry,
+) -> impl Filter<Extract = (Response<Vec<u8>>,), Error = warp::Rejection> + Clone {
+    warp::path!("metrics").and(warp::get()).map(move || {
+        // safe unwrap: the response is valid
+        Response::builder()
+            .header("content-type", TEXT_FORMAT)
+            .body(encode(&registry))
+            .unwrap()
+    })
+}
+
+/// Binds a server of the metrics of the `registry` on the `/metrics` endpoint to the given
+/// address. The returned future serves the metrics until it is dropped.
+///
+/// # Errors
+/// Fails if the server can't be bound to the address.
+pub fn bind(
+    address: SocketAddr,
+    registry: Registry,
+) -> Result<impl Future<Output = ()>, warp::Error> {
+    let (_, server) = warp::serve(metrics(registry)).try_bind_ephemeral(address)?;
+    Ok(server)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(phase: u8) -> Option<Tags> {
+        let mut tags = Tags::new();
+        tags.add("round_id", 1);
+        tags.add("phase", phase);
+        Some(tags)
+    }
+
+    #[test]
+    fn test_gauge() {
+        let recorder = Recorder::new();
+        recorder.metric(Measurement::Phase, 2.into(), None);
+        recorder.metric(Measurement::RoundParamSum, 0.5.into(), tags(0));
+
+        let metrics = String::from_utf8(encode(&recorder.registry())).unwrap();
+        assert!(metrics.contains("xaynet_phase 2\n"));
+        assert!(metrics.contains("xaynet_round_param_sum 0.5\n"));
+    }
+
+    #[test]
+    fn test_messages() {
+        let recorder = Recorder::new();
+        recorder.metric(Measurement::MessageSum, 1.into(), tags(1));
+        recorder.metric(Measurement::MessageSum, 1.into(), tags(1));
+        recorder.metric(Measurement::MessageRejected, 1.into(), tags(2));
+        recorder.metric(Measurement::MessageDiscarded, 1.into(), tags(2));
+
+        let metrics = String::from_utf8(encode(&recorder.registry())).unwrap();
+        assert!(metrics.contains(r#"xaynet_messages_total{phase="1",status="accepted"} 2"#));
+        assert!(metrics.contains(r#"xaynet_messages_total{phase="2",status="rejected"} 1"#));
+        assert!(metrics.contains(r#"xaynet_messages_total{phase="2",status="discarded"} 1"#));
+    }
+
+    #[test]
+    fn test_multipart_evicted() {
+        let recorder = Recorder::new();
+        let mut tags = Tags::new();
+        tags.add("reason", "expired");
+        recorder.metric(Measurement::MultipartMessagesEvicted, 3.into(), Some(tags));
+
+        let metrics = String::from_utf8(encode(&recorder.registry())).unwrap();
+        assert!(metrics.contains(r#"xaynet_multipart_messages_evicted_total{reason="expired"} 3"#));
+    }
+
+    #[test]
+    fn test_event() {
+        let recorder = Recorder::new();
+        recorder.event("Phase error".into(), Some("error"), None);
+
+        let metrics = String::from_utf8(encode(&recorder.registry())).unwrap();
+        assert!(metrics.contains(r#"xaynet_events_total{title="Phase error"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let recorder = Recorder::new();
+        recorder.metric(Measurement::RoundTotalNumber, 3_u64.into(), None);
+        let filter = metrics(recorder.registry());
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/metrics")
+            .reply(&filter)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], TEXT_FORMAT);
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(body.contains("xaynet_round_total_number 3\n"));
+    }
+
+    #[tokio::test]
+    async fn test_bind_address_in_use() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        assert!(bind(address, Registry::new()).is_err());
+    }
+}
@@ -1,32 +1,425 @@
 //! A HTTP API for the PET protocol interactions.
+//!
+//! # Message errors
+//!
+//! A PET message posted to `/message` is answered with `200 OK` if it has been accepted, which
+//! includes the chunks of a multipart message that is not yet complete. Otherwise, the response
+//! has a JSON body `{"code": <code>, "message": <description>}` and one of these status codes:
+//!
+//! | Error                                             | Status | Code                             |
+//! | ------------------------------------------------- | ------ | -------------------------------- |
+//! | [`ServiceError::Decrypt`]                         | 400    | `decrypt`                        |
+//! | [`ServiceError::Parsing`]                         | 400    | `parsing`                        |
+//! | [`ServiceError::InvalidMessageSignature`]         | 400    | `invalid_message_signature`      |
+//! | [`ServiceError::UnsupportedVersion`]              | 400    | `unsupported_version`            |
+//! | [`ServiceError::NotSumEligible`]                  | 403    | `not_sum_eligible`               |
+//! | [`ServiceError::NotUpdateEligible`]               | 403    | `not_update_eligible`            |
+//! | [`ServiceError::InvalidCoordinatorPublicKey`]     | 409    | `invalid_coordinator_public_key` |
+//! | [`ServiceError::UnexpectedMessage`]               | 409    | `unexpected_message`             |
+//! | [`ServiceError::MessageTooLarge`]                 | 413    | `message_too_large`              |
+//! | [`RequestError::MessageRejected`]                 | 409    | `message_rejected`               |
+//! | [`RequestError::MessageDiscarded`]                | 409    | `message_discarded`              |
+//! | [`RequestError::SumPartAdd`]                      | 409    | `duplicate_sum_participant`      |
+//! | [`RequestError::AggregationFailed`]               | 422    | `aggregation_failed`             |
+//! | [`RequestError::LocalSeedDictAdd`]                | 422    | `invalid_local_seed_dict`        |
+//! | [`RequestError::MaskScoreIncr`]                   | 422    | `invalid_mask`                   |
+//! | [`RequestError::CoordinatorStorage`]              | 500    | `internal_error`                 |
+//! | [`RequestError::InternalError`]                   | 500    | `internal_error`                 |
+//! | [`ServiceError::InternalError`]                   | 500    | `internal_error`                 |
+//! | [`ServiceError::MultipartCapacityExceeded`]       | 503    | `multipart_capacity_exceeded`    |
+//!
+//! The [`RequestError`]s are wrapped in [`ServiceError::StateMachine`]. A message for an outdated
+//! round is encrypted for a different coordinator key and therefore fails with `decrypt` or
+//! `invalid_coordinator_public_key`. Only messages which failed with `internal_error` or
+//! `multipart_capacity_exceeded` are worth resending.
+//!
+//! The header of a PET message carries the protocol version of the participant. The coordinator
+//! only accepts the versions of the `protocol` settings and announces its own version in the round
+//! parameters. Messages of other versions fail with `unsupported_version`, so participants have to
+//! be updated before the minimal version is raised.
+//!
+//! # Global model encoding
+//!
+//! The global model on `/model` is serialized with `bincode` by default. A client which accepts
+//! the media type `application/x-xaynet-primitive-model` receives the model weights as primitive
+//! values in little endian byte order instead, see [`PrimitiveModel`]. The data type of the weights
+//! is requested with a `data-type` parameter, e.g. `f32`, and defaults to the data type of the
+//! current round. The weights are compressed with `zstd` if the client accepts the `zstd` content
+//! encoding. The `Content-Type` and `Content-Encoding` headers of the response state the actual
+//! encoding, e.g. `application/x-xaynet-primitive-model; data-type=f32`. If the weights can't be
+//! converted into the requested data type, e.g. because they exceed its range, the response is
+//! `406 Not Acceptable`.
+//!
+//! # Global model history
+//!
+//! The infos of the global models of the completed rounds are listed on `/models` in pages of at
+//! most 100 infos ordered by the round ids, serialized with `bincode`, see [`GlobalModelInfo`]. A
+//! page starts at the round of the `from` query parameter, e.g. `/models?from=42&limit=10`, and
+//! an empty page marks the end of the history. The global model of a round is served on
+//! `/models/{round_id}`. The history is pruned along with the global models by the retention
+//! policy of the `model_storage` settings.
+//!
+//! # Proofs of global models
+//!
+//! The proofs of the global models which have been published to the trust anchor are listed on
+//! `/proofs` in the order of their publication, and the proof of the global model of a round is
+//! served on `/proofs/{round_id}`. Both are serialized with `bincode`, see [`ModelProof`]. The
+//! public key to verify the proofs with is served on `/proofs/key`. A trust anchor which doesn't
+//! keep the proofs responds with an empty list and `204 No Content` respectively.
+//!
+//! [`PrimitiveModel`]: xaynet_core::mask::PrimitiveModel
+//! [`ModelProof`]: xaynet_core::proof::ModelProof
 
 use std::convert::Infallible;
 #[cfg(feature = "tls")]
 use std::path::PathBuf;
 
 use bytes::Bytes;
+use futures::{future, stream, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::{error, warn};
+use tracing::{debug, error, warn};
 use warp::{
-    http::{Response, StatusCode},
+    http::{header, HeaderValue, Response, StatusCode},
     reply::Reply,
+    sse,
     Filter,
 };
 #[cfg(feature = "tls")]
 use warp::{Server, TlsServer};
 
 use crate::{
-    services::{fetchers::Fetcher, messages::PetMessageHandler},
+    services::{
+        fetchers::{Cached, EncodedModel, Fetcher, ModelEncoding, ModelEncodingError},
+        messages::{PetMessageHandler, ServiceError},
+    },
     settings::ApiSettings,
+    state_machine::{
+        events::{Event, EventListener, EventSubscriber, ModelUpdate},
+        phases::PhaseName,
+        RequestError,
+    },
+    storage::{CoordinatorStorage, ModelStorage, Storage, StorageResult, TrustAnchor},
 };
-use xaynet_core::{crypto::ByteObject, ParticipantPublicKey};
+use xaynet_core::{
+    common::{GlobalModelInfo, RoundParameters},
+    crypto::{ByteObject, PublicSigningKey},
+    mask::{DataType, Model},
+    proof::ModelProof,
+    ParticipantPublicKey,
+};
+
+/// The media type of a global model encoded as primitive values.
+const PRIMITIVE_MODEL_MEDIA_TYPE: &str = "application/x-xaynet-primitive-model";
 
 #[derive(Deserialize, Serialize)]
 struct SeedDictQuery {
     pk: String,
 }
 
+/// The maximal number of [`GlobalModelInfo`]s on a page of the global model history.
+const MAX_MODEL_HISTORY_PAGE: usize = 100;
+
+/// The query for a page of the global model history.
+#[derive(Deserialize, Serialize)]
+struct ModelHistoryQuery {
+    /// The round from which on the page lists the global models.
+    #[serde(default)]
+    from: u64,
+    /// The maximal number of global models on the page.
+    limit: Option<usize>,
+}
+
+/// The error response to a PET message which has not been accepted.
+#[derive(Debug, Serialize)]
+struct MessageError {
+    code: &'static str,
+    message: String,
+}
+
+impl MessageError {
+    /// Maps a message handling error to its status and error code.
+    ///
+    /// The mapping is part of the API and documented in the [module](self) docs.
+    fn status_and_code(error: &ServiceError) -> (StatusCode, &'static str) {
+        match error {
+            ServiceError::Decrypt => (StatusCode::BAD_REQUEST, "decrypt"),
+            ServiceError::Parsing(_) => (StatusCode::BAD_REQUEST, "parsing"),
+            ServiceError::InvalidMessageSignature => {
+                (StatusCode::BAD_REQUEST, "invalid_message_signature")
+            }
+            ServiceError::UnsupportedVersion { .. } => {
+                (StatusCode::BAD_REQUEST, "unsupported_version")
+            }
+            ServiceError::NotSumEligible => (StatusCode::FORBIDDEN, "not_sum_eligible"),
+            ServiceError::NotUpdateEligible => (StatusCode::FORBIDDEN, "not_update_eligible"),
+            ServiceError::InvalidCoordinatorPublicKey => {
+                (StatusCode::CONFLICT, "invalid_coordinator_public_key")
+            }
+            ServiceError::UnexpectedMessage => (StatusCode::CONFLICT, "unexpected_message"),
+            ServiceError::MessageTooLarge(_) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "message_too_large")
+            }
+            ServiceError::MultipartCapacityExceeded => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "multipart_capacity_exceeded",
+            ),
+            ServiceError::StateMachine(error) => match error {
+                RequestError::MessageRejected => (StatusCode::CONFLICT, "message_rejected"),
+                RequestError::MessageDiscarded => (StatusCode::CONFLICT, "message_discarded"),
+                RequestError::SumPartAdd(_) => (StatusCode::CONFLICT, "duplicate_sum_participant"),
+                RequestError::AggregationFailed => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "aggregation_failed")
+                }
+                RequestError::LocalSeedDictAdd(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "invalid_local_seed_dict")
+                }
+                RequestError::MaskScoreIncr(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "invalid_mask")
+                }
+                RequestError::CoordinatorStorage(_) | RequestError::InternalError(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+                }
+            },
+            ServiceError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        }
+    }
+}
+
+/// The readiness of a storage backend.
+///
+/// The error of an unavailable backend is only logged, because it may reveal internals of the
+/// deployment like hostnames or paths.
+#[derive(Debug, Serialize)]
+struct BackendReadiness {
+    ready: bool,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl From<StorageResult<()>> for BackendReadiness {
+    fn from(result: StorageResult<()>) -> Self {
+        Self {
+            ready: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+}
+
+/// The readiness of the coordinator.
+#[derive(Debug, Serialize)]
+struct Readiness {
+    phase: PhaseName,
+    coordinator_storage: BackendReadiness,
+    model_storage: BackendReadiness,
+    trust_anchor: BackendReadiness,
+}
+
+impl Readiness {
+    /// Checks whether the state machine runs without errors and all backends are ready.
+    fn is_ready(&self) -> bool {
+        !matches!(self.phase, PhaseName::Error | PhaseName::Shutdown)
+            && self.coordinator_storage.ready
+            && self.model_storage.ready
+            && self.trust_anchor.ready
+    }
+}
+
+/// The health of the coordinator.
+#[derive(Debug, Serialize)]
+struct Health {
+    phase: PhaseName,
+}
+
+/// A health check of the state machine and the storage backends.
+#[derive(Clone)]
+pub struct HealthCheck<S>
+where
+    S: Storage,
+{
+    store: S,
+    phase: EventListener<PhaseName>,
+}
+
+impl<S> HealthCheck<S>
+where
+    S: Storage,
+{
+    /// Creates a new health check of the `store` and the state machine of the
+    /// `event_subscriber`.
+    pub fn new(store: S, event_subscriber: &EventSubscriber) -> Self {
+        Self {
+            store,
+            phase: event_subscriber.phase_listener(),
+        }
+    }
+
+    /// Gets the current phase of the state machine.
+    fn phase(&self) -> PhaseName {
+        self.phase.get_latest().event
+    }
+
+    /// Checks the readiness of each storage backend.
+    async fn readiness(&self) -> Readiness {
+        let mut store = self.store.clone();
+        Readiness {
+            phase: self.phase(),
+            coordinator_storage: CoordinatorStorage::is_ready(&mut store).await.into(),
+            model_storage: ModelStorage::is_ready(&mut store).await.into(),
+            trust_anchor: TrustAnchor::is_ready(&mut store).await.into(),
+        }
+    }
+}
+
+/// The global model history of the coordinator.
+///
+/// The history lists the [`GlobalModelInfo`]s of the completed rounds whose global models have
+/// been saved in the model storage and are retained. Each global model can be downloaded by the id
+/// of the round in which it was created, as long as it is retained by the model storage, and
+/// verified against the
+/// [`ModelProof`] which has been published to the trust anchor.
+#[derive(Clone)]
+pub struct ModelHistory<S>
+where
+    S: Storage,
+{
+    store: S,
+}
+
+impl<S> ModelHistory<S>
+where
+    S: Storage,
+{
+    /// Creates a new global model history of the `store`.
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Gets the infos of at most `limit` global models from the given round on, in the order of
+    /// their creation.
+    async fn infos(&self, from_round_id: u64, limit: usize) -> StorageResult<Vec<GlobalModelInfo>> {
+        self.store
+            .clone()
+            .global_model_history(from_round_id, limit)
+            .await
+    }
+
+    /// Gets the info of the global model which was created in the given round.
+    async fn info(&self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>> {
+        self.store.clone().global_model_info(round_id).await
+    }
+
+    /// Gets the global model with the given id.
+    async fn model(&self, model_id: &str) -> StorageResult<Option<Model>> {
+        self.store.clone().global_model(model_id).await
+    }
+
+    /// Gets the published proofs in the order of their publication.
+    async fn proofs(&self) -> StorageResult<Vec<ModelProof>> {
+        self.store.clone().proofs().await
+    }
+
+    /// Gets the published proof of the global model which was created in the given round.
+    async fn proof(&self, round_id: u64) -> StorageResult<Option<ModelProof>> {
+        self.store.clone().proof(round_id).await
+    }
+
+    /// Gets the public key to verify the published proofs with.
+    async fn proof_key(&self) -> StorageResult<Option<PublicSigningKey>> {
+        self.store.clone().proof_key().await
+    }
+}
+
+/// A push notification of a new phase.
+#[derive(Debug, Serialize)]
+struct PhaseNotification {
+    round_id: u64,
+    phase: PhaseName,
+}
+
+/// A push notification of new round parameters.
+#[derive(Debug, Serialize)]
+struct ParamsNotification {
+    round_id: u64,
+}
+
+/// A push notification of a new or invalidated global model.
+#[derive(Debug, Serialize)]
+struct ModelNotification {
+    round_id: u64,
+    available: bool,
+}
+
+/// Push notifications of the state machine events which are relevant for participants.
+///
+/// The notifications are sent as server-sent events named `phase`, `params` and `model`. Each
+/// event carries a JSON object with the `round_id` and, for the `phase` event, the `phase` or, for
+/// the `model` event, whether a global model is `available`. The current state is sent right
+/// after connecting.
+#[derive(Clone)]
+pub struct Notifications {
+    phase: EventListener<PhaseName>,
+    params: EventListener<RoundParameters>,
+    model: EventListener<ModelUpdate>,
+}
+
+impl Notifications {
+    /// Creates new push notifications of the state machine of the `event_subscriber`.
+    pub fn new(event_subscriber: &EventSubscriber) -> Self {
+        Self {
+            phase: event_subscriber.phase_listener(),
+            params: event_subscriber.params_listener(),
+            model: event_subscriber.model_listener(),
+        }
+    }
+
+    /// Streams the current state and all following events.
+    fn stream(self) -> impl Stream<Item = Result<sse::Event, warp::Error>> + Send + 'static {
+        let phase = notification_stream(self.phase, |event| {
+            sse::Event::default()
+                .event("phase")
+                .json_data(PhaseNotification {
+                    round_id: event.round_id,
+                    phase: event.event,
+                })
+        });
+        let params = notification_stream(self.params, |event| {
+            sse::Event::default()
+                .event("params")
+                .json_data(ParamsNotification {
+                    round_id: event.round_id,
+                })
+        });
+        let model = notification_stream(self.model, |event| {
+            sse::Event::default()
+                .event("model")
+                .json_data(ModelNotification {
+                    round_id: event.round_id,
+                    available: matches!(event.event, ModelUpdate::New { .. }),
+                })
+        });
+        stream::select(params, stream::select(phase, model))
+    }
+}
+
+/// Streams the latest and all following events of the `listener` as server-sent events.
+fn notification_stream<E, F>(
+    listener: EventListener<E>,
+    into_sse: F,
+) -> impl Stream<Item = Result<sse::Event, warp::Error>> + Send + 'static
+where
+    E: Clone + Send + Sync + 'static,
+    F: Fn(Event<E>) -> Result<sse::Event, warp::Error> + Send + 'static,
+{
+    let latest = listener.get_latest();
+    let next = stream::unfold(listener, |mut listener| async move {
+        listener.next().await.map(|event| (event, listener))
+    });
+    stream::once(future::ready(latest))
+        .chain(next)
+        .map(into_sse)
+}
+
 /// Starts a HTTP server at the given address, listening to GET requests for
 /// data and POST requests containing PET messages.
 ///
@@ -34,16 +427,24 @@ struct SeedDictQuery {
 ///   authentication as well as trusted anchors for TLS client authentication.
 /// * `fetcher`: fetcher for responding to data requests.
 /// * `pet_message_handler`: handler for responding to PET messages.
+/// * `health_check`: health check for responding to liveness and readiness probes.
+/// * `notifications`: push notifications for participants subscribed to the events.
+/// * `model_history`: global model history for responding to requests for older global models
+///   and their proofs.
 ///
 /// # Errors
 /// Fails if the TLS settings are invalid.
-pub async fn serve<F>(
+pub async fn serve<F, S>(
     api_settings: ApiSettings,
     fetcher: F,
     pet_message_handler: PetMessageHandler,
+    health_check: HealthCheck<S>,
+    notifications: Notifications,
+    model_history: ModelHistory<S>,
 ) -> Result<(), RestError>
 where
     F: Fetcher + Sync + Send + 'static + Clone,
+    S: Storage,
 {
     let message = warp::path!("message")
         .and(warp::post())
@@ -53,6 +454,7 @@ where
 
     let sum_dict = warp::path!("sums")
         .and(warp::get())
+        .and(if_none_match())
         .and(with_fetcher(fetcher.clone()))
         .and_then(handle_sums);
 
@@ -60,24 +462,78 @@ where
         .and(warp::get())
         .and(warp::query::<SeedDictQuery>())
         .and_then(part_pk)
+        .and(if_none_match())
         .and(with_fetcher(fetcher.clone()))
         .and_then(handle_seeds);
 
     let round_params = warp::path!("params")
         .and(warp::get())
+        .and(if_none_match())
         .and(with_fetcher(fetcher.clone()))
         .and_then(handle_params);
 
     let model = warp::path!("model")
         .and(warp::get())
+        .and(model_encoding())
+        .and(if_none_match())
         .and(with_fetcher(fetcher.clone()))
         .and_then(handle_model);
 
+    let model_infos = warp::path!("models")
+        .and(warp::get())
+        .and(warp::query::<ModelHistoryQuery>())
+        .and(with_model_history(model_history.clone()))
+        .and_then(handle_model_infos);
+
+    let model_by_round = warp::path!("models" / u64)
+        .and(warp::get())
+        .and(if_none_match())
+        .and(with_model_history(model_history.clone()))
+        .and_then(handle_model_by_round);
+
+    let proofs = warp::path!("proofs")
+        .and(warp::get())
+        .and(with_model_history(model_history.clone()))
+        .and_then(handle_proofs);
+
+    let proof_key = warp::path!("proofs" / "key")
+        .and(warp::get())
+        .and(with_model_history(model_history.clone()))
+        .and_then(handle_proof_key);
+
+    let proof_by_round = warp::path!("proofs" / u64)
+        .and(warp::get())
+        .and(with_model_history(model_history))
+        .and_then(handle_proof_by_round);
+
+    let health = warp::path!("health")
+        .and(warp::get())
+        .and(with_health_check(health_check.clone()))
+        .and_then(handle_health);
+
+    let ready = warp::path!("ready")
+        .and(warp::get())
+        .and(with_health_check(health_check))
+        .and_then(handle_ready);
+
+    let events = warp::path!("events")
+        .and(warp::get())
+        .and(with_notifications(notifications))
+        .map(handle_events);
+
     let routes = message
         .or(round_params)
         .or(sum_dict)
         .or(seed_dict)
         .or(model)
+        .or(model_infos)
+        .or(model_by_round)
+        .or(proofs)
+        .or(proof_key)
+        .or(proof_by_round)
+        .or(health)
+        .or(ready)
+        .or(events)
         .recover(handle_reject)
         .with(warp::log("http"));
 
@@ -94,103 +550,310 @@ async fn handle_message(
     body: Bytes,
     mut handler: PetMessageHandler,
 ) -> Result<impl warp::Reply, Infallible> {
-    let _ = handler.handle_message(body.to_vec()).await.map_err(|e| {
-        warn!("failed to handle message: {:?}", e);
-    });
-    Ok(warp::reply())
+    Ok(match handler.handle_message(body.to_vec()).await {
+        Ok(()) => warp::reply().into_response(),
+        Err(e) => {
+            warn!("failed to handle message: {:?}", e);
+            let (status, code) = MessageError::status_and_code(&e);
+            let error = MessageError {
+                code,
+                message: e.to_string(),
+            };
+            warp::reply::with_status(warp::reply::json(&error), status).into_response()
+        }
+    })
+}
+
+/// Handles and responds to a subscription to the push notifications.
+fn handle_events(notifications: Notifications) -> impl warp::Reply {
+    sse::reply(sse::keep_alive().stream(notifications.stream()))
 }
 
 /// Handles and responds to a request for the sum dictionary.
-async fn handle_sums<F: Fetcher>(mut fetcher: F) -> Result<impl warp::Reply, Infallible> {
+async fn handle_sums<F: Fetcher>(
+    if_none_match: Option<String>,
+    mut fetcher: F,
+) -> Result<impl warp::Reply, Infallible> {
     Ok(match fetcher.sum_dict().await {
         Err(e) => {
             warn!("failed to handle sum dict request: {:?}", e);
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Vec::new())
-                .unwrap()
-        }
-        Ok(None) => Response::builder()
-            .status(StatusCode::NO_CONTENT)
-            .body(Vec::new())
-            .unwrap(),
-        Ok(Some(dict)) => {
-            let bytes = bincode::serialize(dict.as_ref()).unwrap();
-            Response::builder()
-                .header("Content-Type", "application/octet-stream")
-                .status(StatusCode::OK)
-                .body(bytes)
-                .unwrap()
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
         }
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Ok(Some(cached)) => reply_cached(cached, if_none_match),
     })
 }
 
 /// Handles and responds to a request for the seed dictionary.
 async fn handle_seeds<F: Fetcher>(
     pk: ParticipantPublicKey,
+    if_none_match: Option<String>,
     mut fetcher: F,
 ) -> Result<impl warp::Reply, Infallible> {
-    Ok(match fetcher.seed_dict().await {
+    Ok(match fetcher.seed_dict(pk).await {
         Err(e) => {
             warn!("failed to handle seed dict request: {:?}", e);
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Vec::new())
-                .unwrap()
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
         }
-        Ok(Some(dict)) if dict.get(&pk).is_some() => {
-            let bytes = bincode::serialize(dict.as_ref().get(&pk).unwrap()).unwrap();
-            Response::builder()
-                .header("Content-Type", "application/octet-stream")
-                .status(StatusCode::OK)
-                .body(bytes)
-                .unwrap()
-        }
-        _ => Response::builder()
-            .status(StatusCode::NO_CONTENT)
-            .body(Vec::new())
-            .unwrap(),
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Ok(Some(cached)) => reply_cached(cached, if_none_match),
     })
 }
 
-/// Handles and responds to a request for the global model.
-async fn handle_model<F: Fetcher>(mut fetcher: F) -> Result<impl warp::Reply, Infallible> {
-    Ok(match fetcher.model().await {
-        Ok(Some(model)) => Response::builder()
-            .status(StatusCode::OK)
-            .body(bincode::serialize(model.as_ref()).unwrap())
-            .unwrap(),
-        Ok(None) => Response::builder()
-            .status(StatusCode::NO_CONTENT)
-            .body(Vec::new())
-            .unwrap(),
-        Err(e) => {
-            warn!("failed to handle model request: {:?}", e);
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Vec::new())
-                .unwrap()
+/// Handles and responds to a request for the global model in the given encoding.
+async fn handle_model<F: Fetcher>(
+    encoding: ModelEncoding,
+    if_none_match: Option<String>,
+    mut fetcher: F,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(match fetcher.model(encoding).await {
+        Ok(Some(EncodedModel { encoding, cached })) => {
+            let mut response = reply_cached(cached, if_none_match);
+            let headers = response.headers_mut();
+            headers.insert(
+                header::VARY,
+                HeaderValue::from_static("accept, accept-encoding"),
+            );
+            if let ModelEncoding::Primitive {
+                data_type: Some(data_type),
+                compressed,
+            } = encoding
+            {
+                let content_type =
+                    format!("{}; data-type={}", PRIMITIVE_MODEL_MEDIA_TYPE, data_type);
+                // safe unwrap: the content type is a valid header value
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_str(&content_type).unwrap(),
+                );
+                if compressed {
+                    headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+                }
+            }
+            response
         }
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Err(e) => match e.downcast_ref::<ModelEncodingError>() {
+            Some(ModelEncodingError::Cast(e)) => {
+                debug!("failed to handle model request: {}", e);
+                reply_status(StatusCode::NOT_ACCEPTABLE)
+            }
+            _ => {
+                warn!("failed to handle model request: {:?}", e);
+                reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        },
     })
 }
 
 /// Handles and responds to a request for the round parameters.
-async fn handle_params<F: Fetcher>(mut fetcher: F) -> Result<impl warp::Reply, Infallible> {
+async fn handle_params<F: Fetcher>(
+    if_none_match: Option<String>,
+    mut fetcher: F,
+) -> Result<impl warp::Reply, Infallible> {
     Ok(match fetcher.round_params().await {
-        Ok(params) => Response::builder()
-            .status(StatusCode::OK)
-            .body(bincode::serialize(&params).unwrap())
-            .unwrap(),
+        Ok(cached) => reply_cached(cached, if_none_match),
         Err(e) => {
             warn!("failed to handle round parameters request: {:?}", e);
-            Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Vec::new())
-                .unwrap()
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+/// Handles and responds to a request for a page of the global model history.
+async fn handle_model_infos<S: Storage>(
+    query: ModelHistoryQuery,
+    model_history: ModelHistory<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    let limit = query
+        .limit
+        .unwrap_or(MAX_MODEL_HISTORY_PAGE)
+        .min(MAX_MODEL_HISTORY_PAGE);
+    Ok(match model_history.infos(query.from, limit).await {
+        Ok(infos) => reply_bincode(&infos),
+        Err(e) => {
+            warn!("failed to handle global model history request: {:?}", e);
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+/// Handles and responds to a request for the global model of the given round.
+///
+/// The global model is only loaded from the model storage if the `If-None-Match` header of the
+/// request doesn't match its id already.
+async fn handle_model_by_round<S: Storage>(
+    round_id: u64,
+    if_none_match: Option<String>,
+    model_history: ModelHistory<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    let info = match model_history.info(round_id).await {
+        Ok(Some(info)) => info,
+        Ok(None) => return Ok(reply_status(StatusCode::NO_CONTENT)),
+        Err(e) => {
+            warn!("failed to handle global model request: {:?}", e);
+            return Ok(reply_status(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+    let etag = format!("\"{}\"", info.model_id);
+    if let Some(ref tags) = if_none_match {
+        if etag_matches(tags, &etag) {
+            let unmodified = Cached {
+                etag: info.model_id,
+                body: Bytes::new(),
+            };
+            return Ok(reply_cached(unmodified, if_none_match));
+        }
+    }
+    Ok(match model_history.model(&info.model_id).await {
+        Ok(Some(model)) => {
+            // safe unwrap: the model is serializable
+            let body = Bytes::from(bincode::serialize(&model).unwrap());
+            let cached = Cached {
+                etag: info.model_id,
+                body,
+            };
+            reply_cached(cached, None)
+        }
+        // the global model has been removed from the model storage in the meantime
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("failed to handle global model request: {:?}", e);
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
         }
     })
 }
 
+/// Handles and responds to a request for the published proofs of the global models.
+async fn handle_proofs<S: Storage>(
+    model_history: ModelHistory<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(match model_history.proofs().await {
+        Ok(proofs) => reply_bincode(&proofs),
+        Err(e) => {
+            warn!("failed to handle proofs request: {:?}", e);
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+/// Handles and responds to a request for the published proof of the global model of the given
+/// round.
+async fn handle_proof_by_round<S: Storage>(
+    round_id: u64,
+    model_history: ModelHistory<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(match model_history.proof(round_id).await {
+        Ok(Some(proof)) => reply_bincode(&proof),
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("failed to handle proof request: {:?}", e);
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+/// Handles and responds to a request for the public key to verify the published proofs with.
+async fn handle_proof_key<S: Storage>(
+    model_history: ModelHistory<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(match model_history.proof_key().await {
+        Ok(Some(key)) => reply_bincode(&key),
+        Ok(None) => reply_status(StatusCode::NO_CONTENT),
+        Err(e) => {
+            warn!("failed to handle proof key request: {:?}", e);
+            reply_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })
+}
+
+/// Replies with the data serialized with `bincode`.
+fn reply_bincode<T: Serialize>(data: &T) -> Response<Bytes> {
+    // safe unwraps: the data is serializable and the response is valid
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .status(StatusCode::OK)
+        .body(Bytes::from(bincode::serialize(data).unwrap()))
+        .unwrap()
+}
+
+/// Replies with an empty body.
+fn reply_status(status: StatusCode) -> Response<Bytes> {
+    // safe unwrap: the response is valid
+    Response::builder()
+        .status(status)
+        .body(Bytes::new())
+        .unwrap()
+}
+
+/// Replies with the cached body, or with `NOT_MODIFIED` if the `If-None-Match` header of the
+/// request matches its entity tag.
+fn reply_cached(cached: Cached, if_none_match: Option<String>) -> Response<Bytes> {
+    let etag = format!("\"{}\"", cached.etag);
+    let response = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "no-cache");
+    // safe unwraps: the responses are valid
+    if if_none_match.map_or(false, |tags| etag_matches(&tags, &etag)) {
+        response
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Bytes::new())
+            .unwrap()
+    } else {
+        response
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .status(StatusCode::OK)
+            .body(cached.body)
+            .unwrap()
+    }
+}
+
+/// Checks whether the `If-None-Match` header value matches the quoted entity tag.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|tag| {
+        // the weak comparison is sufficient for `If-None-Match`
+        tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+    })
+}
+
+/// Handles and responds to a liveness probe.
+///
+/// The coordinator is alive unless the state machine has shut down.
+async fn handle_health<S: Storage>(
+    health_check: HealthCheck<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    let phase = health_check.phase();
+    let code = if phase == PhaseName::Shutdown {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&Health { phase }),
+        code,
+    ))
+}
+
+/// Handles and responds to a readiness probe.
+///
+/// The coordinator is ready if the state machine runs without errors and all storage backends
+/// are ready.
+async fn handle_ready<S: Storage>(
+    health_check: HealthCheck<S>,
+) -> Result<impl warp::Reply, Infallible> {
+    let readiness = health_check.readiness().await;
+    let code = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        warn!("coordinator not ready: {:?}", readiness);
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&readiness),
+        code,
+    ))
+}
+
 /// Converts a PET message handler into a `warp` filter.
 fn with_message_handler(
     handler: PetMessageHandler,
@@ -198,6 +861,75 @@ fn with_message_handler(
     warp::any().map(move || handler.clone())
 }
 
+/// Extracts the `If-None-Match` header of a conditional request.
+fn if_none_match() -> impl Filter<Extract = (Option<String>,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(header::IF_NONE_MATCH.as_str())
+}
+
+/// Extracts the requested encoding of the global model from the `Accept` and `Accept-Encoding`
+/// headers.
+fn model_encoding() -> impl Filter<Extract = (ModelEncoding,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>(header::ACCEPT.as_str())
+        .and(warp::header::optional::<String>(
+            header::ACCEPT_ENCODING.as_str(),
+        ))
+        .map(|accept: Option<String>, accept_encoding: Option<String>| {
+            parse_model_encoding(accept.as_deref(), accept_encoding.as_deref())
+        })
+}
+
+/// Parses the requested encoding of the global model.
+///
+/// The primitive encoding is only used if it is acceptable and its data type is valid, otherwise
+/// the model is serialized with `bincode`.
+fn parse_model_encoding(accept: Option<&str>, accept_encoding: Option<&str>) -> ModelEncoding {
+    let data_type = accept
+        .and_then(|accept| find_acceptable(accept, PRIMITIVE_MODEL_MEDIA_TYPE))
+        .and_then(
+            |params| match params.iter().find(|(name, _)| *name == "data-type") {
+                Some((_, data_type)) => data_type.parse::<DataType>().ok().map(Some),
+                None => Some(None),
+            },
+        );
+    match data_type {
+        Some(data_type) => ModelEncoding::Primitive {
+            data_type,
+            compressed: accept_encoding
+                .and_then(|accept_encoding| find_acceptable(accept_encoding, "zstd"))
+                .is_some(),
+        },
+        None => ModelEncoding::Bincode,
+    }
+}
+
+/// Finds the `value` in an `Accept` or `Accept-Encoding` header and returns its parameters.
+///
+/// Returns `None` if the value is missing or has the weight `q=0`, i.e. it is not acceptable.
+fn find_acceptable<'a>(header: &'a str, value: &str) -> Option<Vec<(&'a str, &'a str)>> {
+    header.split(',').find_map(|element| {
+        let mut parts = element.split(';').map(str::trim);
+        // safe unwrap: `split` yields at least one part
+        if !parts.next().unwrap().eq_ignore_ascii_case(value) {
+            return None;
+        }
+        let params = parts
+            .map(|param| {
+                let mut param = param.splitn(2, '=').map(str::trim);
+                // safe unwrap: `splitn` yields at least one part
+                (param.next().unwrap(), param.next().unwrap_or(""))
+            })
+            .collect::<Vec<_>>();
+        let rejected = params
+            .iter()
+            .any(|(name, weight)| *name == "q" && weight.parse::<f32>().map_or(false, |q| q <= 0.));
+        if rejected {
+            None
+        } else {
+            Some(params)
+        }
+    })
+}
+
 /// Converts a data fetcher into a `warp` filter.
 fn with_fetcher<F: Fetcher + Sync + Send + 'static + Clone>(
     fetcher: F,
@@ -205,6 +937,27 @@ fn with_fetcher<F: Fetcher + Sync + Send + 'static + Clone>(
     warp::any().map(move || fetcher.clone())
 }
 
+/// Converts push notifications into a `warp` filter.
+fn with_notifications(
+    notifications: Notifications,
+) -> impl Filter<Extract = (Notifications,), Error = Infallible> + Clone {
+    warp::any().map(move || notifications.clone())
+}
+
+/// Converts a global model history into a `warp` filter.
+fn with_model_history<S: Storage>(
+    model_history: ModelHistory<S>,
+) -> impl Filter<Extract = (ModelHistory<S>,), Error = Infallible> + Clone {
+    warp::any().map(move || model_history.clone())
+}
+
+/// Converts a health check into a `warp` filter.
+fn with_health_check<S: Storage>(
+    health_check: HealthCheck<S>,
+) -> impl Filter<Extract = (HealthCheck<S>,), Error = Infallible> + Clone {
+    warp::any().map(move || health_check.clone())
+}
+
 /// Extracts a participant public key from the url query string
 async fn part_pk(query: SeedDictQuery) -> Result<ParticipantPublicKey, warp::Rejection> {
     match base64::decode(query.pk.as_bytes()) {
@@ -313,3 +1066,411 @@ where
     .await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        services::{fetchers, tests::utils::new_event_channels},
+        settings::{MultipartSettings, ProtocolSettings},
+        state_machine::requests::RequestReceiver,
+        storage::{
+            coordinator_storage::in_memory::InMemory,
+            model_storage::{filesystem, noop::NoOp},
+            tests::utils::create_global_model,
+            trust_anchor::proof_log,
+            Retention,
+            Store,
+        },
+    };
+    use xaynet_core::{common::RoundSeed, mask::FromPrimitives};
+
+    #[test]
+    fn test_message_error_status_and_code() {
+        assert_eq!(
+            MessageError::status_and_code(&ServiceError::NotSumEligible),
+            (StatusCode::FORBIDDEN, "not_sum_eligible"),
+        );
+        assert_eq!(
+            MessageError::status_and_code(&ServiceError::StateMachine(
+                RequestError::MessageDiscarded
+            )),
+            (StatusCode::CONFLICT, "message_discarded"),
+        );
+        assert_eq!(
+            MessageError::status_and_code(&ServiceError::InternalError("oops".into())),
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        );
+        assert_eq!(
+            MessageError::status_and_code(&ServiceError::MessageTooLarge(1)),
+            (StatusCode::PAYLOAD_TOO_LARGE, "message_too_large"),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_message_error() {
+        let (_publisher, subscriber) = new_event_channels();
+        let (_request_rx, request_tx) = RequestReceiver::new();
+        let handler = PetMessageHandler::new(
+            &subscriber,
+            request_tx,
+            ProtocolSettings::default(),
+            MultipartSettings::default(),
+        );
+
+        let response = handle_message(Bytes::from_static(b"garbage"), handler)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            r#"{"code":"decrypt","message":"Failed to decrypt the message with the coordinator secret key"}"#,
+        );
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"1_ab\"", "\"1_ab\""));
+        assert!(etag_matches("\"0_cd\", W/\"1_ab\"", "\"1_ab\""));
+        assert!(etag_matches("*", "\"1_ab\""));
+        assert!(!etag_matches("\"0_cd\"", "\"1_ab\""));
+        assert!(!etag_matches("1_ab", "\"1_ab\""));
+    }
+
+    #[test]
+    fn test_parse_model_encoding() {
+        assert_eq!(
+            parse_model_encoding(None, Some("zstd")),
+            ModelEncoding::Bincode,
+        );
+        assert_eq!(
+            parse_model_encoding(Some("application/octet-stream"), None),
+            ModelEncoding::Bincode,
+        );
+        assert_eq!(
+            parse_model_encoding(Some("application/x-xaynet-primitive-model"), None),
+            ModelEncoding::Primitive {
+                data_type: None,
+                compressed: false,
+            },
+        );
+        assert_eq!(
+            parse_model_encoding(
+                Some("*/*;q=0.5, application/x-xaynet-primitive-model;data-type=f32"),
+                Some("gzip, zstd"),
+            ),
+            ModelEncoding::Primitive {
+                data_type: Some(DataType::F32),
+                compressed: true,
+            },
+        );
+        assert_eq!(
+            parse_model_encoding(
+                Some("application/x-xaynet-primitive-model; data-type=i64"),
+                Some("zstd;q=0"),
+            ),
+            ModelEncoding::Primitive {
+                data_type: Some(DataType::I64),
+                compressed: false,
+            },
+        );
+        assert_eq!(
+            parse_model_encoding(
+                Some("application/x-xaynet-primitive-model; data-type=u8"),
+                None,
+            ),
+            ModelEncoding::Bincode,
+        );
+        assert_eq!(
+            parse_model_encoding(Some("application/x-xaynet-primitive-model; q=0"), None),
+            ModelEncoding::Bincode,
+        );
+    }
+
+    #[test]
+    fn test_reply_cached() {
+        let cached = Cached {
+            etag: "1_ab".into(),
+            body: Bytes::from_static(b"body"),
+        };
+
+        let response = reply_cached(cached.clone(), None);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], "\"1_ab\"");
+        assert_eq!(response.body(), &cached.body);
+
+        let response = reply_cached(cached.clone(), Some("\"0_cd\"".into()));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), &cached.body);
+
+        let response = reply_cached(cached, Some("\"1_ab\"".into()));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], "\"1_ab\"");
+        assert!(response.body().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_model_not_acceptable() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let model = Model::from_primitives(vec![1_f64, f64::MAX].into_iter()).unwrap();
+        publisher.broadcast_model(ModelUpdate::New {
+            id: "1_model".into(),
+            model: Arc::new(model),
+        });
+        let fetcher = fetchers::fetcher(&subscriber);
+        let encoding = |data_type| ModelEncoding::Primitive {
+            data_type: Some(data_type),
+            compressed: false,
+        };
+
+        let response = handle_model(encoding(DataType::F64), None, fetcher.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the weights exceed the range of the data type
+        let response = handle_model(encoding(DataType::F32), None, fetcher)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[tokio::test]
+    async fn test_model_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let model_store = filesystem::Client::new(dir.path(), Retention::default())
+            .await
+            .unwrap();
+        let mut store = Store::new(InMemory::new(), model_store);
+        let model_history = ModelHistory::new(store.clone());
+        let query = |from, limit| ModelHistoryQuery { from, limit };
+
+        let response = handle_model_infos(query(0, None), model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let infos: Vec<GlobalModelInfo> = bincode::deserialize(&body).unwrap();
+        assert!(infos.is_empty());
+
+        let model = create_global_model(10);
+        let round_seed = RoundSeed::generate();
+        let model_id = store
+            .set_global_model(1, &round_seed, &model)
+            .await
+            .unwrap();
+        let info = GlobalModelInfo {
+            round_id: 1,
+            round_seed,
+            model_id: model_id.clone(),
+            model_length: model.len(),
+            completed_at: 1_600_000_000,
+        };
+        store.add_global_model_info(&info).await.unwrap();
+
+        let response = handle_model_infos(query(0, Some(1)), model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let infos: Vec<GlobalModelInfo> = bincode::deserialize(&body).unwrap();
+        assert_eq!(infos, vec![info]);
+
+        // the page after the latest round is empty
+        let response = handle_model_infos(query(2, None), model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let infos: Vec<GlobalModelInfo> = bincode::deserialize(&body).unwrap();
+        assert!(infos.is_empty());
+
+        let response = handle_model_by_round(1, None, model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ETAG],
+            format!("\"{}\"", model_id)
+        );
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert_eq!(bincode::deserialize::<Model>(&body).unwrap(), model);
+
+        let etag = Some(format!("\"{}\"", model_id));
+        let response = handle_model_by_round(1, etag, model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = handle_model_by_round(2, None, model_history)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_proofs() {
+        let dir = tempfile::tempdir().unwrap();
+        let trust_anchor = proof_log::Client::new(dir.path()).await.unwrap();
+        let mut store = Store::new_with_trust_anchor(InMemory::new(), NoOp, trust_anchor.clone());
+        let model_history = ModelHistory::new(store.clone());
+
+        let response = handle_proof_by_round(1, model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let model = create_global_model(10);
+        store
+            .publish_proof(1, &RoundSeed::generate(), &model)
+            .await
+            .unwrap();
+
+        let response = handle_proofs(model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let proofs: Vec<ModelProof> = bincode::deserialize(&body).unwrap();
+        assert_eq!(proofs.len(), 1);
+
+        let response = handle_proof_by_round(1, model_history.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let proof: ModelProof = bincode::deserialize(&body).unwrap();
+        assert_eq!(proof, proofs[0]);
+        assert!(proof.verify_model(&model));
+
+        let response = handle_proof_key(model_history)
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let key: PublicSigningKey = bincode::deserialize(&body).unwrap();
+        assert_eq!(key, trust_anchor.public_key().await);
+        assert!(proof.verify_signature(&key));
+    }
+
+    #[tokio::test]
+    async fn test_notifications() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let params = subscriber.params_listener().get_latest().event;
+        let mut stream = Box::pin(Notifications::new(&subscriber).stream());
+
+        // the current state is sent first
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            events.push(stream.next().await.unwrap().unwrap().to_string());
+        }
+        events.sort();
+        assert_eq!(
+            events,
+            vec![
+                "event:model\ndata:{\"round_id\":0,\"available\":false}\n\n",
+                "event:params\ndata:{\"round_id\":0}\n\n",
+                "event:phase\ndata:{\"round_id\":0,\"phase\":\"idle\"}\n\n",
+            ],
+        );
+
+        publisher.set_round_id(1);
+        publisher.broadcast_params(params);
+        let event = stream.next().await.unwrap().unwrap().to_string();
+        assert_eq!(event, "event:params\ndata:{\"round_id\":1}\n\n");
+
+        publisher.broadcast_phase(PhaseName::Sum);
+        let event = stream.next().await.unwrap().unwrap().to_string();
+        assert_eq!(
+            event,
+            "event:phase\ndata:{\"round_id\":1,\"phase\":\"sum\"}\n\n",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let health_check = HealthCheck::new(Store::new(InMemory::new(), NoOp), &subscriber);
+
+        publisher.broadcast_phase(PhaseName::Error);
+        let response = handle_health(health_check.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        publisher.broadcast_phase(PhaseName::Shutdown);
+        let response = handle_health(health_check).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_ready() {
+        let (mut publisher, subscriber) = new_event_channels();
+        let health_check = HealthCheck::new(Store::new(InMemory::new(), NoOp), &subscriber);
+
+        publisher.broadcast_phase(PhaseName::Sum);
+        let response = handle_ready(health_check.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        publisher.broadcast_phase(PhaseName::Error);
+        let response = handle_ready(health_check).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_ready_backend_unavailable() {
+        let (_publisher, subscriber) = new_event_channels();
+        let dir = tempfile::tempdir().unwrap();
+        let model_store = filesystem::Client::new(dir.path(), Retention::default())
+            .await
+            .unwrap();
+        let health_check = HealthCheck::new(Store::new(InMemory::new(), model_store), &subscriber);
+        dir.close().unwrap();
+
+        let readiness = health_check.readiness().await;
+        assert!(readiness.coordinator_storage.ready);
+        assert!(!readiness.model_storage.ready);
+        assert!(readiness.model_storage.error.is_some());
+        assert!(!readiness.is_ready());
+
+        let response = handle_ready(health_check).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            r#"{"phase":"idle","coordinator_storage":{"ready":true},"model_storage":{"ready":false},"trust_anchor":{"ready":true}}"#,
+        );
+    }
+}
@@ -2,6 +2,9 @@
 //!
 //! There are multiple such services and the [`Fetcher`] trait
 //! provides a single unifying interface for all of these.
+//!
+//! The services serialize the data only once per change and serve the [`Cached`] body along
+//! with an entity tag which identifies the data.
 
 mod model;
 mod round_parameters;
@@ -11,16 +14,62 @@ mod sum_dict;
 use std::task::{Context, Poll};
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use futures::future::poll_fn;
+use serde::Serialize;
 use tower::{layer::Layer, Service, ServiceBuilder};
 
 pub use self::{
-    model::{ModelRequest, ModelResponse, ModelService},
+    model::{
+        EncodedModel,
+        ModelEncoding,
+        ModelEncodingError,
+        ModelRequest,
+        ModelResponse,
+        ModelService,
+    },
     round_parameters::{RoundParamsRequest, RoundParamsResponse, RoundParamsService},
     seed_dict::{SeedDictRequest, SeedDictResponse, SeedDictService},
     sum_dict::{SumDictRequest, SumDictResponse, SumDictService},
 };
 use crate::state_machine::events::EventSubscriber;
+use xaynet_core::{common::RoundSeed, crypto::ByteObject, SumParticipantPublicKey};
+
+/// A serialized response body along with the entity tag of the data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cached {
+    /// The entity tag which identifies the data.
+    pub etag: String,
+    /// The serialized data, e.g. with `bincode`.
+    pub body: Bytes,
+}
+
+impl Cached {
+    /// Gets the `cache`d body if it has the `etag`, otherwise serializes the `data` and caches
+    /// the new body.
+    fn get_or_serialize<T>(cache: &mut Option<Cached>, etag: String, data: &T) -> Cached
+    where
+        T: Serialize + ?Sized,
+    {
+        match cache {
+            Some(cached) if cached.etag == etag => cached.clone(),
+            _ => {
+                // safe unwrap: the data of the fetchers is serializable
+                let body = Bytes::from(bincode::serialize(data).unwrap());
+                let cached = Cached { etag, body };
+                *cache = Some(cached.clone());
+                cached
+            }
+        }
+    }
+}
+
+/// Creates the entity tag of data which is published once per round.
+///
+/// The round id alone is not unique, because it is reset with the coordinator state.
+fn round_etag(round_id: u64, round_seed: &RoundSeed) -> String {
+    format!("{}_{}", round_id, hex::encode(round_seed.as_slice()))
+}
 
 /// A single interface for retrieving data from the coordinator.
 #[async_trait]
@@ -28,12 +77,16 @@ pub trait Fetcher {
     /// Fetch the parameters for the current round
     async fn round_params(&mut self) -> Result<RoundParamsResponse, FetchError>;
 
-    /// Fetch the latest global model.
-    async fn model(&mut self) -> Result<ModelResponse, FetchError>;
+    /// Fetch the latest global model in the given encoding.
+    async fn model(&mut self, encoding: ModelEncoding) -> Result<ModelResponse, FetchError>;
 
-    /// Fetch the global seed dictionary. Each sum2 participant needs a
-    /// different portion of that dictionary.
-    async fn seed_dict(&mut self) -> Result<SeedDictResponse, FetchError>;
+    /// Fetch the portion of the global seed dictionary for the given sum
+    /// participant. Each sum2 participant needs a different portion of
+    /// that dictionary.
+    async fn seed_dict(
+        &mut self,
+        pk: SumParticipantPublicKey,
+    ) -> Result<SeedDictResponse, FetchError>;
 
     /// Fetch the sum dictionary. The update participants need this
     /// dictionary to encrypt their masking seed for each sum
@@ -90,26 +143,35 @@ where
         .map_err(into_fetch_error)?)
     }
 
-    async fn model(&mut self) -> Result<ModelResponse, FetchError> {
+    async fn model(&mut self, encoding: ModelEncoding) -> Result<ModelResponse, FetchError> {
         poll_fn(|cx| <Model as Service<ModelRequest>>::poll_ready(&mut self.model, cx))
             .await
             .map_err(into_fetch_error)?;
-        Ok(
-            <Model as Service<ModelRequest>>::call(&mut self.model, ModelRequest)
-                .await
-                .map_err(into_fetch_error)?,
-        )
+        <Model as Service<ModelRequest>>::call(&mut self.model, ModelRequest { encoding })
+            .await
+            .map_err(|e| {
+                let e: Box<dyn std::error::Error + 'static + Sync + Send> = e.into();
+                // keep the encoding error, so that it can be told apart from a failed service
+                match e.downcast::<ModelEncodingError>() {
+                    Ok(e) => FetchError::from(*e),
+                    Err(e) => into_fetch_error(e),
+                }
+            })
     }
 
-    async fn seed_dict(&mut self) -> Result<SeedDictResponse, FetchError> {
+    async fn seed_dict(
+        &mut self,
+        pk: SumParticipantPublicKey,
+    ) -> Result<SeedDictResponse, FetchError> {
         poll_fn(|cx| <SeedDict as Service<SeedDictRequest>>::poll_ready(&mut self.seed_dict, cx))
             .await
             .map_err(into_fetch_error)?;
-        Ok(
-            <SeedDict as Service<SeedDictRequest>>::call(&mut self.seed_dict, SeedDictRequest)
-                .await
-                .map_err(into_fetch_error)?,
+        Ok(<SeedDict as Service<SeedDictRequest>>::call(
+            &mut self.seed_dict,
+            SeedDictRequest { pk },
         )
+        .await
+        .map_err(into_fetch_error)?)
     }
 
     async fn sum_dict(&mut self) -> Result<SumDictResponse, FetchError> {
@@ -1,48 +1,174 @@
 use std::{
-    sync::Arc,
+    collections::HashMap,
     task::{Context, Poll},
 };
 
+use bytes::Bytes;
 use futures::future::{self, Ready};
+use thiserror::Error;
 use tower::Service;
 use tracing::error_span;
 use tracing_futures::{Instrument, Instrumented};
 
-use crate::state_machine::events::{EventListener, EventSubscriber, ModelUpdate};
-use xaynet_core::mask::Model;
+use crate::{
+    services::fetchers::Cached,
+    state_machine::events::{EventListener, EventSubscriber, ModelUpdate},
+};
+use xaynet_core::{
+    common::RoundParameters,
+    mask::{DataType, Model, ModelCastError, PrimitiveModel},
+};
+
+/// The encoding of the global model.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ModelEncoding {
+    /// The model serialized with `bincode`.
+    Bincode,
+    /// The weights of the model as primitive values in little endian byte order, see
+    /// [`PrimitiveModel`].
+    Primitive {
+        /// The data type of the weights. Defaults to the data type of the current round.
+        data_type: Option<DataType>,
+        /// Whether the weights are compressed with `zstd`.
+        compressed: bool,
+    },
+}
+
+impl Default for ModelEncoding {
+    fn default() -> Self {
+        Self::Bincode
+    }
+}
 
 /// [`ModelService`]'s request type
 #[derive(Default, Clone, Eq, PartialEq, Debug)]
-pub struct ModelRequest;
+pub struct ModelRequest {
+    /// The requested encoding of the global model.
+    pub encoding: ModelEncoding,
+}
+
+/// The global model in the requested encoding.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EncodedModel {
+    /// The encoding of the model. The data type of a primitive encoding is always set.
+    pub encoding: ModelEncoding,
+    /// The encoded model. The entity tag is the global model id, followed by the data type and
+    /// the compression of a primitive encoding, e.g. `<id>-f32-zstd`.
+    pub cached: Cached,
+}
 
 /// [`ModelService`]'s response type.
 ///
 /// The response is `None` when no model is currently available.
-pub type ModelResponse = Option<Arc<Model>>;
+pub type ModelResponse = Option<EncodedModel>;
+
+/// Errors which can occur while encoding the global model.
+#[derive(Debug, Error)]
+pub enum ModelEncodingError {
+    #[error("the model weights can't be converted into the requested data type: {0}")]
+    Cast(#[from] ModelCastError),
+    #[error("the model weights can't be compressed: {0}")]
+    Compression(#[from] std::io::Error),
+}
 
 /// A service that serves the latest available global model
-pub struct ModelService(EventListener<ModelUpdate>);
+pub struct ModelService {
+    model: EventListener<ModelUpdate>,
+    params: EventListener<RoundParameters>,
+    /// The latest encoded model for each encoding.
+    cache: HashMap<ModelEncoding, Cached>,
+}
 
 impl ModelService {
     pub fn new(events: &EventSubscriber) -> Self {
-        Self(events.model_listener())
+        Self {
+            model: events.model_listener(),
+            params: events.params_listener(),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Encodes the `model` with the given `id` or gets it from the cache.
+    fn encode(
+        &mut self,
+        id: &str,
+        model: &Model,
+        encoding: ModelEncoding,
+    ) -> Result<EncodedModel, ModelEncodingError> {
+        match encoding {
+            ModelEncoding::Bincode => {
+                let cached = self.get_or_encode(encoding, id.to_string(), || {
+                    // safe unwrap: the model is serializable
+                    Ok(bincode::serialize(model).unwrap())
+                })?;
+                Ok(EncodedModel { encoding, cached })
+            }
+            ModelEncoding::Primitive {
+                data_type,
+                compressed,
+            } => {
+                let data_type = data_type
+                    .unwrap_or_else(|| self.params.get_latest().event.mask_config.vect.data_type);
+                let encoding = ModelEncoding::Primitive {
+                    data_type: Some(data_type),
+                    compressed,
+                };
+                let etag = if compressed {
+                    format!("{}-{}-zstd", id, data_type)
+                } else {
+                    format!("{}-{}", id, data_type)
+                };
+                let cached = self.get_or_encode(encoding, etag, || {
+                    let bytes = PrimitiveModel::from_model(model, data_type)?.to_bytes();
+                    if compressed {
+                        // level 0 selects the default compression level of zstd
+                        Ok(zstd::encode_all(bytes.as_slice(), 0)?)
+                    } else {
+                        Ok(bytes)
+                    }
+                })?;
+                Ok(EncodedModel { encoding, cached })
+            }
+        }
+    }
+
+    /// Gets the cached model if it has the `etag`, otherwise encodes the model and caches the new
+    /// body.
+    fn get_or_encode<F>(
+        &mut self,
+        encoding: ModelEncoding,
+        etag: String,
+        encode: F,
+    ) -> Result<Cached, ModelEncodingError>
+    where
+        F: FnOnce() -> Result<Vec<u8>, ModelEncodingError>,
+    {
+        match self.cache.get(&encoding) {
+            Some(cached) if cached.etag == etag => Ok(cached.clone()),
+            _ => {
+                let body = Bytes::from(encode()?);
+                let cached = Cached { etag, body };
+                self.cache.insert(encoding, cached.clone());
+                Ok(cached)
+            }
+        }
     }
 }
 
 impl Service<ModelRequest> for ModelService {
     type Response = ModelResponse;
-    type Error = std::convert::Infallible;
+    type Error = ModelEncodingError;
     type Future = Instrumented<Ready<Result<Self::Response, Self::Error>>>;
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, _req: ModelRequest) -> Self::Future {
-        future::ready(match self.0.get_latest().event {
+    fn call(&mut self, req: ModelRequest) -> Self::Future {
+        let response = match self.model.get_latest().event {
             ModelUpdate::Invalidate => Ok(None),
-            ModelUpdate::New(model) => Ok(Some(model)),
-        })
-        .instrument(error_span!("model_fetch_request"))
+            ModelUpdate::New { id, model } => self.encode(&id, &model, req.encoding).map(Some),
+        };
+        future::ready(response).instrument(error_span!("model_fetch_request"))
     }
 }
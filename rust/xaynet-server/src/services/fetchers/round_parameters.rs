@@ -5,27 +5,38 @@ use tower::Service;
 use tracing::error_span;
 use tracing_futures::{Instrument, Instrumented};
 
-use crate::state_machine::events::{EventListener, EventSubscriber};
+use crate::{
+    services::fetchers::{round_etag, Cached},
+    state_machine::events::{EventListener, EventSubscriber},
+};
 use xaynet_core::common::RoundParameters;
 
 /// [`RoundParamsService`]'s request type
 #[derive(Default, Clone, Eq, PartialEq, Debug)]
 pub struct RoundParamsRequest;
 
-/// [`RoundParamsService`]'s response type
-pub type RoundParamsResponse = RoundParameters;
+/// [`RoundParamsService`]'s response type.
+///
+/// The entity tag is derived from the round id and the round seed.
+pub type RoundParamsResponse = Cached;
 
 /// A service that serves the round parameters for the current round.
-pub struct RoundParamsService(EventListener<RoundParameters>);
+pub struct RoundParamsService {
+    params: EventListener<RoundParameters>,
+    cache: Option<Cached>,
+}
 
 impl RoundParamsService {
     pub fn new(events: &EventSubscriber) -> Self {
-        Self(events.params_listener())
+        Self {
+            params: events.params_listener(),
+            cache: None,
+        }
     }
 }
 
 impl Service<RoundParamsRequest> for RoundParamsService {
-    type Response = RoundParameters;
+    type Response = RoundParamsResponse;
     type Error = std::convert::Infallible;
     type Future = Instrumented<Ready<Result<Self::Response, Self::Error>>>;
 
@@ -34,7 +45,13 @@ impl Service<RoundParamsRequest> for RoundParamsService {
     }
 
     fn call(&mut self, _req: RoundParamsRequest) -> Self::Future {
-        future::ready(Ok(self.0.get_latest().event))
-            .instrument(error_span!("round_params_fetch_request"))
+        let params = self.params.get_latest();
+        let etag = round_etag(params.round_id, &params.event.seed);
+        future::ready(Ok(Cached::get_or_serialize(
+            &mut self.cache,
+            etag,
+            &params.event,
+        )))
+        .instrument(error_span!("round_params_fetch_request"))
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    sync::Arc,
+    collections::HashMap,
     task::{Context, Poll},
 };
 
@@ -8,27 +8,44 @@ use tower::Service;
 use tracing::error_span;
 use tracing_futures::{Instrument, Instrumented};
 
-use crate::state_machine::events::{DictionaryUpdate, EventListener, EventSubscriber};
-use xaynet_core::SeedDict;
+use crate::{
+    services::fetchers::{round_etag, Cached},
+    state_machine::events::{DictionaryUpdate, EventListener, EventSubscriber},
+};
+use xaynet_core::{common::RoundParameters, SeedDict, SumParticipantPublicKey};
 
 /// A service that serves the seed dictionary for the current round.
-pub struct SeedDictService(EventListener<DictionaryUpdate<SeedDict>>);
+pub struct SeedDictService {
+    seed_dict: EventListener<DictionaryUpdate<SeedDict>>,
+    params: EventListener<RoundParameters>,
+    /// The cached portions of the seed dictionary for each sum participant.
+    cache: HashMap<SumParticipantPublicKey, Option<Cached>>,
+}
 
 impl SeedDictService {
     pub fn new(events: &EventSubscriber) -> Self {
-        Self(events.seed_dict_listener())
+        Self {
+            seed_dict: events.seed_dict_listener(),
+            params: events.params_listener(),
+            cache: HashMap::new(),
+        }
     }
 }
 
 /// [`SeedDictService`]'s request type
-#[derive(Default, Clone, Eq, PartialEq, Debug)]
-pub struct SeedDictRequest;
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SeedDictRequest {
+    /// The sum participant which requests its portion of the seed dictionary.
+    pub pk: SumParticipantPublicKey,
+}
 
 /// [`SeedDictService`]'s response type.
 ///
 /// The response is `None` when no seed dictionary is currently
-/// available
-pub type SeedDictResponse = Option<Arc<SeedDict>>;
+/// available or it doesn't contain the sum participant. The entity
+/// tag is derived from the round id and the round seed, since the
+/// seed dictionary is published once per round.
+pub type SeedDictResponse = Option<Cached>;
 
 impl Service<SeedDictRequest> for SeedDictService {
     type Response = SeedDictResponse;
@@ -39,10 +56,21 @@ impl Service<SeedDictRequest> for SeedDictService {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, _req: SeedDictRequest) -> Self::Future {
-        future::ready(match self.0.get_latest().event {
-            DictionaryUpdate::Invalidate => Ok(None),
-            DictionaryUpdate::New(dict) => Ok(Some(dict)),
+    fn call(&mut self, req: SeedDictRequest) -> Self::Future {
+        let seed_dict = self.seed_dict.get_latest();
+        future::ready(match seed_dict.event {
+            DictionaryUpdate::Invalidate => {
+                self.cache.clear();
+                Ok(None)
+            }
+            DictionaryUpdate::New(dict) => match dict.get(&req.pk) {
+                Some(update_dict) => {
+                    let etag = round_etag(seed_dict.round_id, &self.params.get_latest().event.seed);
+                    let cache = self.cache.entry(req.pk).or_default();
+                    Ok(Some(Cached::get_or_serialize(cache, etag, update_dict)))
+                }
+                None => Ok(None),
+            },
         })
         .instrument(error_span!("seed_dict_fetch_request"))
     }
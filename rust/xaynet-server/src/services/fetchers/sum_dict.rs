@@ -1,18 +1,22 @@
-use std::{
-    sync::Arc,
-    task::{Context, Poll},
-};
+use std::task::{Context, Poll};
 
 use futures::future::{self, Ready};
 use tower::Service;
 use tracing::error_span;
 use tracing_futures::{Instrument, Instrumented};
 
-use crate::state_machine::events::{DictionaryUpdate, EventListener, EventSubscriber};
-use xaynet_core::SumDict;
+use crate::{
+    services::fetchers::{round_etag, Cached},
+    state_machine::events::{DictionaryUpdate, EventListener, EventSubscriber},
+};
+use xaynet_core::{common::RoundParameters, SumDict};
 
 /// A service that returns the sum dictionary for the current round.
-pub struct SumDictService(EventListener<DictionaryUpdate<SumDict>>);
+pub struct SumDictService {
+    sum_dict: EventListener<DictionaryUpdate<SumDict>>,
+    params: EventListener<RoundParameters>,
+    cache: Option<Cached>,
+}
 
 /// [`SumDictService`]'s request type
 #[derive(Default, Clone, Eq, PartialEq, Debug)]
@@ -21,12 +25,17 @@ pub struct SumDictRequest;
 /// [`SumDictService`]'s response type.
 ///
 /// The response is `None` when no sum dictionary is currently
-/// available
-pub type SumDictResponse = Option<Arc<SumDict>>;
+/// available. The entity tag is derived from the round id and the
+/// round seed, since the sum dictionary is published once per round.
+pub type SumDictResponse = Option<Cached>;
 
 impl SumDictService {
     pub fn new(events: &EventSubscriber) -> Self {
-        Self(events.sum_dict_listener())
+        Self {
+            sum_dict: events.sum_dict_listener(),
+            params: events.params_listener(),
+            cache: None,
+        }
     }
 }
 
@@ -40,9 +49,17 @@ impl Service<SumDictRequest> for SumDictService {
     }
 
     fn call(&mut self, _req: SumDictRequest) -> Self::Future {
-        future::ready(match self.0.get_latest().event {
+        let sum_dict = self.sum_dict.get_latest();
+        future::ready(match sum_dict.event {
             DictionaryUpdate::Invalidate => Ok(None),
-            DictionaryUpdate::New(dict) => Ok(Some(dict)),
+            DictionaryUpdate::New(dict) => {
+                let etag = round_etag(sum_dict.round_id, &self.params.get_latest().event.seed);
+                Ok(Some(Cached::get_or_serialize(
+                    &mut self.cache,
+                    etag,
+                    dict.as_ref(),
+                )))
+            }
         })
         .instrument(error_span!("sum_dict_fetch_request"))
     }
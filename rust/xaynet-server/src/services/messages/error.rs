@@ -12,6 +12,9 @@ pub enum ServiceError {
     #[error("Failed to parse the message: {0:?}")]
     Parsing(DecodeError),
 
+    #[error("Unsupported protocol version {version}, the accepted versions are {min} to {max}")]
+    UnsupportedVersion { version: u16, min: u16, max: u16 },
+
     #[error("Invalid message signature")]
     InvalidMessageSignature,
 
@@ -21,6 +24,12 @@ pub enum ServiceError {
     #[error("The message was not expected in the current phase")]
     UnexpectedMessage,
 
+    #[error("The multipart message exceeds the maximal size of {0} bytes")]
+    MessageTooLarge(usize),
+
+    #[error("The coordinator buffers too many incomplete multipart messages")]
+    MultipartCapacityExceeded,
+
     // FIXME: we need to refine the state machine errors and the
     // conversion into a service error
     #[error("the state machine failed to process the request: {0:?}")]
@@ -8,6 +8,7 @@ use tracing::{debug, info, trace, warn};
 
 use crate::{
     services::messages::{BoxedServiceFuture, ServiceError},
+    settings::ProtocolSettings,
     state_machine::{
         events::{EventListener, EventSubscriber},
         phases::PhaseName,
@@ -84,6 +85,60 @@ impl<S> Layer<S> for BufferWrapperLayer {
     }
 }
 
+/// A service that discards messages of a protocol version which is not accepted
+#[derive(Debug, Clone)]
+struct VersionFilter<S> {
+    /// The accepted protocol versions
+    protocol: ProtocolSettings,
+    /// Next service to be called
+    next_svc: S,
+}
+
+impl<T, S> Service<RawMessage<T>> for VersionFilter<S>
+where
+    T: AsRef<[u8]> + Send + 'static,
+    S: Service<RawMessage<T>, Response = Message, Error = ServiceError>,
+    S::Future: Sync + Send + 'static,
+{
+    type Response = Message;
+    type Error = ServiceError;
+    type Future = BoxedServiceFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.next_svc.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: RawMessage<T>) -> Self::Future {
+        let version = req.buffer.version();
+        if self.protocol.accepts(version) {
+            let fut = self.next_svc.call(req);
+            Box::pin(async move { fut.await })
+        } else {
+            warn!("found an unsupported protocol version {}", version);
+            Box::pin(future::ready(Err(ServiceError::UnsupportedVersion {
+                version,
+                min: self.protocol.min_version,
+                max: self.protocol.max_version,
+            })))
+        }
+    }
+}
+
+struct VersionFilterLayer {
+    protocol: ProtocolSettings,
+}
+
+impl<S> Layer<S> for VersionFilterLayer {
+    type Service = VersionFilter<S>;
+
+    fn layer(&self, service: S) -> VersionFilter<S> {
+        VersionFilter {
+            protocol: self.protocol,
+            next_svc: service,
+        }
+    }
+}
+
 /// A service that discards messages that are not expected in the current phase
 #[derive(Debug, Clone)]
 struct PhaseFilter<S> {
@@ -309,7 +364,9 @@ where
 }
 
 type InnerService = BufferWrapper<
-    PhaseFilter<ConcurrencyLimit<SignatureVerifier<CoordinatorPublicKeyValidator<Parser>>>>,
+    VersionFilter<
+        PhaseFilter<ConcurrencyLimit<SignatureVerifier<CoordinatorPublicKeyValidator<Parser>>>>,
+    >,
 >;
 
 #[derive(Debug, Clone)]
@@ -334,9 +391,14 @@ where
 }
 
 impl MessageParser {
-    pub fn new(events: &EventSubscriber, thread_pool: Arc<ThreadPool>) -> Self {
+    pub fn new(
+        events: &EventSubscriber,
+        thread_pool: Arc<ThreadPool>,
+        protocol: ProtocolSettings,
+    ) -> Self {
         let inner = ServiceBuilder::new()
             .layer(BufferWrapperLayer)
+            .layer(VersionFilterLayer { protocol })
             .layer(PhaseFilterLayer {
                 phase: events.phase_listener(),
             })
@@ -360,11 +422,18 @@ mod tests {
         services::tests::utils,
         state_machine::events::{EventPublisher, EventSubscriber},
     };
+    use xaynet_core::message::PROTOCOL_VERSION;
 
     fn spawn_svc() -> (EventPublisher, EventSubscriber, Spawn<MessageParser>) {
+        spawn_svc_with_protocol(ProtocolSettings::default())
+    }
+
+    fn spawn_svc_with_protocol(
+        protocol: ProtocolSettings,
+    ) -> (EventPublisher, EventSubscriber, Spawn<MessageParser>) {
         let (publisher, subscriber) = utils::new_event_channels();
         let thread_pool = Arc::new(ThreadPoolBuilder::new().build().unwrap());
-        let task = Spawn::new(MessageParser::new(&subscriber, thread_pool));
+        let task = Spawn::new(MessageParser::new(&subscriber, thread_pool, protocol));
         (publisher, subscriber, task)
     }
 
@@ -407,4 +476,28 @@ mod tests {
             _ => panic!("expected ServiceError::UnexpectedMessage got {:?}", err),
         }
     }
+
+    #[tokio::test]
+    async fn test_unsupported_version() {
+        let (mut publisher, subscriber, mut task) = spawn_svc_with_protocol(ProtocolSettings {
+            min_version: PROTOCOL_VERSION,
+            max_version: PROTOCOL_VERSION,
+        });
+        assert_ready!(task.poll_ready::<Vec<u8>>()).unwrap();
+
+        let round_params = subscriber.params_listener().get_latest().event;
+        let (message, signing_keys) = utils::new_sum_message(&round_params);
+        let mut serialized_message = utils::serialize_message(&message, &signing_keys);
+        publisher.broadcast_phase(PhaseName::Sum);
+
+        // Simulate a participant which predates the protocol versioning
+        MessageBuffer::new(&mut serialized_message)
+            .unwrap()
+            .set_version(0);
+        let err = task.call(serialized_message).await.unwrap_err();
+        match err {
+            ServiceError::UnsupportedVersion { version: 0, .. } => {}
+            _ => panic!("expected ServiceError::UnsupportedVersion got {:?}", err),
+        }
+    }
 }
@@ -25,18 +25,26 @@ use self::{
     state_machine::StateMachine,
     task_validator::TaskValidator,
 };
-use crate::state_machine::{events::EventSubscriber, requests::RequestSender};
+use crate::{
+    settings::{MultipartSettings, ProtocolSettings},
+    state_machine::{events::EventSubscriber, requests::RequestSender},
+};
 
 impl PetMessageHandler {
-    pub fn new(event_subscriber: &EventSubscriber, requests_tx: RequestSender) -> Self {
+    pub fn new(
+        event_subscriber: &EventSubscriber,
+        requests_tx: RequestSender,
+        protocol_settings: ProtocolSettings,
+        multipart_settings: MultipartSettings,
+    ) -> Self {
         // TODO: make this configurable. Users should be able to
         // choose how many threads they want etc.
         //
         // TODO: don't unwrap
         let thread_pool = Arc::new(ThreadPoolBuilder::new().build().unwrap());
         let decryptor = Decryptor::new(event_subscriber, thread_pool.clone());
-        let multipart_handler = MultipartHandler::new();
-        let message_parser = MessageParser::new(event_subscriber, thread_pool);
+        let multipart_handler = MultipartHandler::new(event_subscriber, multipart_settings);
+        let message_parser = MessageParser::new(event_subscriber, thread_pool, protocol_settings);
         let task_validator = TaskValidator::new(event_subscriber);
         let state_machine = StateMachine::new(requests_tx);
 
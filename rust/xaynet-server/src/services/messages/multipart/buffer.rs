@@ -7,7 +7,7 @@ use std::{
 /// A data structure for reading a multipart message
 pub struct MultipartMessageBuffer {
     /// message chunks that haven't been read yet
-    remaining_chunks: BTreeMapIter<u16, Vec<u8>>,
+    remaining_chunks: BTreeMapIter<u32, Vec<u8>>,
     /// chunk being read
     current_chunk: Option<VecIter<u8>>,
     /// total length of the buffer
@@ -16,8 +16,8 @@ pub struct MultipartMessageBuffer {
     consumed: usize,
 }
 
-impl From<BTreeMap<u16, Vec<u8>>> for MultipartMessageBuffer {
-    fn from(map: BTreeMap<u16, Vec<u8>>) -> Self {
+impl From<BTreeMap<u32, Vec<u8>>> for MultipartMessageBuffer {
+    fn from(map: BTreeMap<u32, Vec<u8>>) -> Self {
         let initial_length = map.values().fold(0, |acc, chunk| acc + chunk.len());
         Self {
             remaining_chunks: map.into_iter(),
@@ -70,7 +70,7 @@ mod tests {
 
     #[test]
     fn test() {
-        let mut map: BTreeMap<u16, Vec<u8>> = BTreeMap::new();
+        let mut map: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
         map.insert(1, vec![0, 1, 2]);
         map.insert(2, vec![3]);
         map.insert(3, vec![4, 5]);
@@ -6,7 +6,11 @@ use std::task::{Context, Poll};
 use futures::future::TryFutureExt;
 use tower::{buffer::Buffer, Service, ServiceBuilder};
 
-use crate::services::messages::ServiceError;
+use crate::{
+    services::messages::ServiceError,
+    settings::MultipartSettings,
+    state_machine::events::EventSubscriber,
+};
 use xaynet_core::message::Message;
 
 type Inner = Buffer<service::MultipartHandler, Message>;
@@ -33,11 +37,11 @@ impl Service<Message> for MultipartHandler {
 }
 
 impl MultipartHandler {
-    pub fn new() -> Self {
+    pub fn new(events: &EventSubscriber, settings: MultipartSettings) -> Self {
         Self(
             ServiceBuilder::new()
                 .buffer(100)
-                .service(service::MultipartHandler::new()),
+                .service(service::MultipartHandler::new(events, settings)),
         )
     }
 }
@@ -1,8 +1,12 @@
 use std::{
     collections::{BTreeMap, HashMap},
+    io::Read,
+    mem,
     task::Poll,
+    time::{Duration, Instant},
 };
 
+use anyhow::{anyhow, Context as _};
 use futures::{
     future::{self, Ready},
     task::Context,
@@ -10,12 +14,29 @@ use futures::{
 use tower::Service;
 use tracing::{debug, trace, warn};
 
-use crate::services::messages::{multipart::buffer::MultipartMessageBuffer, ServiceError};
+use crate::{
+    metric,
+    metrics::Measurement,
+    services::messages::{multipart::buffer::MultipartMessageBuffer, ServiceError},
+    settings::MultipartSettings,
+    state_machine::{
+        events::{Event, EventListener, EventSubscriber},
+        phases::PhaseName,
+    },
+};
 use xaynet_core::{
     crypto::{PublicEncryptKey, PublicSigningKey},
     message::{Chunk, DecodeError, FromBytes, Message, Payload, Sum, Sum2, Tag, Update},
 };
 
+/// The number of bytes which are charged against the buffer for each incomplete message in
+/// addition to its chunks, so that many small messages can't exhaust the memory.
+const MESSAGE_OVERHEAD: usize = mem::size_of::<(MessageId, MessageBuilder)>();
+
+/// The number of bytes which are charged against the buffer for each chunk in addition to its
+/// data, so that many empty chunks can't exhaust the memory.
+const CHUNK_OVERHEAD: usize = mem::size_of::<(u32, Vec<u8>)>();
+
 /// A `MessageBuilder` stores chunks of a multipart message. Once it
 /// has all the chunks, it can be consumed and turned into a
 /// full-blown [`Message`] (see [`into_message()`]).
@@ -30,22 +51,40 @@ pub struct MessageBuilder {
     coordinator_pk: PublicEncryptKey,
     /// Message type
     tag: Tag,
+    /// Whether the concatenated chunks are compressed
+    is_compressed: bool,
     /// The ID of the last chunk is actually the total number of
     /// chunks this message is made of.
-    last_chunk_id: Option<u16>,
+    last_chunk_id: Option<u32>,
+    /// The total number of chunks announced by extended chunks
+    count: Option<u32>,
     /// Chunks, ordered by ID
-    data: BTreeMap<u16, Vec<u8>>,
+    data: BTreeMap<u32, Vec<u8>>,
+    /// The number of bytes of the chunks
+    size: usize,
+    /// The time at which the last chunk was added
+    updated_at: Instant,
 }
 
 impl MessageBuilder {
     /// Create a new [`MessageBuilder`] that contains no chunk.
-    fn new(tag: Tag, participant_pk: PublicSigningKey, coordinator_pk: PublicEncryptKey) -> Self {
+    fn new(
+        tag: Tag,
+        is_compressed: bool,
+        participant_pk: PublicSigningKey,
+        coordinator_pk: PublicEncryptKey,
+        now: Instant,
+    ) -> Self {
         MessageBuilder {
             tag,
+            is_compressed,
             participant_pk,
             coordinator_pk,
             data: BTreeMap::new(),
             last_chunk_id: None,
+            count: None,
+            size: 0,
+            updated_at: now,
         }
     }
 
@@ -60,24 +99,71 @@ impl MessageBuilder {
             .unwrap_or(false)
     }
 
-    /// Add a chunk.
-    fn add_chunk(&mut self, chunk: Chunk) {
-        let Chunk { id, last, data, .. } = chunk;
-        if last {
+    /// The number of bytes which the message occupies in the buffer.
+    fn buffered_size(&self) -> usize {
+        MESSAGE_OVERHEAD + self.data.len() * CHUNK_OVERHEAD + self.size
+    }
+
+    /// Add a chunk. A chunk which has been added before is replaced.
+    ///
+    /// # Errors
+    /// Fails if the chunk contradicts the chunks which have been added before, i.e. if extended
+    /// and legacy chunks are mixed, if it announces a different total number of chunks or a
+    /// different last chunk, or if its ID exceeds the ID of the last chunk. The message is left
+    /// unchanged in that case.
+    fn add_chunk(&mut self, chunk: Chunk, now: Instant) -> Result<(), DecodeError> {
+        let Chunk {
+            id,
+            last,
+            count,
+            data,
+            ..
+        } = chunk;
+        if !self.data.is_empty() && count != self.count {
+            return Err(match (self.count, count) {
+                (Some(expected), Some(count)) => anyhow!(
+                    "chunk announces {} chunks instead of {} chunks",
+                    count,
+                    expected
+                ),
+                _ => anyhow!("mixed extended and legacy chunks"),
+            });
+        }
+        if let Some(count) = count {
+            // An extended chunk announces the total number of chunks
+            // upfront, the parser ensures it is at least 1 and
+            // larger than the chunk ID
+            self.count = Some(count);
+            self.last_chunk_id = Some(count - 1);
+        } else if last {
+            if matches!(self.last_chunk_id, Some(last_chunk_id) if last_chunk_id != id)
+                || matches!(self.data.keys().next_back(), Some(max_id) if *max_id > id)
+            {
+                return Err(anyhow!("conflicting last chunk {}", id));
+            }
             self.last_chunk_id = Some(id);
+        } else if matches!(self.last_chunk_id, Some(last_chunk_id) if id > last_chunk_id) {
+            return Err(anyhow!("chunk {} exceeds the last chunk", id));
         }
-        self.data.insert(id, data);
+        self.size += data.len();
+        if let Some(replaced) = self.data.insert(id, data) {
+            self.size -= replaced.len();
+        }
+        self.updated_at = now;
+        Ok(())
     }
 
     /// Aggregate all the chunks. This method should only be called
     /// when all the chunks are here, otherwise the aggregated message
-    /// will be invalid.
-    fn into_message(self) -> Result<Message, DecodeError> {
-        let mut bytes = MultipartMessageBuffer::from(self.data);
-        let payload = match self.tag {
-            Tag::Sum => Sum::from_byte_stream(&mut bytes).map(Into::into)?,
-            Tag::Update => Update::from_byte_stream(&mut bytes).map(Into::into)?,
-            Tag::Sum2 => Sum2::from_byte_stream(&mut bytes).map(Into::into)?,
+    /// will be invalid. The payload of a compressed message may not
+    /// decompress to more than `max_size` bytes.
+    fn into_message(self, max_size: usize) -> Result<Message, DecodeError> {
+        let payload = if self.is_compressed {
+            let bytes =
+                decompress(&self.data, max_size).context("failed to decompress the payload")?;
+            parse_payload(self.tag, &mut bytes.into_iter())?
+        } else {
+            parse_payload(self.tag, &mut MultipartMessageBuffer::from(self.data))?
         };
         let message = Message {
             signature: None,
@@ -85,33 +171,255 @@ impl MessageBuilder {
             coordinator_pk: self.coordinator_pk,
             tag: self.tag,
             is_multipart: false,
+            is_compressed: false,
             payload,
         };
         Ok(message)
     }
 }
 
+/// Decompresses the concatenated chunks.
+///
+/// The decompression stops as soon as more than `max_size` bytes have been decompressed, so that
+/// a small compressed message can't make the coordinator allocate arbitrarily much memory.
+///
+/// # Errors
+/// Fails if the data is not compressed with zstd or if the decompressed data exceeds `max_size`
+/// bytes.
+fn decompress(chunks: &BTreeMap<u32, Vec<u8>>, max_size: usize) -> Result<Vec<u8>, DecodeError> {
+    let compressed = chunks
+        .values()
+        .map(Vec::as_slice)
+        .collect::<Vec<_>>()
+        .concat();
+    let mut bytes = Vec::new();
+    zstd::stream::read::Decoder::new(compressed.as_slice())?
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() > max_size {
+        return Err(anyhow!("decompressed payload exceeds {} bytes", max_size));
+    }
+    Ok(bytes)
+}
+
+/// Parses the payload of a message with the given tag.
+fn parse_payload<I>(tag: Tag, bytes: &mut I) -> Result<Payload, DecodeError>
+where
+    I: Iterator<Item = u8> + ExactSizeIterator,
+{
+    Ok(match tag {
+        Tag::Sum => Sum::from_byte_stream(bytes)?.into(),
+        Tag::Update => Update::from_byte_stream(bytes)?.into(),
+        Tag::Sum2 => Sum2::from_byte_stream(bytes)?.into(),
+    })
+}
+
 /// [`MessageId`] uniquely identifies a multipart message by its ID
 /// (which uniquely identify a message _for a given participant_), and
 /// the participant public key.
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub struct MessageId {
-    message_id: u16,
+    message_id: u32,
     participant_pk: PublicSigningKey,
 }
 
 /// A service that handles multipart messages.
+///
+/// The incomplete messages are limited by the [`MultipartSettings`]. They are evicted if they
+/// exceed the maximal message size, if they don't receive new chunks before the timeout or if the
+/// phase changes.
+///
+/// Chunks are handled idempotently, so that participants can resend chunks whose acknowledgment
+/// got lost: a duplicate chunk of an incomplete message replaces the previous one and a duplicate
+/// chunk of a completed message is ignored until the completed message expires like an
+/// incomplete one.
 pub struct MultipartHandler {
     message_builders: HashMap<MessageId, MessageBuilder>,
+    /// The completion time of the completed messages
+    completed: HashMap<MessageId, Instant>,
+    /// The limits of the incomplete messages
+    settings: MultipartSettings,
+    /// The number of bytes of all incomplete messages
+    buffered_size: usize,
+    /// A listener to retrieve the current phase
+    phase: EventListener<PhaseName>,
+    /// The phase of the incomplete messages
+    current_phase: Event<PhaseName>,
 }
 
 impl MultipartHandler {
-    #[allow(dead_code)]
-    pub fn new() -> Self {
+    pub fn new(events: &EventSubscriber, settings: MultipartSettings) -> Self {
+        let phase = events.phase_listener();
+        let current_phase = phase.get_latest();
         Self {
             message_builders: HashMap::new(),
+            completed: HashMap::new(),
+            settings,
+            buffered_size: 0,
+            phase,
+            current_phase,
+        }
+    }
+
+    /// Evicts all incomplete messages if the phase changed.
+    fn evict_outdated(&mut self) {
+        let phase = self.phase.get_latest();
+        if phase != self.current_phase {
+            self.current_phase = phase;
+            self.evict_where("phase", |_| true);
+            self.completed.clear();
+        }
+    }
+
+    /// Evicts the incomplete messages which didn't receive a chunk before the timeout.
+    fn evict_expired(&mut self, now: Instant) {
+        let timeout = Duration::from_secs(self.settings.timeout);
+        self.evict_where("expired", |builder| {
+            now.saturating_duration_since(builder.updated_at) >= timeout
+        });
+        self.completed
+            .retain(|_, completed_at| now.saturating_duration_since(*completed_at) < timeout);
+    }
+
+    /// Evicts the incomplete messages which satisfy the `predicate`.
+    fn evict_where<F>(&mut self, reason: &'static str, predicate: F)
+    where
+        F: Fn(&MessageBuilder) -> bool,
+    {
+        let before = self.message_builders.len();
+        let buffered_size = &mut self.buffered_size;
+        self.message_builders.retain(|_, builder| {
+            let evict = predicate(builder);
+            if evict {
+                *buffered_size -= builder.buffered_size();
+            }
+            !evict
+        });
+        let evicted = before - self.message_builders.len();
+        if evicted > 0 {
+            debug!(
+                "evicted {} incomplete multipart messages ({})",
+                evicted, reason
+            );
+            metric!(
+                Measurement::MultipartMessagesEvicted,
+                evicted as u64,
+                ("reason", reason)
+            );
         }
     }
+
+    /// Removes the message with the given ID.
+    fn remove(&mut self, id: &MessageId) -> Option<MessageBuilder> {
+        let builder = self.message_builders.remove(id)?;
+        self.buffered_size -= builder.buffered_size();
+        Some(builder)
+    }
+
+    /// Adds a chunk to the message with the given ID and returns the message if it is complete.
+    ///
+    /// # Errors
+    /// Fails if the chunk exceeds the maximal buffered size, if the message exceeds the maximal
+    /// message size or if the chunk contradicts the previous chunks of the message. The message is
+    /// evicted in the latter two cases.
+    fn add_chunk(
+        &mut self,
+        id: MessageId,
+        chunk: Chunk,
+        new_builder: impl FnOnce() -> MessageBuilder,
+        now: Instant,
+    ) -> Result<Option<MessageBuilder>, ServiceError> {
+        if self.completed.contains_key(&id) {
+            debug!(
+                "ignoring a duplicate chunk of a completed multipart message (id = {})",
+                id.message_id
+            );
+            return Ok(None);
+        }
+
+        // The sizes of the message and of the buffer once the chunk is added, where a resent chunk
+        // replaces the previous one
+        let chunk_size = chunk.data.len();
+        let (message_size, buffered_size) = match self.message_builders.get(&id) {
+            Some(builder) => match builder.data.get(&chunk.id) {
+                Some(replaced) => (
+                    builder.size - replaced.len() + chunk_size,
+                    self.buffered_size - replaced.len() + chunk_size,
+                ),
+                None => (
+                    builder.size + chunk_size,
+                    self.buffered_size + CHUNK_OVERHEAD + chunk_size,
+                ),
+            },
+            None => (
+                chunk_size,
+                self.buffered_size + MESSAGE_OVERHEAD + CHUNK_OVERHEAD + chunk_size,
+            ),
+        };
+
+        if buffered_size > self.settings.max_buffered_size {
+            warn!("rejected a chunk, too many incomplete multipart messages");
+            return Err(ServiceError::MultipartCapacityExceeded);
+        }
+
+        if message_size > self.settings.max_message_size {
+            warn!(
+                "multipart message (id = {}) exceeds the maximal size",
+                id.message_id
+            );
+            if self.remove(&id).is_some() {
+                metric!(
+                    Measurement::MultipartMessagesEvicted,
+                    1,
+                    ("reason", "message_size")
+                );
+            }
+            return Err(ServiceError::MessageTooLarge(
+                self.settings.max_message_size,
+            ));
+        }
+
+        // If we don't have a partial message for this ID, create
+        // an empty one, and add the chunk to the partial message.
+        let mp_message = self.message_builders.entry(id.clone()).or_insert_with(|| {
+            debug!("new multipart message (id = {})", id.message_id);
+            new_builder()
+        });
+        if let Err(error) = mp_message.add_chunk(chunk, now) {
+            warn!(
+                "invalid chunk of multipart message (id = {}): {}",
+                id.message_id, error
+            );
+            self.remove(&id);
+            metric!(
+                Measurement::MultipartMessagesEvicted,
+                1,
+                ("reason", "invalid_chunk")
+            );
+            return Err(ServiceError::Parsing(error));
+        }
+        self.buffered_size = buffered_size;
+
+        // Check if the message is complete, and if so remove it
+        if mp_message.has_all_chunks() {
+            self.completed.insert(id.clone(), now);
+            Ok(self.remove(&id))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Records the number and the size of the incomplete messages.
+    fn record_metrics(&self) {
+        metric!(
+            Measurement::MultipartMessagesPartial,
+            self.message_builders.len() as u64
+        );
+        metric!(
+            Measurement::MultipartBufferedBytes,
+            self.buffered_size as u64
+        );
+    }
 }
 
 impl Service<Message> for MultipartHandler {
@@ -132,8 +440,12 @@ impl Service<Message> for MultipartHandler {
         }
 
         debug!("handling multipart message");
+        let now = Instant::now();
+        self.evict_outdated();
+        self.evict_expired(now);
         if let Message {
             tag,
+            is_compressed,
             participant_pk,
             coordinator_pk,
             payload: Payload::Chunk(chunk),
@@ -144,33 +456,28 @@ impl Service<Message> for MultipartHandler {
                 message_id: chunk.message_id,
                 participant_pk,
             };
-            // If we don't have a partial message for this ID, create
-            // an empty one.
-            let mp_message = self.message_builders.entry(id.clone()).or_insert_with(|| {
-                debug!("new multipart message (id = {})", id.message_id);
-                MessageBuilder::new(tag, participant_pk, coordinator_pk)
-            });
-            // Add the chunk to the partial message
-            mp_message.add_chunk(chunk);
-
-            // Check if the message is complete, and if so parse it
-            // and return it
-            if mp_message.has_all_chunks() {
-                debug!("received the final message chunk, now parsing the full message");
-                // This entry exists, because `mp_message` above
-                // refers to it, so it's ok to unwrap.
-                match self.message_builders.remove(&id).unwrap().into_message() {
-                    Ok(message) => {
-                        debug!("multipart message succesfully parsed");
-                        ready_ok(Some(message))
-                    }
-                    Err(e) => {
-                        warn!("invalid multipart message: {}", e);
-                        ready_err(ServiceError::Parsing(e))
+            let new_builder =
+                || MessageBuilder::new(tag, is_compressed, participant_pk, coordinator_pk, now);
+            let result = self.add_chunk(id, chunk, new_builder, now);
+            self.record_metrics();
+
+            // Parse the message if it is complete
+            match result {
+                Ok(Some(mp_message)) => {
+                    debug!("received the final message chunk, now parsing the full message");
+                    match mp_message.into_message(self.settings.max_message_size) {
+                        Ok(message) => {
+                            debug!("multipart message succesfully parsed");
+                            ready_ok(Some(message))
+                        }
+                        Err(e) => {
+                            warn!("invalid multipart message: {}", e);
+                            ready_err(ServiceError::Parsing(e))
+                        }
                     }
                 }
-            } else {
-                ready_ok(None)
+                Ok(None) => ready_ok(None),
+                Err(e) => ready_err(e),
             }
         } else {
             // This cannot happen, because parsing have fail
@@ -196,9 +503,21 @@ mod tests {
     use xaynet_core::crypto::{ByteObject, PublicEncryptKey, Signature};
 
     use super::*;
+    use crate::{
+        services::tests::utils::new_event_channels,
+        state_machine::events::EventPublisher,
+    };
 
     fn spawn_svc() -> Spawn<MultipartHandler> {
-        Spawn::new(MultipartHandler::new())
+        spawn_svc_with_settings(MultipartSettings::default()).1
+    }
+
+    fn spawn_svc_with_settings(
+        settings: MultipartSettings,
+    ) -> (EventPublisher, Spawn<MultipartHandler>) {
+        let (publisher, subscriber) = new_event_channels();
+        let task = Spawn::new(MultipartHandler::new(&subscriber, settings));
+        (publisher, task)
     }
 
     fn sum() -> (Vec<u8>, Sum) {
@@ -222,7 +541,7 @@ mod tests {
         let participant_pk = PublicSigningKey::zeroed();
         let coordinator_pk = PublicEncryptKey::zeroed();
         let tag = Tag::Sum;
-        MessageBuilder::new(tag, participant_pk, coordinator_pk)
+        MessageBuilder::new(tag, false, participant_pk, coordinator_pk, Instant::now())
     }
 
     fn chunks(mut data: Vec<u8>) -> (Chunk, Chunk, Chunk, Chunk, Chunk) {
@@ -258,30 +577,35 @@ mod tests {
             id: 0,
             message_id: 1234,
             last: false,
+            count: None,
             data,
         };
         let chunk2 = Chunk {
             id: 1,
             message_id: 1234,
             last: false,
+            count: None,
             data: data2,
         };
         let chunk3 = Chunk {
             id: 2,
             message_id: 1234,
             last: false,
+            count: None,
             data: data3,
         };
         let chunk4 = Chunk {
             id: 3,
             message_id: 1234,
             last: false,
+            count: None,
             data: data4,
         };
         let chunk5 = Chunk {
             id: 4,
             message_id: 1234,
             last: true,
+            count: None,
             data: data5,
         };
         (chunk1, chunk2, chunk3, chunk4, chunk5)
@@ -296,32 +620,33 @@ mod tests {
         assert!(msg.data.is_empty());
         assert!(msg.last_chunk_id.is_none());
 
-        msg.add_chunk(c1);
+        msg.add_chunk(c1, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 1);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c2);
+        msg.add_chunk(c2, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 2);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c3);
+        msg.add_chunk(c3, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 3);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c4);
+        msg.add_chunk(c4, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 4);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c5);
+        msg.add_chunk(c5, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 5);
         assert_eq!(msg.last_chunk_id, Some(4));
         assert!(msg.has_all_chunks());
+        assert_eq!(msg.size, 96);
 
-        let actual = msg.into_message().unwrap();
+        let actual = msg.into_message(usize::MAX).unwrap();
         let expected =
             Message::new_sum(PublicSigningKey::zeroed(), PublicEncryptKey::zeroed(), sum);
 
@@ -337,38 +662,207 @@ mod tests {
         assert!(msg.data.is_empty());
         assert!(msg.last_chunk_id.is_none());
 
-        msg.add_chunk(c3);
+        msg.add_chunk(c3, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 1);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c1);
+        msg.add_chunk(c1, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 2);
         assert!(msg.last_chunk_id.is_none());
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c5);
+        msg.add_chunk(c5, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 3);
         assert_eq!(msg.last_chunk_id, Some(4));
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c2);
+        msg.add_chunk(c2, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 4);
         assert_eq!(msg.last_chunk_id, Some(4));
         assert!(!msg.has_all_chunks());
 
-        msg.add_chunk(c4);
+        msg.add_chunk(c4, Instant::now()).unwrap();
         assert_eq!(msg.data.len(), 5);
         assert_eq!(msg.last_chunk_id, Some(4));
         assert!(msg.has_all_chunks());
 
-        let actual = msg.into_message().unwrap();
+        let actual = msg.into_message(usize::MAX).unwrap();
         let expected =
             Message::new_sum(PublicSigningKey::zeroed(), PublicEncryptKey::zeroed(), sum);
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_message_builder_extended() {
+        let mut msg = message_builder();
+        let (data, sum) = sum();
+        let (data1, data2) = data.split_at(data.len() / 2);
+        let chunk = |id, data: &[u8]| Chunk {
+            id,
+            message_id: 0x0001_0000,
+            last: id == 1,
+            count: Some(2),
+            data: data.to_vec(),
+        };
+
+        // the total number of chunks is known before the last chunk
+        msg.add_chunk(chunk(0, data1), Instant::now()).unwrap();
+        assert_eq!(msg.last_chunk_id, Some(1));
+        assert!(!msg.has_all_chunks());
+
+        msg.add_chunk(chunk(1, data2), Instant::now()).unwrap();
+        assert!(msg.has_all_chunks());
+
+        let actual = msg.into_message(usize::MAX).unwrap();
+        let expected =
+            Message::new_sum(PublicSigningKey::zeroed(), PublicEncryptKey::zeroed(), sum);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_message_builder_inconsistent_chunks() {
+        let chunk = |id, last, count| Chunk {
+            id,
+            message_id: 1234,
+            last,
+            count,
+            data: vec![0],
+        };
+
+        // extended chunks must announce the same number of chunks
+        let mut msg = message_builder();
+        msg.add_chunk(chunk(0, false, Some(3)), Instant::now())
+            .unwrap();
+        assert!(msg
+            .add_chunk(chunk(1, false, Some(2)), Instant::now())
+            .is_err());
+        assert_eq!(msg.last_chunk_id, Some(2));
+        assert_eq!(msg.data.len(), 1);
+        assert_eq!(msg.size, 1);
+
+        // extended and legacy chunks can't be mixed
+        assert!(msg
+            .add_chunk(chunk(1, false, None), Instant::now())
+            .is_err());
+        let mut msg = message_builder();
+        msg.add_chunk(chunk(0, false, None), Instant::now())
+            .unwrap();
+        assert!(msg
+            .add_chunk(chunk(1, false, Some(2)), Instant::now())
+            .is_err());
+
+        // legacy chunks must agree on the last chunk
+        msg.add_chunk(chunk(2, true, None), Instant::now()).unwrap();
+        assert!(msg.add_chunk(chunk(1, true, None), Instant::now()).is_err());
+        assert!(msg
+            .add_chunk(chunk(3, false, None), Instant::now())
+            .is_err());
+        assert_eq!(msg.last_chunk_id, Some(2));
+        assert!(!msg.has_all_chunks());
+    }
+
+    #[tokio::test]
+    async fn test_evict_inconsistent_chunk() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings::default());
+        let chunk = |id, count| Chunk {
+            id,
+            message_id: 1234,
+            last: false,
+            count: Some(count),
+            data: vec![0],
+        };
+
+        assert!(send(&mut task, 1, &chunk(0, 3)).await.unwrap().is_none());
+        match send(&mut task, 1, &chunk(1, 2)).await.unwrap_err() {
+            ServiceError::Parsing(_) => {}
+            err => panic!("expected Parsing got {:?}", err),
+        }
+        assert!(task.get_ref().message_builders.is_empty());
+        assert_eq!(task.get_ref().buffered_size, 0);
+    }
+
+    #[test]
+    fn test_message_builder_compressed() {
+        let (data, sum) = sum();
+        let compressed = zstd::encode_all(data.as_slice(), 0).unwrap();
+        let (data1, data2) = compressed.split_at(compressed.len() / 2);
+        let mut msg = MessageBuilder::new(
+            Tag::Sum,
+            true,
+            PublicSigningKey::zeroed(),
+            PublicEncryptKey::zeroed(),
+            Instant::now(),
+        );
+
+        msg.add_chunk(
+            Chunk {
+                id: 1,
+                message_id: 1234,
+                last: true,
+                count: None,
+                data: data2.to_vec(),
+            },
+            Instant::now(),
+        )
+        .unwrap();
+        msg.add_chunk(
+            Chunk {
+                id: 0,
+                message_id: 1234,
+                last: false,
+                count: None,
+                data: data1.to_vec(),
+            },
+            Instant::now(),
+        )
+        .unwrap();
+        assert!(msg.has_all_chunks());
+
+        let actual = msg.clone().into_message(usize::MAX).unwrap();
+        let expected =
+            Message::new_sum(PublicSigningKey::zeroed(), PublicEncryptKey::zeroed(), sum);
+        assert_eq!(actual, expected);
+
+        // the decompressed payload is limited
+        assert!(msg.clone().into_message(data.len() - 1).is_err());
+
+        // the chunks must be compressed if the message is flagged as compressed
+        msg.data = iter::once((0, data)).collect();
+        msg.last_chunk_id = Some(0);
+        assert!(msg.into_message(usize::MAX).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decompression_bomb() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings {
+            max_message_size: 1024,
+            ..MultipartSettings::default()
+        });
+        let compressed = zstd::encode_all(vec![0_u8; 1024 * 1024].as_slice(), 0).unwrap();
+        assert!(compressed.len() < 1024);
+        let chunk = Chunk {
+            id: 0,
+            message_id: 1234,
+            last: true,
+            count: None,
+            data: compressed,
+        };
+        let mut message = Message::new_multipart(
+            PublicSigningKey::zeroed(),
+            PublicEncryptKey::zeroed(),
+            chunk,
+            Tag::Sum,
+        );
+        message.is_compressed = true;
+
+        match task.call(message).await.unwrap_err() {
+            ServiceError::Parsing(_) => {}
+            err => panic!("expected Parsing got {:?}", err),
+        }
+    }
+
     #[tokio::test]
     async fn message_handler() {
         let mut task = spawn_svc();
@@ -443,4 +937,185 @@ mod tests {
         assert_eq!(res1, Message::new_sum(pk1, coordinator_pk, sum.clone()));
         assert_eq!(res2, Message::new_sum(pk2, coordinator_pk, sum.clone()));
     }
+
+    /// Sends a chunk of a multipart message from the given participant.
+    async fn send(
+        task: &mut Spawn<MultipartHandler>,
+        participant: u8,
+        chunk: &Chunk,
+    ) -> Result<Option<Message>, ServiceError> {
+        let participant_pk =
+            PublicSigningKey::from_slice(&[participant; PublicSigningKey::LENGTH]).unwrap();
+        let message = Message::new_multipart(
+            participant_pk,
+            PublicEncryptKey::zeroed(),
+            chunk.clone(),
+            Tag::Sum,
+        );
+        task.call(message).await
+    }
+
+    #[tokio::test]
+    async fn test_message_too_large() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings {
+            max_message_size: 50,
+            ..MultipartSettings::default()
+        });
+        let (c1, _, _, _, c5) = chunks(sum().0);
+
+        assert!(send(&mut task, 1, &c1).await.unwrap().is_none());
+        assert_eq!(
+            task.get_ref().buffered_size,
+            MESSAGE_OVERHEAD + CHUNK_OVERHEAD + 1
+        );
+        match send(&mut task, 1, &c5).await.unwrap_err() {
+            ServiceError::MessageTooLarge(50) => {}
+            err => panic!("expected MessageTooLarge got {:?}", err),
+        }
+        assert!(task.get_ref().message_builders.is_empty());
+        assert_eq!(task.get_ref().buffered_size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_resend_chunk_near_max_message_size() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings {
+            max_message_size: 96,
+            ..MultipartSettings::default()
+        });
+        let (c1, c2, c3, c4, c5) = chunks(sum().0);
+
+        assert!(send(&mut task, 1, &c5).await.unwrap().is_none());
+        assert!(send(&mut task, 1, &c4).await.unwrap().is_none());
+        // the resent chunk replaces the previous one instead of adding to the message size
+        assert!(send(&mut task, 1, &c5).await.unwrap().is_none());
+        assert_eq!(task.get_ref().message_builders.len(), 1);
+
+        for chunk in &[&c1, &c2] {
+            assert!(send(&mut task, 1, chunk).await.unwrap().is_none());
+        }
+        assert!(send(&mut task, 1, &c3).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_capacity_exceeded() {
+        let overhead = MESSAGE_OVERHEAD + CHUNK_OVERHEAD;
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings {
+            max_message_size: 100,
+            max_buffered_size: 100 + 2 * overhead,
+            ..MultipartSettings::default()
+        });
+        let (c1, _, _, _, c5) = chunks(sum().0);
+
+        assert!(send(&mut task, 1, &c5).await.unwrap().is_none());
+        match send(&mut task, 2, &c5).await.unwrap_err() {
+            ServiceError::MultipartCapacityExceeded => {}
+            err => panic!("expected MultipartCapacityExceeded got {:?}", err),
+        }
+        // smaller chunks still fit into the buffer
+        assert!(send(&mut task, 2, &c1).await.unwrap().is_none());
+        assert_eq!(task.get_ref().message_builders.len(), 2);
+        assert_eq!(task.get_ref().buffered_size, 87 + 2 * overhead);
+
+        // resending a chunk replaces it
+        assert!(send(&mut task, 2, &c1).await.unwrap().is_none());
+        assert_eq!(task.get_ref().buffered_size, 87 + 2 * overhead);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_exceeded_by_overhead() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings {
+            max_message_size: 100,
+            max_buffered_size: 2 * (MESSAGE_OVERHEAD + CHUNK_OVERHEAD),
+            ..MultipartSettings::default()
+        });
+        let empty = |id| Chunk {
+            id,
+            message_id: 1234,
+            last: false,
+            count: None,
+            data: Vec::new(),
+        };
+
+        // empty chunks and messages are charged against the buffer as well
+        assert!(send(&mut task, 1, &empty(0)).await.unwrap().is_none());
+        assert!(send(&mut task, 2, &empty(0)).await.unwrap().is_none());
+        match send(&mut task, 3, &empty(0)).await.unwrap_err() {
+            ServiceError::MultipartCapacityExceeded => {}
+            err => panic!("expected MultipartCapacityExceeded got {:?}", err),
+        }
+        match send(&mut task, 1, &empty(1)).await.unwrap_err() {
+            ServiceError::MultipartCapacityExceeded => {}
+            err => panic!("expected MultipartCapacityExceeded got {:?}", err),
+        }
+        assert_eq!(task.get_ref().message_builders.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_evict_expired() {
+        let (_publisher, mut task) = spawn_svc_with_settings(MultipartSettings::default());
+        let (c1, c2, _, _, _) = chunks(sum().0);
+
+        assert!(send(&mut task, 1, &c1).await.unwrap().is_none());
+        task.get_mut().evict_expired(Instant::now());
+        assert_eq!(task.get_ref().message_builders.len(), 1);
+
+        assert!(send(&mut task, 2, &c2).await.unwrap().is_none());
+        let timeout = Duration::from_secs(MultipartSettings::default().timeout);
+        task.get_mut().evict_expired(Instant::now() + timeout);
+        assert!(task.get_ref().message_builders.is_empty());
+        assert_eq!(task.get_ref().buffered_size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_evict_on_phase_change() {
+        let (mut publisher, mut task) = spawn_svc_with_settings(MultipartSettings::default());
+        let (c1, c2, _, _, _) = chunks(sum().0);
+
+        assert!(send(&mut task, 1, &c1).await.unwrap().is_none());
+        assert!(send(&mut task, 2, &c1).await.unwrap().is_none());
+        assert_eq!(task.get_ref().message_builders.len(), 2);
+
+        publisher.broadcast_phase(PhaseName::Sum);
+        assert!(send(&mut task, 1, &c2).await.unwrap().is_none());
+        assert_eq!(task.get_ref().message_builders.len(), 1);
+        assert_eq!(
+            task.get_ref().buffered_size,
+            MESSAGE_OVERHEAD + CHUNK_OVERHEAD + 2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_chunks() {
+        let (mut publisher, mut task) = spawn_svc_with_settings(MultipartSettings::default());
+        let (c1, c2, c3, c4, c5) = chunks(sum().0);
+
+        for chunk in &[&c1, &c2, &c3, &c4] {
+            assert!(send(&mut task, 1, chunk).await.unwrap().is_none());
+        }
+        assert!(send(&mut task, 1, &c4).await.unwrap().is_none());
+        assert!(send(&mut task, 1, &c5).await.unwrap().is_some());
+
+        // resent chunks of a completed message are ignored
+        assert!(send(&mut task, 1, &c5).await.unwrap().is_none());
+        assert!(send(&mut task, 1, &c1).await.unwrap().is_none());
+        assert!(task.get_ref().message_builders.is_empty());
+        assert_eq!(task.get_ref().completed.len(), 1);
+
+        let timeout = Duration::from_secs(MultipartSettings::default().timeout);
+        task.get_mut().evict_expired(Instant::now() + timeout);
+        assert!(task.get_ref().completed.is_empty());
+
+        // the completed messages are forgotten on a phase change
+        task.get_mut().completed.insert(
+            MessageId {
+                message_id: c1.message_id,
+                participant_pk: PublicSigningKey::zeroed(),
+            },
+            Instant::now(),
+        );
+        publisher.broadcast_phase(PhaseName::Sum);
+        assert!(send(&mut task, 1, &c1).await.unwrap().is_none());
+        assert!(task.get_ref().completed.is_empty());
+        assert_eq!(task.get_ref().message_builders.len(), 1);
+    }
 }
@@ -1,11 +1,16 @@
 use std::{collections::HashMap, sync::Arc};
 
+use serde::Serialize;
+
 use tokio_test::assert_ready;
 use tower_test::mock::Spawn;
 
 use crate::{
     services::{
         fetchers::{
+            Cached,
+            EncodedModel,
+            ModelEncoding,
             ModelRequest,
             ModelService,
             RoundParamsRequest,
@@ -22,12 +27,24 @@ use crate::{
 use xaynet_core::{
     common::{RoundParameters, RoundSeed},
     crypto::{ByteObject, PublicEncryptKey, PublicSigningKey},
-    mask::{EncryptedMaskSeed, Model},
+    mask::{DataType, EncryptedMaskSeed, FromPrimitives, Model, PrimitiveModel},
+    message::PROTOCOL_VERSION,
     SeedDict,
     SumDict,
     UpdateSeedDict,
 };
 
+fn cached<T: Serialize + ?Sized>(etag: &str, data: &T) -> Cached {
+    Cached {
+        etag: etag.to_string(),
+        body: bincode::serialize(data).unwrap().into(),
+    }
+}
+
+fn round_etag(round_id: u64, params: &RoundParameters) -> String {
+    format!("{}_{}", round_id, hex::encode(params.seed.as_slice()))
+}
+
 #[tokio::test]
 async fn test_model_svc() {
     let (mut publisher, subscriber) = new_event_channels();
@@ -35,19 +52,74 @@ async fn test_model_svc() {
     let mut task = Spawn::new(ModelService::new(&subscriber));
     assert_ready!(task.poll_ready()).unwrap();
 
-    let resp = task.call(ModelRequest).await;
-    assert_eq!(resp, Ok(None));
+    let resp = task.call(ModelRequest::default()).await.unwrap();
+    assert_eq!(resp, None);
 
-    let model = Arc::new(Model::from(vec![]));
-    publisher.broadcast_model(ModelUpdate::New(model.clone()));
+    let model = Arc::new(Model::from_primitives(vec![1_f32, -2.5, 1e10].into_iter()).unwrap());
+    publisher.broadcast_model(ModelUpdate::New {
+        id: "1_model".into(),
+        model: model.clone(),
+    });
     assert_ready!(task.poll_ready()).unwrap();
-    let resp = task.call(ModelRequest).await;
-    assert_eq!(resp, Ok(Some(model)));
+    let resp = task.call(ModelRequest::default()).await.unwrap();
+    assert_eq!(
+        resp,
+        Some(EncodedModel {
+            encoding: ModelEncoding::Bincode,
+            cached: cached("1_model", model.as_ref()),
+        }),
+    );
+
+    // the data type defaults to the one of the round
+    let encoding = ModelEncoding::Primitive {
+        data_type: None,
+        compressed: false,
+    };
+    assert_ready!(task.poll_ready()).unwrap();
+    let resp = task.call(ModelRequest { encoding }).await.unwrap().unwrap();
+    assert_eq!(
+        resp.encoding,
+        ModelEncoding::Primitive {
+            data_type: Some(DataType::F32),
+            compressed: false,
+        },
+    );
+    assert_eq!(resp.cached.etag, "1_model-f32");
+    assert_eq!(
+        resp.cached.body,
+        PrimitiveModel::F32(vec![1., -2.5, 1e10]).to_bytes(),
+    );
+
+    let encoding = ModelEncoding::Primitive {
+        data_type: Some(DataType::F64),
+        compressed: true,
+    };
+    assert_ready!(task.poll_ready()).unwrap();
+    let resp = task.call(ModelRequest { encoding }).await.unwrap().unwrap();
+    assert_eq!(resp.encoding, encoding);
+    assert_eq!(resp.cached.etag, "1_model-f64-zstd");
+    assert_eq!(
+        zstd::decode_all(resp.cached.body.as_ref()).unwrap(),
+        PrimitiveModel::F64(vec![1., -2.5, 1e10]).to_bytes(),
+    );
+
+    // the encoded model is cached
+    assert_ready!(task.poll_ready()).unwrap();
+    let cached_resp = task.call(ModelRequest { encoding }).await.unwrap().unwrap();
+    assert_eq!(cached_resp.cached.body.as_ptr(), resp.cached.body.as_ptr());
+
+    // the weights don't fit into the data type
+    let encoding = ModelEncoding::Primitive {
+        data_type: Some(DataType::I32),
+        compressed: false,
+    };
+    assert_ready!(task.poll_ready()).unwrap();
+    assert!(task.call(ModelRequest { encoding }).await.is_err());
 
     publisher.broadcast_model(ModelUpdate::Invalidate);
     assert_ready!(task.poll_ready()).unwrap();
-    let resp = task.call(ModelRequest).await;
-    assert_eq!(resp, Ok(None));
+    let resp = task.call(ModelRequest::default()).await.unwrap();
+    assert_eq!(resp, None);
 }
 
 #[tokio::test]
@@ -59,7 +131,10 @@ async fn test_round_params_svc() {
     assert_ready!(task.poll_ready()).unwrap();
 
     let resp = task.call(RoundParamsRequest).await;
-    assert_eq!(resp, Ok(initial_params));
+    assert_eq!(
+        resp,
+        Ok(cached(&round_etag(0, &initial_params), &initial_params)),
+    );
 
     let params = RoundParameters {
         pk: PublicEncryptKey::fill_with(0x11),
@@ -68,11 +143,23 @@ async fn test_round_params_svc() {
         seed: RoundSeed::fill_with(0x11),
         mask_config: mask_config().into(),
         model_length: 42,
+        privacy_budget: None,
+        version: PROTOCOL_VERSION,
+        starts_at: None,
     };
+    publisher.set_round_id(1);
     publisher.broadcast_params(params.clone());
     assert_ready!(task.poll_ready()).unwrap();
     let resp = task.call(RoundParamsRequest).await;
-    assert_eq!(resp, Ok(params));
+    assert_eq!(resp, Ok(cached(&round_etag(1, &params), &params)));
+
+    // the body is only serialized once per change
+    assert_ready!(task.poll_ready()).unwrap();
+    let cached_resp = task.call(RoundParamsRequest).await;
+    assert_eq!(
+        cached_resp.unwrap().body.as_ptr(),
+        resp.unwrap().body.as_ptr(),
+    );
 }
 
 fn dummy_seed_dict() -> SeedDict {
@@ -102,18 +189,29 @@ async fn test_seed_dict_svc() {
     let mut task = Spawn::new(SeedDictService::new(&subscriber));
     assert_ready!(task.poll_ready()).unwrap();
 
-    let resp = task.call(SeedDictRequest).await;
+    let params = subscriber.params_listener().get_latest().event;
+    let pk = PublicSigningKey::fill_with(0xaa);
+
+    let resp = task.call(SeedDictRequest { pk }).await;
     assert_eq!(resp, Ok(None));
 
     let seed_dict = Arc::new(dummy_seed_dict());
     publisher.broadcast_seed_dict(DictionaryUpdate::New(seed_dict.clone()));
     assert_ready!(task.poll_ready()).unwrap();
-    let resp = task.call(SeedDictRequest).await;
-    assert_eq!(resp, Ok(Some(seed_dict)));
+    let resp = task.call(SeedDictRequest { pk }).await;
+    assert_eq!(
+        resp,
+        Ok(Some(cached(&round_etag(0, &params), &seed_dict[&pk]))),
+    );
+
+    let unknown_pk = PublicSigningKey::fill_with(0xcc);
+    assert_ready!(task.poll_ready()).unwrap();
+    let resp = task.call(SeedDictRequest { pk: unknown_pk }).await;
+    assert_eq!(resp, Ok(None));
 
     publisher.broadcast_seed_dict(DictionaryUpdate::Invalidate);
     assert_ready!(task.poll_ready()).unwrap();
-    let resp = task.call(SeedDictRequest).await;
+    let resp = task.call(SeedDictRequest { pk }).await;
     assert_eq!(resp, Ok(None));
 }
 
@@ -140,11 +238,15 @@ async fn test_sum_dict_svc() {
     let resp = task.call(SumDictRequest).await;
     assert_eq!(resp, Ok(None));
 
+    let params = subscriber.params_listener().get_latest().event;
     let sum_dict = Arc::new(dummy_sum_dict());
     publisher.broadcast_sum_dict(DictionaryUpdate::New(sum_dict.clone()));
     assert_ready!(task.poll_ready()).unwrap();
     let resp = task.call(SumDictRequest).await;
-    assert_eq!(resp, Ok(Some(sum_dict)));
+    assert_eq!(
+        resp,
+        Ok(Some(cached(&round_etag(0, &params), sum_dict.as_ref()))),
+    );
 
     publisher.broadcast_sum_dict(DictionaryUpdate::Invalidate);
     assert_ready!(task.poll_ready()).unwrap();
@@ -6,7 +6,7 @@ use xaynet_core::{
     common::{RoundParameters, RoundSeed},
     crypto::{ByteObject, EncryptKeyPair, PublicEncryptKey, SigningKeyPair},
     mask::{self, MaskConfig},
-    message::{Message, Sum},
+    message::{Message, Sum, PROTOCOL_VERSION},
 };
 
 pub fn mask_config() -> MaskConfig {
@@ -30,6 +30,9 @@ pub fn new_event_channels() -> (EventPublisher, EventSubscriber) {
         seed: RoundSeed::generate(),
         mask_config: mask_config().into(),
         model_length: 0,
+        privacy_budget: None,
+        version: PROTOCOL_VERSION,
+        starts_at: None,
     };
     let phase = PhaseName::Idle;
     let round_id = 0;
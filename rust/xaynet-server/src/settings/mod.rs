@@ -2,16 +2,23 @@
 //!
 //! Values defined in the configuration file can be overridden by environment variables. Examples of
 //! configuration files can be found in the `configs/` directory located in the repository root.
-
-#[cfg(feature = "tls")]
-use std::path::PathBuf;
-use std::{fmt, path::Path};
+//!
+//! The PET and logging settings can be changed while the coordinator is running by sending it a
+//! `SIGHUP`, which reloads the configuration file, see [`Settings::reload()`].
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use config::{Config, ConfigError, Environment};
 use redis::{ConnectionInfo, IntoConnectionInfo};
 use serde::{
     de::{self, Deserializer, Visitor},
     Deserialize,
+    Serialize,
 };
 use thiserror::Error;
 use tracing_subscriber::filter::EnvFilter;
@@ -19,14 +26,14 @@ use validator::{Validate, ValidationError, ValidationErrors};
 
 use xaynet_core::{
     mask::{BoundType, DataType, GroupType, MaskConfig, ModelType},
-    message::{SUM_COUNT_MIN, UPDATE_COUNT_MIN},
+    message::{PROTOCOL_VERSION, SUM_COUNT_MIN, UPDATE_COUNT_MIN},
 };
 
 #[cfg(feature = "model-persistence")]
 #[cfg_attr(docsrs, doc(cfg(feature = "model-persistence")))]
 pub mod s3;
 #[cfg(feature = "model-persistence")]
-pub use self::{s3::RestoreSettings, s3::S3BucketsSettings, s3::S3Settings};
+pub use self::{s3::S3BucketsSettings, s3::S3Settings};
 
 #[derive(Error, Debug)]
 /// An error related to loading and validation of settings.
@@ -35,9 +42,12 @@ pub enum SettingsError {
     Loading(#[from] ConfigError),
     #[error("validation failed: {0}")]
     Validation(#[from] ValidationErrors),
+    #[error("the {0} settings cannot be changed while the coordinator is running")]
+    Immutable(&'static str),
 }
 
 #[derive(Debug, Validate, Deserialize)]
+#[validate(schema(function = "validate_settings"))]
 /// The combined settings.
 ///
 /// Each section in the configuration file corresponds to the identically named settings field.
@@ -48,17 +58,42 @@ pub struct Settings {
     pub mask: MaskSettings,
     pub log: LoggingSettings,
     pub model: ModelSettings,
+    #[serde(default)]
+    #[validate]
+    pub aggregation: AggregationSettings,
+    #[serde(default)]
     #[validate]
     pub metrics: MetricsSettings,
+    #[serde(default)]
+    #[validate]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    #[validate]
+    pub model_storage: ModelStorageSettings,
     pub redis: RedisSettings,
     #[cfg(feature = "model-persistence")]
     #[validate]
     pub s3: S3Settings,
-    #[cfg(feature = "model-persistence")]
-    #[validate]
+    #[serde(default)]
     pub restore: RestoreSettings,
     #[serde(default)]
+    #[validate]
     pub trust_anchor: TrustAnchorSettings,
+    #[serde(default)]
+    #[validate]
+    pub protocol: ProtocolSettings,
+    #[serde(default)]
+    #[validate]
+    pub multipart: MultipartSettings,
+    #[serde(default)]
+    #[validate]
+    pub admin: Option<AdminSettings>,
+    #[serde(default)]
+    #[validate]
+    pub cluster: Option<ClusterSettings>,
+    #[serde(default)]
+    #[validate]
+    pub schedule: ScheduleSettings,
 }
 
 impl Settings {
@@ -72,16 +107,61 @@ impl Settings {
         Ok(settings)
     }
 
+    /// Reloads and validates the settings via a configuration file while the coordinator is
+    /// running.
+    ///
+    /// Only the PET and logging settings of the reloaded settings are applied by a running
+    /// coordinator. The masking settings, the model length and the differential privacy settings
+    /// must stay the same as the `current` ones, because the participants, the global models and
+    /// the privacy budget of the ongoing training depend on them.
+    ///
+    /// # Errors
+    /// Fails when the loading of the configuration file or its validation failed or when an
+    /// immutable setting has changed.
+    pub fn reload(
+        path: impl AsRef<Path>,
+        current_pet: &PetSettings,
+        current_mask: MaskSettings,
+        current_model: &ModelSettings,
+    ) -> Result<Self, SettingsError> {
+        let settings = Self::new(path)?;
+        if MaskConfig::from(settings.mask) != MaskConfig::from(current_mask) {
+            return Err(SettingsError::Immutable("mask"));
+        }
+        if settings.model.length != current_model.length {
+            return Err(SettingsError::Immutable("model"));
+        }
+        if settings.pet.dp != current_pet.dp {
+            return Err(SettingsError::Immutable("pet.dp"));
+        }
+        Ok(settings)
+    }
+
     fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
         let mut config = Config::new();
         config.merge(config::File::from(path.as_ref()))?;
         config.merge(Environment::with_prefix("xaynet").separator("__"))?;
         config.try_into()
     }
+
+    /// Checks that a cluster shares the coordinator data via Redis.
+    fn validate_settings(&self) -> Result<(), ValidationError> {
+        if self.cluster.is_some() && self.storage.backend != StorageBackend::Redis {
+            return Err(ValidationError::new(
+                "cluster requires the redis storage backend",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_settings(s: &Settings) -> Result<(), ValidationError> {
+    s.validate_settings()
 }
 
 /// The PET protocol count settings.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct PetSettingsCount {
     /// The minimal number of participants selected in a phase.
     pub min: u64,
@@ -90,7 +170,7 @@ pub struct PetSettingsCount {
 }
 
 /// The PET protocol time settings.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct PetSettingsTime {
     /// The minimal amount of time reserved for a phase.
     pub min: u64,
@@ -99,7 +179,7 @@ pub struct PetSettingsTime {
 }
 
 /// The PET protocol `sum` phase settings.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct PetSettingsSum {
     /// The probability of participants selected for preparing and computing the aggregated mask.
     /// The value must be between `0` and `1` (i.e. `0 < sum.prob < 1`).
@@ -167,7 +247,7 @@ pub struct PetSettingsSum {
 }
 
 /// The PET protocol `update` phase settings.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct PetSettingsUpdate {
     /// The probability of participants selected for submitting an updated local model for
     /// aggregation. The value must be between `0` and `1` (i.e. `0 < update.prob <= 1`). Here, `1`
@@ -240,7 +320,7 @@ pub struct PetSettingsUpdate {
 }
 
 /// The PET protocol `sum2` phase settings.
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct PetSettingsSum2 {
     /// The minimal and maximal number of participants selected for submitting the aggregated masks.
     ///
@@ -292,10 +372,190 @@ pub struct PetSettingsSum2 {
     /// XAYNET_PET__SUM2__TIME__MAX=3600
     /// ```
     pub time: PetSettingsTime,
+
+    /// The minimal number of sum participants that must agree on the aggregated mask, if any.
+    ///
+    /// If a quorum is set, the `sum2` phase doesn't fail when `sum2.count.min` messages haven't
+    /// been processed until `sum2.time.max` has elapsed, as long as at least `sum2.quorum` messages
+    /// have been processed. The global model is then unmasked with the aggregated mask that was
+    /// submitted by at least `sum2.quorum` sum participants. This keeps rounds alive if sum
+    /// participants drop out after the `sum` phase. The quorum must be greater or equal to `1`
+    /// (i.e. `sum2.quorum >= 1`) and less or equal to the minimal value of the `sum2` phase (i.e.
+    /// `sum2.quorum <= sum2.count.min`).
+    ///
+    /// The mask seeds are not secret-shared among the sum participants. Every sum participant
+    /// receives the encrypted mask seeds of all update participants, hence the mask of a single
+    /// sum participant unmasks the global model and nothing has to be recovered from the sum
+    /// participants which dropped out. The round still fails if fewer than `sum2.quorum` sum
+    /// participants submit the same mask.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.sum2]
+    /// quorum = 3
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__SUM2__QUORUM=3
+    /// ```
+    #[serde(default)]
+    pub quorum: Option<u64>,
+}
+
+/// The PET protocol differential privacy settings.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PetSettingsDp {
+    /// The noise multiplier which the participants are expected to use.
+    ///
+    /// The noise multiplier is the ratio of the standard deviation of the noise to the clipping
+    /// bound of the local models. It must be greater than `0` (i.e. `dp.noise_multiplier > 0`).
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.dp]
+    /// noise_multiplier = 1.1
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__DP__NOISE_MULTIPLIER=1.1
+    /// ```
+    pub noise_multiplier: f64,
+
+    /// The delta of the (epsilon, delta)-differential privacy guarantee of the global model.
+    ///
+    /// The privacy budget spent over the rounds is tracked wrt this delta. It must be between `0`
+    /// and `1` (i.e. `0 < dp.delta < 1`).
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.dp]
+    /// delta = 1e-5
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__DP__DELTA=1e-5
+    /// ```
+    pub delta: f64,
+}
+
+/// The PET protocol settings of the adaptive selection probability of a phase.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PetSettingsAdaptivePhase {
+    /// The minimal probability which the adaptive selection may choose. The value must be greater
+    /// than `0` (i.e. `0 < min_prob`).
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.adaptive.sum]
+    /// min_prob = 0.001
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__ADAPTIVE__SUM__MIN_PROB=0.001
+    /// ```
+    pub min_prob: f64,
+
+    /// The maximal probability which the adaptive selection may choose. The value must be greater
+    /// or equal to the minimal probability and less than `1` for the `sum` phase (i.e.
+    /// `min_prob <= max_prob < 1`), respectively less or equal to `1` for the `update` phase.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.adaptive.sum]
+    /// max_prob = 0.1
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__ADAPTIVE__SUM__MAX_PROB=0.1
+    /// ```
+    pub max_prob: f64,
+
+    /// The number of messages which the adaptive selection aims for in the phase, if any. It must
+    /// be within the count range of the phase (i.e. `sum.count.min <= adaptive.sum.target <=
+    /// sum.count.max`). Defaults to the middle of the count range.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.adaptive.sum]
+    /// target = 20
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__ADAPTIVE__SUM__TARGET=20
+    /// ```
+    #[serde(default)]
+    pub target: Option<u64>,
+}
+
+/// The PET protocol settings for adapting the selection probabilities between rounds.
+///
+/// The coordinator estimates the number of participants from the messages and the durations of
+/// the `sum` and `update` phases of the previous rounds. Before each round, it chooses the
+/// probabilities which are expected to yield the target number of messages.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PetSettingsAdaptive {
+    /// The adaptive selection settings of the `sum` phase.
+    pub sum: PetSettingsAdaptivePhase,
+
+    /// The adaptive selection settings of the `update` phase.
+    pub update: PetSettingsAdaptivePhase,
+
+    /// The maximal factor by which a probability changes from one round to the next. It must be
+    /// greater or equal to `1` (i.e. `adaptive.max_step >= 1`).
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.adaptive]
+    /// max_step = 2.0
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__ADAPTIVE__MAX_STEP=2.0
+    /// ```
+    pub max_step: f64,
+
+    /// The weight of the latest round in the estimated number of participants, the previous
+    /// rounds are weighted exponentially decreasing. It must be between `0` and `1` (i.e.
+    /// `0 < adaptive.smoothing <= 1`), where `1` only takes the latest round into account.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [pet.adaptive]
+    /// smoothing = 0.5
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PET__ADAPTIVE__SMOOTHING=0.5
+    /// ```
+    pub smoothing: f64,
 }
 
 /// The PET protocol settings.
-#[derive(Debug, Validate, Deserialize, Clone, Copy)]
+#[derive(Debug, Validate, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[validate(schema(function = "validate_pet"))]
 pub struct PetSettings {
     /// The PET settings for the `sum` phase.
@@ -304,6 +564,13 @@ pub struct PetSettings {
     pub update: PetSettingsUpdate,
     /// The PET settings for the `sum2` phase.
     pub sum2: PetSettingsSum2,
+    /// The PET settings for differential privacy, if enabled.
+    #[serde(default)]
+    pub dp: Option<PetSettingsDp>,
+    /// The PET settings for adapting the selection probabilities, if enabled. Otherwise, the
+    /// probabilities of the `sum` and `update` settings are used in every round.
+    #[serde(default)]
+    pub adaptive: Option<PetSettingsAdaptive>,
 }
 
 impl PetSettings {
@@ -311,7 +578,9 @@ impl PetSettings {
     fn validate_pet(&self) -> Result<(), ValidationError> {
         self.validate_counts()?;
         self.validate_times()?;
-        self.validate_probabilities()
+        self.validate_probabilities()?;
+        self.validate_dp()?;
+        self.validate_adaptive()
     }
 
     /// Checks the validity of phase count ranges.
@@ -325,6 +594,9 @@ impl PetSettings {
             && self.sum2.count.min <= self.sum2.count.max
             && self.sum2.count.min <= self.sum.count.max
             && self.sum2.count.max <= self.sum.count.max
+            && self.sum2.quorum.map_or(true, |quorum| {
+                SUM_COUNT_MIN <= quorum && quorum <= self.sum2.count.min
+            })
         {
             Ok(())
         } else {
@@ -358,6 +630,42 @@ impl PetSettings {
             Err(ValidationError::new("starvation"))
         }
     }
+
+    /// Checks the validity of the differential privacy parameters.
+    fn validate_dp(&self) -> Result<(), ValidationError> {
+        if self.dp.map_or(true, |dp| {
+            0. < dp.noise_multiplier && 0. < dp.delta && dp.delta < 1.
+        }) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("invalid dp parameter(s)"))
+        }
+    }
+
+    /// Checks the validity of the adaptive selection parameters.
+    fn validate_adaptive(&self) -> Result<(), ValidationError> {
+        fn valid_phase(phase: &PetSettingsAdaptivePhase, count: &PetSettingsCount) -> bool {
+            0. < phase.min_prob
+                && phase.min_prob <= phase.max_prob
+                && phase.max_prob <= 1.
+                && phase
+                    .target
+                    .map_or(true, |target| count.min <= target && target <= count.max)
+        }
+
+        if self.adaptive.map_or(true, |adaptive| {
+            valid_phase(&adaptive.sum, &self.sum.count)
+                && adaptive.sum.max_prob < 1.
+                && valid_phase(&adaptive.update, &self.update.count)
+                && 1. <= adaptive.max_step
+                && 0. < adaptive.smoothing
+                && adaptive.smoothing <= 1.
+        }) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("invalid adaptive parameter(s)"))
+        }
+    }
 }
 
 /// A wrapper for validate derive.
@@ -477,219 +785,1334 @@ fn validate_api(s: &ApiSettings) -> Result<(), ValidationError> {
     s.validate_api()
 }
 
-#[derive(Debug, Validate, Deserialize, Clone, Copy)]
-/// Masking settings.
-pub struct MaskSettings {
-    /// The order of the finite group.
+#[derive(Debug, Validate, Deserialize, Clone)]
+/// Admin API settings.
+///
+/// The admin API is disabled if the settings are missing.
+pub struct AdminSettings {
+    /// The address to which the admin API should be bound. The admin API should not be reachable
+    /// by the participants.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [mask]
-    /// group_type = "Integer"
+    /// [admin]
+    /// bind_address = "127.0.0.1:8082"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_MASK__GROUP_TYPE=Integer
+    /// XAYNET_ADMIN__BIND_ADDRESS=127.0.0.1:8082
     /// ```
-    pub group_type: GroupType,
+    pub bind_address: std::net::SocketAddr,
 
-    /// The data type of the numbers to be masked.
+    #[validate(length(min = 16))]
+    /// The secret token to authenticate requests to the admin API. It must be sent as a bearer
+    /// token in the `Authorization` header and be at least 16 characters long.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [mask]
-    /// data_type = "F32"
+    /// [admin]
+    /// token = "a-long-and-random-secret"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_MASK__DATA_TYPE=F32
+    /// XAYNET_ADMIN__TOKEN=a-long-and-random-secret
     /// ```
-    pub data_type: DataType,
+    pub token: String,
+}
 
-    /// The bounds of the numbers to be masked.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// The role of a coordinator in a cluster.
+pub enum ClusterRole {
+    /// A candidate for the leader, which runs the state machine once it has been elected.
+    Leader,
+    /// A frontend, which processes the PET messages and forwards them to the leader.
+    Frontend,
+}
+
+#[derive(Debug, Validate, Deserialize, Clone)]
+#[validate(schema(function = "validate_cluster"))]
+/// Cluster settings.
+///
+/// A single coordinator runs the state machine and the REST API if the settings are missing. In a
+/// cluster, the state machine runs on a single leader, which is elected among the leader
+/// candidates via Redis, while any number of frontends decrypt, parse and validate the PET
+/// messages and forward them to the leader. The frontends receive the events of the state machine
+/// from the leader to serve the data of the round. A cluster requires the `redis` storage
+/// backend. A newly elected leader resumes the round of its predecessor if the
+/// [`RestoreSettings`] are enabled.
+pub struct ClusterSettings {
+    /// The role of the coordinator in the cluster.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [mask]
-    /// bound_type = "B0"
+    /// [cluster]
+    /// role = "frontend"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_MASK__BOUND_TYPE=B0
+    /// XAYNET_CLUSTER__ROLE=frontend
     /// ```
-    pub bound_type: BoundType,
+    pub role: ClusterRole,
 
-    /// The maximum number of models to be aggregated.
+    /// The address to which the cluster API of a leader should be bound. It must be set for the
+    /// `leader` role. The cluster API should only be reachable by the coordinators of the cluster,
+    /// because the events streamed to the frontends include the encryption keys of the
+    /// coordinator. Without TLS, the address must be a loopback or private address.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [mask]
-    /// model_type = "M3"
+    /// [cluster]
+    /// bind_address = "10.0.0.1:8083"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_MASK__MODEL_TYPE=M3
+    /// XAYNET_CLUSTER__BIND_ADDRESS=10.0.0.1:8083
     /// ```
-    pub model_type: ModelType,
-}
-
-impl From<MaskSettings> for MaskConfig {
-    fn from(
-        MaskSettings {
-            group_type,
-            data_type,
-            bound_type,
-            model_type,
-        }: MaskSettings,
-    ) -> MaskConfig {
-        MaskConfig {
-            group_type,
-            data_type,
-            bound_type,
-            model_type,
-        }
-    }
-}
+    pub bind_address: Option<std::net::SocketAddr>,
 
-#[derive(Debug, Deserialize, Clone)]
-/// Model settings.
-pub struct ModelSettings {
-    /// The expected length of the model. The model length corresponds to the number of elements.
-    /// This value is used to validate the uniform length of the submitted models/masks.
+    /// The URL of the cluster API of a leader under which the frontends reach it. It must be set
+    /// for the `leader` role and be unique among the leader candidates.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [model]
-    /// length = 100
+    /// [cluster]
+    /// url = "http://10.0.0.1:8083"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_MODEL__LENGTH=100
+    /// XAYNET_CLUSTER__URL=http://10.0.0.1:8083
     /// ```
-    pub length: usize,
-}
-
-#[derive(Debug, Deserialize, Validate)]
-/// Metrics settings.
-pub struct MetricsSettings {
-    #[validate]
-    /// Settings for the InfluxDB backend.
-    pub influxdb: InfluxSettings,
-}
+    pub url: Option<String>,
 
-#[derive(Debug, Deserialize, Validate)]
-/// InfluxDB settings.
-pub struct InfluxSettings {
-    #[validate(url)]
-    /// The URL where InfluxDB is running.
+    #[validate(length(min = 16))]
+    /// The secret token shared by the coordinators of the cluster to authenticate requests to the
+    /// cluster API. It must be at least 16 characters long.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [metrics.influxdb]
-    /// url = "http://localhost:8086"
+    /// [cluster]
+    /// token = "a-long-and-random-secret"
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_METRICS__INFLUXDB__URL=http://localhost:8086
+    /// XAYNET_CLUSTER__TOKEN=a-long-and-random-secret
     /// ```
-    pub url: String,
+    pub token: String,
 
-    /// The InfluxDB database name.
+    /// The number of seconds after which the leadership expires if the leader fails to renew
+    /// it. Defaults to 10 seconds. Another leader candidate is elected afterwards.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [metrics.influxdb]
-    /// db = "test"
+    /// [cluster]
+    /// lease = 10
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_METRICS__INFLUXDB__DB=test
+    /// XAYNET_CLUSTER__LEASE=10
     /// ```
-    pub db: String,
-}
+    pub lease: Option<u64>,
 
-#[derive(Debug, Deserialize)]
-/// Redis settings.
-pub struct RedisSettings {
-    /// The URL where Redis is running.
+    #[cfg(feature = "tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+    /// The path to the certificate of the cluster API. A leader serves the cluster API via TLS if
+    /// this is present, in which case `tls_key` must also be present for the `leader` role. The
+    /// frontends trust this certificate in addition to the common root certificates.
     ///
-    /// The format of the URL is `redis://[<username>][:<passwd>@]<hostname>[:port][/<db>]`.
+    /// Requires the `tls` feature to be enabled.
     ///
     /// # Examples
     ///
     /// **TOML**
     /// ```text
-    /// [redis]
-    /// url = "redis://127.0.0.1/"
+    /// [cluster]
+    /// tls_certificate = path/to/tls/files/cluster.pem
     /// ```
     ///
     /// **Environment variable**
     /// ```text
-    /// XAYNET_REDIS__URL=redis://127.0.0.1/
+    /// XAYNET_CLUSTER__TLS_CERTIFICATE=path/to/tls/files/cluster.pem
     /// ```
-    #[serde(deserialize_with = "deserialize_redis_url")]
-    pub url: ConnectionInfo,
-}
-
-fn deserialize_redis_url<'de, D>(deserializer: D) -> Result<ConnectionInfo, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct ConnectionInfoVisitor;
+    pub tls_certificate: Option<PathBuf>,
+
+    #[cfg(feature = "tls")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
+    /// The path to the private key of the cluster API of a leader. If this is present, then
+    /// `tls_certificate` must also be present.
+    ///
+    /// Requires the `tls` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [cluster]
+    /// tls_key = path/to/tls/files/cluster.rsa
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_CLUSTER__TLS_KEY=path/to/tls/files/cluster.rsa
+    /// ```
+    pub tls_key: Option<PathBuf>,
+}
+
+impl ClusterSettings {
+    /// Gets the duration of the leadership.
+    pub fn lease(&self) -> Duration {
+        Duration::from_secs(self.lease.unwrap_or(10))
+    }
+
+    /// Checks whether the cluster API of a leader is served via TLS.
+    pub fn has_tls(&self) -> bool {
+        #[cfg(feature = "tls")]
+        {
+            self.tls_certificate.is_some() && self.tls_key.is_some()
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            false
+        }
+    }
+
+    /// Checks that a leader has a cluster API, which is either served via TLS or only reachable on
+    /// a loopback or private address, and that the lease is positive.
+    fn validate_cluster(&self) -> Result<(), ValidationError> {
+        if self.role == ClusterRole::Leader {
+            let bind_address = match (&self.bind_address, &self.url) {
+                (Some(bind_address), Some(_)) => bind_address,
+                _ => return Err(ValidationError::new("missing leader bind_address or url")),
+            };
+            #[cfg(feature = "tls")]
+            {
+                if self.tls_certificate.is_some() != self.tls_key.is_some() {
+                    return Err(ValidationError::new("invalid cluster tls settings"));
+                }
+            }
+            if !self.has_tls() && !is_internal_address(bind_address) {
+                return Err(ValidationError::new(
+                    "cluster api bound to a public address without tls",
+                ));
+            }
+        }
+        if self.lease == Some(0) {
+            return Err(ValidationError::new("zero cluster lease"));
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether the `address` is a loopback or private address.
+fn is_internal_address(address: &SocketAddr) -> bool {
+    match address.ip() {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private(),
+        // unique local addresses fc00::/7
+        IpAddr::V6(ip) => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_cluster(s: &ClusterSettings) -> Result<(), ValidationError> {
+    s.validate_cluster()
+}
+
+#[derive(Debug, Validate, Deserialize, Clone, Copy)]
+/// Masking settings.
+pub struct MaskSettings {
+    /// The order of the finite group.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [mask]
+    /// group_type = "Integer"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MASK__GROUP_TYPE=Integer
+    /// ```
+    pub group_type: GroupType,
+
+    /// The data type of the numbers to be masked.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [mask]
+    /// data_type = "F32"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MASK__DATA_TYPE=F32
+    /// ```
+    pub data_type: DataType,
+
+    /// The bounds of the numbers to be masked.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [mask]
+    /// bound_type = "B0"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MASK__BOUND_TYPE=B0
+    /// ```
+    pub bound_type: BoundType,
+
+    /// The maximum number of models to be aggregated.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [mask]
+    /// model_type = "M3"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MASK__MODEL_TYPE=M3
+    /// ```
+    pub model_type: ModelType,
+}
+
+impl From<MaskSettings> for MaskConfig {
+    fn from(
+        MaskSettings {
+            group_type,
+            data_type,
+            bound_type,
+            model_type,
+        }: MaskSettings,
+    ) -> MaskConfig {
+        MaskConfig {
+            group_type,
+            data_type,
+            bound_type,
+            model_type,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+/// Model settings.
+pub struct ModelSettings {
+    /// The expected length of the model. The model length corresponds to the number of elements.
+    /// This value is used to validate the uniform length of the submitted models/masks.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [model]
+    /// length = 100
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MODEL__LENGTH=100
+    /// ```
+    pub length: usize,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+/// The strategy to compute the global model from the unmasked aggregated local models.
+pub enum AggregationStrategy {
+    /// Federated averaging, i.e. the unmasked aggregated model becomes the global model.
+    FedAvg,
+    /// Federated averaging with server-side momentum.
+    FedAvgM,
+    /// Federated averaging with the Adam optimizer on the server.
+    FedAdam,
+    /// Federated averaging with the Yogi optimizer on the server.
+    FedYogi,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone, Copy)]
+#[validate(schema(function = "validate_aggregation"))]
+#[serde(default)]
+/// Aggregation settings.
+///
+/// Apart from `FedAvg`, the strategies treat the difference between the unmasked aggregated model
+/// and the previous global model as a pseudo-gradient and apply a server-side optimizer to it.
+pub struct AggregationSettings {
+    /// The aggregation strategy. Defaults to `FedAvg`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// strategy = "FedAdam"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__STRATEGY=FedAdam
+    /// ```
+    pub strategy: AggregationStrategy,
+
+    /// The server learning rate of the `FedAvgM`, `FedAdam` and `FedYogi` strategies. It must be
+    /// greater than `0` (i.e. `server_lr > 0`). Defaults to `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// server_lr = 0.01
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__SERVER_LR=0.01
+    /// ```
+    pub server_lr: f64,
+
+    /// The server momentum of the `FedAvgM` strategy. It must be between `0` and `1` (i.e.
+    /// `0 <= momentum < 1`). Defaults to `0.9`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// momentum = 0.9
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__MOMENTUM=0.9
+    /// ```
+    pub momentum: f64,
+
+    /// The decay rate of the first moment of the `FedAdam` and `FedYogi` strategies. It must be
+    /// between `0` and `1` (i.e. `0 <= beta1 < 1`). Defaults to `0.9`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// beta1 = 0.9
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__BETA1=0.9
+    /// ```
+    pub beta1: f64,
+
+    /// The decay rate of the second moment of the `FedAdam` and `FedYogi` strategies. It must be
+    /// between `0` and `1` (i.e. `0 <= beta2 < 1`). Defaults to `0.99`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// beta2 = 0.99
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__BETA2=0.99
+    /// ```
+    pub beta2: f64,
+
+    /// The degree of adaptivity of the `FedAdam` and `FedYogi` strategies. It must be greater
+    /// than `0` (i.e. `tau > 0`). Defaults to `1e-3`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [aggregation]
+    /// tau = 1e-3
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_AGGREGATION__TAU=1e-3
+    /// ```
+    pub tau: f64,
+}
+
+impl Default for AggregationSettings {
+    fn default() -> Self {
+        Self {
+            strategy: AggregationStrategy::FedAvg,
+            server_lr: 1.,
+            momentum: 0.9,
+            beta1: 0.9,
+            beta2: 0.99,
+            tau: 1e-3,
+        }
+    }
+}
+
+impl AggregationSettings {
+    /// Checks the validity of the optimizer parameters.
+    fn validate_aggregation(&self) -> Result<(), ValidationError> {
+        if 0. < self.server_lr
+            && (0. ..1.).contains(&self.momentum)
+            && (0. ..1.).contains(&self.beta1)
+            && (0. ..1.).contains(&self.beta2)
+            && 0. < self.tau
+        {
+            Ok(())
+        } else {
+            Err(ValidationError::new("invalid aggregation parameter(s)"))
+        }
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_aggregation(s: &AggregationSettings) -> Result<(), ValidationError> {
+    s.validate_aggregation()
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+/// Metrics settings.
+///
+/// The metrics are recorded by each of the configured backends. No metrics are recorded if none
+/// of the backends is configured.
+pub struct MetricsSettings {
+    #[serde(default)]
+    #[validate]
+    /// Settings for the InfluxDB backend.
+    pub influxdb: Option<InfluxSettings>,
+
+    #[serde(default)]
+    /// Settings for the Prometheus backend.
+    pub prometheus: Option<PrometheusSettings>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+/// InfluxDB settings.
+pub struct InfluxSettings {
+    #[validate(url)]
+    /// The URL where InfluxDB is running.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [metrics.influxdb]
+    /// url = "http://localhost:8086"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_METRICS__INFLUXDB__URL=http://localhost:8086
+    /// ```
+    pub url: String,
+
+    /// The InfluxDB database name.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [metrics.influxdb]
+    /// db = "test"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_METRICS__INFLUXDB__DB=test
+    /// ```
+    pub db: String,
+}
+
+#[derive(Debug, Deserialize)]
+/// Prometheus settings.
+pub struct PrometheusSettings {
+    /// The address to which the Prometheus `/metrics` endpoint should be bound.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [metrics.prometheus]
+    /// bind_address = "0.0.0.0:9090"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_METRICS__PROMETHEUS__BIND_ADDRESS=0.0.0.0:9090
+    /// ```
+    pub bind_address: std::net::SocketAddr,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// The backend to store the coordinator data in.
+pub enum StorageBackend {
+    /// A Redis server, see the [`RedisSettings`].
+    Redis,
+    /// The memory of the coordinator process. The coordinator data is lost on a restart.
+    Memory,
+    #[cfg(feature = "embedded-storage")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "embedded-storage")))]
+    /// An embedded database in the directory of the `storage.path` setting.
+    Sled,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone)]
+#[validate(schema(function = "validate_storage"))]
+#[serde(default)]
+/// Storage settings.
+pub struct StorageSettings {
+    /// The backend to store the coordinator data in. Defaults to `redis`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [storage]
+    /// backend = "memory"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_STORAGE__BACKEND=memory
+    /// ```
+    pub backend: StorageBackend,
+
+    /// The directory of the embedded database. It must be set if the `sled` backend is selected.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [storage]
+    /// path = "/var/lib/xaynet/db"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_STORAGE__PATH=/var/lib/xaynet/db
+    /// ```
+    pub path: Option<PathBuf>,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::Redis,
+            path: None,
+        }
+    }
+}
+
+impl StorageSettings {
+    /// Checks that the database directory is set for the embedded backend.
+    fn validate_storage(&self) -> Result<(), ValidationError> {
+        #[cfg(feature = "embedded-storage")]
+        if self.backend == StorageBackend::Sled && self.path.is_none() {
+            return Err(ValidationError::new("missing storage path"));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_storage(s: &StorageSettings) -> Result<(), ValidationError> {
+    s.validate_storage()
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// The backend to store the global models in.
+pub enum ModelStorageBackend {
+    /// No backend. The global models are not persisted.
+    NoOp,
+    /// A local directory, see the `model_storage.path` setting.
+    Filesystem,
+    #[cfg(feature = "model-persistence")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "model-persistence")))]
+    /// An S3 compatible object storage, see the [`S3Settings`].
+    S3,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone)]
+#[validate(schema(function = "validate_model_storage"))]
+#[serde(default)]
+/// Model storage settings.
+pub struct ModelStorageSettings {
+    /// The backend to store the global models in. Defaults to `s3` if the `model-persistence`
+    /// feature is enabled and to `noop` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [model_storage]
+    /// backend = "filesystem"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MODEL_STORAGE__BACKEND=filesystem
+    /// ```
+    pub backend: ModelStorageBackend,
+
+    /// The directory of the global models. It must be set if the `filesystem` backend is
+    /// selected.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [model_storage]
+    /// path = "/var/lib/xaynet/models"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MODEL_STORAGE__PATH=/var/lib/xaynet/models
+    /// ```
+    pub path: Option<PathBuf>,
+
+    /// The maximal number of global models to keep in the `filesystem` backend and in the global
+    /// model history. The latest global model is always kept. Unlimited if not set.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [model_storage]
+    /// max_models = 10
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MODEL_STORAGE__MAX_MODELS=10
+    /// ```
+    pub max_models: Option<usize>,
+
+    /// The maximal age in seconds of global models to keep in the `filesystem` backend and in the
+    /// global model history. The latest global model is always kept. Unlimited if not set.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [model_storage]
+    /// max_age = 86400
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MODEL_STORAGE__MAX_AGE=86400
+    /// ```
+    pub max_age: Option<u64>,
+}
+
+impl Default for ModelStorageSettings {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "model-persistence")]
+            backend: ModelStorageBackend::S3,
+            #[cfg(not(feature = "model-persistence"))]
+            backend: ModelStorageBackend::NoOp,
+            path: None,
+            max_models: None,
+            max_age: None,
+        }
+    }
+}
+
+impl ModelStorageSettings {
+    /// Checks that the model directory is set for the filesystem backend and that at least one
+    /// global model is kept.
+    fn validate_model_storage(&self) -> Result<(), ValidationError> {
+        if self.backend == ModelStorageBackend::Filesystem && self.path.is_none() {
+            return Err(ValidationError::new("missing model storage path"));
+        }
+        if self.max_models == Some(0) {
+            return Err(ValidationError::new("invalid max models"));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_model_storage(s: &ModelStorageSettings) -> Result<(), ValidationError> {
+    s.validate_model_storage()
+}
+
+#[derive(Debug, Deserialize, Validate, Clone, Copy)]
+#[serde(default)]
+/// Restore settings.
+///
+/// Restoring works with every persistent coordinator storage backend, i.e. `redis` and `sled`,
+/// and doesn't require the `model-persistence` feature. The global model is only restored if the
+/// model storage keeps it, i.e. with the `filesystem` or `s3` model storage backend; otherwise the
+/// coordinator resumes from the stored coordinator state without a global model.
+pub struct RestoreSettings {
+    /// If set to `false`, the restoring of coordinator state is prevented.
+    /// Instead, the state is reset and the coordinator is started with the
+    /// settings of the configuration file. Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [restore]
+    /// enable = true
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_RESTORE__ENABLE=false
+    /// ```
+    pub enable: bool,
+
+    /// The minimal number of seconds between two round checkpoints during a phase. Defaults to
+    /// 10 seconds.
+    ///
+    /// The round checkpoint holds the aggregated masked models, hence it is only saved at the
+    /// start of each phase and afterwards at most once per interval when a message has been
+    /// accepted. In between, only the counters of the phase are saved after each accepted
+    /// message. An update phase can only be resumed from a round checkpoint which has been saved
+    /// after the last accepted update message, otherwise a new round is started. An interval of
+    /// `0` saves the round checkpoint after each accepted message.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [restore]
+    /// checkpoint_interval = 10
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_RESTORE__CHECKPOINT_INTERVAL=10
+    /// ```
+    pub checkpoint_interval: u64,
+}
+
+impl Default for RestoreSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            checkpoint_interval: 10,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+/// Redis settings.
+pub struct RedisSettings {
+    /// The URL where Redis is running.
+    ///
+    /// The format of the URL is `redis://[<username>][:<passwd>@]<hostname>[:port][/<db>]`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [redis]
+    /// url = "redis://127.0.0.1/"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_REDIS__URL=redis://127.0.0.1/
+    /// ```
+    #[serde(deserialize_with = "deserialize_redis_url")]
+    pub url: ConnectionInfo,
+}
+
+fn deserialize_redis_url<'de, D>(deserializer: D) -> Result<ConnectionInfo, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ConnectionInfoVisitor;
 
     impl<'de> Visitor<'de> for ConnectionInfoVisitor {
         type Value = ConnectionInfo;
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            write!(
-                formatter,
-                "redis://[<username>][:<passwd>@]<hostname>[:port][/<db>]"
-            )
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                formatter,
+                "redis://[<username>][:<passwd>@]<hostname>[:port][/<db>]"
+            )
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            value
+                .into_connection_info()
+                .map_err(|_| de::Error::invalid_value(serde::de::Unexpected::Str(value), &self))
+        }
+    }
+
+    deserializer.deserialize_str(ConnectionInfoVisitor)
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// The backend to publish the proofs of the global models to.
+pub enum TrustAnchorBackend {
+    /// No backend. The proofs are not published.
+    NoOp,
+    /// A hash-chained log file in the directory of the `trust_anchor.path` setting.
+    Log,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone)]
+#[validate(schema(function = "validate_trust_anchor"))]
+#[serde(default)]
+/// Trust anchor settings.
+pub struct TrustAnchorSettings {
+    /// The backend to publish the proofs of the global models to. Defaults to `noop`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [trust_anchor]
+    /// backend = "log"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_TRUST_ANCHOR__BACKEND=log
+    /// ```
+    pub backend: TrustAnchorBackend,
+
+    /// The directory of the proof log and its signing keys. It must be set if the `log` backend
+    /// is selected.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [trust_anchor]
+    /// path = "/var/lib/xaynet/proofs"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_TRUST_ANCHOR__PATH=/var/lib/xaynet/proofs
+    /// ```
+    pub path: Option<PathBuf>,
+}
+
+impl Default for TrustAnchorSettings {
+    fn default() -> Self {
+        Self {
+            backend: TrustAnchorBackend::NoOp,
+            path: None,
+        }
+    }
+}
+
+impl TrustAnchorSettings {
+    /// Checks that the log directory is set for the log backend.
+    fn validate_trust_anchor(&self) -> Result<(), ValidationError> {
+        if self.backend == TrustAnchorBackend::Log && self.path.is_none() {
+            return Err(ValidationError::new("missing trust anchor path"));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_trust_anchor(s: &TrustAnchorSettings) -> Result<(), ValidationError> {
+    s.validate_trust_anchor()
+}
+
+#[derive(Debug, Deserialize, Validate, Clone, Copy)]
+#[validate(schema(function = "validate_protocol"))]
+#[serde(default)]
+/// PET protocol settings.
+pub struct ProtocolSettings {
+    /// The minimal protocol version of the accepted messages. Defaults to `0`, which are the
+    /// messages of participants that predate the protocol versioning.
+    ///
+    /// Raising the minimal version rejects the messages of outdated participants, which allows to
+    /// roll out a protocol change once enough participants have been updated.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [protocol]
+    /// min_version = 1
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PROTOCOL__MIN_VERSION=1
+    /// ```
+    pub min_version: u16,
+
+    /// The maximal protocol version of the accepted messages. Defaults to the protocol version of
+    /// the coordinator, which is also the highest version it can parse.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [protocol]
+    /// max_version = 2
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_PROTOCOL__MAX_VERSION=1
+    /// ```
+    pub max_version: u16,
+}
+
+impl Default for ProtocolSettings {
+    fn default() -> Self {
+        Self {
+            min_version: 0,
+            max_version: PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl ProtocolSettings {
+    /// Checks that the version range is not empty and supported by the coordinator.
+    fn validate_protocol(&self) -> Result<(), ValidationError> {
+        if self.min_version > self.max_version {
+            return Err(ValidationError::new("empty protocol version range"));
+        }
+        if self.max_version > PROTOCOL_VERSION {
+            return Err(ValidationError::new("unsupported protocol version"));
+        }
+        Ok(())
+    }
+
+    /// Checks whether messages of the protocol `version` are accepted.
+    pub fn accepts(&self, version: u16) -> bool {
+        (self.min_version..=self.max_version).contains(&version)
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_protocol(s: &ProtocolSettings) -> Result<(), ValidationError> {
+    s.validate_protocol()
+}
+
+#[derive(Debug, Deserialize, Validate, Clone, Copy)]
+#[validate(schema(function = "validate_multipart"))]
+#[serde(default)]
+/// Settings for the incomplete multipart messages buffered by the coordinator.
+pub struct MultipartSettings {
+    /// The maximal number of bytes of the chunks of a single multipart message. Defaults to
+    /// 256 MiB. The incomplete message is dropped if it exceeds the limit. The payload of a
+    /// compressed message is rejected if it decompresses to more bytes than the limit.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [multipart]
+    /// max_message_size = 268435456
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MULTIPART__MAX_MESSAGE_SIZE=268435456
+    /// ```
+    pub max_message_size: usize,
+
+    /// The maximal number of bytes of the chunks of all incomplete multipart messages, where each
+    /// message and each chunk is charged a small fixed overhead in addition to its data. Defaults
+    /// to 1 GiB. Further chunks are rejected until enough messages are completed or evicted.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [multipart]
+    /// max_buffered_size = 1073741824
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MULTIPART__MAX_BUFFERED_SIZE=1073741824
+    /// ```
+    pub max_buffered_size: usize,
+
+    /// The number of seconds after which an incomplete multipart message without new chunks is
+    /// evicted. Defaults to 300 seconds. Independently of the timeout, all incomplete messages
+    /// are evicted when the phase changes.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [multipart]
+    /// timeout = 300
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_MULTIPART__TIMEOUT=300
+    /// ```
+    pub timeout: u64,
+}
+
+impl Default for MultipartSettings {
+    fn default() -> Self {
+        Self {
+            max_message_size: 256 * 1024 * 1024,
+            max_buffered_size: 1024 * 1024 * 1024,
+            timeout: 300,
         }
+    }
+}
 
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            value
-                .into_connection_info()
-                .map_err(|_| de::Error::invalid_value(serde::de::Unexpected::Str(value), &self))
+impl MultipartSettings {
+    /// Checks that a single message fits into the buffer and that the timeout is positive.
+    fn validate_multipart(&self) -> Result<(), ValidationError> {
+        if self.max_message_size > self.max_buffered_size {
+            return Err(ValidationError::new(
+                "max_message_size exceeds max_buffered_size",
+            ));
         }
+        if self.timeout == 0 {
+            return Err(ValidationError::new("zero multipart timeout"));
+        }
+        Ok(())
     }
+}
 
-    deserializer.deserialize_str(ConnectionInfoVisitor)
+/// A wrapper for validate derive.
+fn validate_multipart(s: &MultipartSettings) -> Result<(), ValidationError> {
+    s.validate_multipart()
 }
 
-#[derive(Debug, Deserialize, Validate)]
-/// Trust anchor settings.
-pub struct TrustAnchorSettings {}
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// What the coordinator does once the maximal number of rounds has been reached.
+pub enum RoundLimitAction {
+    /// The coordinator stays in the `idle` phase until it is shut down.
+    Idle,
+    /// The coordinator shuts down.
+    Shutdown,
+}
 
-// Default value for the global models bucket
-impl Default for TrustAnchorSettings {
+impl Default for RoundLimitAction {
     fn default() -> Self {
-        Self {}
+        Self::Idle
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Validate, Clone, Copy)]
+#[serde(default)]
+/// Settings for the scheduling of the rounds.
+///
+/// By default, the coordinator starts a new round right after the previous round ended.
+pub struct ScheduleSettings {
+    /// The minimal number of seconds between the end of a round and the start of the next round.
+    /// Defaults to `0`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule]
+    /// cooldown = 600
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__COOLDOWN=600
+    /// ```
+    pub cooldown: u64,
+
+    /// The daily time window in which rounds start, if any.
+    #[validate]
+    pub window: Option<ScheduleWindowSettings>,
+
+    /// The maximal number of rounds, if any. The coordinator doesn't start a round whose round
+    /// id exceeds the limit. Note that the round id also advances with failed and skipped rounds
+    /// and when the coordinator is restarted.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule]
+    /// max_rounds = 100
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__MAX_ROUNDS=100
+    /// ```
+    pub max_rounds: Option<u64>,
+
+    /// What the coordinator does once the maximal number of rounds has been reached. Defaults to
+    /// `idle`.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule]
+    /// after_max_rounds = "shutdown"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__AFTER_MAX_ROUNDS=shutdown
+    /// ```
+    pub after_max_rounds: RoundLimitAction,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone, Copy)]
+#[validate(schema(function = "validate_window"))]
+/// Settings for the daily time window in which rounds start.
+///
+/// A round which started within the window isn't aborted at the end of the window, its duration
+/// is bounded by the phase time settings.
+pub struct ScheduleWindowSettings {
+    /// The local time of day (`HH:MM`) at which the window opens.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule.window]
+    /// start = "22:00"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__WINDOW__START=22:00
+    /// ```
+    #[serde(deserialize_with = "deserialize_time_of_day")]
+    pub start: u32,
+
+    /// The local time of day (`HH:MM`) at which the window closes. The window spans midnight if
+    /// it closes before it opens.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule.window]
+    /// end = "05:00"
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__WINDOW__END=05:00
+    /// ```
+    #[serde(deserialize_with = "deserialize_time_of_day")]
+    pub end: u32,
+
+    /// The offset of the local time to UTC in minutes. Defaults to `0`. Daylight saving time
+    /// isn't taken into account.
+    ///
+    /// # Examples
+    ///
+    /// **TOML**
+    /// ```text
+    /// [schedule.window]
+    /// utc_offset = 60
+    /// ```
+    ///
+    /// **Environment variable**
+    /// ```text
+    /// XAYNET_SCHEDULE__WINDOW__UTC_OFFSET=60
+    /// ```
+    #[serde(default)]
+    pub utc_offset: i32,
+}
+
+impl ScheduleWindowSettings {
+    /// Checks that the window isn't empty and that the offset is less than a day.
+    fn validate_window(&self) -> Result<(), ValidationError> {
+        if self.start == self.end {
+            return Err(ValidationError::new("empty schedule window"));
+        }
+        if self.utc_offset.abs() >= 24 * 60 {
+            return Err(ValidationError::new("invalid utc_offset"));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper for validate derive.
+fn validate_window(s: &ScheduleWindowSettings) -> Result<(), ValidationError> {
+    s.validate_window()
+}
+
+/// Deserializes a time of day (`HH:MM`) into the seconds since midnight.
+fn deserialize_time_of_day<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct TimeOfDayVisitor;
+
+    impl<'de> Visitor<'de> for TimeOfDayVisitor {
+        type Value = u32;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a time of day: HH:MM")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            let parse = |part: &str, max: u32| part.parse::<u32>().ok().filter(|n| *n < max);
+            let mut parts = value.splitn(2, ':');
+            match (
+                parts.next().and_then(|hours| parse(hours, 24)),
+                parts.next().and_then(|minutes| parse(minutes, 60)),
+            ) {
+                (Some(hours), Some(minutes)) => Ok(hours * 3600 + minutes * 60),
+                _ => Err(de::Error::invalid_value(de::Unexpected::Str(value), &self)),
+            }
+        }
     }
+
+    deserializer.deserialize_str(TimeOfDayVisitor)
 }
 
 #[derive(Debug, Deserialize)]
@@ -773,7 +2196,10 @@ mod tests {
                         min: 0,
                         max: 604800,
                     },
+                    quorum: None,
                 },
+                dp: None,
+                adaptive: None,
             }
         }
     }
@@ -841,6 +2267,18 @@ mod tests {
         pet.sum2.count.max = 11;
         pet.sum.count.max = 10;
         assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.sum2.quorum = Some(5);
+        assert!(pet.validate().is_ok());
+
+        let mut pet = PetSettings::default();
+        pet.sum2.quorum = Some(0);
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.sum2.quorum = Some(11);
+        assert!(pet.validate().is_err());
     }
 
     #[test]
@@ -880,6 +2318,338 @@ mod tests {
         assert!(pet.validate().is_err());
     }
 
+    #[test]
+    fn test_validate_pet_dp() {
+        let dp = PetSettingsDp {
+            noise_multiplier: 1.,
+            delta: 1e-5,
+        };
+
+        let mut pet = PetSettings::default();
+        pet.dp = Some(dp);
+        assert!(pet.validate().is_ok());
+
+        let mut pet = PetSettings::default();
+        pet.dp = Some(PetSettingsDp {
+            noise_multiplier: 0.,
+            ..dp
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.dp = Some(PetSettingsDp { delta: 0., ..dp });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.dp = Some(PetSettingsDp { delta: 1., ..dp });
+        assert!(pet.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_pet_adaptive() {
+        let phase = PetSettingsAdaptivePhase {
+            min_prob: 0.001,
+            max_prob: 0.1,
+            target: None,
+        };
+        let adaptive = PetSettingsAdaptive {
+            sum: phase,
+            update: phase,
+            max_step: 2.,
+            smoothing: 0.5,
+        };
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(adaptive);
+        assert!(pet.validate().is_ok());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            sum: PetSettingsAdaptivePhase {
+                min_prob: 0.,
+                ..phase
+            },
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            sum: PetSettingsAdaptivePhase {
+                max_prob: 1.,
+                ..phase
+            },
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            update: PetSettingsAdaptivePhase {
+                max_prob: 1.,
+                ..phase
+            },
+            ..adaptive
+        });
+        assert!(pet.validate().is_ok());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            update: PetSettingsAdaptivePhase {
+                min_prob: 0.2,
+                ..phase
+            },
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            sum: PetSettingsAdaptivePhase {
+                target: Some(101),
+                ..phase
+            },
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            max_step: 0.5,
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+
+        let mut pet = PetSettings::default();
+        pet.adaptive = Some(PetSettingsAdaptive {
+            smoothing: 0.,
+            ..adaptive
+        });
+        assert!(pet.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_aggregation() {
+        assert!(AggregationSettings::default().validate().is_ok());
+
+        let mut aggregation = AggregationSettings::default();
+        aggregation.server_lr = 0.;
+        assert!(aggregation.validate().is_err());
+
+        let mut aggregation = AggregationSettings::default();
+        aggregation.momentum = 1.;
+        assert!(aggregation.validate().is_err());
+
+        let mut aggregation = AggregationSettings::default();
+        aggregation.beta1 = -0.1;
+        assert!(aggregation.validate().is_err());
+
+        let mut aggregation = AggregationSettings::default();
+        aggregation.beta2 = 1.;
+        assert!(aggregation.validate().is_err());
+
+        let mut aggregation = AggregationSettings::default();
+        aggregation.tau = 0.;
+        assert!(aggregation.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_model_storage() {
+        assert!(ModelStorageSettings::default().validate().is_ok());
+
+        let mut model_storage = ModelStorageSettings::default();
+        model_storage.backend = ModelStorageBackend::Filesystem;
+        assert!(model_storage.validate().is_err());
+        model_storage.path = Some(PathBuf::from("models"));
+        assert!(model_storage.validate().is_ok());
+
+        model_storage.max_models = Some(0);
+        assert!(model_storage.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_trust_anchor() {
+        assert!(TrustAnchorSettings::default().validate().is_ok());
+
+        let mut trust_anchor = TrustAnchorSettings::default();
+        trust_anchor.backend = TrustAnchorBackend::Log;
+        assert!(trust_anchor.validate().is_err());
+        trust_anchor.path = Some(PathBuf::from("proofs"));
+        assert!(trust_anchor.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_protocol() {
+        let protocol = ProtocolSettings::default();
+        assert!(protocol.validate().is_ok());
+        assert!(protocol.accepts(0));
+        assert!(protocol.accepts(PROTOCOL_VERSION));
+        assert!(!protocol.accepts(PROTOCOL_VERSION + 1));
+
+        let mut protocol = ProtocolSettings {
+            min_version: PROTOCOL_VERSION,
+            max_version: PROTOCOL_VERSION,
+        };
+        assert!(protocol.validate().is_ok());
+        assert!(!protocol.accepts(0));
+
+        protocol.min_version = PROTOCOL_VERSION + 1;
+        assert!(protocol.validate().is_err());
+        protocol.max_version = PROTOCOL_VERSION + 1;
+        assert!(protocol.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_multipart() {
+        assert!(MultipartSettings::default().validate().is_ok());
+
+        let mut multipart = MultipartSettings::default();
+        multipart.max_message_size = multipart.max_buffered_size + 1;
+        assert!(multipart.validate().is_err());
+
+        let mut multipart = MultipartSettings::default();
+        multipart.timeout = 0;
+        assert!(multipart.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_admin() {
+        let mut admin = AdminSettings {
+            bind_address: ([127, 0, 0, 1], 8082).into(),
+            token: "a-long-and-random-secret".to_string(),
+        };
+        assert!(admin.validate().is_ok());
+
+        admin.token = "short".to_string();
+        assert!(admin.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_cluster() {
+        let mut cluster = ClusterSettings {
+            role: ClusterRole::Frontend,
+            bind_address: None,
+            url: None,
+            token: "a-long-and-random-secret".to_string(),
+            lease: None,
+            #[cfg(feature = "tls")]
+            tls_certificate: None,
+            #[cfg(feature = "tls")]
+            tls_key: None,
+        };
+        assert!(cluster.validate().is_ok());
+        assert_eq!(cluster.lease(), Duration::from_secs(10));
+
+        cluster.role = ClusterRole::Leader;
+        assert!(cluster.validate().is_err());
+        cluster.url = Some("http://10.0.0.1:8083".to_string());
+        for address in &["127.0.0.1", "10.0.0.1", "192.168.0.1", "::1", "fd00::1"] {
+            cluster.bind_address = Some(SocketAddr::new(address.parse().unwrap(), 8083));
+            assert!(cluster.validate().is_ok());
+        }
+        for address in &["0.0.0.0", "1.2.3.4", "::", "2001:db8::1"] {
+            cluster.bind_address = Some(SocketAddr::new(address.parse().unwrap(), 8083));
+            assert!(cluster.validate().is_err());
+        }
+
+        #[cfg(feature = "tls")]
+        {
+            cluster.tls_certificate = Some(PathBuf::from("cluster.pem"));
+            assert!(cluster.validate().is_err());
+            cluster.tls_key = Some(PathBuf::from("cluster.rsa"));
+            assert!(cluster.validate().is_ok());
+            cluster.tls_certificate = None;
+            cluster.tls_key = None;
+        }
+        cluster.bind_address = Some(([10, 0, 0, 1], 8083).into());
+        assert!(cluster.validate().is_ok());
+
+        cluster.lease = Some(0);
+        assert!(cluster.validate().is_err());
+
+        let mut settings = Settings::load("../../configs/config.toml").unwrap();
+        cluster.lease = None;
+        settings.cluster = Some(cluster);
+        assert!(settings.validate().is_ok());
+        settings.storage.backend = StorageBackend::Memory;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        let mut window = ScheduleWindowSettings {
+            start: 22 * 3600,
+            end: 5 * 3600,
+            utc_offset: 60,
+        };
+        assert!(window.validate().is_ok());
+        window.utc_offset = -24 * 60;
+        assert!(window.validate().is_err());
+        window.utc_offset = 0;
+        window.end = window.start;
+        assert!(window.validate().is_err());
+
+        let schedule = ScheduleSettings {
+            window: Some(window),
+            ..ScheduleSettings::default()
+        };
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn test_deserialize_time_of_day() {
+        use serde::de::{
+            value::{Error, StrDeserializer},
+            IntoDeserializer,
+        };
+
+        let time_of_day = |value: &str| {
+            let deserializer: StrDeserializer<Error> = value.into_deserializer();
+            deserialize_time_of_day(deserializer)
+        };
+        assert_eq!(time_of_day("00:00").unwrap(), 0);
+        assert_eq!(time_of_day("22:30").unwrap(), 22 * 3600 + 30 * 60);
+        assert!(time_of_day("24:00").is_err());
+        assert!(time_of_day("12:60").is_err());
+        assert!(time_of_day("12").is_err());
+    }
+
+    #[test]
+    fn test_reload() {
+        let path = "../../configs/config.toml";
+        let Settings {
+            pet, mask, model, ..
+        } = Settings::new(path).unwrap();
+        assert!(Settings::reload(path, &pet, mask, &model).is_ok());
+
+        let changed_mask = MaskSettings {
+            bound_type: BoundType::B2,
+            ..mask
+        };
+        assert!(matches!(
+            Settings::reload(path, &pet, changed_mask, &model),
+            Err(SettingsError::Immutable("mask"))
+        ));
+
+        let changed_model = ModelSettings { length: 5 };
+        assert!(matches!(
+            Settings::reload(path, &pet, mask, &changed_model),
+            Err(SettingsError::Immutable("model"))
+        ));
+
+        let changed_pet = PetSettings {
+            dp: Some(PetSettingsDp {
+                noise_multiplier: 1.1,
+                delta: 1e-5,
+            }),
+            ..pet
+        };
+        assert!(matches!(
+            Settings::reload(path, &changed_pet, mask, &model),
+            Err(SettingsError::Immutable("pet.dp"))
+        ));
+    }
+
     #[cfg(feature = "tls")]
     #[test]
     fn test_validate_api() {
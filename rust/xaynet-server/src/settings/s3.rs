@@ -186,28 +186,6 @@ where
     deserializer.deserialize_any(S3RegionVisitor)
 }
 
-#[derive(Debug, Deserialize, Validate)]
-/// Restore settings.
-pub struct RestoreSettings {
-    /// If set to `false`, the restoring of coordinator state is prevented.
-    /// Instead, the state is reset and the coordinator is started with the
-    /// settings of the configuration file.
-    ///
-    /// # Examples
-    ///
-    /// **TOML**
-    /// ```text
-    /// [restore]
-    /// enable = true
-    /// ```
-    ///
-    /// **Environment variable**
-    /// ```text
-    /// XAYNET_RESTORE__ENABLE=false
-    /// ```
-    pub enable: bool,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
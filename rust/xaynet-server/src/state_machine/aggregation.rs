@@ -0,0 +1,352 @@
+//! Aggregation strategies for the global model.
+//!
+//! The masked local models can only be summed up by the coordinator, hence the unmasked aggregated
+//! model is always the (scalar-weighted) average of the local models. An [`AggregationStrategy`]
+//! computes the new global model from it. Apart from [`FedAvg`], the strategies treat the
+//! difference between the unmasked aggregated model and the previous global model as a
+//! pseudo-gradient and apply a server-side optimizer to it, as described in Reddi et al.,
+//! "Adaptive Federated Optimization" (2020).
+//!
+//! The state of the server-side optimizers is kept in memory. If the coordinator is restored, the
+//! optimizers start from the restored global model with reset moments.
+
+use std::fmt::Debug;
+
+use xaynet_core::mask::{FromPrimitives, IntoPrimitives, Model, ModelCastError};
+
+use crate::settings::{AggregationSettings, AggregationStrategy as Strategy};
+
+/// A strategy to compute the global model from the unmasked aggregated model.
+pub trait AggregationStrategy: Debug + Send + Sync {
+    /// Computes the new global model from the unmasked aggregated `model` of a round.
+    ///
+    /// # Errors
+    /// Fails if the aggregated model can't be converted for the computation.
+    fn aggregate(&mut self, model: Model) -> Result<Model, ModelCastError>;
+
+    /// Sets the current global `model`, e.g. after the coordinator has been restored.
+    fn set_global_model(&mut self, model: &Model) -> Result<(), ModelCastError>;
+}
+
+/// Creates the aggregation strategy which is configured in the `settings`.
+pub fn from_settings(settings: AggregationSettings) -> Box<dyn AggregationStrategy> {
+    let AggregationSettings {
+        strategy,
+        server_lr,
+        momentum,
+        beta1,
+        beta2,
+        tau,
+    } = settings;
+    match strategy {
+        Strategy::FedAvg => Box::new(FedAvg),
+        Strategy::FedAvgM => Box::new(FedAvgM::new(server_lr, momentum)),
+        Strategy::FedAdam => Box::new(FedAdaptive::new(
+            AdaptiveOptimizer::Adam,
+            server_lr,
+            beta1,
+            beta2,
+            tau,
+        )),
+        Strategy::FedYogi => Box::new(FedAdaptive::new(
+            AdaptiveOptimizer::Yogi,
+            server_lr,
+            beta1,
+            beta2,
+            tau,
+        )),
+    }
+}
+
+/// Converts a model into its weights.
+fn to_weights(model: &Model) -> Result<Vec<f64>, ModelCastError> {
+    model.to_primitives().collect()
+}
+
+/// Converts weights into a model.
+fn from_weights(weights: &[f64]) -> Model {
+    Model::from_primitives_bounded(weights.iter().copied())
+}
+
+#[derive(Debug, Default)]
+/// Federated averaging, i.e. the unmasked aggregated model becomes the global model.
+pub struct FedAvg;
+
+impl AggregationStrategy for FedAvg {
+    fn aggregate(&mut self, model: Model) -> Result<Model, ModelCastError> {
+        Ok(model)
+    }
+
+    fn set_global_model(&mut self, _model: &Model) -> Result<(), ModelCastError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+/// Federated averaging with server-side momentum.
+pub struct FedAvgM {
+    /// The server learning rate.
+    server_lr: f64,
+    /// The server momentum.
+    momentum: f64,
+    /// The previous global model.
+    global: Option<Vec<f64>>,
+    /// The momentum buffer.
+    velocity: Vec<f64>,
+}
+
+impl FedAvgM {
+    /// Creates a new FedAvgM strategy.
+    pub fn new(server_lr: f64, momentum: f64) -> Self {
+        Self {
+            server_lr,
+            momentum,
+            global: None,
+            velocity: Vec::new(),
+        }
+    }
+}
+
+impl AggregationStrategy for FedAvgM {
+    fn aggregate(&mut self, model: Model) -> Result<Model, ModelCastError> {
+        let aggregated = to_weights(&model)?;
+        let global = match self.global.take() {
+            Some(global) if global.len() == aggregated.len() => global,
+            // the first global model can't be optimized without a previous one
+            _ => {
+                self.velocity = vec![0.; aggregated.len()];
+                self.global = Some(aggregated);
+                return Ok(model);
+            }
+        };
+
+        let Self {
+            server_lr,
+            momentum,
+            velocity,
+            ..
+        } = self;
+        let global = global
+            .iter()
+            .zip(aggregated)
+            .zip(velocity.iter_mut())
+            .map(|((weight, aggregated), velocity)| {
+                *velocity = *momentum * *velocity + (aggregated - weight);
+                weight + *server_lr * *velocity
+            })
+            .collect::<Vec<_>>();
+        let model = from_weights(&global);
+        self.global = Some(global);
+        Ok(model)
+    }
+
+    fn set_global_model(&mut self, model: &Model) -> Result<(), ModelCastError> {
+        let global = to_weights(model)?;
+        self.velocity = vec![0.; global.len()];
+        self.global = Some(global);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The adaptive server-side optimizer.
+pub enum AdaptiveOptimizer {
+    /// The Adam optimizer.
+    Adam,
+    /// The Yogi optimizer.
+    Yogi,
+}
+
+#[derive(Debug)]
+/// Federated averaging with an adaptive server-side optimizer.
+pub struct FedAdaptive {
+    /// The adaptive optimizer.
+    optimizer: AdaptiveOptimizer,
+    /// The server learning rate.
+    server_lr: f64,
+    /// The decay rate of the first moment.
+    beta1: f64,
+    /// The decay rate of the second moment.
+    beta2: f64,
+    /// The degree of adaptivity.
+    tau: f64,
+    /// The previous global model.
+    global: Option<Vec<f64>>,
+    /// The first moment.
+    m: Vec<f64>,
+    /// The second moment.
+    v: Vec<f64>,
+}
+
+impl FedAdaptive {
+    /// Creates a new adaptive strategy.
+    pub fn new(
+        optimizer: AdaptiveOptimizer,
+        server_lr: f64,
+        beta1: f64,
+        beta2: f64,
+        tau: f64,
+    ) -> Self {
+        Self {
+            optimizer,
+            server_lr,
+            beta1,
+            beta2,
+            tau,
+            global: None,
+            m: Vec::new(),
+            v: Vec::new(),
+        }
+    }
+
+    /// Resets the moments for a global model of the given length.
+    fn reset(&mut self, len: usize) {
+        self.m = vec![0.; len];
+        // the second moment starts at `tau^2` as in the original algorithms
+        self.v = vec![self.tau.powi(2); len];
+    }
+
+    /// Updates the second moment with the squared pseudo-gradient.
+    fn second_moment(&self, v: f64, delta_sq: f64) -> f64 {
+        match self.optimizer {
+            AdaptiveOptimizer::Adam => self.beta2 * v + (1. - self.beta2) * delta_sq,
+            AdaptiveOptimizer::Yogi => v - (1. - self.beta2) * delta_sq * (v - delta_sq).signum(),
+        }
+    }
+}
+
+impl AggregationStrategy for FedAdaptive {
+    fn aggregate(&mut self, model: Model) -> Result<Model, ModelCastError> {
+        let aggregated = to_weights(&model)?;
+        let global = match self.global.take() {
+            Some(global) if global.len() == aggregated.len() => global,
+            // the first global model can't be optimized without a previous one
+            _ => {
+                self.reset(aggregated.len());
+                self.global = Some(aggregated);
+                return Ok(model);
+            }
+        };
+
+        let mut m = std::mem::take(&mut self.m);
+        let mut v = std::mem::take(&mut self.v);
+        let global = global
+            .iter()
+            .zip(aggregated)
+            .zip(m.iter_mut().zip(v.iter_mut()))
+            .map(|((weight, aggregated), (m, v))| {
+                let delta = aggregated - weight;
+                *m = self.beta1 * *m + (1. - self.beta1) * delta;
+                *v = self.second_moment(*v, delta.powi(2));
+                weight + self.server_lr * *m / (v.sqrt() + self.tau)
+            })
+            .collect::<Vec<_>>();
+        self.m = m;
+        self.v = v;
+        let model = from_weights(&global);
+        self.global = Some(global);
+        Ok(model)
+    }
+
+    fn set_global_model(&mut self, model: &Model) -> Result<(), ModelCastError> {
+        let global = to_weights(model)?;
+        self.reset(global.len());
+        self.global = Some(global);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(weights: &[f64]) -> Model {
+        from_weights(weights)
+    }
+
+    fn weights(model: Model) -> Vec<f64> {
+        model.into_primitives_unchecked().collect()
+    }
+
+    fn assert_weights(model: Model, expected: &[f64]) {
+        let weights = weights(model);
+        assert_eq!(weights.len(), expected.len());
+        for (weight, expected) in weights.iter().zip(expected) {
+            assert!(
+                (weight - expected).abs() < 1e-9,
+                "{} != {}",
+                weight,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_fedavg() {
+        let mut strategy = FedAvg;
+        let aggregated = model(&[1., 2.]);
+        assert_eq!(strategy.aggregate(aggregated.clone()).unwrap(), aggregated);
+    }
+
+    #[test]
+    fn test_fedavgm() {
+        let mut strategy = FedAvgM::new(1., 0.5);
+
+        // the first aggregated model becomes the global model
+        assert_weights(strategy.aggregate(model(&[1., 1.])).unwrap(), &[1., 1.]);
+
+        // delta = [1, -1], velocity = [1, -1]
+        assert_weights(strategy.aggregate(model(&[2., 0.])).unwrap(), &[2., 0.]);
+
+        // delta = [1, 1], velocity = [1.5, 0.5]
+        assert_weights(strategy.aggregate(model(&[3., 1.])).unwrap(), &[3.5, 0.5]);
+    }
+
+    #[test]
+    fn test_fedavgm_without_momentum_is_fedavg() {
+        let mut strategy = FedAvgM::new(1., 0.);
+        strategy.set_global_model(&model(&[0., 0.])).unwrap();
+        assert_weights(strategy.aggregate(model(&[1., 2.])).unwrap(), &[1., 2.]);
+        assert_weights(strategy.aggregate(model(&[3., -1.])).unwrap(), &[3., -1.]);
+    }
+
+    #[test]
+    fn test_fedadam() {
+        let mut strategy = FedAdaptive::new(AdaptiveOptimizer::Adam, 0.1, 0.9, 0.99, 1e-3);
+        strategy.set_global_model(&model(&[0.])).unwrap();
+
+        let m = 0.1 * 1.;
+        let v = 0.99 * 1e-6 + 0.01 * 1.;
+        let expected = 0.1 * m / (f64::sqrt(v) + 1e-3);
+        assert_weights(strategy.aggregate(model(&[1.])).unwrap(), &[expected]);
+    }
+
+    #[test]
+    fn test_fedyogi() {
+        let mut strategy = FedAdaptive::new(AdaptiveOptimizer::Yogi, 0.1, 0.9, 0.99, 1e-3);
+        strategy.set_global_model(&model(&[0.])).unwrap();
+
+        let m = 0.1 * 2.;
+        let v = 1e-6 + 0.01 * 4.;
+        let expected = 0.1 * m / (f64::sqrt(v) + 1e-3);
+        assert_weights(strategy.aggregate(model(&[2.])).unwrap(), &[expected]);
+    }
+
+    #[test]
+    fn test_first_model_without_global_model() {
+        let strategies: Vec<Box<dyn AggregationStrategy>> = vec![
+            from_settings(AggregationSettings::default()),
+            Box::new(FedAvgM::new(0.5, 0.9)),
+            Box::new(FedAdaptive::new(
+                AdaptiveOptimizer::Adam,
+                0.5,
+                0.9,
+                0.99,
+                1e-3,
+            )),
+        ];
+        for mut strategy in strategies {
+            assert_weights(strategy.aggregate(model(&[1., -1.])).unwrap(), &[1., -1.]);
+        }
+    }
+}
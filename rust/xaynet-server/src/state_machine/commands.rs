@@ -0,0 +1,85 @@
+//! This module provides the `Command`, `CommandSender` and `CommandReceiver` types.
+//!
+//! Commands allow an operator to control the [`StateMachine`] from the outside, e.g. via the
+//! admin API. A command interrupts the phase which is currently waiting for requests, except for
+//! an update of the PET settings, which takes effect when the next round starts.
+//!
+//! [`StateMachine`]: crate::state_machine
+
+use derive_more::From;
+use futures::future::FutureExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::settings::PetSettings;
+
+/// Error that occurs when a [`CommandSender`] tries to send a command on a closed `Command` channel.
+#[derive(Debug, Error)]
+#[error("the CommandSender cannot be used because the state machine shut down")]
+pub struct CommandChannelClosed;
+
+/// A command to control the [`StateMachine`].
+///
+/// [`StateMachine`]: crate::state_machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// Aborts the current round and starts a new round.
+    SkipRound,
+    /// Shuts the state machine down.
+    Shutdown,
+    /// Replaces the PET settings, starting with the next round.
+    ///
+    /// The settings must have been validated and must keep the immutable settings, see
+    /// [`Settings::reload()`].
+    ///
+    /// [`Settings::reload()`]: crate::settings::Settings::reload
+    UpdatePetSettings(Box<PetSettings>),
+}
+
+/// A handle to send commands to the [`StateMachine`].
+///
+/// [`StateMachine`]: crate::state_machine
+#[derive(Clone, From, Debug)]
+pub struct CommandSender(mpsc::UnboundedSender<Command>);
+
+impl CommandSender {
+    /// Sends a command to the [`StateMachine`].
+    ///
+    /// # Errors
+    /// Fails if the [`StateMachine`] has already shut down.
+    ///
+    /// [`StateMachine`]: crate::state_machine
+    pub fn send(&self, command: Command) -> Result<(), CommandChannelClosed> {
+        self.0.send(command).map_err(|_| CommandChannelClosed)
+    }
+}
+
+/// The receiver half of the `Command` channel that is used by the [`StateMachine`] to receive
+/// commands.
+///
+/// [`StateMachine`]: crate::state_machine
+#[derive(From, Debug)]
+pub struct CommandReceiver(mpsc::UnboundedReceiver<Command>);
+
+impl CommandReceiver {
+    /// Creates a new `Command` channel and returns the [`CommandReceiver`] as well as the
+    /// [`CommandSender`] half.
+    pub fn new() -> (Self, CommandSender) {
+        let (tx, rx) = mpsc::unbounded_channel::<Command>();
+        (CommandReceiver::from(rx), CommandSender::from(tx))
+    }
+
+    /// Receives the next command.
+    ///
+    /// Returns `None` if all [`CommandSender`]s have been dropped.
+    pub async fn recv(&mut self) -> Option<Command> {
+        self.0.recv().await
+    }
+
+    /// Tries to retrieve the next command without blocking.
+    pub fn try_recv(&mut self) -> Option<Command> {
+        self.0.recv().now_or_never().flatten()
+    }
+}
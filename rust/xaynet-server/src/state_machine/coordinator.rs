@@ -2,20 +2,24 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::settings::{
-    MaskSettings,
-    ModelSettings,
-    PetSettings,
-    PetSettingsCount,
-    PetSettingsSum,
-    PetSettingsSum2,
-    PetSettingsTime,
-    PetSettingsUpdate,
+use crate::{
+    settings::{
+        MaskSettings,
+        ModelSettings,
+        PetSettings,
+        PetSettingsCount,
+        PetSettingsSum,
+        PetSettingsSum2,
+        PetSettingsTime,
+        PetSettingsUpdate,
+    },
+    state_machine::{phases::PhaseName, selection::AdaptiveSelection},
 };
 use xaynet_core::{
-    common::{RoundParameters, RoundSeed},
+    common::{LegacyRoundParameters, RoundParameters, RoundSeed},
     crypto::{ByteObject, EncryptKeyPair},
-    mask::MaskConfig,
+    mask::{Aggregation, MaskConfig, PrivacyBudget},
+    message::PROTOCOL_VERSION,
 };
 
 /// The phase count parameters.
@@ -81,7 +85,7 @@ impl From<PetSettingsUpdate> for PhaseParameters {
 
 impl From<PetSettingsSum2> for PhaseParameters {
     fn from(sum2: PetSettingsSum2) -> Self {
-        let PetSettingsSum2 { count, time } = sum2;
+        let PetSettingsSum2 { count, time, .. } = sum2;
         Self {
             count: count.into(),
             time: time.into(),
@@ -104,6 +108,10 @@ pub struct CoordinatorState {
     pub update: PhaseParameters,
     /// The sum2 phase parameters.
     pub sum2: PhaseParameters,
+    /// The minimal number of sum participants that must agree on the aggregated mask.
+    pub quorum: Option<u64>,
+    /// The adaptive selection of the `sum` and `update` probabilities, if enabled.
+    pub adaptive: Option<AdaptiveSelection>,
 }
 
 impl CoordinatorState {
@@ -120,6 +128,11 @@ impl CoordinatorState {
             seed: RoundSeed::zeroed(),
             mask_config: MaskConfig::from(mask_settings).into(),
             model_length: model_settings.length,
+            privacy_budget: pet_settings
+                .dp
+                .map(|dp| PrivacyBudget::new(dp.noise_multiplier, dp.delta)),
+            version: PROTOCOL_VERSION,
+            starts_at: None,
         };
         let round_id = 0;
         Self {
@@ -129,6 +142,131 @@ impl CoordinatorState {
             sum: pet_settings.sum.into(),
             update: pet_settings.update.into(),
             sum2: pet_settings.sum2.into(),
+            quorum: pet_settings.sum2.quorum,
+            adaptive: pet_settings.adaptive.map(|adaptive| {
+                AdaptiveSelection::new(adaptive, pet_settings.sum.count, pet_settings.update.count)
+            }),
         }
     }
+
+    /// Replaces the parameters which derive from the PET settings.
+    ///
+    /// If the adaptive selection stays enabled, the estimated participation and the current
+    /// probabilities are kept and the new bounds apply when the probabilities of the next round
+    /// are chosen. Otherwise, the probabilities of the `pet_settings` are used. The privacy budget
+    /// is kept, since the differential privacy settings can't be changed.
+    pub fn update_pet_settings(&mut self, pet_settings: PetSettings) {
+        let adaptive = pet_settings.adaptive.map(|adaptive| {
+            let mut selection =
+                AdaptiveSelection::new(adaptive, pet_settings.sum.count, pet_settings.update.count);
+            if let Some(current) = self.adaptive.as_ref() {
+                selection.sum.participation = current.sum.participation;
+                selection.update.participation = current.update.participation;
+            }
+            selection
+        });
+        if adaptive.is_none() || self.adaptive.is_none() {
+            self.round_params.sum = pet_settings.sum.prob;
+            self.round_params.update = pet_settings.update.prob;
+        }
+        self.adaptive = adaptive;
+        self.sum = pet_settings.sum.into();
+        self.update = pet_settings.update.into();
+        self.sum2 = pet_settings.sum2.into();
+        self.quorum = pet_settings.sum2.quorum;
+    }
+
+    /// Deserializes a coordinator state from its bincode representation.
+    ///
+    /// A state which has been stored before the protocol versioning is converted into a state
+    /// without a quorum, an adaptive selection and a privacy budget.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the error of the current format if `bytes` holds neither format.
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes).or_else(|error| {
+            bincode::deserialize::<LegacyCoordinatorState>(bytes)
+                .map(Into::into)
+                .map_err(|_| error)
+        })
+    }
+}
+
+/// The coordinator state as it was stored before the protocol versioning.
+///
+/// The fields of the current state have been appended, hence a legacy state never deserializes
+/// as a current one.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LegacyCoordinatorState {
+    pub keys: EncryptKeyPair,
+    pub round_id: u64,
+    pub round_params: LegacyRoundParameters,
+    pub sum: PhaseParameters,
+    pub update: PhaseParameters,
+    pub sum2: PhaseParameters,
+}
+
+impl From<LegacyCoordinatorState> for CoordinatorState {
+    fn from(state: LegacyCoordinatorState) -> Self {
+        let LegacyCoordinatorState {
+            keys,
+            round_id,
+            round_params,
+            sum,
+            update,
+            sum2,
+        } = state;
+        Self {
+            keys,
+            round_id,
+            round_params: round_params.into(),
+            sum,
+            update,
+            sum2,
+            quorum: None,
+            adaptive: None,
+        }
+    }
+}
+
+/// The progress of the current round, which allows a restored coordinator to resume the round.
+///
+/// The sum dictionary, the seed dictionary and the mask dictionary of the round are kept in the
+/// coordinator storage anyways, hence the checkpoint only holds the in-memory state of the phase.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoundCheckpoint {
+    /// The round of the checkpoint.
+    pub round_id: u64,
+    /// The current phase.
+    pub phase: PhaseName,
+    /// The unix timestamp (in seconds) at which the phase started.
+    pub started_at: u64,
+    /// The number of messages successfully processed.
+    pub accepted: u64,
+    /// The number of messages which failed to be processed.
+    pub rejected: u64,
+    /// The number of messages discarded without being processed.
+    pub discarded: u64,
+    /// The aggregated masked models, once the update phase has started.
+    pub aggregation: Option<Aggregation>,
+}
+
+/// The progress of the current phase, which is saved after every accepted message.
+///
+/// Unlike the [`RoundCheckpoint`], the progress doesn't hold the aggregated masked models, hence
+/// it is cheap to save. The counters of the progress supersede the ones of the round checkpoint of
+/// the same phase.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoundProgress {
+    /// The round of the progress.
+    pub round_id: u64,
+    /// The current phase.
+    pub phase: PhaseName,
+    /// The number of messages successfully processed.
+    pub accepted: u64,
+    /// The number of messages which failed to be processed.
+    pub rejected: u64,
+    /// The number of messages discarded without being processed.
+    pub discarded: u64,
 }
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use tokio::sync::watch;
 
 use crate::state_machine::phases::PhaseName;
@@ -14,7 +15,7 @@ use xaynet_core::{
 };
 
 /// An event emitted by the coordinator.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Event<E> {
     /// Metadata that associates this event to the round in which it is
     /// emitted.
@@ -25,19 +26,45 @@ pub struct Event<E> {
 
 // FIXME: should we simply use `Option`s here?
 /// Global model update event.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ModelUpdate {
     Invalidate,
-    New(Arc<Model>),
+    New {
+        /// The global model id, see [`ModelStorage::create_global_model_id()`].
+        ///
+        /// [`ModelStorage::create_global_model_id()`]: crate::storage::ModelStorage::create_global_model_id
+        id: String,
+        /// The global model.
+        model: Arc<Model>,
+    },
 }
 
 /// Dictionary update event.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DictionaryUpdate<D> {
     Invalidate,
     New(Arc<D>),
 }
 
+/// The progress of the current phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseStatus {
+    /// The number of messages successfully processed.
+    pub accepted: u64,
+    /// The number of messages which failed to be processed.
+    pub rejected: u64,
+    /// The number of messages discarded without being processed.
+    pub discarded: u64,
+    /// The unix timestamp (in seconds) at which the phase started.
+    pub started_at: u64,
+    /// The unix timestamp (in seconds) until which the phase processes messages, even if there
+    /// are already enough messages. `None` if the phase doesn't process messages.
+    pub min_deadline: Option<u64>,
+    /// The unix timestamp (in seconds) at which the phase times out if there are not enough
+    /// messages. `None` if the phase doesn't process messages.
+    pub max_deadline: Option<u64>,
+}
+
 /// A convenience type to emit any coordinator event.
 #[derive(Debug)]
 pub struct EventPublisher {
@@ -46,6 +73,7 @@ pub struct EventPublisher {
     keys_tx: EventBroadcaster<EncryptKeyPair>,
     params_tx: EventBroadcaster<RoundParameters>,
     phase_tx: EventBroadcaster<PhaseName>,
+    status_tx: EventBroadcaster<PhaseStatus>,
     model_tx: EventBroadcaster<ModelUpdate>,
     sum_dict_tx: EventBroadcaster<DictionaryUpdate<SumDict>>,
     seed_dict_tx: EventBroadcaster<DictionaryUpdate<SeedDict>>,
@@ -58,6 +86,7 @@ pub struct EventSubscriber {
     keys_rx: EventListener<EncryptKeyPair>,
     params_rx: EventListener<RoundParameters>,
     phase_rx: EventListener<PhaseName>,
+    status_rx: EventListener<PhaseStatus>,
     model_rx: EventListener<ModelUpdate>,
     sum_dict_rx: EventListener<DictionaryUpdate<SumDict>>,
     seed_dict_rx: EventListener<DictionaryUpdate<SeedDict>>,
@@ -82,6 +111,11 @@ impl EventPublisher {
             event: phase,
         });
 
+        let (status_tx, status_rx) = watch::channel::<Event<PhaseStatus>>(Event {
+            round_id,
+            event: PhaseStatus::default(),
+        });
+
         let (model_tx, model_rx) = watch::channel::<Event<ModelUpdate>>(Event {
             round_id,
             event: model,
@@ -109,6 +143,7 @@ impl EventPublisher {
             keys_tx: keys_tx.into(),
             params_tx: params_tx.into(),
             phase_tx: phase_tx.into(),
+            status_tx: status_tx.into(),
             model_tx: model_tx.into(),
             sum_dict_tx: sum_dict_tx.into(),
             seed_dict_tx: seed_dict_tx.into(),
@@ -118,6 +153,7 @@ impl EventPublisher {
             keys_rx: keys_rx.into(),
             params_rx: params_rx.into(),
             phase_rx: phase_rx.into(),
+            status_rx: status_rx.into(),
             model_rx: model_rx.into(),
             sum_dict_rx: sum_dict_rx.into(),
             seed_dict_rx: seed_dict_rx.into(),
@@ -153,6 +189,11 @@ impl EventPublisher {
         let _ = self.phase_tx.broadcast(self.event(phase));
     }
 
+    /// Emit a phase status event
+    pub fn broadcast_status(&mut self, status: PhaseStatus) {
+        let _ = self.status_tx.broadcast(self.event(status));
+    }
+
     /// Emit a model event
     pub fn broadcast_model(&mut self, update: ModelUpdate) {
         let _ = self.model_tx.broadcast(self.event(update));
@@ -186,6 +227,11 @@ impl EventSubscriber {
         self.phase_rx.clone()
     }
 
+    /// Get a listener for phase status events
+    pub fn status_listener(&self) -> EventListener<PhaseStatus> {
+        self.status_rx.clone()
+    }
+
     /// Get a listener for new model events
     pub fn model_listener(&self) -> EventListener<ModelUpdate> {
         self.model_rx.clone()
@@ -221,6 +267,14 @@ where
     pub fn get_latest(&self) -> Event<E> {
         self.0.borrow().clone()
     }
+
+    /// Waits for the next `Event<E>` emitted by the coordinator.
+    ///
+    /// Returns `None` if the [`EventPublisher`] has been dropped.
+    pub async fn next(&mut self) -> Option<Event<E>> {
+        self.0.changed().await.ok()?;
+        Some(self.get_latest())
+    }
 }
 
 /// A channel to send `Event<E>` to all the `EventListener<E>`.
@@ -1,25 +1,37 @@
 //! A state machine initializer.
 
+use std::{sync::Arc, time::Duration};
+
 use thiserror::Error;
-#[cfg(feature = "model-persistence")]
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-#[cfg(feature = "model-persistence")]
-use crate::settings::RestoreSettings;
 use crate::{
-    settings::{MaskSettings, ModelSettings, PetSettings},
+    settings::{
+        AggregationSettings,
+        MaskSettings,
+        ModelSettings,
+        PetSettings,
+        RestoreSettings,
+        ScheduleSettings,
+    },
     state_machine::{
-        coordinator::CoordinatorState,
-        events::{EventPublisher, EventSubscriber, ModelUpdate},
-        phases::{Idle, PhaseName, PhaseState, Shared},
+        aggregation,
+        commands::{CommandReceiver, CommandSender},
+        coordinator::{CoordinatorState, RoundCheckpoint},
+        events::{DictionaryUpdate, EventPublisher, EventSubscriber, ModelUpdate},
+        phases::{Idle, PhaseName, PhaseState, Shared, Sum, Sum2, Unmask, Update},
         requests::{RequestReceiver, RequestSender},
+        schedule::Schedule,
         StateMachine,
     },
     storage::{Storage, StorageError},
 };
 
-#[cfg(feature = "model-persistence")]
-use xaynet_core::mask::Model;
+use xaynet_core::{
+    mask::{Model, ModelCastError},
+    SeedDict,
+    SumDict,
+};
 
 type StateMachineInitializationResult<T> = Result<T, StateMachineInitializationError>;
 
@@ -40,6 +52,24 @@ pub enum StateMachineInitializationError {
     GlobalModelUnavailable(String),
     #[error("{0}")]
     GlobalModelInvalid(String),
+    #[error("initializing the aggregation strategy failed: {0}")]
+    AggregationStrategy(ModelCastError),
+    #[error("fetching round checkpoint failed: {0}")]
+    FetchRoundCheckpoint(StorageError),
+    #[error("fetching global model history failed: {0}")]
+    FetchGlobalModelHistory(StorageError),
+    #[error("fetching dictionaries of the interrupted round failed: {0}")]
+    FetchDictionaries(StorageError),
+}
+
+/// An interrupted round which is resumed by the state machine.
+struct InterruptedRound {
+    /// The checkpoint of the round.
+    checkpoint: RoundCheckpoint,
+    /// The sum dictionary, if the sum phase has been completed.
+    sum_dict: Option<SumDict>,
+    /// The seed dictionary, if the update phase has been completed.
+    seed_dict: Option<SeedDict>,
 }
 
 /// The state machine initializer that initializes a new state machine.
@@ -50,8 +80,9 @@ where
     pet_settings: PetSettings,
     mask_settings: MaskSettings,
     model_settings: ModelSettings,
-    #[cfg(feature = "model-persistence")]
+    aggregation_settings: AggregationSettings,
     restore_settings: RestoreSettings,
+    schedule_settings: ScheduleSettings,
     store: S,
 }
 
@@ -64,31 +95,22 @@ where
         pet_settings: PetSettings,
         mask_settings: MaskSettings,
         model_settings: ModelSettings,
-        #[cfg(feature = "model-persistence")] restore_settings: RestoreSettings,
+        aggregation_settings: AggregationSettings,
+        restore_settings: RestoreSettings,
+        schedule_settings: ScheduleSettings,
         store: S,
     ) -> Self {
         Self {
             pet_settings,
             mask_settings,
             model_settings,
-            #[cfg(feature = "model-persistence")]
+            aggregation_settings,
             restore_settings,
+            schedule_settings,
             store,
         }
     }
 
-    #[cfg(not(feature = "model-persistence"))]
-    /// Initializes a new [`StateMachine`] with the given settings.
-    pub async fn init(
-        mut self,
-    ) -> StateMachineInitializationResult<(StateMachine<S>, RequestSender, EventSubscriber)> {
-        // crucial: init must be called before anything else in this module
-        sodiumoxide::init().or(Err(StateMachineInitializationError::CryptoInit))?;
-
-        let (coordinator_state, global_model) = { self.from_settings().await? };
-        Ok(self.init_state_machine(coordinator_state, global_model))
-    }
-
     // Creates a new [`CoordinatorState`] from the given settings and deletes
     // all coordinator data. Should only be called for the first start
     // or if we need to perform reset.
@@ -109,40 +131,98 @@ where
         ))
     }
 
-    // Initializes a new [`StateMachine`] with its components.
+    // Initializes a new [`StateMachine`] with its components, which resumes the interrupted
+    // round if any.
     fn init_state_machine(
         self,
         coordinator_state: CoordinatorState,
         global_model: ModelUpdate,
-    ) -> (StateMachine<S>, RequestSender, EventSubscriber) {
-        let (event_publisher, event_subscriber) = EventPublisher::init(
+        interrupted: Option<InterruptedRound>,
+    ) -> StateMachineInitializationResult<(
+        StateMachine<S>,
+        RequestSender,
+        CommandSender,
+        EventSubscriber,
+    )> {
+        let mut aggregator = aggregation::from_settings(self.aggregation_settings);
+        if let ModelUpdate::New { ref model, .. } = global_model {
+            aggregator
+                .set_global_model(model)
+                .map_err(StateMachineInitializationError::AggregationStrategy)?;
+        }
+
+        let phase = interrupted
+            .as_ref()
+            .map_or(PhaseName::Idle, |round| round.checkpoint.phase);
+        let (mut event_publisher, event_subscriber) = EventPublisher::init(
             coordinator_state.round_id,
             coordinator_state.keys.clone(),
             coordinator_state.round_params.clone(),
-            PhaseName::Idle,
+            phase,
             global_model,
         );
 
         let (request_rx, request_tx) = RequestReceiver::new();
+        let (command_rx, command_tx) = CommandReceiver::new();
 
-        let shared = Shared::new(coordinator_state, event_publisher, request_rx, self.store);
+        let checkpoint = interrupted.map(|round| {
+            if let Some(sum_dict) = round.sum_dict {
+                event_publisher.broadcast_sum_dict(DictionaryUpdate::New(Arc::new(sum_dict)));
+            }
+            if let Some(seed_dict) = round.seed_dict {
+                event_publisher.broadcast_seed_dict(DictionaryUpdate::New(Arc::new(seed_dict)));
+            }
+            round.checkpoint
+        });
 
-        let state_machine = StateMachine::from(PhaseState::<Idle, _>::new(shared));
-        (state_machine, request_tx, event_subscriber)
+        let mut shared = Shared::new(
+            coordinator_state,
+            event_publisher,
+            request_rx,
+            command_rx,
+            self.store,
+            aggregator,
+        );
+        shared.checkpoints = self.restore_settings.enable;
+        shared.checkpoint_interval = Duration::from_secs(self.restore_settings.checkpoint_interval);
+        shared.schedule = Schedule::from(self.schedule_settings);
+
+        let state_machine = match checkpoint {
+            Some(checkpoint) => Self::resume(shared, checkpoint),
+            None => StateMachine::from(PhaseState::<Idle, _>::new(shared)),
+        };
+        Ok((state_machine, request_tx, command_tx, event_subscriber))
+    }
+
+    // Resumes the phase of the round `checkpoint`, which has been checked by
+    // [`StateMachineInitializer::restore_round`].
+    fn resume(mut shared: Shared<S>, checkpoint: RoundCheckpoint) -> StateMachine<S> {
+        shared.restore_status(&checkpoint);
+        match (checkpoint.phase, checkpoint.aggregation) {
+            (PhaseName::Sum, None) => PhaseState::<Sum, _>::resume(shared).into(),
+            (PhaseName::Update, Some(model_agg)) => {
+                PhaseState::<Update, _>::resume(shared, model_agg).into()
+            }
+            (PhaseName::Sum2, Some(model_agg)) => {
+                PhaseState::<Sum2, _>::resume(shared, model_agg).into()
+            }
+            (PhaseName::Unmask, Some(model_agg)) => {
+                PhaseState::<Unmask, _>::new(shared, model_agg).into()
+            }
+            _ => unreachable!("the round checkpoint can't be resumed"),
+        }
     }
 }
 
-#[cfg(feature = "model-persistence")]
-#[cfg_attr(docsrs, doc(cfg(feature = "model-persistence")))]
 impl<S> StateMachineInitializer<S>
 where
     S: Storage,
 {
     /// Initializes a new [`StateMachine`] by trying to restore the previous coordinator state
     /// along with the latest global model. After a successful initialization, the state machine
-    /// always starts from a new round. This means that the round id is increased by one.
-    /// If the state machine is reset during the initialization, the state machine starts
-    /// with the round id `1`.
+    /// resumes the interrupted round if possible, otherwise it starts from a new round. This means
+    /// that the round id is increased by one. If the state machine is reset during the
+    /// initialization, the state machine starts with the round id `1`.
     ///
     /// # Behavior
     /// ![](https://mermaid.ink/svg/eyJjb2RlIjoic2VxdWVuY2VEaWFncmFtXG4gICAgYWx0IHJlc3RvcmUuZW5hYmxlID0gZmFsc2VcbiAgICAgICAgQ29vcmRpbmF0b3ItPj4rUmVkaXM6IGZsdXNoIGRiXG4gICAgICAgIE5vdGUgb3ZlciBDb29yZGluYXRvcixSZWRpczogc3RhcnQgZnJvbSBzZXR0aW5nc1xuICAgIGVsc2VcbiAgICAgICAgQ29vcmRpbmF0b3ItPj4rUmVkaXM6IGdldCBzdGF0ZVxuICAgICAgICBSZWRpcy0tPj4tQ29vcmRpbmF0b3I6IHN0YXRlXG4gICAgICAgIGFsdCBzdGF0ZSBub24tZXhpc3RlbnRcbiAgICAgICAgICAgIENvb3JkaW5hdG9yLT4-K1JlZGlzOiBmbHVzaCBkYlxuICAgICAgICAgICAgTm90ZSBvdmVyIENvb3JkaW5hdG9yLFJlZGlzOiBzdGFydCBmcm9tIHNldHRpbmdzXG4gICAgICAgIGVsc2Ugc3RhdGUgZXhpc3RcbiAgICAgICAgICAgIENvb3JkaW5hdG9yLT4-K1JlZGlzOiBnZXQgbGF0ZXN0IGdsb2JhbCBtb2RlbCBpZFxuICAgICAgICAgICAgUmVkaXMtLT4-LUNvb3JkaW5hdG9yOiBnbG9iYWwgbW9kZWwgaWRcbiAgICAgICAgICAgIGFsdCBnbG9iYWwgbW9kZWwgaWQgbm9uLWV4aXN0ZW50XG4gICAgICAgICAgICAgICAgTm90ZSBvdmVyIENvb3JkaW5hdG9yLFMzOiByZXN0b3JlIGNvb3JkaW5hdG9yIHdpdGggbGF0ZXN0IHN0YXRlIGJ1dCB3aXRob3V0IGEgZ2xvYmFsIG1vZGVsXG4gICAgICAgICAgICBlbHNlIGdsb2JhbCBtb2RlbCBpZCBleGlzdFxuICAgICAgICAgICAgICBDb29yZGluYXRvci0-PitTMzogZ2V0IGdsb2JhbCBtb2RlbFxuICAgICAgICAgICAgICBTMy0tPj4tQ29vcmRpbmF0b3I6IGdsb2JhbCBtb2RlbFxuICAgICAgICAgICAgICBhbHQgZ2xvYmFsIG1vZGVsIG5vbi1leGlzdGVudFxuICAgICAgICAgICAgICAgIE5vdGUgb3ZlciBDb29yZGluYXRvcixTMzogZXhpdCB3aXRoIGVycm9yXG4gICAgICAgICAgICAgIGVsc2UgZ2xvYmFsIG1vZGVsIGV4aXN0XG4gICAgICAgICAgICAgICAgTm90ZSBvdmVyIENvb3JkaW5hdG9yLFMzOiByZXN0b3JlIGNvb3JkaW5hdG9yIHdpdGggbGF0ZXN0IHN0YXRlIGFuZCBsYXRlc3QgZ2xvYmFsIG1vZGVsXG4gICAgICAgICAgICAgIGVuZFxuICAgICAgICAgICAgZW5kXG4gICAgICAgICAgZW5kXG4gICAgICAgIGVuZCIsIm1lcm1haWQiOnsidGhlbWUiOiJkZWZhdWx0IiwidGhlbWVWYXJpYWJsZXMiOnsiYmFja2dyb3VuZCI6IndoaXRlIiwicHJpbWFyeUNvbG9yIjoiI0VDRUNGRiIsInNlY29uZGFyeUNvbG9yIjoiI2ZmZmZkZSIsInRlcnRpYXJ5Q29sb3IiOiJoc2woODAsIDEwMCUsIDk2LjI3NDUwOTgwMzklKSIsInByaW1hcnlCb3JkZXJDb2xvciI6ImhzbCgyNDAsIDYwJSwgODYuMjc0NTA5ODAzOSUpIiwic2Vjb25kYXJ5Qm9yZGVyQ29sb3IiOiJoc2woNjAsIDYwJSwgODMuNTI5NDExNzY0NyUpIiwidGVydGlhcnlCb3JkZXJDb2xvciI6ImhzbCg4MCwgNjAlLCA4Ni4yNzQ1MDk4MDM5JSkiLCJwcmltYXJ5VGV4dENvbG9yIjoiIzEzMTMwMCIsInNlY29uZGFyeVRleHRDb2xvciI6IiMwMDAwMjEiLCJ0ZXJ0aWFyeVRleHRDb2xvciI6InJnYig5LjUwMDAwMDAwMDEsIDkuNTAwMDAwMDAwMSwgOS41MDAwMDAwMDAxKSIsImxpbmVDb2xvciI6IiMzMzMzMzMiLCJ0ZXh0Q29sb3IiOiIjMzMzIiwibWFpbkJrZyI6IiNFQ0VDRkYiLCJzZWNvbmRCa2ciOiIjZmZmZmRlIiwiYm9yZGVyMSI6IiM5MzcwREIiLCJib3JkZXIyIjoiI2FhYWEzMyIsImFycm93aGVhZENvbG9yIjoiIzMzMzMzMyIsImZvbnRGYW1pbHkiOiJcInRyZWJ1Y2hldCBtc1wiLCB2ZXJkYW5hLCBhcmlhbCIsImZvbnRTaXplIjoiMTZweCIsImxhYmVsQmFja2dyb3VuZCI6IiNlOGU4ZTgiLCJub2RlQmtnIjoiI0VDRUNGRiIsIm5vZGVCb3JkZXIiOiIjOTM3MERCIiwiY2x1c3RlckJrZyI6IiNmZmZmZGUiLCJjbHVzdGVyQm9yZGVyIjoiI2FhYWEzMyIsImRlZmF1bHRMaW5rQ29sb3IiOiIjMzMzMzMzIiwidGl0bGVDb2xvciI6IiMzMzMiLCJlZGdlTGFiZWxCYWNrZ3JvdW5kIjoiI2U4ZThlOCIsImFjdG9yQm9yZGVyIjoiaHNsKDI1OS42MjYxNjgyMjQzLCA1OS43NzY1MzYzMTI4JSwgODcuOTAxOTYwNzg0MyUpIiwiYWN0b3JCa2ciOiIjRUNFQ0ZGIiwiYWN0b3JUZXh0Q29sb3IiOiJibGFjayIsImFjdG9yTGluZUNvbG9yIjoiZ3JleSIsInNpZ25hbENvbG9yIjoiIzMzMyIsInNpZ25hbFRleHRDb2xvciI6IiMzMzMiLCJsYWJlbEJveEJrZ0NvbG9yIjoiI0VDRUNGRiIsImxhYmVsQm94Qm9yZGVyQ29sb3IiOiJoc2woMjU5LjYyNjE2ODIyNDMsIDU5Ljc3NjUzNjMxMjglLCA4Ny45MDE5NjA3ODQzJSkiLCJsYWJlbFRleHRDb2xvciI6ImJsYWNrIiwibG9vcFRleHRDb2xvciI6ImJsYWNrIiwibm90ZUJvcmRlckNvbG9yIjoiI2FhYWEzMyIsIm5vdGVCa2dDb2xvciI6IiNmZmY1YWQiLCJub3RlVGV4dENvbG9yIjoiYmxhY2siLCJhY3RpdmF0aW9uQm9yZGVyQ29sb3IiOiIjNjY2IiwiYWN0aXZhdGlvbkJrZ0NvbG9yIjoiI2Y0ZjRmNCIsInNlcXVlbmNlTnVtYmVyQ29sb3IiOiJ3aGl0ZSIsInNlY3Rpb25Ca2dDb2xvciI6InJnYmEoMTAyLCAxMDIsIDI1NSwgMC40OSkiLCJhbHRTZWN0aW9uQmtnQ29sb3IiOiJ3aGl0ZSIsInNlY3Rpb25Ca2dDb2xvcjIiOiIjZmZmNDAwIiwidGFza0JvcmRlckNvbG9yIjoiIzUzNGZiYyIsInRhc2tCa2dDb2xvciI6IiM4YTkwZGQiLCJ0YXNrVGV4dExpZ2h0Q29sb3IiOiJ3aGl0ZSIsInRhc2tUZXh0Q29sb3IiOiJ3aGl0ZSIsInRhc2tUZXh0RGFya0NvbG9yIjoiYmxhY2siLCJ0YXNrVGV4dE91dHNpZGVDb2xvciI6ImJsYWNrIiwidGFza1RleHRDbGlja2FibGVDb2xvciI6IiMwMDMxNjMiLCJhY3RpdmVUYXNrQm9yZGVyQ29sb3IiOiIjNTM0ZmJjIiwiYWN0aXZlVGFza0JrZ0NvbG9yIjoiI2JmYzdmZiIsImdyaWRDb2xvciI6ImxpZ2h0Z3JleSIsImRvbmVUYXNrQmtnQ29sb3IiOiJsaWdodGdyZXkiLCJkb25lVGFza0JvcmRlckNvbG9yIjoiZ3JleSIsImNyaXRCb3JkZXJDb2xvciI6IiNmZjg4ODgiLCJjcml0QmtnQ29sb3IiOiJyZWQiLCJ0b2RheUxpbmVDb2xvciI6InJlZCIsImxhYmVsQ29sb3IiOiJibGFjayIsImVycm9yQmtnQ29sb3IiOiIjNTUyMjIyIiwiZXJyb3JUZXh0Q29sb3IiOiIjNTUyMjIyIiwiY2xhc3NUZXh0IjoiIzEzMTMwMCIsImZpbGxUeXBlMCI6IiNFQ0VDRkYiLCJmaWxsVHlwZTEiOiIjZmZmZmRlIiwiZmlsbFR5cGUyIjoiaHNsKDMwNCwgMTAwJSwgOTYuMjc0NTA5ODAzOSUpIiwiZmlsbFR5cGUzIjoiaHNsKDEyNCwgMTAwJSwgOTMuNTI5NDExNzY0NyUpIiwiZmlsbFR5cGU0IjoiaHNsKDE3NiwgMTAwJSwgOTYuMjc0NTA5ODAzOSUpIiwiZmlsbFR5cGU1IjoiaHNsKC00LCAxMDAlLCA5My41Mjk0MTE3NjQ3JSkiLCJmaWxsVHlwZTYiOiJoc2woOCwgMTAwJSwgOTYuMjc0NTA5ODAzOSUpIiwiZmlsbFR5cGU3IjoiaHNsKDE4OCwgMTAwJSwgOTMuNTI5NDExNzY0NyUpIn19LCJ1cGRhdGVFZGl0b3IiOmZhbHNlfQ)
@@ -159,22 +239,34 @@ where
     ///   [`StateMachineInitializationError::GlobalModelUnavailable`].
     /// - If a global model exists but its properties do not match the coordinator model settings,
     ///   the initialization will fail with [`StateMachineInitializationError::GlobalModelInvalid`].
+    /// - If a [`RoundCheckpoint`] of the current round exists, the [`StateMachine`] resumes the
+    ///   interrupted phase with the counters, the deadlines and the aggregated masked models of
+    ///   the checkpoint. If the checkpoint is outdated or doesn't match the coordinator state or
+    ///   the stored dictionaries, the [`StateMachine`] starts from a new round instead.
     /// - Any network error will cause the initialization to fail.
     pub async fn init(
         mut self,
-    ) -> StateMachineInitializationResult<(StateMachine<S>, RequestSender, EventSubscriber)> {
+    ) -> StateMachineInitializationResult<(
+        StateMachine<S>,
+        RequestSender,
+        CommandSender,
+        EventSubscriber,
+    )> {
         // crucial: init must be called before anything else in this module
         sodiumoxide::init().or(Err(StateMachineInitializationError::CryptoInit))?;
 
-        let (coordinator_state, global_model) = if self.restore_settings.enable {
-            self.from_previous_state().await?
+        let (coordinator_state, global_model, interrupted) = if self.restore_settings.enable {
+            let (coordinator_state, global_model) = self.from_previous_state().await?;
+            let interrupted = self.restore_round(&coordinator_state).await?;
+            (coordinator_state, global_model, interrupted)
         } else {
             info!("restoring coordinator state is disabled");
             info!("initialize state machine from settings");
-            self.from_settings().await?
+            let (coordinator_state, global_model) = self.from_settings().await?;
+            (coordinator_state, global_model, None)
         };
 
-        Ok(self.init_state_machine(coordinator_state, global_model))
+        self.init_state_machine(coordinator_state, global_model, interrupted)
     }
 
     // see [`StateMachineInitializer::init`]
@@ -229,10 +321,143 @@ where
         );
         Ok((
             coordinator_state,
-            ModelUpdate::New(std::sync::Arc::new(global_model)),
+            ModelUpdate::New {
+                id: global_model_id,
+                model: std::sync::Arc::new(global_model),
+            },
         ))
     }
 
+    // Restores the interrupted round of the coordinator state, if its checkpoint can be resumed.
+    async fn restore_round(
+        &mut self,
+        coordinator_state: &CoordinatorState,
+    ) -> StateMachineInitializationResult<Option<InterruptedRound>> {
+        let mut checkpoint = match self
+            .store
+            .round_checkpoint()
+            .await
+            .map_err(StateMachineInitializationError::FetchRoundCheckpoint)?
+        {
+            Some(checkpoint) if checkpoint.round_id == coordinator_state.round_id => checkpoint,
+            _ => {
+                debug!("no interrupted round to resume");
+                return Ok(None);
+            }
+        };
+
+        // the progress is saved after every accepted message, the checkpoint only periodically
+        if let Some(progress) = self
+            .store
+            .round_progress()
+            .await
+            .map_err(StateMachineInitializationError::FetchRoundCheckpoint)?
+        {
+            if progress.round_id == checkpoint.round_id && progress.phase == checkpoint.phase {
+                checkpoint.accepted = progress.accepted;
+                checkpoint.rejected = progress.rejected;
+                checkpoint.discarded = progress.discarded;
+            }
+        }
+
+        // the round might have been completed right before the coordinator was shut down
+        if self
+            .store
+            .global_model_info(checkpoint.round_id)
+            .await
+            .map_err(StateMachineInitializationError::FetchGlobalModelHistory)?
+            .is_some()
+        {
+            debug!("the interrupted round has already been completed");
+            return Ok(None);
+        }
+
+        let params = &coordinator_state.round_params;
+        let nb_models = match (checkpoint.phase, checkpoint.aggregation.as_ref()) {
+            (PhaseName::Sum, None) => None,
+            (PhaseName::Update, Some(aggregation))
+            | (PhaseName::Sum2, Some(aggregation))
+            | (PhaseName::Unmask, Some(aggregation))
+                if aggregation.len() == params.model_length
+                    && aggregation.config() == params.mask_config =>
+            {
+                Some(aggregation.nb_models())
+            }
+            _ => {
+                warn!(
+                    "the round checkpoint of the {:?} phase doesn't match the coordinator state",
+                    checkpoint.phase,
+                );
+                return Ok(None);
+            }
+        };
+
+        let (sum_dict, seed_dict) = match nb_models {
+            Some(nb_models) => match self.fetch_dicts(checkpoint.phase, nb_models).await? {
+                Some((sum_dict, seed_dict)) => (Some(sum_dict), seed_dict),
+                None => {
+                    warn!(
+                        "the round checkpoint of the {:?} phase doesn't match the dictionaries",
+                        checkpoint.phase,
+                    );
+                    return Ok(None);
+                }
+            },
+            None => (None, None),
+        };
+
+        info!(
+            "resume round {} in the {:?} phase",
+            checkpoint.round_id, checkpoint.phase,
+        );
+        Ok(Some(InterruptedRound {
+            checkpoint,
+            sum_dict,
+            seed_dict,
+        }))
+    }
+
+    // Fetches the dictionaries of an interrupted round after the sum phase and checks whether
+    // the seed dictionary contains the seeds of the `nb_models` aggregated masked models.
+    async fn fetch_dicts(
+        &mut self,
+        phase: PhaseName,
+        nb_models: usize,
+    ) -> StateMachineInitializationResult<Option<(SumDict, Option<SeedDict>)>> {
+        let sum_dict = match self
+            .store
+            .sum_dict()
+            .await
+            .map_err(StateMachineInitializationError::FetchDictionaries)?
+        {
+            Some(sum_dict) => sum_dict,
+            None => return Ok(None),
+        };
+        let seed_dict = self
+            .store
+            .seed_dict()
+            .await
+            .map_err(StateMachineInitializationError::FetchDictionaries)?;
+
+        // a crash between adding a local seed dictionary and saving the checkpoint leaves the
+        // seeds of a masked model which hasn't been aggregated
+        let nb_seeds = seed_dict
+            .as_ref()
+            .and_then(|seed_dict| seed_dict.values().next())
+            .map_or(0, |seeds| seeds.len());
+        if nb_seeds != nb_models {
+            return Ok(None);
+        }
+
+        // the seed dictionary is only published once the update phase has been completed
+        let seed_dict = if phase == PhaseName::Update {
+            None
+        } else {
+            seed_dict
+        };
+        Ok(Some((sum_dict, seed_dict)))
+    }
+
     // Loads a global model and checks its properties for suitability.
     async fn load_global_model(
         &mut self,
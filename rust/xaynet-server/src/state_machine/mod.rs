@@ -18,9 +18,12 @@
 //! **Idle**
 //!
 //! Publishes [`PhaseName::Idle`], increments the `round id` by `1`, invalidates the
-//! [`SumDict`], [`SeedDict`], `scalar` and `mask length`, updates the [`EncryptKeyPair`],
-//! `thresholds` as well as the `seed` and publishes the [`EncryptKeyPair`] and the
-//! [`RoundParameters`].
+//! [`SumDict`], [`SeedDict`], `scalar` and `mask length`, applies pending PET settings, updates
+//! the [`EncryptKeyPair`], `thresholds` as well as the `seed` and publishes the
+//! [`EncryptKeyPair`] and the [`RoundParameters`]. If the [schedule] postpones the round, the
+//! start of the round is published in the [`RoundParameters`] and the phase waits until then.
+//! Once the maximal number of rounds of the [schedule] has been reached, the phase either waits
+//! until the [`StateMachine`] is shut down or moves on to the **Shutdown** phase.
 //!
 //! **Sum**
 //!
@@ -66,6 +69,14 @@
 //!
 //! See [here][requests] for more details.
 //!
+//! # Commands
+//!
+//! Along with the [StateMachineRequest][requests] channel, a [Command][commands] channel is
+//! created, via which an operator can skip the current round, update the PET settings for the
+//! next round or shut the [`StateMachine`] down.
+//! The sender half ([`CommandSender`]) is returned back to the caller of
+//! [`StateMachineInitializer::init()`].
+//!
 //! # Events
 //!
 //! During the execution of the PET protocol, the [`StateMachine`] will publish various events
@@ -76,6 +87,7 @@
 //! See [here][events] for more details.
 //!
 //! [settings]: crate::settings
+//! [schedule]: crate::state_machine::schedule
 //! [`PhaseName::Idle`]: crate::state_machine::phases::PhaseName::Idle
 //! [`PhaseName::Sum`]: crate::state_machine::phases::PhaseName::Sum
 //! [`PhaseName::Update`]: crate::state_machine::phases::PhaseName::Update
@@ -91,14 +103,20 @@
 //! [requests]: crate::state_machine::requests
 //! [`RequestSender`]: crate::state_machine::requests::RequestSender
 //! [`RequestReceiver`]: crate::state_machine::requests::RequestReceiver
+//! [commands]: crate::state_machine::commands
+//! [`CommandSender`]: crate::state_machine::commands::CommandSender
 //! [events]: crate::state_machine::events
 //! [`EventSubscriber`]: crate::state_machine::events::EventSubscriber
 
+pub mod aggregation;
+pub mod commands;
 pub mod coordinator;
 pub mod events;
 pub mod initializer;
 pub mod phases;
 pub mod requests;
+pub mod schedule;
+pub mod selection;
 pub use self::initializer::StateMachineInitializer;
 
 use derive_more::From;
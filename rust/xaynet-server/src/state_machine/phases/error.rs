@@ -8,6 +8,7 @@ use tracing::{error, info};
 use crate::{
     event,
     state_machine::{
+        commands::Command,
         phases::{
             idle::IdleStateError,
             sum::SumStateError,
@@ -32,6 +33,8 @@ pub enum PhaseStateError {
     RequestChannel(&'static str),
     #[error("phase timeout")]
     PhaseTimeout(#[from] tokio::time::error::Elapsed),
+    #[error("phase interrupted by command: {0:?}")]
+    Interrupted(Command),
 
     #[error("idle phase failed: {0}")]
     Idle(#[from] IdleStateError),
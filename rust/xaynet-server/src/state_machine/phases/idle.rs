@@ -1,15 +1,21 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
+use futures::{future, StreamExt};
 use sodiumoxide::crypto::hash::sha256;
 use thiserror::Error;
-use tracing::{debug, info};
+use tokio::time::sleep;
+use tracing::{debug, error, info};
 
 use crate::{
     metric,
     metrics::Measurement,
     state_machine::{
+        commands::Command,
         events::DictionaryUpdate,
-        phases::{Phase, PhaseName, PhaseState, Shared, Sum},
+        phases::{unix_now, Phase, PhaseName, PhaseState, Shared, Shutdown, Sum},
         PhaseStateError,
+        RequestError,
         StateMachine,
     },
     storage::{Storage, StorageError},
@@ -40,6 +46,22 @@ where
     const NAME: PhaseName = PhaseName::Idle;
 
     async fn run(&mut self) -> Result<(), PhaseStateError> {
+        self.purge_outdated_commands()?;
+
+        let round_id = self.shared.state.round_id;
+        if self.shared.schedule.exceeds_max_rounds(round_id) {
+            info!("the maximal number of rounds has been reached");
+            if self.shared.schedule.shuts_down(round_id) {
+                return Ok(());
+            }
+            return self.wait(None).await;
+        }
+
+        if let Some(pet_settings) = self.shared.pending_settings.take() {
+            info!("updating the PET settings");
+            self.shared.state.update_pet_settings(pet_settings);
+        }
+
         info!("updating the keys");
         self.gen_round_keypair();
 
@@ -49,6 +71,9 @@ where
         info!("updating round seeds");
         self.update_round_seed();
 
+        let starts_at = self.shared.schedule.start(unix_now());
+        self.shared.state.round_params.starts_at = starts_at;
+
         self.shared
             .store
             .set_coordinator_state(&self.shared.state)
@@ -88,12 +113,29 @@ where
             ("round_id", self.shared.state.round_id),
             ("phase", Self::NAME as u8)
         );
+        if let Some(budget) = self.shared.state.round_params.privacy_budget {
+            metric!(
+                Measurement::RoundParamPrivacyEpsilon,
+                budget.epsilon(),
+                ("round_id", self.shared.state.round_id),
+                ("phase", Self::NAME as u8)
+            );
+        }
+
+        if let Some(starts_at) = starts_at {
+            info!("postponing the sum phase until {}", starts_at);
+            self.wait(Some(starts_at)).await?;
+        }
 
         Ok(())
     }
 
     fn next(self) -> Option<StateMachine<S>> {
-        Some(PhaseState::<Sum, _>::new(self.shared).into())
+        if self.shared.schedule.shuts_down(self.shared.state.round_id) {
+            Some(PhaseState::<Shutdown, _>::new(self.shared).into())
+        } else {
+            Some(PhaseState::<Sum, _>::new(self.shared).into())
+        }
     }
 }
 
@@ -114,7 +156,39 @@ where
         }
     }
 
-    fn update_round_thresholds(&mut self) {}
+    /// Updates the sum and update probabilities of the round parameters, if the adaptive
+    /// selection is enabled.
+    fn update_round_thresholds(&mut self) {
+        let state = &mut self.shared.state;
+        let adaptive = match state.adaptive.as_ref() {
+            Some(adaptive) => adaptive,
+            None => return,
+        };
+        let params = &mut state.round_params;
+        params.sum = adaptive.sum_probability(params.sum);
+        params.update = adaptive.update_probability(params.update, params.sum);
+        debug!(
+            "sum probability = {}, update probability = {}",
+            params.sum, params.update
+        );
+
+        if let Some(participation) = adaptive.sum.participation {
+            metric!(
+                Measurement::RoundParticipationSum,
+                participation,
+                ("round_id", state.round_id),
+                ("phase", Self::NAME as u8)
+            );
+        }
+        if let Some(participation) = adaptive.update.participation {
+            metric!(
+                Measurement::RoundParticipationUpdate,
+                participation,
+                ("round_id", state.round_id),
+                ("phase", Self::NAME as u8)
+            );
+        }
+    }
 
     /// Updates the seed round parameter.
     fn update_round_seed(&mut self) {
@@ -135,6 +209,57 @@ where
             RoundSeed::from_slice_unchecked(sha256::hash(signature.as_slice()).as_ref());
     }
 
+    /// Waits until the unix timestamp `starts_at` or, if `None`, until the state machine is shut
+    /// down.
+    ///
+    /// A skip command ends the waiting for the start of the round right away. Since no round is
+    /// running in the meantime, requests are rejected and updates of the PET settings are kept
+    /// for the next round.
+    ///
+    /// # Errors
+    /// Returns [`PhaseStateError::Interrupted`] when a shutdown command is received and
+    /// [`PhaseStateError::RequestChannel`] when all sender halves have been dropped.
+    async fn wait(&mut self, starts_at: Option<u64>) -> Result<(), PhaseStateError> {
+        let delay = starts_at.map(|starts_at| starts_at.saturating_sub(unix_now()));
+        let deadline = async move {
+            match delay {
+                Some(delay) => sleep(Duration::from_secs(delay)).await,
+                None => future::pending().await,
+            }
+        };
+        tokio::pin!(deadline);
+
+        loop {
+            tokio::select! {
+                _ = &mut deadline => return Ok(()),
+                Some(command) = self.shared.command_rx.recv() => match command {
+                    Command::SkipRound if starts_at.is_some() => {
+                        info!("starting the sum phase early");
+                        return Ok(());
+                    }
+                    Command::SkipRound => debug!("discarding command {:?}", command),
+                    Command::Shutdown => return Err(PhaseStateError::Interrupted(command)),
+                    Command::UpdatePetSettings(settings) => {
+                        self.shared.pending_settings = Some(*settings);
+                    }
+                },
+                next = self.shared.request_rx.next() => match next {
+                    Some((_, span, resp_tx)) => {
+                        let _span_guard = span.enter();
+                        info!("rejecting request while no round is running");
+                        let _ = resp_tx.send(Err(RequestError::MessageRejected));
+                    }
+                    None => {
+                        error!("request receiver broken: senders have been dropped");
+                        return Err(PhaseStateError::RequestChannel(
+                            "all message senders have been dropped!",
+                        ));
+                    }
+                },
+            }
+        }
+    }
+
     /// Generates fresh round credentials.
     fn gen_round_keypair(&mut self) {
         self.shared.state.keys = EncryptKeyPair::generate();
@@ -148,8 +273,16 @@ mod tests {
 
     use super::*;
     use crate::{
+        settings::{
+            PetSettingsAdaptive,
+            PetSettingsAdaptivePhase,
+            RoundLimitAction,
+            ScheduleSettings,
+        },
         state_machine::{
             events::Event,
+            schedule::Schedule,
+            selection::AdaptiveSelection,
             tests::{builder::StateMachineBuilder, utils},
         },
         storage::{tests::init_store, CoordinatorStorage},
@@ -160,7 +293,7 @@ mod tests {
     async fn integration_round_id_is_updated_when_idle_phase_runs() {
         let store = init_store().await;
         let coordinator_state = utils::coordinator_state();
-        let (shared, _, event_subscriber) = utils::init_shared(coordinator_state, store);
+        let (shared, _, _, event_subscriber) = utils::init_shared(coordinator_state, store);
 
         let keys = event_subscriber.keys_listener();
         let id = keys.get_latest().round_id;
@@ -173,6 +306,122 @@ mod tests {
         assert_eq!(id, 1);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn integration_round_thresholds_are_adapted_when_idle_phase_runs() {
+        let store = init_store().await;
+        let mut coordinator_state = utils::coordinator_state();
+        let pet_settings = utils::pet_settings();
+        let settings = PetSettingsAdaptive {
+            sum: PetSettingsAdaptivePhase {
+                min_prob: 0.01,
+                max_prob: 0.5,
+                target: Some(2),
+            },
+            update: PetSettingsAdaptivePhase {
+                min_prob: 0.25,
+                max_prob: 1.,
+                target: Some(30),
+            },
+            max_step: 2.,
+            smoothing: 0.5,
+        };
+        let mut adaptive =
+            AdaptiveSelection::new(settings, pet_settings.sum.count, pet_settings.update.count);
+        adaptive.sum.participation = Some(10.);
+        adaptive.update.participation = Some(100.);
+        coordinator_state.adaptive = Some(adaptive);
+        let (shared, _, _, event_subscriber) = utils::init_shared(coordinator_state, store);
+
+        let mut idle_phase = PhaseState::<Idle, _>::new(shared);
+        idle_phase.run().await.unwrap();
+
+        // 2 of 10 sum participants and 30 of (1 - 0.2) * 100 update participants
+        let params = event_subscriber.params_listener().get_latest().event;
+        assert!((params.sum - 0.2).abs() < 1e-9);
+        assert!((params.update - 0.375).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_pending_settings_are_applied_when_idle_phase_runs() {
+        let store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _, _, event_subscriber) = utils::init_shared(coordinator_state, store);
+        let mut pet_settings = utils::pet_settings();
+        pet_settings.sum.prob = 0.3;
+        pet_settings.sum2.quorum = Some(1);
+        shared.pending_settings = Some(pet_settings);
+
+        let mut idle_phase = PhaseState::<Idle, _>::new(shared);
+        idle_phase.run().await.unwrap();
+
+        assert!(idle_phase.shared.pending_settings.is_none());
+        assert_eq!(idle_phase.shared.state.quorum, Some(1));
+        let params = event_subscriber.params_listener().get_latest().event;
+        assert!((params.sum - 0.3).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_sum_phase_is_postponed_by_the_schedule() {
+        let store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _request_tx, command_tx, event_subscriber) =
+            utils::init_shared(coordinator_state, store);
+        shared.schedule = Schedule::from(ScheduleSettings {
+            cooldown: 3600,
+            ..ScheduleSettings::default()
+        });
+
+        let mut idle_phase = PhaseState::<Idle, _>::new(shared);
+        let skip = async {
+            sleep(Duration::from_millis(100)).await;
+            command_tx.send(Command::SkipRound).unwrap();
+        };
+        let (res, _) = tokio::join!(idle_phase.run(), skip);
+        res.unwrap();
+
+        let params = event_subscriber.params_listener().get_latest().event;
+        let starts_at = params.starts_at.unwrap();
+        assert!(starts_at > unix_now() + 3000);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_idle_phase_waits_after_max_rounds() {
+        let store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _request_tx, _command_tx, _event_subscriber) =
+            utils::init_shared(coordinator_state, store);
+        shared.schedule = Schedule::from(ScheduleSettings {
+            max_rounds: Some(0),
+            ..ScheduleSettings::default()
+        });
+
+        let mut idle_phase = PhaseState::<Idle, _>::new(shared);
+        let res = tokio::time::timeout(Duration::from_millis(100), idle_phase.run()).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_idle_to_shutdown_after_max_rounds() {
+        let store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _request_tx, _command_tx, _event_subscriber) =
+            utils::init_shared(coordinator_state, store);
+        shared.schedule = Schedule::from(ScheduleSettings {
+            max_rounds: Some(0),
+            after_max_rounds: RoundLimitAction::Shutdown,
+            ..ScheduleSettings::default()
+        });
+
+        let mut idle_phase = PhaseState::<Idle, _>::new(shared);
+        idle_phase.run().await.unwrap();
+        assert!(idle_phase.next().unwrap().is_shutdown());
+    }
+
     #[tokio::test]
     #[serial]
     async fn integration_idle_to_sum() {
@@ -8,10 +8,15 @@ mod sum2;
 mod unmask;
 mod update;
 
-use std::fmt;
+use std::{
+    fmt,
+    mem,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use async_trait::async_trait;
 use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, error_span, info, warn, Span};
 use tracing_futures::Instrument;
 
@@ -27,18 +32,24 @@ pub use self::{
 use crate::{
     metric,
     metrics::Measurement,
+    settings::PetSettings,
     state_machine::{
-        coordinator::CoordinatorState,
-        events::EventPublisher,
+        aggregation::AggregationStrategy,
+        commands::{Command, CommandReceiver},
+        coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress, TimeParameters},
+        events::{EventPublisher, PhaseStatus},
         requests::{RequestReceiver, ResponseSender, StateMachineRequest},
+        schedule::Schedule,
         RequestError,
         StateMachine,
     },
     storage::Storage,
 };
+use xaynet_core::mask::Aggregation;
 
 /// The name of the current phase.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PhaseName {
     Idle,
     Sum,
@@ -71,6 +82,11 @@ where
     ///
     /// [module level documentation]: crate::state_machine
     fn next(self) -> Option<StateMachine<S>>;
+
+    /// The aggregated masked models of this state, which are part of the round checkpoint.
+    fn aggregation(&self) -> Option<&Aggregation> {
+        None
+    }
 }
 
 /// A trait that must be implemented by a state to handle a request.
@@ -108,10 +124,30 @@ where
     pub(in crate::state_machine) state: CoordinatorState,
     /// The request receiver half.
     pub(in crate::state_machine) request_rx: RequestReceiver,
+    /// The command receiver half.
+    pub(in crate::state_machine) command_rx: CommandReceiver,
     /// The event publisher.
     pub(in crate::state_machine) events: EventPublisher,
+    /// The progress of the current phase.
+    pub(in crate::state_machine) status: PhaseStatus,
     /// The store for storing coordinator and model data.
     pub(in crate::state_machine) store: S,
+    /// The strategy to compute the global model.
+    pub(in crate::state_machine) aggregator: Box<dyn AggregationStrategy>,
+    /// Whether the progress of the round is checkpointed to resume the round after a restart.
+    pub(in crate::state_machine) checkpoints: bool,
+    /// The minimal duration between two round checkpoints during a phase.
+    pub(in crate::state_machine) checkpoint_interval: Duration,
+    /// The time at which the latest round checkpoint has been saved.
+    pub(in crate::state_machine) checkpointed_at: Option<Instant>,
+    /// Whether the status of the current phase has been restored from a round checkpoint.
+    pub(in crate::state_machine) resumed: bool,
+    /// The number of seconds which had elapsed in the current phase before it was resumed.
+    pub(in crate::state_machine) elapsed: u64,
+    /// The PET settings which replace the current ones when the next round starts.
+    pub(in crate::state_machine) pending_settings: Option<PetSettings>,
+    /// The schedule of the rounds.
+    pub(in crate::state_machine) schedule: Schedule,
 }
 
 impl<S> fmt::Debug for Shared<S>
@@ -122,7 +158,17 @@ where
         f.debug_struct("Shared")
             .field("state", &self.state)
             .field("request_rx", &self.request_rx)
+            .field("command_rx", &self.command_rx)
             .field("events", &self.events)
+            .field("status", &self.status)
+            .field("aggregator", &self.aggregator)
+            .field("checkpoints", &self.checkpoints)
+            .field("checkpoint_interval", &self.checkpoint_interval)
+            .field("checkpointed_at", &self.checkpointed_at)
+            .field("resumed", &self.resumed)
+            .field("elapsed", &self.elapsed)
+            .field("pending_settings", &self.pending_settings)
+            .field("schedule", &self.schedule)
             .finish()
     }
 }
@@ -136,13 +182,25 @@ where
         coordinator_state: CoordinatorState,
         publisher: EventPublisher,
         request_rx: RequestReceiver,
+        command_rx: CommandReceiver,
         store: S,
+        aggregator: Box<dyn AggregationStrategy>,
     ) -> Self {
         Self {
             state: coordinator_state,
             request_rx,
+            command_rx,
             events: publisher,
+            status: PhaseStatus::default(),
             store,
+            aggregator,
+            checkpoints: false,
+            checkpoint_interval: Duration::from_secs(0),
+            checkpointed_at: None,
+            resumed: false,
+            elapsed: 0,
+            pending_settings: None,
+            schedule: Schedule::default(),
         }
     }
 
@@ -156,6 +214,141 @@ where
     pub fn round_id(&self) -> u64 {
         self.state.round_id
     }
+
+    /// Restores the status of an interrupted phase from the round `checkpoint`.
+    ///
+    /// The restored status is kept when the phase starts, hence the phase continues with the
+    /// counters and the deadlines of the interrupted phase.
+    pub(in crate::state_machine) fn restore_status(&mut self, checkpoint: &RoundCheckpoint) {
+        self.status = PhaseStatus {
+            accepted: checkpoint.accepted,
+            rejected: checkpoint.rejected,
+            discarded: checkpoint.discarded,
+            started_at: checkpoint.started_at,
+            ..PhaseStatus::default()
+        };
+        self.resumed = true;
+    }
+
+    /// Resets the status for the start of the given phase and broadcasts it.
+    fn reset_status(&mut self, phase: PhaseName) {
+        let now = unix_now();
+        let status = if mem::take(&mut self.resumed) {
+            self.status
+        } else {
+            PhaseStatus {
+                started_at: now,
+                ..PhaseStatus::default()
+            }
+        };
+        let started_at = status.started_at;
+        self.elapsed = now.saturating_sub(started_at);
+        let time = match phase {
+            PhaseName::Sum => Some(&self.state.sum.time),
+            PhaseName::Update => Some(&self.state.update.time),
+            PhaseName::Sum2 => Some(&self.state.sum2.time),
+            _ => None,
+        };
+        self.status = PhaseStatus {
+            min_deadline: time.map(|time| started_at + time.min),
+            max_deadline: time.map(|time| started_at + time.max),
+            ..status
+        };
+        self.events.broadcast_status(self.status);
+    }
+
+    /// Gets the minimal and maximal amounts of time (in seconds) which are left for processing
+    /// messages in the current phase.
+    ///
+    /// These are shorter than the phase `time` parameters only if the phase has been resumed.
+    fn time_left(&self, time: &TimeParameters) -> (u64, u64) {
+        (
+            time.min.saturating_sub(self.elapsed),
+            time.max.saturating_sub(self.elapsed),
+        )
+    }
+
+    /// Saves the round checkpoint of the given phase, if enabled.
+    ///
+    /// Failing to save the checkpoint doesn't affect the current round, hence it is only logged.
+    async fn save_checkpoint(&mut self, phase: PhaseName, aggregation: Option<Aggregation>) {
+        if !self.checkpoints {
+            return;
+        }
+        let checkpoint = RoundCheckpoint {
+            round_id: self.state.round_id,
+            phase,
+            started_at: self.status.started_at,
+            accepted: self.status.accepted,
+            rejected: self.status.rejected,
+            discarded: self.status.discarded,
+            aggregation,
+        };
+        if let Err(err) = self.store.set_round_checkpoint(&checkpoint).await {
+            warn!("failed to save the round checkpoint: {}", err);
+        }
+        self.checkpointed_at = Some(Instant::now());
+    }
+
+    /// Checks whether the checkpoint interval has elapsed since the latest round checkpoint.
+    fn is_checkpoint_due(&self) -> bool {
+        self.checkpointed_at.map_or(true, |checkpointed_at| {
+            checkpointed_at.elapsed() >= self.checkpoint_interval
+        })
+    }
+
+    /// Saves the round progress of the given phase, if enabled.
+    ///
+    /// Failing to save the progress doesn't affect the current round, hence it is only logged.
+    async fn save_progress(&mut self, phase: PhaseName) {
+        if !self.checkpoints {
+            return;
+        }
+        let progress = RoundProgress {
+            round_id: self.state.round_id,
+            phase,
+            accepted: self.status.accepted,
+            rejected: self.status.rejected,
+            discarded: self.status.discarded,
+        };
+        if let Err(err) = self.store.set_round_progress(&progress).await {
+            warn!("failed to save the round progress: {}", err);
+        }
+    }
+
+    /// Records the messages of a finished `sum` or `update` phase for the adaptive selection, if
+    /// enabled.
+    fn record_participation(&mut self, phase: PhaseName) {
+        let state = &mut self.state;
+        let adaptive = match state.adaptive.as_mut() {
+            Some(adaptive) => adaptive,
+            None => return,
+        };
+        // the discarded messages have been sent by selected participants as well
+        let messages = self.status.accepted + self.status.discarded;
+        let duration = unix_now().saturating_sub(self.status.started_at);
+        let params = &state.round_params;
+        match phase {
+            PhaseName::Sum => adaptive.record_sum(messages, duration, &state.sum.time, params.sum),
+            PhaseName::Update => adaptive.record_update(
+                messages,
+                duration,
+                &state.update.time,
+                params.sum,
+                params.update,
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Gets the current unix timestamp in seconds.
+pub(in crate::state_machine) fn unix_now() -> u64 {
+    // a system time before the unix epoch is not worth failing for
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
 }
 
 /// The state corresponding to a phase of the PET protocol.
@@ -217,6 +410,7 @@ where
         let res = if self.has_overmuch_messages() {
             // discard if the maximum message count is reached
             self.increment_discarded();
+            self.shared.status.discarded += 1;
             metric!(
                 Measurement::MessageDiscarded,
                 1,
@@ -229,6 +423,8 @@ where
                 // accept if processed successfully
                 ok @ Ok(_) => {
                     self.increment_accepted();
+                    self.shared.status.accepted += 1;
+                    self.save_progress().await;
                     // TODO: currently the metric! macro contains redundant information in case of
                     // accepted messages: the `Measurement::MessageSum/Update/Sum2` as well as the
                     // ("phase", name_u8). once we change those three enum variants to just one
@@ -250,6 +446,7 @@ where
                 // otherwise reject
                 error @ Err(_) => {
                     self.increment_rejected();
+                    self.shared.status.rejected += 1;
                     metric!(
                         Measurement::MessageRejected,
                         1,
@@ -261,6 +458,8 @@ where
             }
         };
 
+        self.shared.events.broadcast_status(self.shared.status);
+
         // This may error out if the receiver has already been dropped but it doesn't matter for us.
         let _ = resp_tx.send(res);
     }
@@ -281,11 +480,27 @@ where
             info!("starting phase");
             info!("broadcasting phase event");
             self.shared.events.broadcast_phase(phase);
+            self.shared.reset_status(phase);
 
             metric!(Measurement::Phase, phase as u8);
 
-            if let Err(err) = self.run().await {
-                return Some(self.into_error_state(err));
+            if matches!(
+                phase,
+                PhaseName::Sum | PhaseName::Update | PhaseName::Sum2 | PhaseName::Unmask
+            ) {
+                self.save_checkpoint().await;
+            }
+
+            let result = self.run().await;
+            if !matches!(result, Err(PhaseStateError::Interrupted(_))) {
+                self.shared.record_participation(phase);
+            }
+            match result {
+                Ok(()) => {}
+                Err(PhaseStateError::Interrupted(command)) => {
+                    return Some(self.into_interrupted_state(command));
+                }
+                Err(err) => return Some(self.into_error_state(err)),
             }
 
             info!("phase ran successfully");
@@ -308,6 +523,26 @@ where
         }.instrument(span).await
     }
 
+    /// Saves the round checkpoint of the current phase, if enabled.
+    async fn save_checkpoint(&mut self) {
+        let aggregation = self.aggregation().cloned();
+        self.shared
+            .save_checkpoint(<Self as Phase<_>>::NAME, aggregation)
+            .await;
+    }
+
+    /// Saves the round progress of the current phase and, once the checkpoint interval has
+    /// elapsed, the round checkpoint, if enabled.
+    ///
+    /// Only the progress is saved after most messages, because the round checkpoint clones and
+    /// serializes the aggregated masked models.
+    async fn save_progress(&mut self) {
+        self.shared.save_progress(<Self as Phase<_>>::NAME).await;
+        if self.shared.checkpoints && self.shared.is_checkpoint_due() {
+            self.save_checkpoint().await;
+        }
+    }
+
     /// Process all the pending requests that are now considered
     /// outdated. This happens at the end of each phase, before
     /// transitioning to the next phase.
@@ -341,14 +576,32 @@ where
     ///
     /// # Errors
     /// Returns [`PhaseStateError::RequestChannel`] when all sender halves have been dropped.
+    ///
+    /// Returns [`PhaseStateError::Interrupted`] when a [`Command`] is received in the meantime,
+    /// unless it is an update of the PET settings, which is postponed to the next round.
     async fn next_request(
         &mut self,
     ) -> Result<(StateMachineRequest, Span, ResponseSender), PhaseStateError> {
         debug!("waiting for the next incoming request");
-        self.shared.request_rx.next().await.ok_or_else(|| {
-            error!("request receiver broken: senders have been dropped");
-            PhaseStateError::RequestChannel("all message senders have been dropped!")
-        })
+        loop {
+            tokio::select! {
+                Some(command) = self.shared.command_rx.recv() => {
+                    if let Command::UpdatePetSettings(settings) = command {
+                        info!("postponing the update of the PET settings to the next round");
+                        self.shared.pending_settings = Some(*settings);
+                        continue;
+                    }
+                    info!("interrupted by command {:?}", command);
+                    return Err(PhaseStateError::Interrupted(command));
+                }
+                next = self.shared.request_rx.next() => {
+                    return next.ok_or_else(|| {
+                        error!("request receiver broken: senders have been dropped");
+                        PhaseStateError::RequestChannel("all message senders have been dropped!")
+                    });
+                }
+            }
+        }
     }
 
     fn try_next_request(
@@ -372,6 +625,36 @@ where
     fn into_error_state(self, err: PhaseStateError) -> StateMachine<T> {
         PhaseState::<PhaseStateError, _>::new(self.shared, err).into()
     }
+
+    fn into_interrupted_state(self, command: Command) -> StateMachine<T> {
+        match command {
+            Command::SkipRound => PhaseState::<Idle, _>::new(self.shared).into(),
+            Command::Shutdown => PhaseState::<Shutdown, _>::new(self.shared).into(),
+            Command::UpdatePetSettings(_) => {
+                unreachable!("an update of the PET settings doesn't interrupt a phase")
+            }
+        }
+    }
+
+    /// Processes the pending commands which were sent before a new round starts.
+    ///
+    /// Skipping the round is obsolete at that point, hence only a shutdown is honored. An update
+    /// of the PET settings is kept until it is applied to the new round.
+    ///
+    /// # Errors
+    /// Returns [`PhaseStateError::Interrupted`] when a shutdown is pending.
+    fn purge_outdated_commands(&mut self) -> Result<(), PhaseStateError> {
+        while let Some(command) = self.shared.command_rx.try_recv() {
+            match command {
+                Command::SkipRound => debug!("discarding outdated command {:?}", command),
+                Command::Shutdown => return Err(PhaseStateError::Interrupted(command)),
+                Command::UpdatePetSettings(settings) => {
+                    self.shared.pending_settings = Some(*settings);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -379,14 +662,17 @@ mod tests {
     use serial_test::serial;
 
     use super::*;
-    use crate::{state_machine::tests::utils, storage::tests::init_store};
+    use crate::{
+        state_machine::tests::utils,
+        storage::{tests::init_store, CoordinatorStorage},
+    };
 
     #[tokio::test]
     #[serial]
     async fn integration_update_round_id() {
         let store = init_store().await;
         let coordinator_state = utils::coordinator_state();
-        let (mut shared, _, event_subscriber) = utils::init_shared(coordinator_state, store);
+        let (mut shared, _, _, event_subscriber) = utils::init_shared(coordinator_state, store);
 
         let phases = event_subscriber.phase_listener();
         // When starting the round ID should be 0
@@ -405,4 +691,102 @@ mod tests {
         let id = phases.get_latest().round_id;
         assert_eq!(id, 1);
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_save_checkpoint() {
+        let mut store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _, _, _) = utils::init_shared(coordinator_state, store.clone());
+
+        // nothing is saved unless the checkpoints are enabled
+        shared.save_checkpoint(PhaseName::Sum, None).await;
+        assert!(store.round_checkpoint().await.unwrap().is_none());
+
+        shared.checkpoints = true;
+        shared.reset_status(PhaseName::Update);
+        shared.status.accepted = 1;
+        let mut state = PhaseState::<Update, _>::new(shared);
+        state.save_checkpoint().await;
+
+        let checkpoint = store.round_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.round_id, state.shared.state.round_id);
+        assert_eq!(checkpoint.phase, PhaseName::Update);
+        assert_eq!(checkpoint.started_at, state.shared.status.started_at);
+        assert_eq!(checkpoint.accepted, 1);
+        assert_eq!(checkpoint.aggregation.as_ref(), state.aggregation());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_save_progress() {
+        let mut store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _, _, _) = utils::init_shared(coordinator_state, store.clone());
+        shared.checkpoints = true;
+        shared.checkpoint_interval = Duration::from_secs(3600);
+        shared.reset_status(PhaseName::Update);
+        let mut state = PhaseState::<Update, _>::new(shared);
+        state.save_checkpoint().await;
+
+        // only the progress is saved until the checkpoint interval has elapsed
+        state.shared.status.accepted = 1;
+        state.save_progress().await;
+        let checkpoint = store.round_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.accepted, 0);
+        let progress = store.round_progress().await.unwrap().unwrap();
+        assert_eq!(progress.round_id, state.shared.state.round_id);
+        assert_eq!(progress.phase, PhaseName::Update);
+        assert_eq!(progress.accepted, 1);
+
+        state.shared.checkpoint_interval = Duration::from_secs(0);
+        state.shared.status.accepted = 2;
+        state.save_progress().await;
+        let checkpoint = store.round_checkpoint().await.unwrap().unwrap();
+        assert_eq!(checkpoint.accepted, 2);
+        let progress = store.round_progress().await.unwrap().unwrap();
+        assert_eq!(progress.accepted, 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn integration_restore_status() {
+        let store = init_store().await;
+        let coordinator_state = utils::coordinator_state();
+        let (mut shared, _, _, event_subscriber) = utils::init_shared(coordinator_state, store);
+
+        let started_at = unix_now() - 10;
+        let checkpoint = RoundCheckpoint {
+            round_id: shared.state.round_id,
+            phase: PhaseName::Sum,
+            started_at,
+            accepted: 1,
+            rejected: 2,
+            discarded: 3,
+            aggregation: None,
+        };
+        shared.restore_status(&checkpoint);
+        shared.reset_status(PhaseName::Sum);
+
+        // the resumed phase keeps its counters and deadlines
+        let status = event_subscriber.status_listener().get_latest().event;
+        assert_eq!(status, shared.status);
+        assert_eq!(status.accepted, 1);
+        assert_eq!(status.rejected, 2);
+        assert_eq!(status.discarded, 3);
+        assert_eq!(status.started_at, started_at);
+        let time = &shared.state.sum.time;
+        assert_eq!(status.min_deadline, Some(started_at + time.min));
+        assert_eq!(status.max_deadline, Some(started_at + time.max));
+        // the phase durations of the test settings have already elapsed
+        assert!(shared.elapsed >= 10);
+        assert_eq!(shared.time_left(time), (0, 0));
+
+        // the next phase starts from scratch
+        shared.reset_status(PhaseName::Update);
+        assert_eq!(shared.status.accepted, 0);
+        assert_eq!(shared.elapsed, 0);
+        let time = &shared.state.update.time;
+        assert_eq!(shared.time_left(time), (time.min, time.max));
+    }
 }
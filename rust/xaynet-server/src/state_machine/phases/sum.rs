@@ -7,7 +7,7 @@ use tracing::{debug, info};
 
 use crate::{
     state_machine::{
-        events::DictionaryUpdate,
+        events::{DictionaryUpdate, PhaseStatus},
         phases::{Handler, Phase, PhaseName, PhaseState, PhaseStateError, Shared, Update},
         requests::{StateMachineRequest, SumRequest},
         RequestError,
@@ -46,8 +46,7 @@ where
     const NAME: PhaseName = PhaseName::Sum;
 
     async fn run(&mut self) -> Result<(), PhaseStateError> {
-        let min_time = self.shared.state.sum.time.min;
-        let max_time = self.shared.state.sum.time.max;
+        let (min_time, max_time) = self.shared.time_left(&self.shared.state.sum.time);
         debug!(
             "in sum phase for min {} and max {} seconds",
             min_time, max_time,
@@ -145,6 +144,24 @@ where
         }
     }
 
+    /// Creates a sum state which continues with the restored status of an interrupted sum phase.
+    pub fn resume(shared: Shared<S>) -> Self {
+        let PhaseStatus {
+            accepted,
+            rejected,
+            discarded,
+            ..
+        } = shared.status;
+        Self {
+            private: Sum {
+                accepted,
+                rejected,
+                discarded,
+            },
+            shared,
+        }
+    }
+
     /// Updates the sum dict with a sum participant request.
     async fn update_sum_dict(
         &mut self,
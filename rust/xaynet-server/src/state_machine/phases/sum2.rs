@@ -1,9 +1,10 @@
 use async_trait::async_trait;
 use tokio::time::{timeout, Duration};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::{
     state_machine::{
+        events::PhaseStatus,
         phases::{Handler, Phase, PhaseName, PhaseState, PhaseStateError, Shared, Unmask},
         requests::{StateMachineRequest, Sum2Request},
         RequestError,
@@ -38,8 +39,7 @@ where
     const NAME: PhaseName = PhaseName::Sum2;
 
     async fn run(&mut self) -> Result<(), PhaseStateError> {
-        let min_time = self.shared.state.sum2.time.min;
-        let max_time = self.shared.state.sum2.time.max;
+        let (min_time, max_time) = self.shared.time_left(&self.shared.state.sum2.time);
         debug!(
             "in sum2 phase for min {} and max {} seconds",
             min_time, max_time,
@@ -47,7 +47,14 @@ where
         self.process_during(Duration::from_secs(min_time)).await?;
 
         let time_left = max_time - min_time;
-        timeout(Duration::from_secs(time_left), self.process_until_enough()).await??;
+        match timeout(Duration::from_secs(time_left), self.process_until_enough()).await {
+            Ok(res) => res?,
+            Err(_) if self.has_quorum() => warn!(
+                "sum2 phase timed out, continuing with a quorum of {} sum2 messages",
+                self.private.accepted,
+            ),
+            Err(elapsed) => return Err(elapsed.into()),
+        }
 
         info!(
             "in total {} sum2 messages accepted (min {} and max {} required)",
@@ -72,6 +79,10 @@ where
     fn next(self) -> Option<StateMachine<S>> {
         Some(PhaseState::<Unmask, _>::new(self.shared, self.private.model_agg).into())
     }
+
+    fn aggregation(&self) -> Option<&Aggregation> {
+        Some(&self.private.model_agg)
+    }
 }
 
 #[async_trait]
@@ -137,6 +148,34 @@ where
         }
     }
 
+    /// Creates a sum2 state which continues with the restored status of an interrupted sum2
+    /// phase.
+    pub fn resume(shared: Shared<S>, model_agg: Aggregation) -> Self {
+        let PhaseStatus {
+            accepted,
+            rejected,
+            discarded,
+            ..
+        } = shared.status;
+        Self {
+            private: Sum2 {
+                model_agg,
+                accepted,
+                rejected,
+                discarded,
+            },
+            shared,
+        }
+    }
+
+    /// Checks whether enough requests have been processed successfully to reach the quorum.
+    fn has_quorum(&self) -> bool {
+        self.shared
+            .state
+            .quorum
+            .map_or(false, |quorum| self.private.accepted >= quorum)
+    }
+
     /// Updates the mask dict with a sum2 participant request.
     async fn update_mask_dict(
         &mut self,
@@ -173,6 +212,7 @@ mod tests {
         common::{RoundParameters, RoundSeed},
         crypto::{ByteObject, EncryptKeyPair},
         mask::{FromPrimitives, Model},
+        message::PROTOCOL_VERSION,
         SumDict,
     };
 
@@ -194,6 +234,9 @@ mod tests {
             seed: RoundSeed::generate(),
             mask_config: utils::mask_config(),
             model_length,
+            privacy_budget: None,
+            version: PROTOCOL_VERSION,
+            starts_at: None,
         };
 
         let n_updaters = 1;
@@ -285,4 +328,99 @@ mod tests {
             }
         );
     }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn integration_sum2_to_unmask_with_quorum() {
+        utils::enable_logging();
+        let model_length = 4;
+        let round_params = RoundParameters {
+            pk: EncryptKeyPair::generate().public,
+            sum: 0.5,
+            update: 1.0,
+            seed: RoundSeed::generate(),
+            mask_config: utils::mask_config(),
+            model_length,
+            privacy_budget: None,
+            version: PROTOCOL_VERSION,
+            starts_at: None,
+        };
+
+        let n_updaters = 1;
+        let n_summers = 2;
+
+        // Generate a sum dictionary with two sum participants, one of which drops out
+        let summer = utils::generate_summer(round_params.clone());
+        let dropped_summer = utils::generate_summer(round_params.clone());
+        let mut sum_dict = SumDict::new();
+        sum_dict.insert(summer.keys.public, summer.ephm_keys.public);
+        sum_dict.insert(dropped_summer.keys.public, dropped_summer.ephm_keys.public);
+
+        let updater = utils::generate_updater(round_params.clone());
+        let scalar = 1.0 / (n_updaters as f64 * round_params.update);
+        let model = Model::from_primitives(vec![0; model_length].into_iter()).unwrap();
+        let (mask_seed, masked_model) = updater.compute_masked_model(&model, scalar);
+        let local_seed_dict = Participant::build_seed_dict(&sum_dict, &mask_seed);
+
+        let mut update_seed_dict = HashMap::new();
+        let encrypted_seed = local_seed_dict.get(&summer.keys.public).unwrap();
+        update_seed_dict.insert(updater.keys.public, encrypted_seed.clone());
+
+        let mut agg = Aggregation::new(summer.mask_settings, model_length);
+        agg.aggregate(masked_model);
+
+        // The sum2 phase requires two messages, but a quorum of one is enough
+        let mut store = init_store().await;
+        let (state_machine, request_tx, _events) = StateMachineBuilder::new(store.clone())
+            .with_seed(round_params.seed.clone())
+            .with_phase(Sum2 {
+                model_agg: agg,
+                accepted: 0,
+                rejected: 0,
+                discarded: 0,
+            })
+            .with_sum_probability(round_params.sum)
+            .with_update_probability(round_params.update)
+            .with_sum_count_min(n_summers)
+            .with_sum_count_max(n_summers + 10)
+            .with_update_count_min(n_updaters)
+            .with_update_count_max(n_updaters + 10)
+            .with_sum2_count_min(n_summers)
+            .with_sum2_count_max(n_summers + 10)
+            .with_sum2_time_min(1)
+            .with_sum2_time_max(2)
+            .with_quorum(1)
+            .with_mask_config(utils::mask_settings().into())
+            .build();
+        assert!(state_machine.is_sum2());
+
+        for summer in &[&summer, &dropped_summer] {
+            store
+                .add_sum_participant(&summer.keys.public, &summer.ephm_keys.public)
+                .await
+                .unwrap();
+        }
+
+        let seeds = summer.decrypt_seeds(&update_seed_dict);
+        let aggregation = summer.aggregate_masks(model_length, &seeds);
+        let msg = summer.compose_sum2_message(aggregation.into());
+
+        let req = async { request_tx.msg(&msg).await.unwrap() };
+        let transition = async { state_machine.next().await.unwrap() };
+        let ((), state_machine) = tokio::join!(req, transition);
+        assert!(state_machine.is_unmask());
+
+        let PhaseState {
+            private: unmask_state,
+            ..
+        } = state_machine.into_unmask_phase_state();
+
+        let mut best_masks = store.best_masks().await.unwrap().unwrap();
+        assert_eq!(best_masks.len(), 1);
+        let (mask, count) = best_masks.pop().unwrap();
+        assert_eq!(count, 1);
+
+        let unmasked_model = unmask_state.aggregation().unwrap().clone().unmask(mask);
+        assert_eq!(unmasked_model, model);
+    }
 }
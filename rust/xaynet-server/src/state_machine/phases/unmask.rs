@@ -1,8 +1,12 @@
-use std::{cmp::Ordering, sync::Arc};
+use std::{
+    cmp::Ordering,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use async_trait::async_trait;
 use thiserror::Error;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::{
     metric,
@@ -14,7 +18,10 @@ use crate::{
     },
     storage::{Storage, StorageError},
 };
-use xaynet_core::mask::{Aggregation, MaskObject, Model, UnmaskingError};
+use xaynet_core::{
+    common::GlobalModelInfo,
+    mask::{Aggregation, MaskObject, Model, ModelCastError, UnmaskingError},
+};
 
 /// Error that occurs during the unmask phase.
 #[derive(Error, Debug)]
@@ -23,11 +30,14 @@ pub enum UnmaskStateError {
     AmbiguousMasks,
     #[error("no mask found")]
     NoMask,
+    #[error("the best mask was computed by {0} sum participants, but the quorum is {1}")]
+    QuorumNotReached(u64, u64),
     #[error("unmasking global model failed: {0}")]
     Unmasking(#[from] UnmaskingError),
+    #[error("aggregating global model failed: {0}")]
+    Aggregation(#[from] ModelCastError),
     #[error("fetching best masks failed: {0}")]
     FetchBestMasks(#[from] StorageError),
-    #[cfg(feature = "model-persistence")]
     #[error("saving the global model failed: {0}")]
     SaveGlobalModel(crate::storage::StorageError),
     #[error("publishing the proof of the global model failed: {0}")]
@@ -61,19 +71,32 @@ where
 
         let global_model = self.end_round(best_masks).await?;
 
-        #[cfg(feature = "model-persistence")]
-        self.save_global_model(&global_model).await?;
+        let global_model_id = if self.shared.store.persists_global_models() {
+            self.save_global_model(&global_model).await?
+        } else {
+            S::create_global_model_id(
+                self.shared.state.round_id,
+                &self.shared.state.round_params.seed,
+            )
+        };
 
         self.shared
             .store
-            .publish_proof(&global_model)
+            .publish_proof(
+                self.shared.state.round_id,
+                &self.shared.state.round_params.seed,
+                &global_model,
+            )
             .await
             .map_err(UnmaskStateError::PublishProof)?;
 
         info!("broadcasting the new global model");
-        self.shared
-            .events
-            .broadcast_model(ModelUpdate::New(Arc::new(global_model)));
+        self.shared.events.broadcast_model(ModelUpdate::New {
+            id: global_model_id,
+            model: Arc::new(global_model),
+        });
+
+        self.spend_privacy_budget();
 
         Ok(())
     }
@@ -81,6 +104,10 @@ where
     fn next(self) -> Option<StateMachine<S>> {
         Some(PhaseState::<Idle, _>::new(self.shared).into())
     }
+
+    fn aggregation(&self) -> Option<&Aggregation> {
+        self.private.model_agg.as_ref()
+    }
 }
 
 impl<S> PhaseState<Unmask, S>
@@ -102,12 +129,12 @@ where
         &mut self,
         mut best_masks: Vec<(MaskObject, u64)>,
     ) -> Result<MaskObject, UnmaskStateError> {
-        let mask = best_masks
+        let (mask, count) = best_masks
             .drain(0..)
             .fold(
                 (None, 0),
                 |(unique_mask, unique_count), (mask, count)| match unique_count.cmp(&count) {
-                    Ordering::Less => (Some(mask), count),
+                    Ordering::Less => (Some((mask, count)), count),
                     Ordering::Greater => (unique_mask, unique_count),
                     Ordering::Equal => (None, unique_count),
                 },
@@ -115,6 +142,14 @@ where
             .0
             .ok_or(UnmaskStateError::AmbiguousMasks)?;
 
+        // each sum participant aggregates the masks of all update participants, hence the sum
+        // participants which dropped out are only missing from the count
+        if let Some(quorum) = self.shared.state.quorum {
+            if count < quorum {
+                return Err(UnmaskStateError::QuorumNotReached(count, quorum));
+            }
+        }
+
         Ok(mask)
     }
 
@@ -131,13 +166,31 @@ where
             .validate_unmasking(&mask)
             .map_err(UnmaskStateError::from)?;
 
-        Ok(model_agg.unmask(mask))
+        let model = model_agg.unmask(mask);
+        self.shared
+            .aggregator
+            .aggregate(model)
+            .map_err(UnmaskStateError::from)
     }
 
-    #[cfg(feature = "model-persistence")]
-    async fn save_global_model(&mut self, global_model: &Model) -> Result<(), UnmaskStateError> {
-        use tracing::warn;
+    /// Spends the privacy budget for the completed round, if differential privacy is enabled.
+    fn spend_privacy_budget(&mut self) {
+        if let Some(budget) = self.shared.state.round_params.privacy_budget.as_mut() {
+            budget.spend();
+            info!(
+                "spent privacy budget of epsilon {} (delta {}) in {} rounds",
+                budget.epsilon(),
+                budget.delta,
+                budget.rounds,
+            );
+        }
+    }
 
+    /// Saves the global model, adds it to the global model history and returns its id.
+    async fn save_global_model(
+        &mut self,
+        global_model: &Model,
+    ) -> Result<String, UnmaskStateError> {
         let round_seed = &self.shared.state.round_params.seed;
         let global_model_id = self
             .shared
@@ -151,7 +204,26 @@ where
             .set_latest_global_model_id(&global_model_id)
             .await
             .map_err(|err| warn!("failed to update latest global model id: {}", err));
-        Ok(())
+
+        // a system time before the unix epoch is not worth failing for
+        let completed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        let info = GlobalModelInfo {
+            round_id: self.shared.state.round_id,
+            round_seed: round_seed.clone(),
+            model_id: global_model_id.clone(),
+            model_length: global_model.len(),
+            completed_at,
+        };
+        let _ = self
+            .shared
+            .store
+            .add_global_model_info(&info)
+            .await
+            .map_err(|err| warn!("failed to add the global model to the history: {}", err));
+        Ok(global_model_id)
     }
 }
 
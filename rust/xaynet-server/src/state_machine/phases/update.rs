@@ -7,7 +7,7 @@ use tracing::{debug, info, warn};
 
 use crate::{
     state_machine::{
-        events::DictionaryUpdate,
+        events::{DictionaryUpdate, PhaseStatus},
         phases::{Handler, Phase, PhaseName, PhaseState, PhaseStateError, Shared, Sum2},
         requests::{StateMachineRequest, UpdateRequest},
         RequestError,
@@ -52,8 +52,7 @@ where
     const NAME: PhaseName = PhaseName::Update;
 
     async fn run(&mut self) -> Result<(), PhaseStateError> {
-        let min_time = self.shared.state.update.time.min;
-        let max_time = self.shared.state.update.time.max;
+        let (min_time, max_time) = self.shared.time_left(&self.shared.state.update.time);
         debug!(
             "in update phase for min {} and max {} seconds",
             min_time, max_time,
@@ -97,6 +96,10 @@ where
     fn next(self) -> Option<StateMachine<S>> {
         Some(PhaseState::<Sum2, _>::new(self.shared, self.private.model_agg).into())
     }
+
+    fn aggregation(&self) -> Option<&Aggregation> {
+        Some(&self.private.model_agg)
+    }
 }
 
 #[async_trait]
@@ -171,6 +174,26 @@ where
         }
     }
 
+    /// Creates an update state which continues with the restored status and the aggregated masked
+    /// models of an interrupted update phase.
+    pub fn resume(shared: Shared<S>, model_agg: Aggregation) -> Self {
+        let PhaseStatus {
+            accepted,
+            rejected,
+            discarded,
+            ..
+        } = shared.status;
+        Self {
+            private: Update {
+                model_agg,
+                accepted,
+                rejected,
+                discarded,
+            },
+            shared,
+        }
+    }
+
     /// Updates the local seed dict and aggregates the masked model.
     async fn update_seed_dict_and_aggregate_mask(
         &mut self,
@@ -244,6 +267,7 @@ mod tests {
         common::{RoundParameters, RoundSeed},
         crypto::{ByteObject, EncryptKeyPair},
         mask::{FromPrimitives, Model},
+        message::PROTOCOL_VERSION,
         SeedDict,
         SumDict,
         UpdateSeedDict,
@@ -267,6 +291,9 @@ mod tests {
             seed: RoundSeed::generate(),
             mask_config: utils::mask_config(),
             model_length,
+            privacy_budget: None,
+            version: PROTOCOL_VERSION,
+            starts_at: None,
         };
         let n_updaters = 1;
         let n_summers = 1;
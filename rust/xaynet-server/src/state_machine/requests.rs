@@ -8,6 +8,7 @@ use std::{
 
 use derive_more::From;
 use futures::{future::FutureExt, Stream};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 use tracing::{trace, Span};
@@ -29,7 +30,7 @@ use xaynet_core::{
 pub struct StateMachineShutdown;
 
 /// A sum request.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SumRequest {
     /// The public key of the participant.
     pub participant_pk: SumParticipantPublicKey,
@@ -38,7 +39,7 @@ pub struct SumRequest {
 }
 
 /// An update request.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateRequest {
     /// The public key of the participant.
     pub participant_pk: UpdateParticipantPublicKey,
@@ -49,7 +50,7 @@ pub struct UpdateRequest {
 }
 
 /// A sum2 request.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Sum2Request {
     /// The public key of the participant.
     pub participant_pk: ParticipantPublicKey,
@@ -60,7 +61,7 @@ pub struct Sum2Request {
 /// A [`StateMachine`] request.
 ///
 /// [`StateMachine`]: crate::state_machine
-#[derive(Debug, From)]
+#[derive(Debug, From, Serialize, Deserialize)]
 pub enum StateMachineRequest {
     Sum(SumRequest),
     Update(UpdateRequest),
@@ -0,0 +1,182 @@
+//! Scheduling of the rounds.
+//!
+//! By default, the `idle` phase of a round is directly followed by its `sum` phase. The schedule
+//! postpones the `sum` phase by a cool-down after the previous round and to the next opening of a
+//! daily time window. Furthermore, the schedule limits the number of rounds.
+
+use crate::settings::{RoundLimitAction, ScheduleSettings, ScheduleWindowSettings};
+
+/// The number of seconds of a day.
+const DAY: i64 = 24 * 60 * 60;
+
+/// The daily time window in which rounds start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Window {
+    /// The local time of day (in seconds) at which the window opens.
+    start: i64,
+    /// The local time of day (in seconds) at which the window closes.
+    end: i64,
+    /// The offset of the local time to UTC (in seconds).
+    utc_offset: i64,
+}
+
+impl From<ScheduleWindowSettings> for Window {
+    fn from(window: ScheduleWindowSettings) -> Self {
+        Self {
+            start: window.start as i64,
+            end: window.end as i64,
+            utc_offset: window.utc_offset as i64 * 60,
+        }
+    }
+}
+
+impl Window {
+    /// Checks whether the local time of day `time` lies within the window.
+    fn contains(&self, time: i64) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            // the window spans midnight
+            self.start <= time || time < self.end
+        }
+    }
+
+    /// Gets the earliest unix timestamp within the window which isn't before `timestamp`.
+    fn next_start(&self, timestamp: u64) -> u64 {
+        let time = (timestamp as i64 + self.utc_offset).rem_euclid(DAY);
+        if self.contains(time) {
+            timestamp
+        } else {
+            timestamp + (self.start - time).rem_euclid(DAY) as u64
+        }
+    }
+}
+
+/// The schedule of the rounds.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    /// The minimal number of seconds between the end of a round and the start of the next round.
+    cooldown: u64,
+    /// The daily time window in which rounds start, if any.
+    window: Option<Window>,
+    /// The maximal number of rounds, if any.
+    max_rounds: Option<u64>,
+    /// What the state machine does once the maximal number of rounds has been reached.
+    after_max_rounds: RoundLimitAction,
+}
+
+impl From<ScheduleSettings> for Schedule {
+    fn from(settings: ScheduleSettings) -> Self {
+        Self {
+            cooldown: settings.cooldown,
+            window: settings.window.map(Window::from),
+            max_rounds: settings.max_rounds,
+            after_max_rounds: settings.after_max_rounds,
+        }
+    }
+}
+
+impl Schedule {
+    /// Checks whether the round `round_id` exceeds the maximal number of rounds.
+    pub fn exceeds_max_rounds(&self, round_id: u64) -> bool {
+        self.max_rounds
+            .map_or(false, |max_rounds| round_id > max_rounds)
+    }
+
+    /// Checks whether the state machine shuts down instead of starting the round `round_id`.
+    pub fn shuts_down(&self, round_id: u64) -> bool {
+        self.exceeds_max_rounds(round_id) && self.after_max_rounds == RoundLimitAction::Shutdown
+    }
+
+    /// Chooses the unix timestamp (in seconds) at which the `sum` phase of a round starts, whose
+    /// previous round ended at `now`.
+    ///
+    /// Returns `None` if the `sum` phase starts right away.
+    pub fn start(&self, now: u64) -> Option<u64> {
+        let earliest = now + self.cooldown;
+        let start = self
+            .window
+            .map_or(earliest, |window| window.next_start(earliest));
+        Some(start).filter(|start| *start > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2021-01-01T00:00:00Z
+    const MIDNIGHT: u64 = 1_609_459_200;
+    const HOUR: u64 = 3600;
+
+    fn schedule(window: Option<ScheduleWindowSettings>) -> Schedule {
+        Schedule::from(ScheduleSettings {
+            window,
+            ..ScheduleSettings::default()
+        })
+    }
+
+    #[test]
+    fn test_unrestricted() {
+        let schedule = Schedule::default();
+        assert_eq!(schedule.start(MIDNIGHT), None);
+        assert!(!schedule.exceeds_max_rounds(u64::MAX));
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let schedule = Schedule::from(ScheduleSettings {
+            cooldown: 600,
+            ..ScheduleSettings::default()
+        });
+        assert_eq!(schedule.start(MIDNIGHT), Some(MIDNIGHT + 600));
+    }
+
+    #[test]
+    fn test_window() {
+        // 01:00 - 05:00
+        let schedule = schedule(Some(ScheduleWindowSettings {
+            start: HOUR as u32,
+            end: 5 * HOUR as u32,
+            utc_offset: 0,
+        }));
+        assert_eq!(schedule.start(MIDNIGHT), Some(MIDNIGHT + HOUR));
+        assert_eq!(schedule.start(MIDNIGHT + 2 * HOUR), None);
+        assert_eq!(
+            schedule.start(MIDNIGHT + 5 * HOUR),
+            Some(MIDNIGHT + 25 * HOUR)
+        );
+    }
+
+    #[test]
+    fn test_window_spans_midnight() {
+        // 22:00 - 05:00 in UTC+1
+        let schedule = schedule(Some(ScheduleWindowSettings {
+            start: 22 * HOUR as u32,
+            end: 5 * HOUR as u32,
+            utc_offset: 60,
+        }));
+        // 01:00 local time
+        assert_eq!(schedule.start(MIDNIGHT), None);
+        // 12:00 local time
+        let noon = MIDNIGHT + 11 * HOUR;
+        assert_eq!(schedule.start(noon), Some(noon + 10 * HOUR));
+    }
+
+    #[test]
+    fn test_max_rounds() {
+        let mut settings = ScheduleSettings {
+            max_rounds: Some(3),
+            ..ScheduleSettings::default()
+        };
+        let schedule = Schedule::from(settings);
+        assert!(!schedule.exceeds_max_rounds(3));
+        assert!(schedule.exceeds_max_rounds(4));
+        assert!(!schedule.shuts_down(4));
+
+        settings.after_max_rounds = RoundLimitAction::Shutdown;
+        let schedule = Schedule::from(settings);
+        assert!(!schedule.shuts_down(3));
+        assert!(schedule.shuts_down(4));
+    }
+}
@@ -0,0 +1,239 @@
+//! Adaptive selection probabilities.
+//!
+//! The `sum` and `update` probabilities of the round parameters determine how many participants
+//! take part in a round. If the adaptive selection is enabled, the coordinator estimates the
+//! participation from the messages and the durations of the `sum` and `update` phases of the
+//! previous rounds and chooses the probabilities for the next round, which are expected to yield
+//! the target number of messages.
+//!
+//! The participation of a phase is the number of messages that would have been sent during the
+//! minimal phase duration if every participant had been selected for the task. The number of
+//! messages of a phase includes the discarded ones, because they were sent by selected
+//! participants. A phase which took longer than its minimal duration, because not enough messages
+//! were sent, is scaled down to its minimal duration.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    settings::{PetSettingsAdaptive, PetSettingsAdaptivePhase, PetSettingsCount},
+    state_machine::coordinator::TimeParameters,
+};
+
+/// The adaptive selection parameters of a phase.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdaptivePhase {
+    /// The minimal selection probability.
+    pub min_prob: f64,
+    /// The maximal selection probability.
+    pub max_prob: f64,
+    /// The number of messages to aim for.
+    pub target: f64,
+    /// The estimated participation, if any phase has been recorded yet.
+    pub participation: Option<f64>,
+}
+
+impl AdaptivePhase {
+    fn new(settings: PetSettingsAdaptivePhase, count: PetSettingsCount) -> Self {
+        let target = settings
+            .target
+            .map(|target| target as f64)
+            .unwrap_or_else(|| (count.min as f64 + count.max as f64) / 2.);
+        Self {
+            min_prob: settings.min_prob,
+            max_prob: settings.max_prob,
+            target,
+            participation: None,
+        }
+    }
+}
+
+/// The adaptive selection parameters and the estimated participation of the `sum` and `update`
+/// phases.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveSelection {
+    /// The adaptive selection of the `sum` phase.
+    pub sum: AdaptivePhase,
+    /// The adaptive selection of the `update` phase.
+    pub update: AdaptivePhase,
+    /// The maximal factor by which a probability changes from one round to the next.
+    pub max_step: f64,
+    /// The weight of the latest round in the estimated participation.
+    pub smoothing: f64,
+}
+
+impl AdaptiveSelection {
+    /// Creates the adaptive selection from the settings.
+    pub fn new(
+        settings: PetSettingsAdaptive,
+        sum_count: PetSettingsCount,
+        update_count: PetSettingsCount,
+    ) -> Self {
+        Self {
+            sum: AdaptivePhase::new(settings.sum, sum_count),
+            update: AdaptivePhase::new(settings.update, update_count),
+            max_step: settings.max_step,
+            smoothing: settings.smoothing,
+        }
+    }
+
+    /// Records the `messages` of a `sum` phase which lasted for `duration` seconds with the
+    /// `sum` probability.
+    pub fn record_sum(&mut self, messages: u64, duration: u64, time: &TimeParameters, sum: f64) {
+        let participation = projected(messages, duration, time) / sum;
+        self.sum.participation = Some(self.smooth(self.sum.participation, participation));
+    }
+
+    /// Records the `messages` of an `update` phase which lasted for `duration` seconds with the
+    /// `sum` and `update` probabilities.
+    ///
+    /// Only the participants which are not selected for the `sum` task can be selected for the
+    /// `update` task.
+    pub fn record_update(
+        &mut self,
+        messages: u64,
+        duration: u64,
+        time: &TimeParameters,
+        sum: f64,
+        update: f64,
+    ) {
+        let participation = projected(messages, duration, time) / ((1. - sum) * update);
+        self.update.participation = Some(self.smooth(self.update.participation, participation));
+    }
+
+    /// Chooses the `sum` probability of the next round, given the `current` one.
+    pub fn sum_probability(&self, current: f64) -> f64 {
+        let target = self
+            .sum
+            .participation
+            .map(|participation| self.sum.target / participation);
+        self.bounded(&self.sum, current, target)
+    }
+
+    /// Chooses the `update` probability of the next round, given the `current` one and the `sum`
+    /// probability of the next round.
+    pub fn update_probability(&self, current: f64, sum: f64) -> f64 {
+        let target = self
+            .update
+            .participation
+            .map(|participation| self.update.target / (participation * (1. - sum)));
+        self.bounded(&self.update, current, target)
+    }
+
+    /// Updates the estimated participation with the participation of the latest round.
+    fn smooth(&self, estimated: Option<f64>, latest: f64) -> f64 {
+        match estimated {
+            Some(estimated) => self.smoothing * latest + (1. - self.smoothing) * estimated,
+            None => latest,
+        }
+    }
+
+    /// Moves the `current` probability towards the `target` probability, limited by the maximal
+    /// step and the bounds of the `phase`.
+    ///
+    /// The `target` probability is infinite if no participant showed up, in which case the
+    /// probability is increased by the maximal step.
+    fn bounded(&self, phase: &AdaptivePhase, current: f64, target: Option<f64>) -> f64 {
+        let target = target.unwrap_or(current);
+        target
+            .max(current / self.max_step)
+            .min(current * self.max_step)
+            .max(phase.min_prob)
+            .min(phase.max_prob)
+    }
+}
+
+/// Projects the `messages` of a phase which lasted for `duration` seconds onto the minimal
+/// duration of the phase.
+fn projected(messages: u64, duration: u64, time: &TimeParameters) -> f64 {
+    if time.min == 0 || duration <= time.min {
+        messages as f64
+    } else {
+        messages as f64 * time.min as f64 / duration as f64
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn adaptive() -> AdaptiveSelection {
+        let phase = PetSettingsAdaptivePhase {
+            min_prob: 0.01,
+            max_prob: 0.5,
+            target: Some(20),
+        };
+        let settings = PetSettingsAdaptive {
+            sum: phase,
+            update: phase,
+            max_step: 2.,
+            smoothing: 0.5,
+        };
+        let count = PetSettingsCount { min: 10, max: 100 };
+        AdaptiveSelection::new(settings, count, count)
+    }
+
+    fn time(min: u64) -> TimeParameters {
+        TimeParameters { min, max: 3600 }
+    }
+
+    #[test]
+    fn test_target_defaults_to_middle() {
+        let phase = PetSettingsAdaptivePhase {
+            min_prob: 0.01,
+            max_prob: 0.5,
+            target: None,
+        };
+        let count = PetSettingsCount { min: 10, max: 100 };
+        assert_eq!(AdaptivePhase::new(phase, count).target, 55.);
+    }
+
+    #[test]
+    fn test_unchanged_without_participation() {
+        let adaptive = adaptive();
+        assert_eq!(adaptive.sum_probability(0.1), 0.1);
+        assert_eq!(adaptive.update_probability(0.2, 0.1), 0.2);
+    }
+
+    #[test]
+    fn test_sum_probability() {
+        let mut adaptive = adaptive();
+
+        // 40 messages with a probability of 0.1 => participation of 400, target 20 => 0.05
+        adaptive.record_sum(40, 10, &time(10), 0.1);
+        assert_eq!(adaptive.sum.participation, Some(400.));
+        assert!((adaptive.sum_probability(0.1) - 0.05).abs() < 1e-9);
+
+        // the next round is smoothed: (400 + 200) / 2 = 300
+        adaptive.record_sum(10, 10, &time(10), 0.05);
+        assert_eq!(adaptive.sum.participation, Some(300.));
+    }
+
+    #[test]
+    fn test_update_probability() {
+        let mut adaptive = adaptive();
+
+        // 9 messages with a sum probability of 0.1 and an update probability of 0.5 in twice the
+        // minimal time => participation of 10
+        adaptive.record_update(9, 20, &time(10), 0.1, 0.5);
+        assert!((adaptive.update.participation.unwrap() - 10.).abs() < 1e-9);
+
+        // the target of 20 messages needs more than the maximal step and the maximal probability
+        assert_eq!(adaptive.update_probability(0.2, 0.1), 0.4);
+        assert_eq!(adaptive.update_probability(0.4, 0.1), 0.5);
+    }
+
+    #[test]
+    fn test_probability_bounds() {
+        let mut adaptive = adaptive();
+
+        // no participants showed up
+        adaptive.record_sum(0, 3600, &time(10), 0.1);
+        assert_eq!(adaptive.sum_probability(0.1), 0.2);
+
+        // too many participants showed up
+        adaptive.sum.participation = Some(1e9);
+        assert_eq!(adaptive.sum_probability(0.1), 0.05);
+        assert_eq!(adaptive.sum_probability(0.015), 0.01);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     state_machine::{
+        commands::CommandSender,
         coordinator::CoordinatorState,
         events::EventSubscriber,
         phases::{self, Phase, PhaseState},
@@ -42,13 +43,25 @@ where
     S: Storage,
 {
     pub fn build(self) -> (StateMachine<S>, RequestSender, EventSubscriber) {
+        let (state_machine, request_tx, _, event_subscriber) = self.build_with_commands();
+        (state_machine, request_tx, event_subscriber)
+    }
+
+    pub fn build_with_commands(
+        self,
+    ) -> (
+        StateMachine<S>,
+        RequestSender,
+        CommandSender,
+        EventSubscriber,
+    ) {
         let Self {
             coordinator_state,
             phase_state,
             store,
         } = self;
 
-        let (mut shared, request_tx, event_subscriber) =
+        let (mut shared, request_tx, command_tx, event_subscriber) =
             utils::init_shared(coordinator_state, store);
 
         // Make sure the events that the listeners have are up to date
@@ -70,7 +83,7 @@ where
         };
 
         let state_machine = StateMachine::from(state);
-        (state_machine, request_tx, event_subscriber)
+        (state_machine, request_tx, command_tx, event_subscriber)
     }
 
     #[allow(dead_code)]
@@ -135,6 +148,11 @@ where
         self
     }
 
+    pub fn with_quorum(mut self, quorum: u64) -> Self {
+        self.coordinator_state.quorum = Some(quorum);
+        self
+    }
+
     pub fn with_model_length(mut self, model_length: usize) -> Self {
         self.coordinator_state.round_params.model_length = model_length;
         self
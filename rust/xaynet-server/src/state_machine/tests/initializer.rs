@@ -1,9 +1,30 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serial_test::serial;
 
-use super::utils::{mask_settings, model_settings, pet_settings};
+use super::utils::{aggregation_settings, mask_settings, model_settings, pet_settings};
+use crate::{
+    settings::{RestoreSettings, ScheduleSettings},
+    state_machine::{
+        coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
+        events::DictionaryUpdate as Dict,
+        phases::{PhaseName as Phase, PhaseState},
+        StateMachineInitializer,
+    },
+    storage::{
+        tests::{
+            init_store,
+            utils::{
+                add_local_seed_entries,
+                create_and_add_sum_participant_entries,
+                create_local_seed_entries,
+            },
+        },
+        CoordinatorStorage,
+    },
+};
 #[cfg(feature = "model-persistence")]
 use crate::{
-    settings::RestoreSettings,
     state_machine::{
         events::{DictionaryUpdate, ModelUpdate},
         initializer::StateMachineInitializationError,
@@ -12,10 +33,7 @@ use crate::{
     storage::tests::utils::create_global_model,
     storage::ModelStorage,
 };
-use crate::{
-    state_machine::{coordinator::CoordinatorState, StateMachineInitializer},
-    storage::{tests::init_store, CoordinatorStorage},
-};
+use xaynet_core::mask::Aggregation;
 
 #[cfg(feature = "model-persistence")]
 #[tokio::test]
@@ -26,11 +44,17 @@ async fn integration_state_machine_initializer_no_restore() {
         pet_settings(),
         mask_settings(),
         model_settings(),
-        RestoreSettings { enable: false },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: false,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
-    let (state_machine, _request_sender, event_subscriber) = smi.init().await.unwrap();
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
 
     assert!(state_machine.is_idle());
 
@@ -59,11 +83,17 @@ async fn integration_state_machine_initializer_no_state() {
         pet_settings(),
         mask_settings(),
         model_settings(),
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
-    let (state_machine, _request_sender, event_subscriber) = smi.init().await.unwrap();
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
 
     assert!(state_machine.is_idle());
 
@@ -105,11 +135,17 @@ async fn integration_state_machine_initializer_without_global_model() {
         pet_settings,
         mask_settings,
         model_settings,
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
-    let (state_machine, _request_sender, event_subscriber) = smi.init().await.unwrap();
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
 
     assert!(state_machine.is_idle());
 
@@ -162,11 +198,17 @@ async fn integration_state_machine_initializer_with_global_model() {
         pet_settings,
         mask_settings,
         model_settings,
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
-    let (state_machine, _request_sender, event_subscriber) = smi.init().await.unwrap();
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
 
     assert!(state_machine.is_idle());
 
@@ -181,7 +223,7 @@ async fn integration_state_machine_initializer_with_global_model() {
 
     let global_model = event_subscriber.model_listener().get_latest().event;
     assert!(
-        matches!(global_model, ModelUpdate::New(broadcasted_model) if uploaded_global_model == *broadcasted_model)
+        matches!(global_model, ModelUpdate::New { model: broadcasted_model, .. } if uploaded_global_model == *broadcasted_model)
     );
 
     let round_id = event_subscriber.params_listener().get_latest().round_id;
@@ -221,7 +263,12 @@ async fn integration_state_machine_initializer_failed_because_of_wrong_size() {
         pet_settings,
         mask_settings,
         model_settings,
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
@@ -258,7 +305,12 @@ async fn integration_state_machine_initializer_failed_to_find_global_model() {
         pet_settings,
         mask_settings,
         model_settings,
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store,
     );
 
@@ -285,8 +337,12 @@ async fn integration_state_machine_initializer_reset_state() {
         pet_settings,
         mask_settings,
         model_settings,
-        #[cfg(feature = "model-persistence")]
-        RestoreSettings { enable: true },
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
         store.clone(),
     );
 
@@ -299,3 +355,137 @@ async fn integration_state_machine_initializer_reset_state() {
     assert!(store.latest_global_model_id().await.unwrap().is_none());
     assert_eq!(store.number_of_unique_masks().await.unwrap(), 0);
 }
+
+/// Creates the checkpoint of an update phase of the given round without any aggregated masked
+/// models.
+fn update_checkpoint(state: &CoordinatorState) -> RoundCheckpoint {
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    RoundCheckpoint {
+        round_id: state.round_id,
+        phase: Phase::Update,
+        started_at,
+        accepted: 0,
+        rejected: 1,
+        discarded: 2,
+        aggregation: Some(Aggregation::new(
+            state.round_params.mask_config,
+            state.round_params.model_length,
+        )),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn integration_state_machine_initializer_resume_round() {
+    let pet_settings = pet_settings();
+    let mask_settings = mask_settings();
+    let model_settings = model_settings();
+
+    // the round was interrupted in the update phase
+    let mut store = init_store().await;
+    let mut state = CoordinatorState::new(pet_settings, mask_settings, model_settings.clone());
+    let new_round_id = 13;
+    state.round_id = new_round_id;
+    store.set_coordinator_state(&state).await.unwrap();
+    create_and_add_sum_participant_entries(&mut store, 1).await;
+    let checkpoint = update_checkpoint(&state);
+    store.set_round_checkpoint(&checkpoint).await.unwrap();
+    // the progress has been saved after the checkpoint
+    let progress = RoundProgress {
+        round_id: new_round_id,
+        phase: Phase::Update,
+        accepted: 0,
+        rejected: 3,
+        discarded: 4,
+    };
+    store.set_round_progress(&progress).await.unwrap();
+
+    let smi = StateMachineInitializer::new(
+        pet_settings,
+        mask_settings,
+        model_settings,
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
+        store,
+    );
+
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
+
+    assert!(state_machine.is_update());
+
+    let phase = event_subscriber.phase_listener().get_latest().event;
+    assert!(matches!(phase, Phase::Update));
+
+    let sum_dict = event_subscriber.sum_dict_listener().get_latest().event;
+    assert!(matches!(sum_dict, Dict::New(sum_dict) if sum_dict.len() == 1));
+
+    let seed_dict = event_subscriber.seed_dict_listener().get_latest().event;
+    assert!(matches!(seed_dict, Dict::Invalidate));
+
+    let round_id = event_subscriber.params_listener().get_latest().round_id;
+    assert_eq!(round_id, new_round_id);
+
+    // the update phase continues with the deadlines of the checkpoint and the counters of the
+    // progress
+    let PhaseState { shared, .. } = state_machine.into_update_phase_state();
+    assert!(shared.checkpoints);
+    assert!(shared.resumed);
+    assert_eq!(shared.status.started_at, checkpoint.started_at);
+    assert_eq!(shared.status.rejected, progress.rejected);
+    assert_eq!(shared.status.discarded, progress.discarded);
+}
+
+#[tokio::test]
+#[serial]
+async fn integration_state_machine_initializer_inconsistent_checkpoint() {
+    let pet_settings = pet_settings();
+    let mask_settings = mask_settings();
+    let model_settings = model_settings();
+
+    // a local seed dict has been added, but the checkpoint wasn't saved afterwards
+    let mut store = init_store().await;
+    let mut state = CoordinatorState::new(pet_settings, mask_settings, model_settings.clone());
+    let new_round_id = 15;
+    state.round_id = new_round_id;
+    store.set_coordinator_state(&state).await.unwrap();
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 1).await;
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    add_local_seed_entries(&mut store, &local_seed_dicts[..1]).await;
+    store
+        .set_round_checkpoint(&update_checkpoint(&state))
+        .await
+        .unwrap();
+
+    let smi = StateMachineInitializer::new(
+        pet_settings,
+        mask_settings,
+        model_settings,
+        aggregation_settings(),
+        RestoreSettings {
+            enable: true,
+            ..RestoreSettings::default()
+        },
+        ScheduleSettings::default(),
+        store,
+    );
+
+    let (state_machine, _request_sender, _command_sender, event_subscriber) =
+        smi.init().await.unwrap();
+
+    // the state machine starts from a new round instead
+    assert!(state_machine.is_idle());
+
+    let phase = event_subscriber.phase_listener().get_latest().event;
+    assert!(matches!(phase, Phase::Idle));
+
+    let sum_dict = event_subscriber.sum_dict_listener().get_latest().event;
+    assert!(matches!(sum_dict, Dict::Invalidate));
+}
@@ -6,12 +6,21 @@ pub mod utils;
 use serial_test::serial;
 
 use crate::{
+    settings::PetSettingsTime,
     state_machine::{
+        commands::Command,
         events::Event,
         phases::PhaseName,
         tests::{
             builder::StateMachineBuilder,
-            utils::{enable_logging, generate_summer, generate_updater, mask_config, Participant},
+            utils::{
+                enable_logging,
+                generate_summer,
+                generate_updater,
+                mask_config,
+                pet_settings,
+                Participant,
+            },
         },
     },
     storage::{tests::init_store, CoordinatorStorage},
@@ -20,6 +29,7 @@ use xaynet_core::{
     common::{RoundParameters, RoundSeed},
     crypto::{ByteObject, EncryptKeyPair},
     mask::{FromPrimitives, Model},
+    message::PROTOCOL_VERSION,
 };
 
 #[tokio::test]
@@ -34,6 +44,9 @@ async fn integration_full_round() {
         seed: RoundSeed::generate(),
         mask_config: mask_config(),
         model_length,
+        privacy_budget: None,
+        version: PROTOCOL_VERSION,
+        starts_at: None,
     };
     let n_summers = 3;
     let n_updaters = 3;
@@ -124,11 +137,17 @@ async fn integration_full_round() {
         let global_model_id = store.latest_global_model_id().await.unwrap().unwrap();
         let store_model = store.global_model(&global_model_id).await.unwrap().unwrap();
         assert!(
-            matches!(events.model_listener().get_latest().event, super::events::ModelUpdate::New(broadcasted_model) if store_model == *broadcasted_model)
+            matches!(events.model_listener().get_latest().event, super::events::ModelUpdate::New { id, model: broadcasted_model } if store_model == *broadcasted_model && id == global_model_id)
         );
 
         let get_global_model_id = store.latest_global_model_id().await.unwrap().unwrap();
         assert_eq!(global_model_id, get_global_model_id);
+
+        let history = store.global_model_history(0, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].round_id, 42);
+        assert_eq!(history[0].model_id, global_model_id);
+        assert_eq!(history[0].model_length, store_model.len());
     }
 
     assert!(state_machine.is_idle());
@@ -161,3 +180,88 @@ async fn integration_full_round() {
     assert!(state_machine.is_shutdown());
     assert!(state_machine.next().await.is_none())
 }
+
+#[tokio::test]
+#[serial]
+async fn integration_skip_round_command() {
+    enable_logging();
+    let store = init_store().await;
+    let (state_machine, _requests, commands, events) = StateMachineBuilder::new(store)
+        .with_round_id(42)
+        .with_sum_time_min(0)
+        .with_sum_time_max(60)
+        .build_with_commands();
+
+    // a skip command which is sent before the round starts is discarded
+    commands.send(Command::SkipRound).unwrap();
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_sum());
+
+    // the sum phase is interrupted and a new round starts
+    commands.send(Command::SkipRound).unwrap();
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_idle());
+
+    let status = events.status_listener().get_latest().event;
+    assert_eq!(status.accepted, 0);
+    assert_eq!(status.min_deadline, Some(status.started_at));
+    assert_eq!(status.max_deadline, Some(status.started_at + 60));
+
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_sum());
+    assert_eq!(events.phase_listener().get_latest().round_id, 43);
+}
+
+#[tokio::test]
+#[serial]
+async fn integration_shutdown_command() {
+    enable_logging();
+    let store = init_store().await;
+    let (state_machine, _requests, commands, _events) = StateMachineBuilder::new(store)
+        .with_sum_time_min(0)
+        .with_sum_time_max(60)
+        .build_with_commands();
+
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_sum());
+
+    commands.send(Command::Shutdown).unwrap();
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_shutdown());
+    assert!(state_machine.next().await.is_none());
+    assert!(commands.send(Command::Shutdown).is_err());
+}
+
+#[tokio::test]
+#[serial]
+#[allow(clippy::float_cmp)]
+async fn integration_update_pet_settings_command() {
+    enable_logging();
+    let store = init_store().await;
+    let (state_machine, _requests, commands, events) = StateMachineBuilder::new(store)
+        .with_round_id(42)
+        .with_sum_time_min(0)
+        .with_sum_time_max(60)
+        .build_with_commands();
+
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_sum());
+
+    // the update doesn't interrupt the sum phase, but the following skip command does
+    let mut settings = pet_settings();
+    settings.sum.prob = 0.25;
+    settings.sum.time = PetSettingsTime { min: 0, max: 120 };
+    commands
+        .send(Command::UpdatePetSettings(Box::new(settings)))
+        .unwrap();
+    commands.send(Command::SkipRound).unwrap();
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_idle());
+
+    // the next round runs with the updated settings
+    let state_machine = state_machine.next().await.unwrap();
+    assert!(state_machine.is_sum());
+    assert_eq!(events.params_listener().get_latest().event.sum, 0.25);
+    let status = events.status_listener().get_latest().event;
+    assert_eq!(status.max_deadline, Some(status.started_at + 120));
+}
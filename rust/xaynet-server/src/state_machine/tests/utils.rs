@@ -2,6 +2,7 @@ use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 use crate::{
     settings::{
+        AggregationSettings,
         MaskSettings,
         ModelSettings,
         PetSettings,
@@ -12,6 +13,8 @@ use crate::{
         PetSettingsUpdate,
     },
     state_machine::{
+        aggregation,
+        commands::{CommandReceiver, CommandSender},
         coordinator::CoordinatorState,
         events::{EventPublisher, EventSubscriber, ModelUpdate},
         phases::{PhaseName, Shared},
@@ -206,7 +209,10 @@ pub fn pet_settings() -> PetSettings {
         sum2: PetSettingsSum2 {
             count: PetSettingsCount { min: 1, max: 100 },
             time: PetSettingsTime { min: 1, max: 2 },
+            quorum: None,
         },
+        dp: None,
+        adaptive: None,
     }
 }
 
@@ -214,10 +220,14 @@ pub fn model_settings() -> ModelSettings {
     ModelSettings { length: 1 }
 }
 
+pub fn aggregation_settings() -> AggregationSettings {
+    AggregationSettings::default()
+}
+
 pub fn init_shared<S>(
     coordinator_state: CoordinatorState,
     store: S,
-) -> (Shared<S>, RequestSender, EventSubscriber)
+) -> (Shared<S>, RequestSender, CommandSender, EventSubscriber)
 where
     S: Storage,
 {
@@ -230,9 +240,18 @@ where
     );
 
     let (request_rx, request_tx) = RequestReceiver::new();
+    let (command_rx, command_tx) = CommandReceiver::new();
     (
-        Shared::new(coordinator_state, event_publisher, request_rx, store),
+        Shared::new(
+            coordinator_state,
+            event_publisher,
+            request_rx,
+            command_rx,
+            store,
+            aggregation::from_settings(aggregation_settings()),
+        ),
         request_tx,
+        command_tx,
         event_subscriber,
     )
 }
@@ -0,0 +1,420 @@
+//! An in-memory [`CoordinatorStorage`] backend.
+//!
+//! The coordinator data is kept in the memory of the coordinator process, hence it is lost on a
+//! restart. Each operation holds a lock on the whole data for its entire duration, which makes the
+//! operations atomic like their Lua script counterparts of the Redis backend.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::{
+    state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
+    storage::{
+        CoordinatorStorage,
+        LocalSeedDictAdd,
+        LocalSeedDictAddError,
+        MaskScoreIncr,
+        MaskScoreIncrError,
+        Retention,
+        StorageError,
+        StorageResult,
+        SumPartAdd,
+        SumPartAddError,
+    },
+};
+use xaynet_core::{
+    common::GlobalModelInfo,
+    mask::MaskObject,
+    LocalSeedDict,
+    SeedDict,
+    SumDict,
+    SumParticipantEphemeralPublicKey,
+    SumParticipantPublicKey,
+    UpdateParticipantPublicKey,
+};
+
+/// The coordinator data.
+#[derive(Default)]
+struct Data {
+    /// The bincode encoded coordinator state.
+    coordinator_state: Option<Vec<u8>>,
+    /// The sum dict.
+    sum_dict: SumDict,
+    /// The update participants which have submitted a local seed dict.
+    update_participants: HashSet<UpdateParticipantPublicKey>,
+    /// The seed dict.
+    seed_dict: SeedDict,
+    /// The sum participants which have submitted a mask.
+    mask_submitted: HashSet<SumParticipantPublicKey>,
+    /// The masks and their scores.
+    mask_dict: HashMap<MaskObject, u64>,
+    /// The bincode encoded round checkpoint.
+    round_checkpoint: Option<Vec<u8>>,
+    /// The bincode encoded round progress.
+    round_progress: Option<Vec<u8>>,
+    /// The latest global model id.
+    latest_global_model_id: Option<String>,
+    /// The global model history, indexed by the round ids.
+    global_model_history: BTreeMap<u64, GlobalModelInfo>,
+}
+
+impl Data {
+    /// Deletes the sum dict, seed dict, mask dict, round checkpoint and round progress.
+    fn delete_dicts(&mut self) {
+        self.sum_dict.clear();
+        self.update_participants.clear();
+        self.seed_dict.clear();
+        self.mask_submitted.clear();
+        self.mask_dict.clear();
+        self.round_checkpoint = None;
+        self.round_progress = None;
+    }
+}
+
+/// In-memory store.
+///
+/// Clones of the store share the same data.
+#[derive(Clone, Default)]
+pub struct InMemory {
+    data: Arc<Mutex<Data>>,
+}
+
+impl InMemory {
+    /// Creates a new, empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the coordinator data.
+    fn data(&self) -> StorageResult<MutexGuard<'_, Data>> {
+        self.data
+            .lock()
+            .map_err(|_| anyhow::anyhow!("the in-memory store is poisoned"))
+    }
+}
+
+#[async_trait]
+impl CoordinatorStorage for InMemory {
+    async fn set_coordinator_state(&mut self, state: &CoordinatorState) -> StorageResult<()> {
+        debug!("set coordinator state");
+        let state = bincode::serialize(state)?;
+        self.data()?.coordinator_state = Some(state);
+        Ok(())
+    }
+
+    async fn coordinator_state(&mut self) -> StorageResult<Option<CoordinatorState>> {
+        self.data()?
+            .coordinator_state
+            .as_deref()
+            .map(bincode::deserialize)
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn add_sum_participant(
+        &mut self,
+        pk: &SumParticipantPublicKey,
+        ephm_pk: &SumParticipantEphemeralPublicKey,
+    ) -> StorageResult<SumPartAdd> {
+        debug!("add sum participant with pk {:?}", pk);
+        let mut data = self.data()?;
+        if data.sum_dict.contains_key(pk) {
+            return Ok(SumPartAdd(Err(SumPartAddError::AlreadyExists)));
+        }
+        data.sum_dict.insert(*pk, *ephm_pk);
+        Ok(SumPartAdd(Ok(())))
+    }
+
+    async fn sum_dict(&mut self) -> StorageResult<Option<SumDict>> {
+        debug!("get sum dictionary");
+        let data = self.data()?;
+        if data.sum_dict.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(data.sum_dict.clone()))
+    }
+
+    async fn add_local_seed_dict(
+        &mut self,
+        update_pk: &UpdateParticipantPublicKey,
+        local_seed_dict: &LocalSeedDict,
+    ) -> StorageResult<LocalSeedDictAdd> {
+        debug!(
+            "update seed dictionary for update participant with pk {:?}",
+            update_pk
+        );
+        let mut data = self.data()?;
+
+        // check if the local seed dict has the same length as the sum_dict
+        if local_seed_dict.len() != data.sum_dict.len() {
+            return Ok(LocalSeedDictAdd(Err(LocalSeedDictAddError::LengthMisMatch)));
+        }
+
+        // check if all pks of the local seed dict exists in sum_dict
+        if !local_seed_dict
+            .keys()
+            .all(|sum_pk| data.sum_dict.contains_key(sum_pk))
+        {
+            return Ok(LocalSeedDictAdd(Err(
+                LocalSeedDictAddError::UnknownSumParticipant,
+            )));
+        }
+
+        // check if the update pk already exists (i.e. the local seed dict has already been
+        // submitted)
+        if data.update_participants.contains(update_pk) {
+            return Ok(LocalSeedDictAdd(Err(
+                LocalSeedDictAddError::UpdatePkAlreadySubmitted,
+            )));
+        }
+
+        // this condition should never apply, it is an indication that the data is corrupted
+        if local_seed_dict.keys().any(|sum_pk| {
+            data.seed_dict
+                .get(sum_pk)
+                .map_or(false, |update_seed_dict| {
+                    update_seed_dict.contains_key(update_pk)
+                })
+        }) {
+            return Ok(LocalSeedDictAdd(Err(
+                LocalSeedDictAddError::UpdatePkAlreadyExistsInUpdateSeedDict,
+            )));
+        }
+
+        // update the seed dict
+        data.update_participants.insert(*update_pk);
+        for (sum_pk, seed) in local_seed_dict {
+            data.seed_dict
+                .entry(*sum_pk)
+                .or_default()
+                .insert(*update_pk, seed.clone());
+        }
+        Ok(LocalSeedDictAdd(Ok(())))
+    }
+
+    async fn seed_dict(&mut self) -> StorageResult<Option<SeedDict>> {
+        debug!("get seed dictionary");
+        let data = self.data()?;
+        if data.sum_dict.is_empty() {
+            return Ok(None);
+        }
+
+        let seed_dict = data
+            .sum_dict
+            .keys()
+            .map(|sum_pk| {
+                let update_seed_dict = data.seed_dict.get(sum_pk).cloned().unwrap_or_default();
+                (*sum_pk, update_seed_dict)
+            })
+            .collect();
+        Ok(Some(seed_dict))
+    }
+
+    async fn incr_mask_score(
+        &mut self,
+        sum_pk: &SumParticipantPublicKey,
+        mask: &MaskObject,
+    ) -> StorageResult<MaskScoreIncr> {
+        debug!("increment mask count");
+        let mut data = self.data()?;
+
+        // check if the client participated in sum phase
+        if !data.sum_dict.contains_key(sum_pk) {
+            return Ok(MaskScoreIncr(Err(MaskScoreIncrError::UnknownSumPk)));
+        }
+
+        // check if sum participant has not already submitted a mask
+        if !data.mask_submitted.insert(*sum_pk) {
+            return Ok(MaskScoreIncr(Err(MaskScoreIncrError::MaskAlreadySubmitted)));
+        }
+
+        *data.mask_dict.entry(mask.clone()).or_insert(0) += 1;
+        Ok(MaskScoreIncr(Ok(())))
+    }
+
+    async fn best_masks(&mut self) -> StorageResult<Option<Vec<(MaskObject, u64)>>> {
+        debug!("get best masks");
+        let data = self.data()?;
+        if data.mask_dict.is_empty() {
+            return Ok(None);
+        }
+
+        let mut masks = data
+            .mask_dict
+            .iter()
+            .map(|(mask, count)| (mask.clone(), *count))
+            .collect::<Vec<_>>();
+        masks.sort_unstable_by(|(_, count_1), (_, count_2)| count_2.cmp(count_1));
+        masks.truncate(2);
+        Ok(Some(masks))
+    }
+
+    async fn number_of_unique_masks(&mut self) -> StorageResult<u64> {
+        debug!("get number of unique masks");
+        Ok(self.data()?.mask_dict.len() as u64)
+    }
+
+    async fn set_round_checkpoint(&mut self, checkpoint: &RoundCheckpoint) -> StorageResult<()> {
+        debug!("set round checkpoint of phase {:?}", checkpoint.phase);
+        let checkpoint = bincode::serialize(checkpoint)?;
+        self.data()?.round_checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    async fn round_checkpoint(&mut self) -> StorageResult<Option<RoundCheckpoint>> {
+        debug!("get round checkpoint");
+        self.data()?
+            .round_checkpoint
+            .as_deref()
+            .map(bincode::deserialize)
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn set_round_progress(&mut self, progress: &RoundProgress) -> StorageResult<()> {
+        debug!("set round progress of phase {:?}", progress.phase);
+        let progress = bincode::serialize(progress)?;
+        self.data()?.round_progress = Some(progress);
+        Ok(())
+    }
+
+    async fn round_progress(&mut self) -> StorageResult<Option<RoundProgress>> {
+        debug!("get round progress");
+        self.data()?
+            .round_progress
+            .as_deref()
+            .map(bincode::deserialize)
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn delete_coordinator_data(&mut self) -> StorageResult<()> {
+        debug!("flush coordinator data");
+        let mut data = self.data()?;
+        data.delete_dicts();
+        data.coordinator_state = None;
+        data.latest_global_model_id = None;
+        data.global_model_history.clear();
+        Ok(())
+    }
+
+    async fn delete_dicts(&mut self) -> StorageResult<()> {
+        debug!("flush all dictionaries");
+        self.data()?.delete_dicts();
+        Ok(())
+    }
+
+    async fn set_latest_global_model_id(&mut self, global_model_id: &str) -> StorageResult<()> {
+        debug!("set latest global model with id {}", global_model_id);
+        self.data()?.latest_global_model_id = Some(global_model_id.to_string());
+        Ok(())
+    }
+
+    async fn latest_global_model_id(&mut self) -> StorageResult<Option<String>> {
+        debug!("get latest global model id");
+        Ok(self.data()?.latest_global_model_id.clone())
+    }
+
+    async fn add_global_model_info(&mut self, info: &GlobalModelInfo) -> StorageResult<()> {
+        debug!("add global model info of round {}", info.round_id);
+        self.data()?
+            .global_model_history
+            .insert(info.round_id, info.clone());
+        Ok(())
+    }
+
+    async fn global_model_info(&mut self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>> {
+        debug!("get global model info of round {}", round_id);
+        Ok(self.data()?.global_model_history.get(&round_id).cloned())
+    }
+
+    async fn global_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> StorageResult<Vec<GlobalModelInfo>> {
+        debug!("get global model history from round {}", from_round_id);
+        Ok(self
+            .data()?
+            .global_model_history
+            .range(from_round_id..)
+            .map(|(_, info)| info.clone())
+            .take(limit)
+            .collect())
+    }
+
+    async fn prune_global_model_history(&mut self, retention: &Retention) -> StorageResult<()> {
+        debug!("prune global model history");
+        let mut data = self.data()?;
+        let pruned = data
+            .global_model_history
+            .values()
+            .rev()
+            .enumerate()
+            .filter(|(index, info)| !retention.retains_info(*index, info))
+            .map(|(_, info)| info.round_id)
+            .collect::<Vec<_>>();
+        for round_id in pruned {
+            data.global_model_history.remove(&round_id);
+        }
+        Ok(())
+    }
+
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+// Functions that are not needed in the state machine but handy for testing.
+impl InMemory {
+    // Removes an update pk from the the `update_participants` set.
+    pub fn remove_update_participant(&mut self, update_pk: &UpdateParticipantPublicKey) -> bool {
+        self.data().unwrap().update_participants.remove(update_pk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::{coordinator_storage::coordinator_storage_tests, utils::*};
+
+    coordinator_storage_tests!(test_, InMemory::new());
+
+    #[tokio::test]
+    async fn test_seed_dict_update_pk_already_exists_in_update_seed_dict() {
+        let mut store = InMemory::new();
+        let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+        let local_seed_dicts = create_local_seed_entries(&sum_pks);
+        let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+        update_result.iter().for_each(|res| assert!(res.is_ok()));
+
+        let (update_participant, local_seed_dict) = local_seed_dicts.get(0).unwrap().clone();
+        assert!(store.remove_update_participant(&update_participant));
+
+        let update_result =
+            add_local_seed_entries(&mut store, &[(update_participant, local_seed_dict)]).await;
+        update_result.into_iter().for_each(|res| {
+            assert!(matches!(
+                res.into_inner().unwrap_err(),
+                LocalSeedDictAddError::UpdatePkAlreadyExistsInUpdateSeedDict
+            ))
+        });
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_data() {
+        let mut store = InMemory::new();
+        let mut clone = store.clone();
+
+        let sum_pks = create_and_add_sum_participant_entries(&mut store, 1).await;
+        let sum_dict = clone.sum_dict().await.unwrap().unwrap();
+        assert!(sum_dict.contains_key(&sum_pks[0]));
+    }
+}
@@ -1,3 +1,7 @@
 //! Storage backends to manage the coordinator state.
 
+pub mod in_memory;
 pub mod redis;
+#[cfg(feature = "embedded-storage")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-storage")))]
+pub mod sled;
@@ -6,7 +6,7 @@ use redis::{ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRe
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    state_machine::coordinator::CoordinatorState,
+    state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
     storage::{
         LocalSeedDictAdd,
         LocalSeedDictAddError,
@@ -17,6 +17,7 @@ use crate::{
     },
 };
 use xaynet_core::{
+    common::GlobalModelInfo,
     crypto::{ByteObject, PublicEncryptKey, PublicSigningKey},
     mask::{EncryptedMaskSeed, MaskObject},
     LocalSeedDict,
@@ -120,7 +121,8 @@ impl_byte_object_redis_traits!(PublicSigningKey);
 impl_byte_object_redis_traits!(EncryptedMaskSeed);
 
 /// Implements ['FromRedisValue'] and ['ToRedisArgs'] for types that implement
-/// ['Serialize`] and [`Deserialize']. The data is de/serialized via bincode.
+/// ['Serialize`] and [`Deserialize']. The data is de/serialized via bincode, unless a custom
+/// deserialization function is given.
 ///
 /// # Panics
 ///
@@ -132,10 +134,13 @@ impl_byte_object_redis_traits!(EncryptedMaskSeed);
 /// - https://github.com/servo/bincode/issues/130#issuecomment-284641263
 macro_rules! impl_bincode_redis_traits {
     ($ty: ty) => {
+        impl_bincode_redis_traits!($ty, bincode::deserialize);
+    };
+    ($ty: ty, $deserialize: path) => {
         impl FromRedisValue for $ty {
             fn from_redis_value(v: &Value) -> RedisResult<$ty> {
                 match *v {
-                    Value::Data(ref bytes) => bincode::deserialize(bytes)
+                    Value::Data(ref bytes) => $deserialize(bytes)
                         .map_err(|e| redis_type_error("Invalid data", Some(e.to_string()))),
                     _ => Err(redis_type_error("Response not bincode compatible", None)),
                 }
@@ -166,8 +171,12 @@ macro_rules! impl_bincode_redis_traits {
 // CoordinatorState is pretty straightforward:
 // - all the sequences have known length (
 // - no untagged enum
-// so bincode will not panic.
-impl_bincode_redis_traits!(CoordinatorState);
+// so bincode will not panic. A state stored before the protocol versioning is converted.
+impl_bincode_redis_traits!(CoordinatorState, CoordinatorState::from_bytes);
+
+// The same holds for the RoundCheckpoint and the RoundProgress.
+impl_bincode_redis_traits!(RoundCheckpoint);
+impl_bincode_redis_traits!(RoundProgress);
 
 #[derive(From, Into, Serialize, Deserialize)]
 pub(crate) struct MaskObjectRead(MaskObject);
@@ -187,6 +196,24 @@ impl ToRedisArgs for MaskObjectWrite<'_> {
     }
 }
 
+#[derive(From, Into, Serialize, Deserialize)]
+pub(crate) struct GlobalModelInfoRead(GlobalModelInfo);
+
+impl_bincode_redis_traits!(GlobalModelInfoRead);
+
+#[derive(From, Serialize)]
+pub(crate) struct GlobalModelInfoWrite<'a>(&'a GlobalModelInfo);
+
+impl ToRedisArgs for GlobalModelInfoWrite<'_> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let data = bincode::serialize(self).unwrap();
+        data.write_redis_args(out)
+    }
+}
+
 #[derive(From)]
 pub(crate) struct LocalSeedDictWrite<'a>(&'a LocalSeedDict);
 
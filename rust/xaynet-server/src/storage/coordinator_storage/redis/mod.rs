@@ -33,13 +33,23 @@
 //!         (mask_object_1, 2), // (mask: bincode encoded string, score/counter: number)
 //!         (mask_object_2, 1)
 //!     ],
-//!     "latest_global_model_id": global_model_id
+//!     // Round checkpoint
+//!     "round_checkpoint": "...", // bincode encoded string
+//!     "round_progress": "...", // bincode encoded string
+//!     "latest_global_model_id": global_model_id,
+//!     // Global model history
+//!     "global_models": [ // sorted set
+//!         (GlobalModelInfo_1, 1), // (info: bincode encoded string, score: round id)
+//!         (GlobalModelInfo_2, 2)
+//!     ],
+//!     // Cluster leader lease, which expires unless it is renewed
+//!     "leader": "http://10.0.0.1:8083" // cluster API URL of the leader
 //! }
 //! ```
 
 pub(in crate::storage) mod impls;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, convert::TryInto, time::Duration};
 
 use async_trait::async_trait;
 use redis::{aio::ConnectionManager, AsyncCommands, IntoConnectionInfo, Pipeline, Script};
@@ -48,6 +58,8 @@ use tracing::debug;
 
 use self::impls::{
     EncryptedMaskSeedRead,
+    GlobalModelInfoRead,
+    GlobalModelInfoWrite,
     LocalSeedDictWrite,
     MaskObjectRead,
     MaskObjectWrite,
@@ -57,17 +69,19 @@ use self::impls::{
     PublicSigningKeyWrite,
 };
 use crate::{
-    state_machine::coordinator::CoordinatorState,
+    state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
     storage::{
         CoordinatorStorage,
         LocalSeedDictAdd,
         MaskScoreIncr,
+        Retention,
         StorageError,
         StorageResult,
         SumPartAdd,
     },
 };
 use xaynet_core::{
+    common::GlobalModelInfo,
     mask::MaskObject,
     LocalSeedDict,
     SeedDict,
@@ -127,6 +141,10 @@ impl Client {
         // delete mask dict
         pipe.del("mask_submitted").ignore();
         pipe.del("mask_dict").ignore();
+
+        // delete round checkpoint
+        pipe.del("round_checkpoint").ignore();
+        pipe.del("round_progress").ignore();
         Ok(pipe)
     }
 }
@@ -378,6 +396,54 @@ impl CoordinatorStorage for Client {
             .map_err(to_storage_err)
     }
 
+    async fn set_round_checkpoint(&mut self, checkpoint: &RoundCheckpoint) -> StorageResult<()> {
+        debug!("set round checkpoint of phase {:?}", checkpoint.phase);
+        // https://redis.io/commands/set
+        // > Set key to hold the string value. If key already holds a value,
+        //   it is overwritten, regardless of its type.
+        // Possible return value in our case:
+        // > Simple string reply: OK if SET was executed correctly.
+        self.connection
+            .set("round_checkpoint", checkpoint)
+            .await
+            .map_err(to_storage_err)
+    }
+
+    async fn round_checkpoint(&mut self) -> StorageResult<Option<RoundCheckpoint>> {
+        debug!("get round checkpoint");
+        // https://redis.io/commands/get
+        // > Return value
+        //   Bulk string reply: the value of key, or nil when key does not exist.
+        self.connection
+            .get("round_checkpoint")
+            .await
+            .map_err(to_storage_err)
+    }
+
+    async fn set_round_progress(&mut self, progress: &RoundProgress) -> StorageResult<()> {
+        debug!("set round progress of phase {:?}", progress.phase);
+        // https://redis.io/commands/set
+        // > Set key to hold the string value. If key already holds a value,
+        //   it is overwritten, regardless of its type.
+        // Possible return value in our case:
+        // > Simple string reply: OK if SET was executed correctly.
+        self.connection
+            .set("round_progress", progress)
+            .await
+            .map_err(to_storage_err)
+    }
+
+    async fn round_progress(&mut self) -> StorageResult<Option<RoundProgress>> {
+        debug!("get round progress");
+        // https://redis.io/commands/get
+        // > Return value
+        //   Bulk string reply: the value of key, or nil when key does not exist.
+        self.connection
+            .get("round_progress")
+            .await
+            .map_err(to_storage_err)
+    }
+
     /// # Note
     /// This method is **not** an atomic operation.
     async fn delete_coordinator_data(&mut self) -> StorageResult<()> {
@@ -385,6 +451,7 @@ impl CoordinatorStorage for Client {
         let mut pipe = self.create_flush_dicts_pipeline().await?;
         pipe.del("coordinator_state").ignore();
         pipe.del("latest_global_model_id").ignore();
+        pipe.del("global_models").ignore();
         pipe.atomic()
             .query_async(&mut self.connection)
             .await
@@ -429,6 +496,124 @@ impl CoordinatorStorage for Client {
             .map_err(to_storage_err)
     }
 
+    async fn add_global_model_info(&mut self, info: &GlobalModelInfo) -> StorageResult<()> {
+        debug!("add global model info of round {}", info.round_id);
+        // https://redis.io/commands/zremrangebyscore
+        // > Removes all elements in the sorted set stored at key with a score between min and max
+        //   (inclusive).
+        // https://redis.io/commands/zadd
+        // > Adds all the specified members with the specified scores to the sorted set stored at
+        //   key. If key does not exist, a new sorted set with the specified members as sole
+        //   members is created.
+        //
+        // The score of an info is its round id, hence the info of the same round is replaced.
+        // We ignore the return values because we are not interested in them.
+        redis::pipe()
+            .atomic()
+            .zrembyscore("global_models", info.round_id, info.round_id)
+            .ignore()
+            .zadd(
+                "global_models",
+                GlobalModelInfoWrite::from(info),
+                info.round_id,
+            )
+            .ignore()
+            .query_async(&mut self.connection)
+            .await
+            .map_err(to_storage_err)
+    }
+
+    async fn global_model_info(&mut self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>> {
+        debug!("get global model info of round {}", round_id);
+        // https://redis.io/commands/zrangebyscore
+        // > Returns all the elements in the sorted set at key with a score between min and max
+        //   (including elements with score equal to min or max).
+        // > Return value
+        //   Array reply: list of elements in the specified score range.
+        let infos: Vec<GlobalModelInfoRead> = self
+            .connection
+            .zrangebyscore("global_models", round_id, round_id)
+            .await
+            .map_err(to_storage_err)?;
+        Ok(infos.into_iter().next().map(GlobalModelInfo::from))
+    }
+
+    async fn global_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> StorageResult<Vec<GlobalModelInfo>> {
+        debug!("get global model history from round {}", from_round_id);
+        // https://redis.io/commands/zrangebyscore
+        // > The optional LIMIT argument can be used to only get a range of the matching elements
+        //   (similar to SELECT LIMIT offset, count in SQL).
+        // > Return value
+        //   Array reply: list of elements in the specified score range, or an empty list when key
+        //   does not exist.
+        let history: Vec<GlobalModelInfoRead> = self
+            .connection
+            .zrangebyscore_limit(
+                "global_models",
+                from_round_id,
+                "+inf",
+                0,
+                limit.try_into().unwrap_or(isize::MAX),
+            )
+            .await
+            .map_err(to_storage_err)?;
+        Ok(history.into_iter().map(GlobalModelInfo::from).collect())
+    }
+
+    /// # Note
+    /// This method is **not** an atomic operation.
+    async fn prune_global_model_history(&mut self, retention: &Retention) -> StorageResult<()> {
+        debug!("prune global model history");
+        if let Some(max_models) = retention.max_models {
+            // https://redis.io/commands/zremrangebyrank
+            // > Removes all elements in the sorted set stored at key with rank between start and
+            //   stop. [...] negative numbers indicate offsets starting at the element with the
+            //   highest score.
+            // the latest info is always kept
+            let stop = -(max_models.max(1).try_into().unwrap_or(isize::MAX)) - 1;
+            self.connection
+                .zremrangebyrank("global_models", 0, stop)
+                .await
+                .map_err(to_storage_err)
+                .map(|_: u64| ())?;
+        }
+
+        if retention.max_age.is_some() {
+            // the rounds complete in order, hence the oldest infos expire first
+            loop {
+                // https://redis.io/commands/zcard
+                // > Return value
+                //   Integer reply: the cardinality (number of elements) of the sorted set, or 0
+                //   if key does not exist.
+                let count: usize = self
+                    .connection
+                    .zcard("global_models")
+                    .await
+                    .map_err(to_storage_err)?;
+                let oldest: Vec<GlobalModelInfoRead> = self
+                    .connection
+                    .zrange("global_models", 0, 0)
+                    .await
+                    .map_err(to_storage_err)?;
+                match oldest.into_iter().next().map(GlobalModelInfo::from) {
+                    Some(info) if !retention.retains_info(count.saturating_sub(1), &info) => {
+                        self.connection
+                            .zrembyscore("global_models", info.round_id, info.round_id)
+                            .await
+                            .map_err(to_storage_err)
+                            .map(|_: u64| ())?;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn is_ready(&mut self) -> StorageResult<()> {
         // https://redis.io/commands/ping
         redis::cmd("PING")
@@ -438,6 +623,45 @@ impl CoordinatorStorage for Client {
     }
 }
 
+// Functions of the election of a cluster leader among the coordinators which share the Redis
+// instance.
+impl Client {
+    /// Acquires the leader lease for the `leader` or renews it if the `leader` already holds it.
+    ///
+    /// The lease expires after the `ttl` unless it is renewed. Returns whether the `leader` holds
+    /// the lease.
+    pub async fn acquire_leader_lease(&mut self, leader: &str, ttl: Duration) -> RedisResult<bool> {
+        let script = Script::new(
+            r#"
+                local leader = redis.call("GET", "leader")
+                if leader == false then
+                    redis.call("SET", "leader", ARGV[1], "PX", ARGV[2])
+                    return 1
+                end
+                if leader == ARGV[1] then
+                    redis.call("PEXPIRE", "leader", ARGV[2])
+                    return 1
+                end
+                return 0
+            "#,
+        );
+
+        script
+            .arg(leader)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.connection)
+            .await
+    }
+
+    /// Gets the current leader, if any coordinator holds the leader lease.
+    pub async fn leader(&mut self) -> RedisResult<Option<String>> {
+        // https://redis.io/commands/get
+        // > Return value
+        //   Bulk string reply: the value of key, or nil when key does not exist.
+        self.connection.get("leader").await
+    }
+}
+
 #[cfg(test)]
 // Functions that are not needed in the state machine but handy for testing.
 impl Client {
@@ -550,10 +774,18 @@ pub(in crate) mod tests {
     use self::impls::SumDictDeleteError;
     use super::*;
     use crate::{
-        state_machine::tests::utils::{mask_settings, model_settings, pet_settings},
-        storage::{tests::utils::*, LocalSeedDictAddError, MaskScoreIncrError, SumPartAddError},
+        state_machine::{
+            coordinator::LegacyCoordinatorState,
+            tests::utils::{mask_settings, model_settings, pet_settings},
+        },
+        storage::{
+            tests::{coordinator_storage::coordinator_storage_tests, utils::*},
+            LocalSeedDictAddError,
+            SumPartAddError,
+        },
     };
     use serial_test::serial;
+    use xaynet_core::{common::LegacyRoundParameters, message::PROTOCOL_VERSION};
 
     async fn create_redis_client() -> Client {
         Client::new("redis://127.0.0.1/").await.unwrap()
@@ -565,194 +797,7 @@ pub(in crate) mod tests {
         client
     }
 
-    #[tokio::test]
-    #[serial]
-    async fn integration_set_and_get_coordinator_state() {
-        // test the writing and reading of the coordinator state
-        let mut client = init_client().await;
-
-        let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
-        client.set_coordinator_state(&set_state).await.unwrap();
-
-        let get_state = client.coordinator_state().await.unwrap().unwrap();
-
-        assert_eq!(set_state, get_state)
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_coordinator_empty() {
-        // test the reading of a non existing coordinator state
-        let mut client = init_client().await;
-
-        let get_state = client.coordinator_state().await.unwrap();
-
-        assert_eq!(None, get_state)
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_incr_mask_score() {
-        // test the increment of the mask counter
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 3).await;
-        let mask = create_mask_zeroed(10);
-        for sum_pk in sum_pks {
-            let res = client.incr_mask_score(&sum_pk, &mask).await;
-            assert!(res.is_ok())
-        }
-
-        let best_masks = client.best_masks().await.unwrap().unwrap();
-        assert!(best_masks.len() == 1);
-
-        let (best_mask, count) = best_masks.into_iter().next().unwrap();
-        assert_eq!(best_mask, mask);
-        assert_eq!(count, 3);
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_incr_mask_count_unknown_sum_pk() {
-        // test the writing and reading of one mask
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let (sum_pk, _) = create_sum_participant_entry();
-        let mask = create_mask_zeroed(10);
-        let unknown_sum_pk = client.incr_mask_score(&sum_pk, &mask).await.unwrap();
-
-        assert!(matches!(
-            unknown_sum_pk.into_inner().unwrap_err(),
-            MaskScoreIncrError::UnknownSumPk
-        ));
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_incr_mask_score_sum_pk_already_submitted() {
-        // test the writing and reading of one mask
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let mut sum_pks = create_and_add_sum_participant_entries(&mut client, 1).await;
-        let sum_pk = sum_pks.pop().unwrap();
-        let mask = create_mask_zeroed(10);
-        let result = client.incr_mask_score(&sum_pk, &mask).await.unwrap();
-        assert!(result.is_ok());
-
-        let already_submitted = client.incr_mask_score(&sum_pk, &mask).await.unwrap();
-
-        assert!(matches!(
-            already_submitted.into_inner().unwrap_err(),
-            MaskScoreIncrError::MaskAlreadySubmitted
-        ));
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_best_masks_only_one_mask() {
-        // test the writing and reading of one mask
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 1).await;
-        let mask = create_mask_zeroed(10);
-        let res = client.incr_mask_score(sum_pks.get(0).unwrap(), &mask).await;
-        assert!(res.is_ok());
-
-        let best_masks = client.best_masks().await.unwrap().unwrap();
-        assert!(best_masks.len() == 1);
-
-        let (best_mask, count) = best_masks.into_iter().next().unwrap();
-        assert_eq!(best_mask, mask);
-        assert_eq!(count, 1);
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_best_masks_two_masks() {
-        // test the writing and reading of two masks
-        // the first mask is incremented twice
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-        let mask_1 = create_mask_zeroed(10);
-        for sum_pk in sum_pks {
-            let res = client.incr_mask_score(&sum_pk, &mask_1).await;
-            assert!(res.is_ok())
-        }
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 1).await;
-        let mask_2 = create_mask_zeroed(100);
-        for sum_pk in sum_pks {
-            let res = client.incr_mask_score(&sum_pk, &mask_2).await;
-            assert!(res.is_ok())
-        }
-
-        let best_masks = client.best_masks().await.unwrap().unwrap();
-        assert!(best_masks.len() == 2);
-        let mut best_masks_iter = best_masks.into_iter();
-
-        let (first_mask, count) = best_masks_iter.next().unwrap();
-        assert_eq!(first_mask, mask_1);
-        assert_eq!(count, 2);
-        let (second_mask, count) = best_masks_iter.next().unwrap();
-        assert_eq!(second_mask, mask_2);
-        assert_eq!(count, 1);
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_best_masks_no_mask() {
-        // ensure that get_best_masks returns an empty vec if no mask exist
-        let mut client = init_client().await;
-
-        let best_masks = client.best_masks().await.unwrap();
-        assert!(best_masks.is_none())
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_number_of_unique_masks_empty() {
-        // ensure that get_best_masks returns an empty vec if no mask exist
-        let mut client = init_client().await;
-
-        let number_of_unique_masks = client.number_of_unique_masks().await.unwrap();
-        assert_eq!(number_of_unique_masks, 0)
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_number_of_unique_masks() {
-        // ensure that get_best_masks returns an empty vec if no mask exist
-        let mut client = init_client().await;
-
-        let should_be_none = client.best_masks().await.unwrap();
-        assert!(should_be_none.is_none());
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 4).await;
-        for (number, sum_pk) in sum_pks.iter().enumerate() {
-            let mask_1 = create_mask(10, number as u32);
-            let res = client.incr_mask_score(&sum_pk, &mask_1).await;
-            assert!(res.is_ok())
-        }
-
-        let number_of_unique_masks = client.number_of_unique_masks().await.unwrap();
-        assert_eq!(number_of_unique_masks, 4)
-    }
+    coordinator_storage_tests!(integration_, init_client().await, #[serial]);
 
     #[tokio::test]
     #[serial]
@@ -809,85 +854,6 @@ pub(in crate) mod tests {
         assert!(sum_dict.is_none());
     }
 
-    #[tokio::test]
-    #[serial]
-    async fn integration_seed_dict() {
-        let mut client = init_client().await;
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-        let local_seed_dicts = create_local_seed_entries(&sum_pks);
-
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.iter().for_each(|res| assert!(res.is_ok()));
-
-        let redis_sum_dict = client.sum_dict().await.unwrap().unwrap();
-        let seed_dict = create_seed_dict(redis_sum_dict, &local_seed_dicts);
-
-        let redis_seed_dict = client.seed_dict().await.unwrap().unwrap();
-        assert_eq!(seed_dict, redis_seed_dict)
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_seed_dict_len_mis_match() {
-        let mut client = init_client().await;
-
-        let mut sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-
-        // remove one sum pk to create invalid local seed dicts
-        sum_pks.pop();
-
-        let local_seed_dicts = create_local_seed_entries(&sum_pks);
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.into_iter().for_each(|res| {
-            assert!(matches!(
-                res.into_inner().unwrap_err(),
-                LocalSeedDictAddError::LengthMisMatch
-            ))
-        });
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_seed_dict_unknown_sum_participant() {
-        let mut client = init_client().await;
-
-        let mut sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-
-        // replace a known sum_pk with an unknown one
-        sum_pks.pop();
-        let (pk, _) = create_sum_participant_entry();
-        sum_pks.push(pk);
-
-        let local_seed_dicts = create_local_seed_entries(&sum_pks);
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.into_iter().for_each(|res| {
-            assert!(matches!(
-                res.into_inner().unwrap_err(),
-                LocalSeedDictAddError::UnknownSumParticipant
-            ))
-        });
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_seed_dict_update_pk_already_submitted() {
-        let mut client = init_client().await;
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-
-        let local_seed_dicts = create_local_seed_entries(&sum_pks);
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.iter().for_each(|res| assert!(res.is_ok()));
-
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.into_iter().for_each(|res| {
-            assert!(matches!(
-                res.into_inner().unwrap_err(),
-                LocalSeedDictAddError::UpdatePkAlreadySubmitted
-            ))
-        });
-    }
-
     #[tokio::test]
     #[serial]
     async fn integration_seed_dict_update_pk_already_exists_in_update_seed_dict() {
@@ -947,56 +913,53 @@ pub(in crate) mod tests {
 
     #[tokio::test]
     #[serial]
-    async fn integration_flush_dicts() {
+    async fn integration_restore_legacy_coordinator_state() {
         let mut client = init_client().await;
 
-        // write some data into redis
-        let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
-        let res = client.set_coordinator_state(&set_state).await;
-        assert!(res.is_ok());
-
-        let res = client.set_latest_global_model_id("global_model_id").await;
-        assert!(res.is_ok());
-
-        let sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
-
-        let local_seed_dicts = create_local_seed_entries(&sum_pks);
-        let update_result = add_local_seed_entries(&mut client, &local_seed_dicts).await;
-        update_result.iter().for_each(|res| assert!(res.is_ok()));
-
-        let mask = create_mask_zeroed(10);
-        client
-            .incr_mask_score(sum_pks.get(0).unwrap(), &mask)
+        // write a coordinator state as it was stored before the protocol versioning
+        let CoordinatorState {
+            keys,
+            round_params,
+            sum,
+            update,
+            sum2,
+            ..
+        } = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
+        let legacy_state = LegacyCoordinatorState {
+            keys,
+            round_id: 7,
+            round_params: LegacyRoundParameters {
+                pk: round_params.pk,
+                sum: round_params.sum,
+                update: round_params.update,
+                seed: round_params.seed,
+                mask_config: round_params.mask_config,
+                model_length: round_params.model_length,
+            },
+            sum,
+            update,
+            sum2,
+        };
+        let legacy_bytes = bincode::serialize(&legacy_state).unwrap();
+        let _: () = client
+            .connection
+            .set("coordinator_state", legacy_bytes)
             .await
             .unwrap();
 
-        // remove dicts
-        let res = client.delete_dicts().await;
-        assert!(res.is_ok());
-
-        // ensure that only the coordinator state and latest global model id exists
-        let res = client.coordinator_state().await;
-        assert!(res.unwrap().is_some());
-
-        let res = client.latest_global_model_id().await;
-        assert!(res.unwrap().is_some());
-
-        let res = client.sum_dict().await;
-        assert!(res.unwrap().is_none());
-
-        let res = client.seed_dict().await;
-        assert!(res.unwrap().is_none());
-
-        let res = client.mask_submitted_set().await;
-        assert!(res.unwrap().is_empty());
-
-        let res = client.best_masks().await;
-        assert!(res.unwrap().is_none());
+        let state = client.coordinator_state().await.unwrap().unwrap();
+        assert_eq!(state.round_id, 7);
+        assert_eq!(state.round_params.privacy_budget, None);
+        assert_eq!(state.round_params.version, PROTOCOL_VERSION);
+        assert_eq!(state.round_params.starts_at, None);
+        assert_eq!(state.quorum, None);
+        assert!(state.adaptive.is_none());
+        assert_eq!(state, CoordinatorState::from(legacy_state));
     }
 
     #[tokio::test]
     #[serial]
-    async fn integration_flush_coordinator_data() {
+    async fn integration_flush_coordinator_data_deletes_all_keys() {
         let mut client = init_client().await;
 
         // write some data into redis
@@ -1007,6 +970,11 @@ pub(in crate) mod tests {
         let res = client.set_latest_global_model_id("global_model_id").await;
         assert!(res.is_ok());
 
+        let res = client
+            .add_global_model_info(&create_global_model_info(1))
+            .await;
+        assert!(res.is_ok());
+
         let sum_pks = create_and_add_sum_participant_entries(&mut client, 2).await;
 
         let local_seed_dicts = create_local_seed_entries(&sum_pks);
@@ -1019,6 +987,11 @@ pub(in crate) mod tests {
             .await
             .unwrap();
 
+        let res = client
+            .set_round_checkpoint(&create_round_checkpoint(1))
+            .await;
+        assert!(res.is_ok());
+
         // remove all coordinator data
         let res = client.delete_coordinator_data().await;
         assert!(res.is_ok());
@@ -1029,36 +1002,21 @@ pub(in crate) mod tests {
 
     #[tokio::test]
     #[serial]
-    async fn integration_set_and_get_latest_global_model_id() {
-        // test the writing and reading of the global model id
-        let mut client = init_client().await;
-
-        let set_id = "global_model_id";
-        client.set_latest_global_model_id(set_id).await.unwrap();
-
-        let get_id = client.latest_global_model_id().await.unwrap().unwrap();
-
-        assert_eq!(set_id, get_id)
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_is_ready_ok() {
-        // test is_ready command
+    async fn integration_acquire_leader_lease() {
+        // test the election of a leader
         let mut client = init_client().await;
-
-        let res = client.is_ready().await;
-        assert!(res.is_ok())
-    }
-
-    #[tokio::test]
-    #[serial]
-    async fn integration_get_latest_global_model_id_empty() {
-        // test the reading of a non existing global model id
-        let mut client = init_client().await;
-
-        let get_id = client.latest_global_model_id().await.unwrap();
-
-        assert_eq!(None, get_id)
+        let ttl = Duration::from_millis(500);
+
+        assert_eq!(client.leader().await.unwrap(), None);
+        assert!(client.acquire_leader_lease("leader-1", ttl).await.unwrap());
+        assert!(!client.acquire_leader_lease("leader-2", ttl).await.unwrap());
+        assert!(client.acquire_leader_lease("leader-1", ttl).await.unwrap());
+        assert_eq!(client.leader().await.unwrap().as_deref(), Some("leader-1"));
+
+        // the lease expires without renewals
+        tokio::time::sleep(2 * ttl).await;
+        assert_eq!(client.leader().await.unwrap(), None);
+        assert!(client.acquire_leader_lease("leader-2", ttl).await.unwrap());
+        assert_eq!(client.leader().await.unwrap().as_deref(), Some("leader-2"));
     }
 }
@@ -0,0 +1,541 @@
+//! A [sled] [`CoordinatorStorage`] backend.
+//!
+//! The coordinator data is persisted in an embedded database in a local directory, hence the
+//! coordinator can be restored after a restart without an external storage service.
+//!
+//! # Data Model
+//!
+//! All data is stored in the default tree of the database. The keys of the dictionaries are
+//! prefixed with the name of the dictionary.
+//!
+//!```text
+//! {
+//!     // Coordinator state
+//!     "coordinator_state": "...", // bincode encoded
+//!     // Sum dict
+//!     "sum_dict/" SumParticipantPublicKey: SumParticipantEphemeralPublicKey,
+//!     // Seed dict
+//!     "update_participants/" UpdateParticipantPublicKey: "",
+//!     "seed_dict/" SumParticipantPublicKey UpdateParticipantPublicKey: EncryptedMaskSeed,
+//!     // Mask dict
+//!     "mask_submitted/" SumParticipantPublicKey: "",
+//!     "mask_dict/" MaskObject: score, // mask: bincode encoded, score: big endian u64
+//!     // Round checkpoint
+//!     "round_checkpoint": "...", // bincode encoded
+//!     "round_progress": "...", // bincode encoded
+//!     "latest_global_model_id": global_model_id,
+//!     // Global model history
+//!     "global_models/" round_id: GlobalModelInfo // round_id: big endian u64, info: bincode encoded
+//! }
+//! ```
+//!
+//! Each operation holds a lock for its entire duration and applies its writes in a single atomic
+//! batch, which makes the operations atomic like their Lua script counterparts of the Redis
+//! backend. Every successful write is flushed to the disk before the operation returns, hence an
+//! accepted message is not lost if the coordinator crashes afterwards.
+//!
+//! [sled]: https://docs.rs/sled
+
+use std::{
+    convert::TryInto,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use sled::{Batch, Db, IVec};
+use tracing::debug;
+
+use crate::{
+    state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
+    storage::{
+        CoordinatorStorage,
+        LocalSeedDictAdd,
+        LocalSeedDictAddError,
+        MaskScoreIncr,
+        MaskScoreIncrError,
+        Retention,
+        StorageError,
+        StorageResult,
+        SumPartAdd,
+        SumPartAddError,
+    },
+};
+use xaynet_core::{
+    common::GlobalModelInfo,
+    crypto::ByteObject,
+    mask::{EncryptedMaskSeed, MaskObject},
+    LocalSeedDict,
+    SeedDict,
+    SumDict,
+    SumParticipantEphemeralPublicKey,
+    SumParticipantPublicKey,
+    UpdateParticipantPublicKey,
+};
+
+pub use sled::Error as SledError;
+
+const COORDINATOR_STATE: &[u8] = b"coordinator_state";
+const LATEST_GLOBAL_MODEL_ID: &[u8] = b"latest_global_model_id";
+const ROUND_CHECKPOINT: &[u8] = b"round_checkpoint";
+const ROUND_PROGRESS: &[u8] = b"round_progress";
+const SUM_DICT: &[u8] = b"sum_dict/";
+const UPDATE_PARTICIPANTS: &[u8] = b"update_participants/";
+const SEED_DICT: &[u8] = b"seed_dict/";
+const MASK_SUBMITTED: &[u8] = b"mask_submitted/";
+const MASK_DICT: &[u8] = b"mask_dict/";
+const GLOBAL_MODELS: &[u8] = b"global_models/";
+
+/// The prefixes of the dictionaries.
+const DICTS: [&[u8]; 5] = [
+    SUM_DICT,
+    UPDATE_PARTICIPANTS,
+    SEED_DICT,
+    MASK_SUBMITTED,
+    MASK_DICT,
+];
+
+/// Concatenates the parts of a key.
+fn key(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+/// Decodes a public key from the given bytes.
+fn decode_pk<K: ByteObject>(bytes: &[u8]) -> StorageResult<K> {
+    K::from_slice(bytes).ok_or_else(|| anyhow::anyhow!("invalid public key in sled database"))
+}
+
+/// Sled client.
+///
+/// Clones of the client share the same database.
+#[derive(Clone)]
+pub struct Client {
+    db: Db,
+    lock: Arc<Mutex<()>>,
+}
+
+impl Client {
+    /// Creates a new sled client.
+    ///
+    /// The database is opened in the directory at `path`, which is created if it doesn't exist
+    /// yet.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, SledError> {
+        let db = sled::open(path)?;
+        Ok(Self::from_db(db))
+    }
+
+    /// Creates a new sled client for an already opened database.
+    pub fn from_db(db: Db) -> Self {
+        Self {
+            db,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Locks the database for an operation.
+    fn lock(&self) -> StorageResult<MutexGuard<'_, ()>> {
+        self.lock
+            .lock()
+            .map_err(|_| anyhow::anyhow!("the sled client is poisoned"))
+    }
+
+    /// Counts the entries with the given key prefix.
+    fn count(&self, prefix: &[u8]) -> StorageResult<usize> {
+        let mut count = 0;
+        for key in self.db.scan_prefix(prefix).keys() {
+            key?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Adds the removal of all entries with the given key prefixes to the `batch`.
+    fn remove_prefixes(&self, batch: &mut Batch, prefixes: &[&[u8]]) -> StorageResult<()> {
+        for prefix in prefixes {
+            for key in self.db.scan_prefix(prefix).keys() {
+                batch.remove(key?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the database to the disk.
+    async fn flush(&self) -> StorageResult<()> {
+        self.db.flush_async().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CoordinatorStorage for Client {
+    async fn set_coordinator_state(&mut self, state: &CoordinatorState) -> StorageResult<()> {
+        debug!("set coordinator state");
+        self.db
+            .insert(COORDINATOR_STATE, bincode::serialize(state)?)?;
+        self.flush().await
+    }
+
+    async fn coordinator_state(&mut self) -> StorageResult<Option<CoordinatorState>> {
+        self.db
+            .get(COORDINATOR_STATE)?
+            .map(|state| CoordinatorState::from_bytes(&state))
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn add_sum_participant(
+        &mut self,
+        pk: &SumParticipantPublicKey,
+        ephm_pk: &SumParticipantEphemeralPublicKey,
+    ) -> StorageResult<SumPartAdd> {
+        debug!("add sum participant with pk {:?}", pk);
+        {
+            let _lock = self.lock()?;
+            let key = key(&[SUM_DICT, pk.as_slice()]);
+            if self.db.contains_key(&key)? {
+                return Ok(SumPartAdd(Err(SumPartAddError::AlreadyExists)));
+            }
+            self.db.insert(key, ephm_pk.as_slice())?;
+        }
+        self.flush().await?;
+        Ok(SumPartAdd(Ok(())))
+    }
+
+    async fn sum_dict(&mut self) -> StorageResult<Option<SumDict>> {
+        debug!("get sum dictionary");
+        let mut sum_dict = SumDict::new();
+        for entry in self.db.scan_prefix(SUM_DICT) {
+            let (key, value) = entry?;
+            sum_dict.insert(decode_pk(&key[SUM_DICT.len()..])?, decode_pk(&value)?);
+        }
+
+        if sum_dict.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(sum_dict))
+    }
+
+    async fn add_local_seed_dict(
+        &mut self,
+        update_pk: &UpdateParticipantPublicKey,
+        local_seed_dict: &LocalSeedDict,
+    ) -> StorageResult<LocalSeedDictAdd> {
+        debug!(
+            "update seed dictionary for update participant with pk {:?}",
+            update_pk
+        );
+        {
+            let _lock = self.lock()?;
+
+            // check if the local seed dict has the same length as the sum_dict
+            if local_seed_dict.len() != self.count(SUM_DICT)? {
+                return Ok(LocalSeedDictAdd(Err(LocalSeedDictAddError::LengthMisMatch)));
+            }
+
+            // check if all pks of the local seed dict exists in sum_dict
+            for sum_pk in local_seed_dict.keys() {
+                if !self.db.contains_key(key(&[SUM_DICT, sum_pk.as_slice()]))? {
+                    return Ok(LocalSeedDictAdd(Err(
+                        LocalSeedDictAddError::UnknownSumParticipant,
+                    )));
+                }
+            }
+
+            // check if the update pk already exists (i.e. the local seed dict has already been
+            // submitted)
+            let update_participant = key(&[UPDATE_PARTICIPANTS, update_pk.as_slice()]);
+            if self.db.contains_key(&update_participant)? {
+                return Ok(LocalSeedDictAdd(Err(
+                    LocalSeedDictAddError::UpdatePkAlreadySubmitted,
+                )));
+            }
+
+            // update the seed dict
+            let mut batch = Batch::default();
+            batch.insert(update_participant, IVec::default());
+            for (sum_pk, seed) in local_seed_dict {
+                let key = key(&[SEED_DICT, sum_pk.as_slice(), update_pk.as_slice()]);
+                if self.db.contains_key(&key)? {
+                    // this condition should never apply, it is an indication that the data is
+                    // corrupted
+                    return Ok(LocalSeedDictAdd(Err(
+                        LocalSeedDictAddError::UpdatePkAlreadyExistsInUpdateSeedDict,
+                    )));
+                }
+                batch.insert(key, seed.as_slice());
+            }
+            self.db.apply_batch(batch)?;
+        }
+        self.flush().await?;
+        Ok(LocalSeedDictAdd(Ok(())))
+    }
+
+    async fn seed_dict(&mut self) -> StorageResult<Option<SeedDict>> {
+        debug!("get seed dictionary");
+        let mut seed_dict = SeedDict::new();
+        for sum_pk in self.db.scan_prefix(SUM_DICT).keys() {
+            let sum_pk: SumParticipantPublicKey = decode_pk(&sum_pk?[SUM_DICT.len()..])?;
+            seed_dict.insert(sum_pk, Default::default());
+        }
+
+        if seed_dict.is_empty() {
+            return Ok(None);
+        }
+
+        let pk_length = SumParticipantPublicKey::LENGTH;
+        for entry in self.db.scan_prefix(SEED_DICT) {
+            let (key, seed) = entry?;
+            let key = &key[SEED_DICT.len()..];
+            let sum_pk: SumParticipantPublicKey = decode_pk(&key[..pk_length])?;
+            let update_pk: UpdateParticipantPublicKey = decode_pk(&key[pk_length..])?;
+            let seed = EncryptedMaskSeed::from_slice(&seed)
+                .ok_or_else(|| anyhow::anyhow!("invalid mask seed in sled database"))?;
+            if let Some(update_seed_dict) = seed_dict.get_mut(&sum_pk) {
+                update_seed_dict.insert(update_pk, seed);
+            }
+        }
+
+        Ok(Some(seed_dict))
+    }
+
+    async fn incr_mask_score(
+        &mut self,
+        sum_pk: &SumParticipantPublicKey,
+        mask: &MaskObject,
+    ) -> StorageResult<MaskScoreIncr> {
+        debug!("increment mask count");
+        {
+            let _lock = self.lock()?;
+
+            // check if the client participated in sum phase
+            if !self.db.contains_key(key(&[SUM_DICT, sum_pk.as_slice()]))? {
+                return Ok(MaskScoreIncr(Err(MaskScoreIncrError::UnknownSumPk)));
+            }
+
+            // check if sum participant has not already submitted a mask
+            let mask_submitted = key(&[MASK_SUBMITTED, sum_pk.as_slice()]);
+            if self.db.contains_key(&mask_submitted)? {
+                return Ok(MaskScoreIncr(Err(MaskScoreIncrError::MaskAlreadySubmitted)));
+            }
+
+            let mask = key(&[MASK_DICT, &bincode::serialize(mask)?]);
+            let score = match self.db.get(&mask)? {
+                Some(score) => u64::from_be_bytes(score.as_ref().try_into()?),
+                None => 0,
+            };
+
+            let mut batch = Batch::default();
+            batch.insert(mask_submitted, IVec::default());
+            batch.insert(mask, &(score + 1).to_be_bytes());
+            self.db.apply_batch(batch)?;
+        }
+        self.flush().await?;
+        Ok(MaskScoreIncr(Ok(())))
+    }
+
+    async fn best_masks(&mut self) -> StorageResult<Option<Vec<(MaskObject, u64)>>> {
+        debug!("get best masks");
+        let mut masks = Vec::new();
+        for entry in self.db.scan_prefix(MASK_DICT) {
+            let (mask, score) = entry?;
+            let score = u64::from_be_bytes(score.as_ref().try_into()?);
+            masks.push((mask, score));
+        }
+
+        if masks.is_empty() {
+            return Ok(None);
+        }
+
+        masks.sort_unstable_by(|(_, score_1), (_, score_2)| score_2.cmp(score_1));
+        let masks = masks
+            .into_iter()
+            .take(2)
+            .map(|(mask, score)| Ok((bincode::deserialize(&mask[MASK_DICT.len()..])?, score)))
+            .collect::<StorageResult<_>>()?;
+        Ok(Some(masks))
+    }
+
+    async fn number_of_unique_masks(&mut self) -> StorageResult<u64> {
+        debug!("get number of unique masks");
+        Ok(self.count(MASK_DICT)? as u64)
+    }
+
+    async fn set_round_checkpoint(&mut self, checkpoint: &RoundCheckpoint) -> StorageResult<()> {
+        debug!("set round checkpoint of phase {:?}", checkpoint.phase);
+        self.db
+            .insert(ROUND_CHECKPOINT, bincode::serialize(checkpoint)?)?;
+        self.flush().await
+    }
+
+    async fn round_checkpoint(&mut self) -> StorageResult<Option<RoundCheckpoint>> {
+        debug!("get round checkpoint");
+        self.db
+            .get(ROUND_CHECKPOINT)?
+            .map(|checkpoint| bincode::deserialize(&checkpoint))
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn set_round_progress(&mut self, progress: &RoundProgress) -> StorageResult<()> {
+        debug!("set round progress of phase {:?}", progress.phase);
+        self.db
+            .insert(ROUND_PROGRESS, bincode::serialize(progress)?)?;
+        self.flush().await
+    }
+
+    async fn round_progress(&mut self) -> StorageResult<Option<RoundProgress>> {
+        debug!("get round progress");
+        self.db
+            .get(ROUND_PROGRESS)?
+            .map(|progress| bincode::deserialize(&progress))
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn delete_coordinator_data(&mut self) -> StorageResult<()> {
+        debug!("flush coordinator data");
+        {
+            let _lock = self.lock()?;
+            let mut batch = Batch::default();
+            self.remove_prefixes(&mut batch, &DICTS)?;
+            self.remove_prefixes(&mut batch, &[GLOBAL_MODELS])?;
+            batch.remove(COORDINATOR_STATE);
+            batch.remove(LATEST_GLOBAL_MODEL_ID);
+            batch.remove(ROUND_CHECKPOINT);
+            batch.remove(ROUND_PROGRESS);
+            self.db.apply_batch(batch)?;
+        }
+        self.flush().await
+    }
+
+    async fn delete_dicts(&mut self) -> StorageResult<()> {
+        debug!("flush all dictionaries");
+        {
+            let _lock = self.lock()?;
+            let mut batch = Batch::default();
+            self.remove_prefixes(&mut batch, &DICTS)?;
+            batch.remove(ROUND_CHECKPOINT);
+            batch.remove(ROUND_PROGRESS);
+            self.db.apply_batch(batch)?;
+        }
+        self.flush().await
+    }
+
+    async fn set_latest_global_model_id(&mut self, global_model_id: &str) -> StorageResult<()> {
+        debug!("set latest global model with id {}", global_model_id);
+        self.db.insert(LATEST_GLOBAL_MODEL_ID, global_model_id)?;
+        self.flush().await
+    }
+
+    async fn latest_global_model_id(&mut self) -> StorageResult<Option<String>> {
+        debug!("get latest global model id");
+        self.db
+            .get(LATEST_GLOBAL_MODEL_ID)?
+            .map(|id| String::from_utf8(id.to_vec()))
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn add_global_model_info(&mut self, info: &GlobalModelInfo) -> StorageResult<()> {
+        debug!("add global model info of round {}", info.round_id);
+        let key = key(&[GLOBAL_MODELS, &info.round_id.to_be_bytes()]);
+        self.db.insert(key, bincode::serialize(info)?)?;
+        self.flush().await
+    }
+
+    async fn global_model_info(&mut self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>> {
+        debug!("get global model info of round {}", round_id);
+        self.db
+            .get(key(&[GLOBAL_MODELS, &round_id.to_be_bytes()]))?
+            .map(|info| bincode::deserialize(&info))
+            .transpose()
+            .map_err(StorageError::from)
+    }
+
+    async fn global_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> StorageResult<Vec<GlobalModelInfo>> {
+        debug!("get global model history from round {}", from_round_id);
+        // the big endian round ids keep the infos ordered by their rounds
+        let start = key(&[GLOBAL_MODELS, &from_round_id.to_be_bytes()]);
+        let end = key(&[GLOBAL_MODELS, &u64::MAX.to_be_bytes()]);
+        let mut history = Vec::new();
+        for info in self.db.range(start..=end).values().take(limit) {
+            history.push(bincode::deserialize(&info?)?);
+        }
+        Ok(history)
+    }
+
+    async fn prune_global_model_history(&mut self, retention: &Retention) -> StorageResult<()> {
+        debug!("prune global model history");
+        if *retention == Retention::default() {
+            return Ok(());
+        }
+
+        {
+            let _lock = self.lock()?;
+            let mut batch = Batch::default();
+            for (index, entry) in self.db.scan_prefix(GLOBAL_MODELS).rev().enumerate() {
+                let (key, info) = entry?;
+                if !retention.retains_info(index, &bincode::deserialize(&info)?) {
+                    batch.remove(key);
+                }
+            }
+            self.db.apply_batch(batch)?;
+        }
+        self.flush().await
+    }
+
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        // a read fails if the database has encountered an unrecoverable error
+        self.db.contains_key(COORDINATOR_STATE)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state_machine::tests::utils::{mask_settings, model_settings, pet_settings},
+        storage::tests::{coordinator_storage::coordinator_storage_tests, utils::*},
+    };
+
+    fn init_client() -> Client {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        Client::from_db(db)
+    }
+
+    coordinator_storage_tests!(test_, init_client());
+
+    #[tokio::test]
+    async fn test_restore_after_reopen() {
+        let path = std::env::temp_dir().join(format!("xaynet-sled-{}", rand::random::<u64>()));
+        let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
+        let sum_pks = {
+            let mut client = Client::new(&path).unwrap();
+            client.set_coordinator_state(&set_state).await.unwrap();
+            client.set_latest_global_model_id("id").await.unwrap();
+            client
+                .add_global_model_info(&create_global_model_info(1))
+                .await
+                .unwrap();
+            create_and_add_sum_participant_entries(&mut client, 2).await
+        };
+
+        let mut client = Client::new(&path).unwrap();
+        let get_state = client.coordinator_state().await.unwrap().unwrap();
+        assert_eq!(set_state, get_state);
+        let get_id = client.latest_global_model_id().await.unwrap().unwrap();
+        assert_eq!(get_id, "id");
+        let history = client.global_model_history(0, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        let sum_dict = client.sum_dict().await.unwrap().unwrap();
+        assert!(sum_pks.iter().all(|sum_pk| sum_dict.contains_key(sum_pk)));
+
+        drop(client);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -17,6 +17,7 @@ pub use self::{
         MaskScoreIncr,
         MaskScoreIncrError,
         ModelStorage,
+        Retention,
         Storage,
         StorageError,
         StorageResult,
@@ -0,0 +1,296 @@
+//! A filesystem [`ModelStorage`] backend.
+//!
+//! Each global model is stored bincode encoded in its own file `<global_model_id>.model` in a
+//! local directory. Old global models are deleted according to the [`Retention`] policy, but the
+//! latest global model is always kept.
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use tokio::fs;
+use tracing::{debug, warn};
+
+use crate::storage::{ModelStorage, Retention, StorageResult};
+use xaynet_core::{common::RoundSeed, mask::Model};
+
+/// The file extension of stored global models.
+const EXTENSION: &str = "model";
+
+/// A stored global model.
+struct StoredModel {
+    /// The round in which the global model was created.
+    round_id: u64,
+    /// The last modification time of the file.
+    modified: SystemTime,
+    /// The path of the file.
+    path: PathBuf,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    dir: Arc<PathBuf>,
+    retention: Retention,
+}
+
+impl Client {
+    /// Creates a new filesystem client which stores the global models in the directory at
+    /// `path`. The directory is created if it doesn't exist yet.
+    pub async fn new(path: impl AsRef<Path>, retention: Retention) -> std::io::Result<Self> {
+        let dir = path.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).await?;
+        Ok(Self {
+            dir: Arc::new(dir),
+            retention,
+        })
+    }
+
+    // Gets the path of the file for the global model with the given id.
+    fn model_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, EXTENSION))
+    }
+
+    // Lists the stored global models, ordered from the latest to the oldest round.
+    async fn stored_models(&self) -> std::io::Result<Vec<StoredModel>> {
+        let mut models = Vec::new();
+        let mut entries = fs::read_dir(self.dir.as_path()).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let round_id = match path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split('_').next())
+                .and_then(|round_id| round_id.parse().ok())
+            {
+                Some(round_id) => round_id,
+                None => continue,
+            };
+            let modified = entry.metadata().await?.modified()?;
+            models.push(StoredModel {
+                round_id,
+                modified,
+                path,
+            });
+        }
+        models.sort_unstable_by(|model_1, model_2| model_2.round_id.cmp(&model_1.round_id));
+        Ok(models)
+    }
+
+    // Deletes the global models which are not retained. The latest global model is always kept.
+    async fn apply_retention(&self) -> std::io::Result<()> {
+        if self.retention == Retention::default() {
+            return Ok(());
+        }
+
+        let now = SystemTime::now();
+        for (index, model) in self.stored_models().await?.into_iter().enumerate() {
+            let age = now.duration_since(model.modified).unwrap_or_default();
+            if !self.retention.retains(index, age) {
+                debug!("delete global model {}", model.path.display());
+                fs::remove_file(&model.path).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ModelStorage for Client {
+    async fn set_global_model(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<String> {
+        let id = Self::create_global_model_id(round_id, round_seed);
+        debug!("store global model: {}", id);
+
+        let path = self.model_path(&id);
+        if fs::metadata(&path).await.is_ok() {
+            return Err(anyhow::anyhow!("global model {} already exists", id));
+        }
+
+        // write to a temporary file first, such that no partially written model is ever read
+        let data = bincode::serialize(global_model)?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, data).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        if let Err(err) = self.apply_retention().await {
+            warn!(
+                "failed to apply the retention policy to global models: {}",
+                err
+            );
+        }
+
+        Ok(id)
+    }
+
+    async fn global_model(&mut self, id: &str) -> StorageResult<Option<Model>> {
+        debug!("get global model {:?}", id);
+        // the id must not be able to escape the model directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Ok(None);
+        }
+
+        match fs::read(self.model_path(id)).await {
+            Ok(data) => Ok(Some(bincode::deserialize(&data)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        if fs::metadata(self.dir.as_path()).await?.is_dir() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{} is not a directory", self.dir.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::storage::tests::utils::create_global_model;
+    use xaynet_core::crypto::ByteObject;
+
+    async fn init_client(retention: Retention) -> (Client, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let client = Client::new(dir.path(), retention).await.unwrap();
+        (client, dir)
+    }
+
+    async fn stored_round_ids(client: &Client) -> Vec<u64> {
+        client
+            .stored_models()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|model| model.round_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_set_and_get_global_model() {
+        let (mut client, _dir) = init_client(Retention::default()).await;
+
+        let global_model = create_global_model(10);
+        let id = client
+            .set_global_model(1, &RoundSeed::generate(), &global_model)
+            .await
+            .unwrap();
+
+        let stored_model = client.global_model(&id).await.unwrap().unwrap();
+        assert_eq!(global_model, stored_model);
+    }
+
+    #[tokio::test]
+    async fn test_set_global_model_already_exists() {
+        let (mut client, _dir) = init_client(Retention::default()).await;
+
+        let global_model = create_global_model(10);
+        let round_seed = RoundSeed::generate();
+        client
+            .set_global_model(1, &round_seed, &global_model)
+            .await
+            .unwrap();
+
+        let already_exists = client.set_global_model(1, &round_seed, &global_model).await;
+        assert!(already_exists.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_global_model_non_existent() {
+        let (mut client, _dir) = init_client(Retention::default()).await;
+
+        let id = Client::create_global_model_id(1, &RoundSeed::generate());
+        let res = client.global_model(&id).await.unwrap();
+        assert!(res.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_global_model_invalid_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Client::new(dir.path().join("models"), Retention::default())
+            .await
+            .unwrap();
+
+        let outside = dir.path().join("outside.model");
+        fs::write(
+            &outside,
+            bincode::serialize(&create_global_model(10)).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let res = client.global_model("../outside").await.unwrap();
+        assert!(res.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retention_max_models() {
+        let (mut client, _dir) = init_client(Retention {
+            max_models: Some(2),
+            max_age: None,
+        })
+        .await;
+
+        let global_model = create_global_model(10);
+        let mut ids = Vec::new();
+        for round_id in 1..=4 {
+            let id = client
+                .set_global_model(round_id, &RoundSeed::generate(), &global_model)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        assert_eq!(stored_round_ids(&client).await, vec![4, 3]);
+        assert!(client.global_model(&ids[0]).await.unwrap().is_none());
+        assert!(client.global_model(&ids[3]).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_retention_max_age_keeps_latest_model() {
+        let (mut client, _dir) = init_client(Retention {
+            max_models: None,
+            max_age: Some(Duration::from_secs(0)),
+        })
+        .await;
+
+        let global_model = create_global_model(10);
+        client
+            .set_global_model(1, &RoundSeed::generate(), &global_model)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let id = client
+            .set_global_model(2, &RoundSeed::generate(), &global_model)
+            .await
+            .unwrap();
+
+        assert_eq!(stored_round_ids(&client).await, vec![2]);
+        assert!(client.global_model(&id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_is_ready() {
+        let (mut client, dir) = init_client(Retention::default()).await;
+        assert!(client.is_ready().await.is_ok());
+
+        dir.close().unwrap();
+        assert!(client.is_ready().await.is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Storage backends to manage global models.
 
+pub mod filesystem;
 pub mod noop;
 #[cfg(feature = "model-persistence")]
 #[cfg_attr(docsrs, doc(cfg(feature = "model-persistence")))]
@@ -22,6 +22,10 @@ impl ModelStorage for NoOp {
         Err(anyhow::anyhow!("No-op model store"))
     }
 
+    fn persists_global_models(&self) -> bool {
+        false
+    }
+
     async fn is_ready(&mut self) -> StorageResult<()> {
         Ok(())
     }
@@ -3,13 +3,14 @@
 use async_trait::async_trait;
 
 use crate::{
-    state_machine::coordinator::CoordinatorState,
+    state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress},
     storage::{
         trust_anchor::noop::NoOp,
         CoordinatorStorage,
         LocalSeedDictAdd,
         MaskScoreIncr,
         ModelStorage,
+        Retention,
         Storage,
         StorageResult,
         SumPartAdd,
@@ -17,8 +18,10 @@ use crate::{
     },
 };
 use xaynet_core::{
-    common::RoundSeed,
+    common::{GlobalModelInfo, RoundSeed},
+    crypto::PublicSigningKey,
     mask::{MaskObject, Model},
+    proof::ModelProof,
     LocalSeedDict,
     SeedDict,
     SumDict,
@@ -41,6 +44,8 @@ where
     model: M,
     /// A trust anchor.
     trust_anchor: T,
+    /// The retention policy of the global model history.
+    retention: Retention,
 }
 
 impl<C, M, T> Store<C, M, T>
@@ -54,8 +59,16 @@ where
             coordinator,
             model,
             trust_anchor,
+            retention: Retention::default(),
         }
     }
+
+    /// Sets the [`Retention`] policy of the global model history, which should be the one of the
+    /// model store. The history is pruned whenever a [`GlobalModelInfo`] is added.
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
 }
 
 impl<C, M> Store<C, M, NoOp>
@@ -69,6 +82,7 @@ where
             coordinator,
             model,
             trust_anchor: NoOp,
+            retention: Retention::default(),
         }
     }
 }
@@ -130,6 +144,22 @@ where
         self.coordinator.number_of_unique_masks().await
     }
 
+    async fn set_round_checkpoint(&mut self, checkpoint: &RoundCheckpoint) -> StorageResult<()> {
+        self.coordinator.set_round_checkpoint(checkpoint).await
+    }
+
+    async fn round_checkpoint(&mut self) -> StorageResult<Option<RoundCheckpoint>> {
+        self.coordinator.round_checkpoint().await
+    }
+
+    async fn set_round_progress(&mut self, progress: &RoundProgress) -> StorageResult<()> {
+        self.coordinator.set_round_progress(progress).await
+    }
+
+    async fn round_progress(&mut self) -> StorageResult<Option<RoundProgress>> {
+        self.coordinator.round_progress().await
+    }
+
     async fn delete_coordinator_data(&mut self) -> StorageResult<()> {
         self.coordinator.delete_coordinator_data().await
     }
@@ -146,6 +176,31 @@ where
         self.coordinator.latest_global_model_id().await
     }
 
+    async fn add_global_model_info(&mut self, info: &GlobalModelInfo) -> StorageResult<()> {
+        self.coordinator.add_global_model_info(info).await?;
+        self.coordinator
+            .prune_global_model_history(&self.retention)
+            .await
+    }
+
+    async fn global_model_info(&mut self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>> {
+        self.coordinator.global_model_info(round_id).await
+    }
+
+    async fn global_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> StorageResult<Vec<GlobalModelInfo>> {
+        self.coordinator
+            .global_model_history(from_round_id, limit)
+            .await
+    }
+
+    async fn prune_global_model_history(&mut self, retention: &Retention) -> StorageResult<()> {
+        self.coordinator.prune_global_model_history(retention).await
+    }
+
     async fn is_ready(&mut self) -> StorageResult<()> {
         self.coordinator.is_ready().await
     }
@@ -173,6 +228,10 @@ where
         self.model.global_model(id).await
     }
 
+    fn persists_global_models(&self) -> bool {
+        self.model.persists_global_models()
+    }
+
     async fn is_ready(&mut self) -> StorageResult<()> {
         self.model.is_ready().await
     }
@@ -185,8 +244,27 @@ where
     M: ModelStorage,
     T: TrustAnchor,
 {
-    async fn publish_proof(&mut self, global_model: &Model) -> StorageResult<()> {
-        self.trust_anchor.publish_proof(global_model).await
+    async fn publish_proof(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<()> {
+        self.trust_anchor
+            .publish_proof(round_id, round_seed, global_model)
+            .await
+    }
+
+    async fn proofs(&mut self) -> StorageResult<Vec<ModelProof>> {
+        self.trust_anchor.proofs().await
+    }
+
+    async fn proof(&mut self, round_id: u64) -> StorageResult<Option<ModelProof>> {
+        self.trust_anchor.proof(round_id).await
+    }
+
+    async fn proof_key(&mut self) -> StorageResult<Option<PublicSigningKey>> {
+        self.trust_anchor.proof_key().await
     }
 
     async fn is_ready(&mut self) -> StorageResult<()> {
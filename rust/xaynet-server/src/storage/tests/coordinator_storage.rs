@@ -0,0 +1,528 @@
+//! A test suite for the [`CoordinatorStorage`] backends.
+//!
+//! Each test is generic over the backend. The backends run the whole suite via the
+//! [`coordinator_storage_tests!`] macro.
+
+use std::time::Duration;
+
+use crate::{
+    state_machine::{
+        coordinator::CoordinatorState,
+        tests::utils::{mask_settings, model_settings, pet_settings},
+    },
+    storage::{
+        tests::utils::*,
+        CoordinatorStorage,
+        LocalSeedDictAddError,
+        MaskScoreIncrError,
+        Retention,
+        SumPartAddError,
+    },
+};
+
+/// Generates a test for each test of the suite, which runs against the store that `$init`
+/// evaluates to. The names of the generated tests start with `$prefix` and the generated tests
+/// have the given additional attributes.
+///
+/// ```ignore
+/// coordinator_storage_tests!(integration_, init_client().await, #[serial]);
+/// ```
+macro_rules! coordinator_storage_tests {
+    ($prefix: ident, $init: expr $(, #[$attr: meta])* $(,)?) => {
+        coordinator_storage_tests!(
+            @tests $prefix, $init, [$(#[$attr])*],
+            set_and_get_coordinator_state,
+            get_coordinator_empty,
+            incr_mask_score,
+            get_incr_mask_count_unknown_sum_pk,
+            get_incr_mask_score_sum_pk_already_submitted,
+            get_best_masks_only_one_mask,
+            get_best_masks_two_masks,
+            get_best_masks_no_mask,
+            get_number_of_unique_masks_empty,
+            get_number_of_unique_masks,
+            add_sum_participant,
+            seed_dict,
+            seed_dict_len_mis_match,
+            seed_dict_unknown_sum_participant,
+            seed_dict_update_pk_already_submitted,
+            flush_dicts,
+            flush_coordinator_data,
+            set_and_get_latest_global_model_id,
+            get_latest_global_model_id_empty,
+            add_and_get_global_model_history,
+            prune_global_model_history,
+            set_and_get_round_checkpoint,
+            set_and_get_round_progress,
+            is_ready_ok,
+        );
+    };
+    (@tests $prefix: ident, $init: expr, $attrs: tt, $($test: ident),+ $(,)?) => {
+        $(
+            coordinator_storage_tests!(@test $prefix, $init, $attrs, $test);
+        )+
+    };
+    (@test $prefix: ident, $init: expr, [$(#[$attr: meta])*], $test: ident) => {
+        paste::paste! {
+            #[tokio::test]
+            $(#[$attr])*
+            async fn [<$prefix $test>]() {
+                $crate::storage::tests::coordinator_storage::$test($init).await
+            }
+        }
+    };
+}
+
+pub(crate) use coordinator_storage_tests;
+
+pub async fn set_and_get_coordinator_state(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the coordinator state
+    let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
+    store.set_coordinator_state(&set_state).await.unwrap();
+
+    let get_state = store.coordinator_state().await.unwrap().unwrap();
+
+    assert_eq!(set_state, get_state)
+}
+
+pub async fn get_coordinator_empty(mut store: impl CoordinatorStorage) {
+    // test the reading of a non existing coordinator state
+    let get_state = store.coordinator_state().await.unwrap();
+
+    assert_eq!(None, get_state)
+}
+
+pub async fn incr_mask_score(mut store: impl CoordinatorStorage) {
+    // test the increment of the mask counter
+    let should_be_none = store.best_masks().await.unwrap();
+    assert!(should_be_none.is_none());
+
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 3).await;
+    let mask = create_mask_zeroed(10);
+    for sum_pk in sum_pks {
+        let res = store.incr_mask_score(&sum_pk, &mask).await;
+        assert!(res.is_ok())
+    }
+
+    let best_masks = store.best_masks().await.unwrap().unwrap();
+    assert!(best_masks.len() == 1);
+
+    let (best_mask, count) = best_masks.into_iter().next().unwrap();
+    assert_eq!(best_mask, mask);
+    assert_eq!(count, 3);
+}
+
+pub async fn get_incr_mask_count_unknown_sum_pk(mut store: impl CoordinatorStorage) {
+    // test the writing of a mask of an unknown sum participant
+    let (sum_pk, _) = create_sum_participant_entry();
+    let mask = create_mask_zeroed(10);
+    let unknown_sum_pk = store.incr_mask_score(&sum_pk, &mask).await.unwrap();
+
+    assert!(matches!(
+        unknown_sum_pk.into_inner().unwrap_err(),
+        MaskScoreIncrError::UnknownSumPk
+    ));
+}
+
+pub async fn get_incr_mask_score_sum_pk_already_submitted(mut store: impl CoordinatorStorage) {
+    // test the writing of a second mask of the same sum participant
+    let mut sum_pks = create_and_add_sum_participant_entries(&mut store, 1).await;
+    let sum_pk = sum_pks.pop().unwrap();
+    let mask = create_mask_zeroed(10);
+    let result = store.incr_mask_score(&sum_pk, &mask).await.unwrap();
+    assert!(result.is_ok());
+
+    let already_submitted = store.incr_mask_score(&sum_pk, &mask).await.unwrap();
+
+    assert!(matches!(
+        already_submitted.into_inner().unwrap_err(),
+        MaskScoreIncrError::MaskAlreadySubmitted
+    ));
+}
+
+pub async fn get_best_masks_only_one_mask(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of one mask
+    let mut sum_pks = create_and_add_sum_participant_entries(&mut store, 1).await;
+    let sum_pk = sum_pks.pop().unwrap();
+    let mask = create_mask_zeroed(10);
+    store.incr_mask_score(&sum_pk, &mask).await.unwrap();
+
+    let best_masks = store.best_masks().await.unwrap().unwrap();
+    assert!(best_masks.len() == 1);
+
+    let (best_mask, count) = best_masks.into_iter().next().unwrap();
+    assert_eq!(best_mask, mask);
+    assert_eq!(count, 1);
+}
+
+pub async fn get_best_masks_two_masks(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of two masks
+    // the first mask is incremented twice
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+    let mask_1 = create_mask_zeroed(10);
+    for sum_pk in sum_pks {
+        let res = store.incr_mask_score(&sum_pk, &mask_1).await;
+        assert!(res.is_ok())
+    }
+
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 1).await;
+    let mask_2 = create_mask_zeroed(100);
+    for sum_pk in sum_pks {
+        let res = store.incr_mask_score(&sum_pk, &mask_2).await;
+        assert!(res.is_ok())
+    }
+
+    let best_masks = store.best_masks().await.unwrap().unwrap();
+    assert!(best_masks.len() == 2);
+    let mut best_masks_iter = best_masks.into_iter();
+
+    let (first_mask, count) = best_masks_iter.next().unwrap();
+    assert_eq!(first_mask, mask_1);
+    assert_eq!(count, 2);
+    let (second_mask, count) = best_masks_iter.next().unwrap();
+    assert_eq!(second_mask, mask_2);
+    assert_eq!(count, 1);
+}
+
+pub async fn get_best_masks_no_mask(mut store: impl CoordinatorStorage) {
+    // ensure that get_best_masks returns none if no mask exist
+    let best_masks = store.best_masks().await.unwrap();
+    assert!(best_masks.is_none())
+}
+
+pub async fn get_number_of_unique_masks_empty(mut store: impl CoordinatorStorage) {
+    // ensure that get_number_of_unique_masks returns 0 if no mask exist
+    let number_of_unique_masks = store.number_of_unique_masks().await.unwrap();
+    assert_eq!(number_of_unique_masks, 0)
+}
+
+pub async fn get_number_of_unique_masks(mut store: impl CoordinatorStorage) {
+    // test the counting of distinct masks
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 4).await;
+    for (number, sum_pk) in sum_pks.iter().enumerate() {
+        let mask_1 = create_mask(10, number as u32);
+        let res = store.incr_mask_score(&sum_pk, &mask_1).await;
+        assert!(res.is_ok())
+    }
+
+    let number_of_unique_masks = store.number_of_unique_masks().await.unwrap();
+    assert_eq!(number_of_unique_masks, 4)
+}
+
+pub async fn add_sum_participant(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the sum dict
+    let sum_dict = store.sum_dict().await.unwrap();
+    assert!(sum_dict.is_none());
+
+    // create two entries and write them into the store
+    let mut entries = vec![];
+    for _ in 0..2 {
+        let (pk, epk) = create_sum_participant_entry();
+        let add_new_key = store.add_sum_participant(&pk, &epk).await.unwrap();
+        assert!(add_new_key.is_ok());
+
+        entries.push((pk, epk));
+    }
+
+    // ensure that add_sum_participant returns SumPartAddError::AlreadyExists if the key already exist
+    let (pk, epk) = entries.get(0).unwrap();
+    let key_already_exist = store.add_sum_participant(pk, epk).await.unwrap();
+    assert!(matches!(
+        key_already_exist.into_inner().unwrap_err(),
+        SumPartAddError::AlreadyExists
+    ));
+
+    let sum_dict = store.sum_dict().await.unwrap().unwrap();
+    assert_eq!(sum_dict.len(), 2);
+    for (sum_pk, ephm_pk) in entries.iter() {
+        assert_eq!(sum_dict.get(sum_pk), Some(ephm_pk));
+    }
+}
+
+pub async fn seed_dict(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the seed dict
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.iter().for_each(|res| assert!(res.is_ok()));
+
+    let sum_dict = store.sum_dict().await.unwrap().unwrap();
+    let seed_dict = create_seed_dict(sum_dict, &local_seed_dicts);
+
+    let store_seed_dict = store.seed_dict().await.unwrap().unwrap();
+    assert_eq!(seed_dict, store_seed_dict)
+}
+
+pub async fn seed_dict_len_mis_match(mut store: impl CoordinatorStorage) {
+    let mut sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+    // remove one sum pk to create invalid local seed dicts
+    sum_pks.pop();
+
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.into_iter().for_each(|res| {
+        assert!(matches!(
+            res.into_inner().unwrap_err(),
+            LocalSeedDictAddError::LengthMisMatch
+        ))
+    });
+}
+
+pub async fn seed_dict_unknown_sum_participant(mut store: impl CoordinatorStorage) {
+    let mut sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+    // replace a known sum_pk with an unknown one
+    sum_pks.pop();
+    let (pk, _) = create_sum_participant_entry();
+    sum_pks.push(pk);
+
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.into_iter().for_each(|res| {
+        assert!(matches!(
+            res.into_inner().unwrap_err(),
+            LocalSeedDictAddError::UnknownSumParticipant
+        ))
+    });
+}
+
+pub async fn seed_dict_update_pk_already_submitted(mut store: impl CoordinatorStorage) {
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.iter().for_each(|res| assert!(res.is_ok()));
+
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.into_iter().for_each(|res| {
+        assert!(matches!(
+            res.into_inner().unwrap_err(),
+            LocalSeedDictAddError::UpdatePkAlreadySubmitted
+        ))
+    });
+}
+
+pub async fn flush_dicts(mut store: impl CoordinatorStorage) {
+    // write some data into the store
+    let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
+    let res = store.set_coordinator_state(&set_state).await;
+    assert!(res.is_ok());
+
+    let res = store.set_latest_global_model_id("global_model_id").await;
+    assert!(res.is_ok());
+
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.iter().for_each(|res| assert!(res.is_ok()));
+
+    let mask = create_mask_zeroed(10);
+    store
+        .incr_mask_score(sum_pks.get(0).unwrap(), &mask)
+        .await
+        .unwrap();
+
+    let res = store
+        .set_round_checkpoint(&create_round_checkpoint(1))
+        .await;
+    assert!(res.is_ok());
+
+    let res = store.set_round_progress(&create_round_progress(1)).await;
+    assert!(res.is_ok());
+
+    // remove dicts
+    let res = store.delete_dicts().await;
+    assert!(res.is_ok());
+
+    // ensure that only the coordinator state and latest global model id exists
+    let res = store.coordinator_state().await;
+    assert!(res.unwrap().is_some());
+
+    let res = store.latest_global_model_id().await;
+    assert!(res.unwrap().is_some());
+
+    let res = store.sum_dict().await;
+    assert!(res.unwrap().is_none());
+
+    let res = store.seed_dict().await;
+    assert!(res.unwrap().is_none());
+
+    let res = store.best_masks().await;
+    assert!(res.unwrap().is_none());
+
+    let res = store.round_checkpoint().await;
+    assert!(res.unwrap().is_none());
+
+    let res = store.round_progress().await;
+    assert!(res.unwrap().is_none());
+
+    // ensure that the sum participant can submit a mask again in a new round
+    let (_, ephm_pk) = create_sum_participant_entry();
+    let sum_pk = sum_pks.get(0).unwrap();
+    store.add_sum_participant(sum_pk, &ephm_pk).await.unwrap();
+    let res = store.incr_mask_score(sum_pk, &mask).await.unwrap();
+    assert!(res.is_ok());
+}
+
+pub async fn flush_coordinator_data(mut store: impl CoordinatorStorage) {
+    // write some data into the store
+    let set_state = CoordinatorState::new(pet_settings(), mask_settings(), model_settings());
+    let res = store.set_coordinator_state(&set_state).await;
+    assert!(res.is_ok());
+
+    let res = store.set_latest_global_model_id("global_model_id").await;
+    assert!(res.is_ok());
+
+    let res = store
+        .add_global_model_info(&create_global_model_info(1))
+        .await;
+    assert!(res.is_ok());
+
+    let sum_pks = create_and_add_sum_participant_entries(&mut store, 2).await;
+
+    let local_seed_dicts = create_local_seed_entries(&sum_pks);
+    let update_result = add_local_seed_entries(&mut store, &local_seed_dicts).await;
+    update_result.iter().for_each(|res| assert!(res.is_ok()));
+
+    let mask = create_mask_zeroed(10);
+    store
+        .incr_mask_score(sum_pks.get(0).unwrap(), &mask)
+        .await
+        .unwrap();
+
+    let res = store
+        .set_round_checkpoint(&create_round_checkpoint(1))
+        .await;
+    assert!(res.is_ok());
+
+    let res = store.set_round_progress(&create_round_progress(1)).await;
+    assert!(res.is_ok());
+
+    // remove all coordinator data
+    let res = store.delete_coordinator_data().await;
+    assert!(res.is_ok());
+
+    assert!(store.coordinator_state().await.unwrap().is_none());
+    assert!(store.latest_global_model_id().await.unwrap().is_none());
+    assert!(store.global_model_history(0, 10).await.unwrap().is_empty());
+    assert!(store.sum_dict().await.unwrap().is_none());
+    assert!(store.seed_dict().await.unwrap().is_none());
+    assert!(store.best_masks().await.unwrap().is_none());
+    assert!(store.round_checkpoint().await.unwrap().is_none());
+    assert!(store.round_progress().await.unwrap().is_none());
+}
+
+pub async fn set_and_get_latest_global_model_id(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the global model id
+    let set_id = "global_model_id";
+    store.set_latest_global_model_id(set_id).await.unwrap();
+
+    let get_id = store.latest_global_model_id().await.unwrap().unwrap();
+
+    assert_eq!(set_id, get_id)
+}
+
+pub async fn get_latest_global_model_id_empty(mut store: impl CoordinatorStorage) {
+    // test the reading of a non existing global model id
+    let get_id = store.latest_global_model_id().await.unwrap();
+
+    assert_eq!(None, get_id)
+}
+
+pub async fn add_and_get_global_model_history(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the global model history
+    let history = store.global_model_history(0, 10).await.unwrap();
+    assert!(history.is_empty());
+    assert!(store.global_model_info(1).await.unwrap().is_none());
+
+    let infos: Vec<_> = (1..=5).map(create_global_model_info).collect();
+    for info in infos.iter().rev() {
+        store.add_global_model_info(info).await.unwrap();
+    }
+
+    // the pages are ordered by the round ids
+    let history = store.global_model_history(0, 10).await.unwrap();
+    assert_eq!(history, infos);
+    let history = store.global_model_history(2, 2).await.unwrap();
+    assert_eq!(history, &infos[1..3]);
+    let history = store.global_model_history(6, 10).await.unwrap();
+    assert!(history.is_empty());
+
+    let info = store.global_model_info(3).await.unwrap().unwrap();
+    assert_eq!(info, infos[2]);
+    assert!(store.global_model_info(6).await.unwrap().is_none());
+
+    // the info of a round is replaced
+    let replaced = create_global_model_info(3);
+    store.add_global_model_info(&replaced).await.unwrap();
+    let info = store.global_model_info(3).await.unwrap().unwrap();
+    assert_eq!(info, replaced);
+    assert_eq!(store.global_model_history(0, 10).await.unwrap().len(), 5);
+}
+
+pub async fn prune_global_model_history(mut store: impl CoordinatorStorage) {
+    // test the pruning of the global model history by the retention policy
+    for round_id in 1..=5 {
+        let info = create_global_model_info(round_id);
+        store.add_global_model_info(&info).await.unwrap();
+    }
+
+    store
+        .prune_global_model_history(&Retention::default())
+        .await
+        .unwrap();
+    assert_eq!(store.global_model_history(0, 10).await.unwrap().len(), 5);
+
+    let retention = Retention {
+        max_models: Some(3),
+        max_age: None,
+    };
+    store.prune_global_model_history(&retention).await.unwrap();
+    let history = store.global_model_history(0, 10).await.unwrap();
+    let round_ids: Vec<_> = history.iter().map(|info| info.round_id).collect();
+    assert_eq!(round_ids, vec![3, 4, 5]);
+    assert!(store.global_model_info(2).await.unwrap().is_none());
+
+    // the infos have been completed long ago, but the latest one is kept
+    let retention = Retention {
+        max_models: None,
+        max_age: Some(Duration::from_secs(60)),
+    };
+    store.prune_global_model_history(&retention).await.unwrap();
+    let history = store.global_model_history(0, 10).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].round_id, 5);
+}
+
+pub async fn set_and_get_round_checkpoint(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the round checkpoint
+    let get_checkpoint = store.round_checkpoint().await.unwrap();
+    assert!(get_checkpoint.is_none());
+
+    let set_checkpoint = create_round_checkpoint(1);
+    store.set_round_checkpoint(&set_checkpoint).await.unwrap();
+
+    let get_checkpoint = store.round_checkpoint().await.unwrap().unwrap();
+    assert_eq!(set_checkpoint, get_checkpoint);
+}
+
+pub async fn set_and_get_round_progress(mut store: impl CoordinatorStorage) {
+    // test the writing and reading of the round progress
+    let get_progress = store.round_progress().await.unwrap();
+    assert!(get_progress.is_none());
+
+    let set_progress = create_round_progress(1);
+    store.set_round_progress(&set_progress).await.unwrap();
+
+    let get_progress = store.round_progress().await.unwrap().unwrap();
+    assert_eq!(set_progress, get_progress);
+}
+
+pub async fn is_ready_ok(mut store: impl CoordinatorStorage) {
+    // test the readiness of the store
+    let res = store.is_ready().await;
+    assert!(res.is_ok())
+}
@@ -1,3 +1,4 @@
+pub mod coordinator_storage;
 pub mod utils;
 
 use crate::storage::{coordinator_storage::redis, model_storage, Storage, Store};
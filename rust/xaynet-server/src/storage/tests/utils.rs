@@ -1,12 +1,17 @@
 use num::{bigint::BigUint, traits::identities::Zero};
 
 use crate::{
-    state_machine::tests::utils::mask_settings,
+    state_machine::{
+        coordinator::{RoundCheckpoint, RoundProgress},
+        phases::PhaseName,
+        tests::utils::mask_settings,
+    },
     storage::{CoordinatorStorage, LocalSeedDictAdd},
 };
 use xaynet_core::{
+    common::{GlobalModelInfo, RoundSeed},
     crypto::{ByteObject, EncryptKeyPair, SigningKeyPair},
-    mask::{EncryptedMaskSeed, MaskConfig, MaskObject},
+    mask::{Aggregation, EncryptedMaskSeed, FromPrimitives, MaskConfig, MaskObject, Model},
     LocalSeedDict,
     SeedDict,
     SumDict,
@@ -113,10 +118,38 @@ pub async fn add_local_seed_entries(
     update_result
 }
 
-#[cfg(feature = "model-persistence")]
-use xaynet_core::mask::{FromPrimitives, Model};
-
-#[cfg(feature = "model-persistence")]
 pub fn create_global_model(nb_elements: usize) -> Model {
     Model::from_primitives(vec![0; nb_elements].into_iter()).unwrap()
 }
+
+pub fn create_global_model_info(round_id: u64) -> GlobalModelInfo {
+    GlobalModelInfo {
+        round_id,
+        round_seed: RoundSeed::generate(),
+        model_id: format!("{}_global_model", round_id),
+        model_length: 10,
+        completed_at: 1_600_000_000 + round_id,
+    }
+}
+
+pub fn create_round_checkpoint(round_id: u64) -> RoundCheckpoint {
+    RoundCheckpoint {
+        round_id,
+        phase: PhaseName::Update,
+        started_at: 1_600_000_000,
+        accepted: 1,
+        rejected: 2,
+        discarded: 3,
+        aggregation: Some(Aggregation::from(create_mask(10, 1))),
+    }
+}
+
+pub fn create_round_progress(round_id: u64) -> RoundProgress {
+    RoundProgress {
+        round_id,
+        phase: PhaseName::Update,
+        accepted: 4,
+        rejected: 5,
+        discarded: 6,
+    }
+}
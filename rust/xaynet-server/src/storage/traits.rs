@@ -1,16 +1,19 @@
 //! Storage API.
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use async_trait::async_trait;
 use derive_more::Deref;
 use displaydoc::Display;
 use num_enum::TryFromPrimitive;
 use thiserror::Error;
 
-use crate::state_machine::coordinator::CoordinatorState;
+use crate::state_machine::coordinator::{CoordinatorState, RoundCheckpoint, RoundProgress};
 use xaynet_core::{
-    common::RoundSeed,
-    crypto::ByteObject,
+    common::{GlobalModelInfo, RoundSeed},
+    crypto::{ByteObject, PublicSigningKey},
     mask::{MaskObject, Model},
+    proof::ModelProof,
     LocalSeedDict,
     SeedDict,
     SumDict,
@@ -125,11 +128,46 @@ where
     /// Returns the number of unique masks.
     async fn number_of_unique_masks(&mut self) -> StorageResult<u64>;
 
+    /// Sets the [`RoundCheckpoint`] of the current round.
+    ///
+    /// # Behavior
+    ///
+    /// - If no checkpoint has been set yet, set the checkpoint and return `StorageResult::Ok(())`.
+    /// - If a checkpoint already exists, override the checkpoint and return
+    ///   `StorageResult::Ok(())`.
+    async fn set_round_checkpoint(&mut self, checkpoint: &RoundCheckpoint) -> StorageResult<()>;
+
+    /// Returns the [`RoundCheckpoint`] of the current round.
+    ///
+    /// # Behavior
+    ///
+    /// - If no checkpoint has been set yet, return `StorageResult::Ok(Option::None)`.
+    /// - If a checkpoint exists, return `StorageResult::Ok(Some(RoundCheckpoint))`.
+    async fn round_checkpoint(&mut self) -> StorageResult<Option<RoundCheckpoint>>;
+
+    /// Sets the [`RoundProgress`] of the current phase.
+    ///
+    /// # Behavior
+    ///
+    /// - If no progress has been set yet, set the progress and return `StorageResult::Ok(())`.
+    /// - If a progress already exists, override the progress and return `StorageResult::Ok(())`.
+    async fn set_round_progress(&mut self, progress: &RoundProgress) -> StorageResult<()>;
+
+    /// Returns the [`RoundProgress`] of the current phase.
+    ///
+    /// # Behavior
+    ///
+    /// - If no progress has been set yet, return `StorageResult::Ok(Option::None)`.
+    /// - If a progress exists, return `StorageResult::Ok(Some(RoundProgress))`.
+    async fn round_progress(&mut self) -> StorageResult<Option<RoundProgress>>;
+
     /// Deletes all coordinator data. This includes the coordinator
-    /// state as well as the [`SumDict`], [`SeedDict`] and `mask` dictionary.
+    /// state and the global model history as well as the [`SumDict`],
+    /// [`SeedDict`], `mask` dictionary, [`RoundCheckpoint`] and [`RoundProgress`].
     async fn delete_coordinator_data(&mut self) -> StorageResult<()>;
 
-    /// Deletes the [`SumDict`], [`SeedDict`] and `mask` dictionary.
+    /// Deletes the [`SumDict`], [`SeedDict`], `mask` dictionary, [`RoundCheckpoint`] and
+    /// [`RoundProgress`].
     async fn delete_dicts(&mut self) -> StorageResult<()>;
 
     /// Sets the latest global model id.
@@ -149,6 +187,47 @@ where
     /// - If the global model id exists, return `StorageResult::Ok(Some(String)))`.
     async fn latest_global_model_id(&mut self) -> StorageResult<Option<String>>;
 
+    /// Adds the [`GlobalModelInfo`] of a completed round to the global model history.
+    ///
+    /// # Behavior
+    ///
+    /// - If no info of the round has been added yet, add the info and return
+    ///   `StorageResult::Ok(())`.
+    /// - If an info of the round already exists, override it with the new info and return
+    ///   `StorageResult::Ok(())`.
+    async fn add_global_model_info(&mut self, info: &GlobalModelInfo) -> StorageResult<()>;
+
+    /// Returns the [`GlobalModelInfo`] of the given round from the global model history.
+    ///
+    /// # Behavior
+    ///
+    /// - If the history contains no info of the round, return `StorageResult::Ok(None)`.
+    /// - If the history contains an info of the round, return
+    ///   `StorageResult::Ok(Some(GlobalModelInfo))`.
+    async fn global_model_info(&mut self, round_id: u64) -> StorageResult<Option<GlobalModelInfo>>;
+
+    /// Returns a page of the global model history.
+    ///
+    /// # Behavior
+    ///
+    /// - Return `StorageResult::Ok(Vec<GlobalModelInfo>)` with at most `limit` infos of the
+    ///   rounds from `from_round_id` on, ordered by their round ids. The page is empty if the
+    ///   history contains no such info.
+    async fn global_model_history(
+        &mut self,
+        from_round_id: u64,
+        limit: usize,
+    ) -> StorageResult<Vec<GlobalModelInfo>>;
+
+    /// Deletes the [`GlobalModelInfo`]s which are not retained by the `retention` policy from the
+    /// global model history.
+    ///
+    /// # Behavior
+    ///
+    /// - Delete the infos which are not retained, but always keep the info of the latest round,
+    ///   and return `StorageResult::Ok(())`.
+    async fn prune_global_model_history(&mut self, retention: &Retention) -> StorageResult<()>;
+
     /// Checks if the [`CoordinatorStorage`] is ready to process requests.
     ///
     /// # Behavior
@@ -197,6 +276,14 @@ where
         format!("{}_{}", round_id, round_seed)
     }
 
+    /// Checks if the [`ModelStorage`] persists the global models.
+    ///
+    /// The coordinator only keeps track of the latest global model if it can be restored from
+    /// the [`ModelStorage`] later on. Defaults to `true`.
+    fn persists_global_models(&self) -> bool {
+        true
+    }
+
     /// Checks if the [`ModelStorage`] is ready to process requests.
     ///
     /// # Behavior
@@ -213,13 +300,42 @@ pub trait TrustAnchor
 where
     Self: Clone + Send + Sync + 'static,
 {
-    /// Publishes a proof of the global model.
+    /// Publishes a proof of the global model which was created in the round with the given id
+    /// and seed.
     ///
     /// # Behavior
     ///
     /// Return `StorageResult::Ok(())` if the proof was published successfully,
     /// otherwise return `StorageResult::Err(error)`.
-    async fn publish_proof(&mut self, global_model: &Model) -> StorageResult<()>;
+    async fn publish_proof(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<()>;
+
+    /// Gets the published proofs in the order of their publication.
+    ///
+    /// # Behavior
+    ///
+    /// Return an empty list if the trust anchor doesn't keep the proofs.
+    async fn proofs(&mut self) -> StorageResult<Vec<ModelProof>>;
+
+    /// Gets the latest published proof of the global model which was created in the round with
+    /// the given id.
+    ///
+    /// # Behavior
+    ///
+    /// Return `StorageResult::Ok(None)` if no proof of the round has been published or if the
+    /// trust anchor doesn't keep the proofs.
+    async fn proof(&mut self, round_id: u64) -> StorageResult<Option<ModelProof>>;
+
+    /// Gets the public key to verify the published proofs with.
+    ///
+    /// # Behavior
+    ///
+    /// Return `StorageResult::Ok(None)` if the trust anchor doesn't sign the proofs.
+    async fn proof_key(&mut self) -> StorageResult<Option<PublicSigningKey>>;
 
     /// Checks if the [`TrustAnchor`] is ready to process requests.
     ///
@@ -309,3 +425,33 @@ pub enum MaskScoreIncrError {
     /// sum participant submitted a mask already
     MaskAlreadySubmitted = -2,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The retention policy for the global models and their history.
+pub struct Retention {
+    /// The maximal number of global models to keep. Unlimited if `None`.
+    pub max_models: Option<usize>,
+    /// The maximal age of global models to keep. Unlimited if `None`.
+    pub max_age: Option<Duration>,
+}
+
+impl Retention {
+    /// Checks if a global model is retained, given its `index` in the order from the latest to
+    /// the oldest global model and its `age`. The latest global model is always retained.
+    pub fn retains(&self, index: usize, age: Duration) -> bool {
+        let too_many = self.max_models.map_or(false, |max| index >= max);
+        let too_old = self.max_age.map_or(false, |max| age > max);
+        index == 0 || !(too_many || too_old)
+    }
+
+    /// Checks if the [`GlobalModelInfo`] of a global model is retained, given its `index` in the
+    /// order from the latest to the oldest round. Its age is counted from the completion of the
+    /// round.
+    pub fn retains_info(&self, index: usize, info: &GlobalModelInfo) -> bool {
+        let completed_at = UNIX_EPOCH + Duration::from_secs(info.completed_at);
+        let age = SystemTime::now()
+            .duration_since(completed_at)
+            .unwrap_or_default();
+        self.retains(index, age)
+    }
+}
@@ -1 +1,4 @@
+//! Trust anchors to publish the proofs of global models.
+
 pub mod noop;
+pub mod proof_log;
@@ -1,16 +1,33 @@
 use crate::storage::traits::{StorageResult, TrustAnchor};
 use async_trait::async_trait;
-use xaynet_core::mask::Model;
+use xaynet_core::{common::RoundSeed, crypto::PublicSigningKey, mask::Model, proof::ModelProof};
 
 #[derive(Clone)]
 pub struct NoOp;
 
 #[async_trait]
 impl TrustAnchor for NoOp {
-    async fn publish_proof(&mut self, _global_model: &Model) -> StorageResult<()> {
+    async fn publish_proof(
+        &mut self,
+        _round_id: u64,
+        _round_seed: &RoundSeed,
+        _global_model: &Model,
+    ) -> StorageResult<()> {
         Ok(())
     }
 
+    async fn proofs(&mut self) -> StorageResult<Vec<ModelProof>> {
+        Ok(Vec::new())
+    }
+
+    async fn proof(&mut self, _round_id: u64) -> StorageResult<Option<ModelProof>> {
+        Ok(None)
+    }
+
+    async fn proof_key(&mut self) -> StorageResult<Option<PublicSigningKey>> {
+        Ok(None)
+    }
+
     async fn is_ready(&mut self) -> StorageResult<()> {
         Ok(())
     }
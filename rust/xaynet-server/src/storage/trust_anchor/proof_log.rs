@@ -0,0 +1,317 @@
+//! A [`TrustAnchor`] backend which publishes the proofs of the global models to a log file.
+//!
+//! The log is an append-only text file with one [`ModelProof`] per line. The proofs are signed
+//! with a signing key pair of the coordinator, whose seed is kept in a separate key file next to
+//! the log. The public key is written to a public key file, such that anyone can verify the log
+//! with [`verify_chain()`] and check downloaded global models against it.
+//!
+//! ```text
+//! <dir>/
+//!     proofs.log  // one proof per line
+//!     signing.key // hex encoded seed of the signing key pair
+//!     signing.pub // hex encoded public signing key
+//! ```
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+use tracing::debug;
+
+use crate::storage::{StorageResult, TrustAnchor};
+use xaynet_core::{
+    common::RoundSeed,
+    crypto::{ByteObject, PublicSigningKey, SigningKeyPair, SigningKeySeed},
+    mask::Model,
+    proof::{verify_chain, ModelProof, ParseProofError, ProofError},
+};
+
+const LOG_FILE: &str = "proofs.log";
+const KEY_FILE: &str = "signing.key";
+const PUBLIC_KEY_FILE: &str = "signing.pub";
+
+type ClientResult<T> = Result<T, ClientError>;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("failed to access the proof log: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid signing key file")]
+    InvalidKey,
+    #[error("invalid proof in line {0}: {1}")]
+    Parse(usize, ParseProofError),
+    #[error("the proof log is corrupted: {0}")]
+    Corrupted(#[from] ProofError),
+}
+
+/// The signing keys and the published proofs.
+struct Inner {
+    keys: SigningKeyPair,
+    proofs: Vec<ModelProof>,
+    /// The index of the latest proof of each round in `proofs`.
+    rounds: HashMap<u64, usize>,
+}
+
+impl Inner {
+    /// Appends a published proof.
+    fn push(&mut self, proof: ModelProof) {
+        self.rounds.insert(proof.round_id, self.proofs.len());
+        self.proofs.push(proof);
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    dir: Arc<PathBuf>,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Client {
+    /// Creates a new proof log client in the directory at `path`.
+    ///
+    /// The directory, the log and the signing keys are created if they don't exist yet. An
+    /// existing log is verified and kept in memory, and subsequent proofs are chained to its
+    /// latest proof.
+    ///
+    /// # Errors
+    /// Fails if the files can't be accessed or if an existing log can't be verified.
+    pub async fn new(path: impl AsRef<Path>) -> ClientResult<Self> {
+        let dir = path.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).await?;
+
+        let keys = Self::load_or_create_keys(&dir).await?;
+        let proofs = read_proofs(dir.join(LOG_FILE)).await?;
+        verify_chain(&proofs, &keys.public)?;
+        debug!("loaded proof log with {} proofs", proofs.len());
+
+        let mut inner = Inner {
+            keys,
+            proofs: Vec::with_capacity(proofs.len()),
+            rounds: HashMap::new(),
+        };
+        proofs.into_iter().for_each(|proof| inner.push(proof));
+        Ok(Self {
+            dir: Arc::new(dir),
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// Gets the public key to verify the proofs with.
+    pub async fn public_key(&self) -> PublicSigningKey {
+        self.inner.lock().await.keys.public
+    }
+
+    /// Gets the path of the proof log.
+    pub fn log_path(&self) -> PathBuf {
+        self.dir.join(LOG_FILE)
+    }
+
+    // Loads the signing keys from the key file or creates them if the key file doesn't exist.
+    async fn load_or_create_keys(dir: &Path) -> ClientResult<SigningKeyPair> {
+        let key_path = dir.join(KEY_FILE);
+        let seed = match fs::read_to_string(&key_path).await {
+            Ok(seed) => hex::decode(seed.trim())
+                .ok()
+                .and_then(|seed| SigningKeySeed::from_slice(&seed))
+                .ok_or(ClientError::InvalidKey)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("create signing key file {}", key_path.display());
+                let seed = SigningKeySeed::generate();
+                let mut options = OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                options.mode(0o600);
+                let mut file = options.open(&key_path).await?;
+                file.write_all(hex::encode(seed.as_slice()).as_bytes())
+                    .await?;
+                file.sync_all().await?;
+                seed
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let keys = SigningKeyPair::derive_from_seed(&seed);
+        fs::write(
+            dir.join(PUBLIC_KEY_FILE),
+            hex::encode(keys.public.as_slice()),
+        )
+        .await?;
+        Ok(keys)
+    }
+}
+
+/// Reads the proofs from the log file at `path`, ordered from the first to the latest proof.
+///
+/// A log file which doesn't exist is treated as an empty log.
+///
+/// # Errors
+/// Fails if the log file can't be read or contains an invalid proof.
+pub async fn read_proofs(path: impl AsRef<Path>) -> ClientResult<Vec<ModelProof>> {
+    let log = match fs::read_to_string(path).await {
+        Ok(log) => log,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    log.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            line.parse()
+                .map_err(|err| ClientError::Parse(index + 1, err))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl TrustAnchor for Client {
+    async fn publish_proof(
+        &mut self,
+        round_id: u64,
+        round_seed: &RoundSeed,
+        global_model: &Model,
+    ) -> StorageResult<()> {
+        let mut inner = self.inner.lock().await;
+        let proof = ModelProof::new(
+            round_id,
+            round_seed.clone(),
+            global_model,
+            inner.proofs.last(),
+            &inner.keys.secret,
+        );
+        debug!("publish proof of round {}", round_id);
+
+        let mut log = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(self.log_path())
+            .await?;
+        log.write_all(format!("{}\n", proof).as_bytes()).await?;
+        log.sync_all().await?;
+
+        inner.push(proof);
+        Ok(())
+    }
+
+    async fn proofs(&mut self) -> StorageResult<Vec<ModelProof>> {
+        Ok(self.inner.lock().await.proofs.clone())
+    }
+
+    async fn proof(&mut self, round_id: u64) -> StorageResult<Option<ModelProof>> {
+        let inner = self.inner.lock().await;
+        Ok(inner
+            .rounds
+            .get(&round_id)
+            .map(|index| inner.proofs[*index].clone()))
+    }
+
+    async fn proof_key(&mut self) -> StorageResult<Option<PublicSigningKey>> {
+        Ok(Some(self.public_key().await))
+    }
+
+    async fn is_ready(&mut self) -> StorageResult<()> {
+        if fs::metadata(self.dir.as_path()).await?.is_dir() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{} is not a directory", self.dir.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::tests::utils::create_global_model;
+
+    async fn publish_proofs(client: &mut Client, rounds: std::ops::RangeInclusive<u64>) {
+        for round_id in rounds {
+            client
+                .publish_proof(round_id, &RoundSeed::generate(), &create_global_model(10))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_verify_proofs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Client::new(dir.path()).await.unwrap();
+        publish_proofs(&mut client, 1..=3).await;
+
+        let proofs = read_proofs(client.log_path()).await.unwrap();
+        assert_eq!(proofs.len(), 3);
+        assert!(verify_chain(&proofs, &client.public_key().await).is_ok());
+        assert!(proofs
+            .iter()
+            .all(|proof| proof.verify_model(&create_global_model(10))));
+    }
+
+    #[tokio::test]
+    async fn test_restart_continues_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Client::new(dir.path()).await.unwrap();
+        publish_proofs(&mut client, 1..=2).await;
+        let public_key = client.public_key().await;
+        drop(client);
+
+        let mut client = Client::new(dir.path()).await.unwrap();
+        assert_eq!(client.public_key().await, public_key);
+        publish_proofs(&mut client, 3..=4).await;
+
+        let proofs = read_proofs(client.log_path()).await.unwrap();
+        assert_eq!(proofs.len(), 4);
+        assert!(verify_chain(&proofs, &public_key).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_proofs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Client::new(dir.path()).await.unwrap();
+        publish_proofs(&mut client, 1..=2).await;
+        drop(client);
+
+        // the proofs of the existing log are available after a restart
+        let mut client = Client::new(dir.path()).await.unwrap();
+        publish_proofs(&mut client, 3..=3).await;
+        let proofs = read_proofs(client.log_path()).await.unwrap();
+        assert_eq!(client.proofs().await.unwrap(), proofs);
+        assert_eq!(client.proof(2).await.unwrap().as_ref(), proofs.get(1));
+        assert_eq!(client.proof(3).await.unwrap().as_ref(), proofs.get(2));
+        assert!(client.proof(4).await.unwrap().is_none());
+        assert_eq!(
+            client.proof_key().await.unwrap(),
+            Some(client.public_key().await)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Client::new(dir.path()).await.unwrap();
+        publish_proofs(&mut client, 1..=3).await;
+
+        // remove the second proof from the log
+        let log = fs::read_to_string(client.log_path()).await.unwrap();
+        let log = log
+            .lines()
+            .enumerate()
+            .filter(|(index, _)| *index != 1)
+            .map(|(_, line)| format!("{}\n", line))
+            .collect::<String>();
+        fs::write(client.log_path(), log).await.unwrap();
+
+        assert!(matches!(
+            Client::new(dir.path()).await,
+            Err(ClientError::Corrupted(ProofError::BrokenChain(3)))
+        ));
+    }
+}